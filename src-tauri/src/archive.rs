@@ -0,0 +1,252 @@
+use std::collections::HashSet;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+/// Separates the zip path from the entry name in an `auto_archive` undo entry's
+/// `current_path`, e.g. `D:\archive\logs-2026-03.zip!/app.log`.
+const UNDO_ENTRY_SEPARATOR: &str = "!/";
+
+/// Add `file_path` to the zip at `zip_path`, creating the zip if needed. A same-named
+/// entry already in the archive gets a ` (n)` suffix, like a move collision, unless it
+/// holds the same bytes: then the file was archived before (and its removal failed), so
+/// nothing is written and that entry's name is returned. Returns the entry name used.
+/// A new archive is written next to its final name and renamed into place, so a failure
+/// midway never leaves a truncated zip.
+pub fn append_to_zip(zip_path: &Path, file_path: &Path) -> io::Result<String> {
+    if zip_path.exists() {
+        return append_in_place(zip_path, file_path);
+    }
+    let partial = partial_path(zip_path);
+    let result = File::create(&partial)
+        .and_then(|out| write_entry(ZipWriter::new(out), &entry_file_name(file_path), file_path))
+        .and_then(|_| fs::rename(&partial, zip_path));
+    if result.is_err() {
+        let _ = fs::remove_file(&partial);
+    }
+    result.map(|_| entry_file_name(file_path))
+}
+
+/// Where [`append_to_zip`] builds a new archive (`logs.zip.partial`).
+pub fn partial_path(zip_path: &Path) -> PathBuf {
+    let mut name = zip_path.file_name().unwrap_or_default().to_os_string();
+    name.push(".partial");
+    zip_path.with_file_name(name)
+}
+
+/// Add `file_path` to the existing archive without copying it. The new entry overwrites
+/// the central directory at the end of the file, so that tail is kept in memory and put
+/// back when the append fails.
+fn append_in_place(zip_path: &Path, file_path: &Path) -> io::Result<String> {
+    let file_name = entry_file_name(file_path);
+    let mut file = OpenOptions::new().read(true).write(true).open(zip_path)?;
+    let mut archive = ZipArchive::new(&file).map_err(io::Error::other)?;
+    if let Some(existing) = identical_entry(&mut archive, &file_name, file_path)? {
+        return Ok(existing);
+    }
+    let names: HashSet<String> = archive.file_names().map(String::from).collect();
+    let tail_start = archive.central_directory_start();
+    drop(archive);
+
+    let mut tail = Vec::new();
+    file.seek(SeekFrom::Start(tail_start))?;
+    file.read_to_end(&mut tail)?;
+    let entry_name = unique_entry_name(&file_name, &names);
+    let written = ZipWriter::new_append(&file)
+        .map_err(io::Error::other)
+        .and_then(|writer| write_entry(writer, &entry_name, file_path));
+    if let Err(e) = written {
+        file.set_len(tail_start)?;
+        file.seek(SeekFrom::Start(tail_start))?;
+        file.write_all(&tail)?;
+        return Err(e);
+    }
+    Ok(entry_name)
+}
+
+fn entry_file_name(file_path: &Path) -> String {
+    file_path.file_name().unwrap_or_default().to_string_lossy().to_string()
+}
+
+/// Write `file_path` as `entry_name` and finish the archive.
+fn write_entry<W: Write + Seek>(mut writer: ZipWriter<W>, entry_name: &str, file_path: &Path) -> io::Result<()> {
+    let size = fs::metadata(file_path)?.len();
+    let options = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Deflated)
+        .large_file(size >= u32::MAX as u64);
+    writer.start_file(entry_name, options).map_err(io::Error::other)?;
+    io::copy(&mut File::open(file_path)?, &mut writer)?;
+    writer.finish().map_err(io::Error::other)?;
+    Ok(())
+}
+
+/// Name of an entry stored as `file_name` (or a ` (n)` variant of it) with the same
+/// bytes as `file_path`, if there is one.
+fn identical_entry<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    file_name: &str,
+    file_path: &Path,
+) -> io::Result<Option<String>> {
+    let size = fs::metadata(file_path)?.len();
+    let candidates: Vec<String> = archive
+        .file_names()
+        .filter(|name| is_entry_name_for(name, file_name))
+        .map(String::from)
+        .collect();
+    for name in candidates {
+        let mut entry = archive.by_name(&name).map_err(io::Error::other)?;
+        if entry.size() == size && same_content(&mut entry, &mut File::open(file_path)?)? {
+            return Ok(Some(name));
+        }
+    }
+    Ok(None)
+}
+
+/// Whether `name` is `file_name` or one of the names [`unique_entry_name`] gives it.
+fn is_entry_name_for(name: &str, file_name: &str) -> bool {
+    if name == file_name {
+        return true;
+    }
+    let (stem, ext) = split_extension(file_name);
+    name.strip_prefix(stem)
+        .and_then(|rest| rest.strip_suffix(ext))
+        .and_then(|rest| rest.strip_prefix(" ("))
+        .and_then(|rest| rest.strip_suffix(')'))
+        .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+}
+
+fn same_content(a: &mut impl Read, b: &mut impl Read) -> io::Result<bool> {
+    let (mut buf_a, mut buf_b) = (vec![0u8; 64 * 1024], vec![0u8; 64 * 1024]);
+    loop {
+        let n = read_chunk(a, &mut buf_a)?;
+        if n != read_chunk(b, &mut buf_b)? || buf_a[..n] != buf_b[..n] {
+            return Ok(false);
+        }
+        if n == 0 {
+            return Ok(true);
+        }
+    }
+}
+
+/// Fill `buf` as far as the reader goes; returns the bytes read (0 at the end).
+fn read_chunk(reader: &mut impl Read, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    Ok(filled)
+}
+
+/// `report.pdf` → (`report`, `.pdf`); names without an extension (or dotfiles) keep it all.
+fn split_extension(file_name: &str) -> (&str, &str) {
+    match file_name.rfind('.') {
+        Some(dot) if dot > 0 => (&file_name[..dot], &file_name[dot..]),
+        _ => (file_name, ""),
+    }
+}
+
+fn unique_entry_name(file_name: &str, existing: &HashSet<String>) -> String {
+    if !existing.contains(file_name) {
+        return file_name.to_string();
+    }
+    let (stem, ext) = split_extension(file_name);
+    (1..)
+        .map(|n| format!("{} ({}){}", stem, n, ext))
+        .find(|candidate| !existing.contains(candidate))
+        .expect("unbounded counter always finds a free name")
+}
+
+/// `current_path` value for an undo entry pointing at one archived file.
+pub fn undo_location(zip_path: &Path, entry_name: &str) -> String {
+    format!("{}{}{}", zip_path.display(), UNDO_ENTRY_SEPARATOR, entry_name)
+}
+
+/// Split an [`undo_location`] back into the zip path and entry name.
+/// Entry names never contain `/`, so the last separator is the right one.
+pub fn parse_undo_location(location: &str) -> Option<(PathBuf, &str)> {
+    let (zip, entry) = location.rsplit_once(UNDO_ENTRY_SEPARATOR)?;
+    Some((PathBuf::from(zip), entry))
+}
+
+/// Write one archived entry back out to `to`. The entry stays in the archive.
+pub fn extract_entry(zip_path: &Path, entry_name: &str, to: &Path) -> io::Result<()> {
+    let mut archive = ZipArchive::new(File::open(zip_path)?).map_err(io::Error::other)?;
+    let mut entry = archive.by_name(entry_name).map_err(io::Error::other)?;
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut out = OpenOptions::new().write(true).create_new(true).open(to)?;
+    io::copy(&mut entry, &mut out)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn appends_to_existing_zip_and_renames_duplicates() {
+        let tmp = tempfile::tempdir().unwrap();
+        let zip_path = tmp.path().join("logs.zip");
+        let log = tmp.path().join("app.log");
+
+        fs::write(&log, "first").unwrap();
+        assert_eq!(append_to_zip(&zip_path, &log).unwrap(), "app.log");
+        fs::write(&log, "second").unwrap();
+        assert_eq!(append_to_zip(&zip_path, &log).unwrap(), "app (1).log");
+
+        let restored = tmp.path().join("out").join("app.log");
+        extract_entry(&zip_path, "app.log", &restored).unwrap();
+        assert_eq!(fs::read_to_string(&restored).unwrap(), "first");
+        assert!(extract_entry(&zip_path, "app (1).log", &restored).is_err(), "never overwrites");
+    }
+
+    #[test]
+    fn reappending_the_same_file_reuses_its_entry() {
+        let tmp = tempfile::tempdir().unwrap();
+        let zip_path = tmp.path().join("logs.zip");
+        let log = tmp.path().join("app.log");
+        fs::write(&log, "first").unwrap();
+        append_to_zip(&zip_path, &log).unwrap();
+        fs::write(&log, "second").unwrap();
+        append_to_zip(&zip_path, &log).unwrap();
+        let before = fs::read(&zip_path).unwrap();
+
+        // The original survived an earlier run (its removal failed) and is archived again
+        assert_eq!(append_to_zip(&zip_path, &log).unwrap(), "app (1).log");
+        assert_eq!(fs::read(&zip_path).unwrap(), before);
+        assert!(is_entry_name_for("app (12).log", "app.log"));
+        assert!(!is_entry_name_for("app (x).log", "app.log"));
+    }
+
+    #[test]
+    fn failed_append_leaves_the_archive_untouched() {
+        let tmp = tempfile::tempdir().unwrap();
+        let zip_path = tmp.path().join("logs.zip");
+        let log = tmp.path().join("app.log");
+        fs::write(&log, "first").unwrap();
+        append_to_zip(&zip_path, &log).unwrap();
+        let before = fs::read(&zip_path).unwrap();
+
+        // Reading a directory fails after the new archive has been started
+        let unreadable = tmp.path().join("not-a-file");
+        fs::create_dir(&unreadable).unwrap();
+        assert!(append_to_zip(&zip_path, &unreadable).is_err());
+        assert_eq!(fs::read(&zip_path).unwrap(), before);
+        assert!(!partial_path(&zip_path).exists());
+    }
+
+    #[test]
+    fn undo_location_round_trips() {
+        let location = undo_location(Path::new("/archive/logs!/2026.zip"), "a.log");
+        let (zip, entry) = parse_undo_location(&location).unwrap();
+        assert_eq!(zip, Path::new("/archive/logs!/2026.zip"));
+        assert_eq!(entry, "a.log");
+    }
+}
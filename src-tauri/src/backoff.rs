@@ -0,0 +1,182 @@
+//! Error backoff for rules whose actions keep failing (e.g. an unreachable destination).
+//!
+//! After [`FAILURES_BEFORE_BACKOFF`] consecutive failures a rule's immediate actions are
+//! suppressed for [`BASE_BACKOFF`], doubling with every further failure up to
+//! [`MAX_BACKOFF`]. The first success clears it. State is in memory only, so a restart
+//! retries everything once.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use crate::config::Rule;
+
+/// Consecutive failures tolerated before a rule is suppressed.
+const FAILURES_BEFORE_BACKOFF: u32 = 3;
+
+/// Suppression after the first failure past the threshold.
+const BASE_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Longest suppression, however many failures pile up.
+const MAX_BACKOFF: Duration = Duration::from_secs(6 * 60 * 60);
+
+#[derive(Debug, Clone)]
+struct RuleFailures {
+    rule_name: String,
+    consecutive: u32,
+    last_error: Option<String>,
+    suppressed_until: Option<Instant>,
+}
+
+/// Per-rule failure counts, keyed by rule id.
+#[derive(Debug, Default)]
+pub struct ErrorBackoff {
+    rules: HashMap<String, RuleFailures>,
+}
+
+/// A rule currently failing, for diagnostics.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct RuleBackoff {
+    pub rule_id: String,
+    pub rule_name: String,
+    pub consecutive_failures: u32,
+    pub last_error: Option<String>,
+    /// Seconds until the rule runs again; 0 = not suppressed (yet)
+    pub suppressed_for_secs: u64,
+}
+
+/// How long a rule with `consecutive` failures is suppressed.
+fn backoff_for(consecutive: u32) -> Duration {
+    if consecutive < FAILURES_BEFORE_BACKOFF {
+        return Duration::ZERO;
+    }
+    let doublings = (consecutive - FAILURES_BEFORE_BACKOFF).min(16);
+    (BASE_BACKOFF * 2u32.pow(doublings)).min(MAX_BACKOFF)
+}
+
+impl ErrorBackoff {
+    pub fn is_suppressed(&self, rule_id: &str, now: Instant) -> bool {
+        self.rules
+            .get(rule_id)
+            .and_then(|r| r.suppressed_until)
+            .is_some_and(|until| now < until)
+    }
+
+    pub fn record_failure(&mut self, rule: &Rule, error: Option<&str>, now: Instant) {
+        let entry = self.rules.entry(rule.id.clone()).or_insert_with(|| RuleFailures {
+            rule_name: rule.name.clone(),
+            consecutive: 0,
+            last_error: None,
+            suppressed_until: None,
+        });
+        entry.rule_name = rule.name.clone();
+        entry.consecutive += 1;
+        entry.last_error = error.map(str::to_string);
+        let backoff = backoff_for(entry.consecutive);
+        if !backoff.is_zero() {
+            log::warn!(
+                "Rule '{}' failed {} times in a row; pausing it for {} min",
+                rule.name,
+                entry.consecutive,
+                backoff.as_secs() / 60
+            );
+            entry.suppressed_until = Some(now + backoff);
+        }
+    }
+
+    pub fn record_success(&mut self, rule: &Rule) {
+        self.rules.remove(&rule.id);
+    }
+
+    pub fn snapshot(&self, now: Instant) -> Vec<RuleBackoff> {
+        let mut backoffs: Vec<RuleBackoff> = self
+            .rules
+            .iter()
+            .map(|(id, r)| RuleBackoff {
+                rule_id: id.clone(),
+                rule_name: r.rule_name.clone(),
+                consecutive_failures: r.consecutive,
+                last_error: r.last_error.clone(),
+                suppressed_for_secs: r.suppressed_until.map_or(0, |until| until.saturating_duration_since(now).as_secs()),
+            })
+            .collect();
+        backoffs.sort_by(|a, b| a.rule_name.cmp(&b.rule_name));
+        backoffs
+    }
+}
+
+fn error_backoff() -> &'static Mutex<ErrorBackoff> {
+    static ERROR_BACKOFF: OnceLock<Mutex<ErrorBackoff>> = OnceLock::new();
+    ERROR_BACKOFF.get_or_init(Default::default)
+}
+
+/// Whether `rule`'s actions are currently suppressed after repeated failures.
+pub fn is_backed_off(rule: &Rule) -> bool {
+    error_backoff().lock().is_ok_and(|b| b.is_suppressed(&rule.id, Instant::now()))
+}
+
+/// Count one executed action of `rule` towards (or out of) its backoff.
+pub fn record_outcome(rule: &Rule, success: bool, error: Option<&str>) {
+    if let Ok(mut backoff) = error_backoff().lock() {
+        if success {
+            backoff.record_success(rule);
+        } else {
+            backoff.record_failure(rule, error, Instant::now());
+        }
+    }
+}
+
+/// Rules that are failing, for the watcher diagnostics.
+pub fn current_backoffs() -> Vec<RuleBackoff> {
+    error_backoff().lock().map(|b| b.snapshot(Instant::now())).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{move_to, rule};
+
+    #[test]
+    fn repeated_failures_grow_the_suppression_window() {
+        let r = rule("Broken", "*", move_to(std::path::Path::new("/nowhere")));
+        let mut backoff = ErrorBackoff::default();
+        let start = Instant::now();
+
+        for _ in 0..FAILURES_BEFORE_BACKOFF - 1 {
+            backoff.record_failure(&r, Some("Path not found"), start);
+        }
+        assert!(!backoff.is_suppressed(&r.id, start), "below the threshold nothing is suppressed");
+
+        backoff.record_failure(&r, Some("Path not found"), start);
+        assert!(backoff.is_suppressed(&r.id, start + BASE_BACKOFF - Duration::from_secs(1)));
+        assert!(!backoff.is_suppressed(&r.id, start + BASE_BACKOFF));
+
+        backoff.record_failure(&r, Some("Path not found"), start);
+        assert!(backoff.is_suppressed(&r.id, start + BASE_BACKOFF));
+        assert!(!backoff.is_suppressed(&r.id, start + BASE_BACKOFF * 2));
+
+        let snapshot = backoff.snapshot(start);
+        assert_eq!(snapshot[0].consecutive_failures, FAILURES_BEFORE_BACKOFF + 1);
+        assert_eq!(snapshot[0].suppressed_for_secs, (BASE_BACKOFF * 2).as_secs());
+        assert_eq!(backoff_for(100), MAX_BACKOFF);
+    }
+
+    #[test]
+    fn success_resets_the_backoff() {
+        let r = rule("Flaky", "*", move_to(std::path::Path::new("/share")));
+        let mut backoff = ErrorBackoff::default();
+        let now = Instant::now();
+        for _ in 0..FAILURES_BEFORE_BACKOFF {
+            backoff.record_failure(&r, None, now);
+        }
+        assert!(backoff.is_suppressed(&r.id, now));
+
+        backoff.record_success(&r);
+        assert!(!backoff.is_suppressed(&r.id, now));
+        assert!(backoff.snapshot(now).is_empty());
+
+        // Counting starts over
+        backoff.record_failure(&r, None, now);
+        assert!(!backoff.is_suppressed(&r.id, now));
+    }
+}
@@ -0,0 +1,85 @@
+use crate::scheduler::ScanReport;
+use crate::{config, db, scheduler};
+
+/// Headless mode: `--scan-now` scans every enabled folder once and exits without
+/// opening a window. With `--json` the report (or the error that stopped the scan)
+/// is printed to stdout as a single JSON object; logging always goes to stderr.
+///
+/// Returns the process exit code, or None when the app should start normally.
+pub fn run_headless(args: &[String]) -> Option<i32> {
+    if !args.iter().any(|a| a == "--scan-now") {
+        return None;
+    }
+    let json = args.iter().any(|a| a == "--json");
+
+    let result = db::Database::new()
+        .map_err(|e| format!("Failed to open database: {}", e))
+        .map(|database| scheduler::scan_existing_files_with_report(&config::load_config(), &database));
+
+    let output = match &result {
+        Ok(report) if json => report_json(report),
+        Ok(report) => report_text(report),
+        Err(e) if json => serde_json::json!({ "error": e }).to_string(),
+        Err(e) => format!("Scan failed: {}", e),
+    };
+    println!("{}", output);
+
+    match result {
+        Ok(report) if report.errors.is_empty() => Some(0),
+        _ => Some(1),
+    }
+}
+
+fn report_json(report: &ScanReport) -> String {
+    serde_json::to_string(report).unwrap_or_else(|e| serde_json::json!({ "error": e.to_string() }).to_string())
+}
+
+fn report_text(report: &ScanReport) -> String {
+    let mut lines = vec![format!("{} files processed", report.total_processed)];
+    for folder in &report.folders {
+        let actions: Vec<String> = folder.actions.iter().map(|(action, n)| format!("{} {}", n, action)).collect();
+        lines.push(format!("  {}: {} ({})", folder.path, folder.processed, actions.join(", ")));
+    }
+    for error in &report.errors {
+        lines.push(format!("  error: {}: {}", error.file_path, error.message));
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheduler::{FolderScanReport, ScanError};
+
+    #[test]
+    fn report_serializes_per_folder_and_per_action_counts() {
+        let mut report = ScanReport {
+            total_processed: 3,
+            folders: vec![FolderScanReport {
+                folder_id: "folder-1".to_string(),
+                path: "C:\\Downloads".to_string(),
+                processed: 3,
+                actions: [("move".to_string(), 2), ("scheduled".to_string(), 1)].into(),
+            }],
+            actions: [("move".to_string(), 2), ("scheduled".to_string(), 1)].into(),
+            errors: Vec::new(),
+        };
+        report.errors.push(ScanError { file_path: "C:\\Downloads\\a.pdf".to_string(), message: "Access denied".to_string() });
+
+        let value: serde_json::Value = serde_json::from_str(&report_json(&report)).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "total_processed": 3,
+                "folders": [{
+                    "folder_id": "folder-1",
+                    "path": "C:\\Downloads",
+                    "processed": 3,
+                    "actions": { "move": 2, "scheduled": 1 },
+                }],
+                "actions": { "move": 2, "scheduled": 1 },
+                "errors": [{ "file_path": "C:\\Downloads\\a.pdf", "message": "Access denied" }],
+            })
+        );
+    }
+}
@@ -0,0 +1,28 @@
+use chrono::Utc;
+use tauri::State;
+
+use crate::config;
+use super::AppState;
+
+/// Stop the watcher, scans and scheduled actions everywhere for `minutes`,
+/// e.g. during a big manual reorganization. Returns the RFC3339 time the pause ends.
+#[tauri::command]
+pub fn pause_automation(state: State<AppState>, minutes: u32) -> Result<String, String> {
+    if minutes == 0 {
+        return Err("Pause duration must be at least 1 minute".to_string());
+    }
+    let until = (Utc::now() + chrono::Duration::minutes(minutes as i64)).to_rfc3339();
+    let mut config = state.config.lock().map_err(|e| e.to_string())?;
+    config.settings.paused_until = Some(until.clone());
+    config::save_config(&config)?;
+    Ok(until)
+}
+
+/// Clear a global pause early.
+#[tauri::command]
+pub fn resume_automation(state: State<AppState>) -> Result<(), String> {
+    let mut config = state.config.lock().map_err(|e| e.to_string())?;
+    config.settings.paused_until = None;
+    config::save_config(&config)?;
+    Ok(())
+}
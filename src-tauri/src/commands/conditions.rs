@@ -1,5 +1,18 @@
-use crate::condition;
-use crate::config::Condition;
+use crate::condition::{self, EvalOptions, FileContext, GoldenResult, TokenSpan};
+use crate::config::{Action, Condition, Rule};
+use crate::rules::is_whitelisted_with_relative_path;
+
+/// Outcome of running a whole rule against one sample name in `test_rule`.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct RuleTestResult {
+    pub file_name: String,
+    /// The condition matched (regardless of the whitelist)
+    pub matched: bool,
+    /// The rule's whitelist excludes this name
+    pub whitelisted: bool,
+    /// What the rule would do, e.g. "Move to D:\sorted\pdfs"; None unless it would fire
+    pub action_preview: Option<String>,
+}
 
 /// Parse condition text syntax into a Condition tree.
 /// Returns the parsed condition, or an error with a message.
@@ -14,6 +27,19 @@ pub fn condition_to_text(cond: Condition) -> Result<String, String> {
     Ok(condition::to_text(&cond))
 }
 
+/// Parse condition text and return it fully parenthesized, showing how AND/OR/XOR
+/// precedence grouped it.
+#[tauri::command]
+pub fn condition_to_ast_string(text: String) -> Result<String, String> {
+    Ok(condition::to_explicit_text(&condition::parse(&text)?))
+}
+
+/// Tokenize condition text into typed spans for syntax highlighting in the editor.
+#[tauri::command]
+pub fn tokenize_condition(text: String) -> Result<Vec<TokenSpan>, String> {
+    condition::tokenize_spans(&text)
+}
+
 /// Validate condition text and return any error.
 #[tauri::command]
 pub fn validate_condition_text(text: String) -> Result<(), String> {
@@ -25,3 +51,105 @@ pub fn validate_condition_text(text: String) -> Result<(), String> {
 pub fn test_condition(cond: Condition, file_name: String) -> Result<bool, String> {
     Ok(condition::evaluate(&cond, &file_name))
 }
+
+/// Run a golden file of `{ condition_text, filename, expected_match }` cases and
+/// report each one's outcome, so parser regressions show up after an update.
+#[tauri::command]
+pub fn run_condition_golden_tests(path: String) -> Result<Vec<GoldenResult>, String> {
+    let cases = condition::load_golden_file(std::path::Path::new(&path))?;
+    Ok(condition::run_golden_cases(&cases))
+}
+
+/// Run a rule (condition, whitelist, match_subdirectories) against sample names
+/// without touching the filesystem. Names may be relative paths like `sub/a.pdf`;
+/// metadata conditions (age, size, tags, content) never match here.
+#[tauri::command]
+pub fn test_rule(rule: Rule, file_names: Vec<String>) -> Vec<RuleTestResult> {
+    file_names.iter().map(|name| test_rule_on_name(&rule, name)).collect()
+}
+
+fn test_rule_on_name(rule: &Rule, name: &str) -> RuleTestResult {
+    let relative_path = name.replace('\\', "/");
+    let file_name = relative_path.rsplit('/').next().unwrap_or_default();
+    let whitelisted = is_whitelisted_with_relative_path(file_name, Some(&relative_path), &rule.whitelist);
+    let subject = if rule.match_subdirectories { relative_path.as_str() } else { file_name };
+    let opts = EvalOptions { case_sensitive: rule.case_sensitive };
+    let matched = condition::evaluate_with_options(&rule.condition, subject, &FileContext::default(), opts);
+
+    RuleTestResult {
+        file_name: name.to_string(),
+        matched,
+        whitelisted,
+        action_preview: (matched && !whitelisted).then(|| action_preview(&rule.action)),
+    }
+}
+
+fn action_preview(action: &Action) -> String {
+    let (verb, delay) = match action {
+        Action::Move { destination, keep_source, delay_minutes, .. } => {
+            let verb = if *keep_source { "Copy" } else { "Move" };
+            (format!("{} to {}", verb, destination.display()), *delay_minutes)
+        }
+        Action::MoveByCategory { root } => (format!("Move into {} by category", root.display()), 0),
+        // A rule straight from the UI may still carry the pre-v1 `after_days`
+        Action::Delete { after_days, delay_minutes } if *delay_minutes == 0 => {
+            ("Delete".to_string(), after_days.saturating_mul(24 * 60))
+        }
+        Action::Delete { delay_minutes, .. } => ("Delete".to_string(), *delay_minutes),
+        Action::Archive { destination, archive_name } => {
+            (format!("Archive into {}", destination.join(archive_name).display()), 0)
+        }
+        Action::Link { destination, hard } => {
+            let kind = if *hard { "Hard-link" } else { "Link" };
+            (format!("{} into {}", kind, destination.display()), 0)
+        }
+        Action::Notify { .. } => ("Notify".to_string(), 0),
+        Action::RunCommand { program, .. } => (format!("Run {}", program), 0),
+        Action::Review { .. } => ("Flag for review".to_string(), 0),
+    };
+    match delay {
+        0 => verb,
+        d if d % (24 * 60) == 0 => format!("{} after {} day(s)", verb, d / (24 * 60)),
+        d => format!("{} after {} min", verb, d),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{move_to, rule};
+    use std::path::Path;
+
+    #[test]
+    fn test_rule_applies_whitelist_and_previews_action() {
+        let mut r = rule("PDFs", "*.pdf", move_to(Path::new("/sorted/pdfs")));
+        r.whitelist = vec!["keep*".to_string()];
+
+        let results = test_rule(r, vec!["a.pdf".into(), "keep.pdf".into(), "b.txt".into()]);
+
+        assert!(results[0].matched && !results[0].whitelisted);
+        assert_eq!(results[0].action_preview.as_deref(), Some("Move to /sorted/pdfs"));
+        assert!(results[1].matched && results[1].whitelisted);
+        assert_eq!(results[1].action_preview, None);
+        assert!(!results[2].matched);
+        assert_eq!(results[2].action_preview, None);
+    }
+
+    #[test]
+    fn test_rule_matches_relative_path_when_enabled() {
+        let mut r = rule("Scans", "scans/*", Action::Delete { after_days: 0, delay_minutes: 60 });
+        assert!(!test_rule_on_name(&r, "scans/a.png").matched);
+        r.match_subdirectories = true;
+        let result = test_rule_on_name(&r, "scans\\a.png");
+        assert!(result.matched);
+        assert_eq!(result.action_preview.as_deref(), Some("Delete after 60 min"));
+    }
+
+    #[test]
+    fn delayed_actions_preview_their_delay() {
+        let legacy = rule("Old", "*.log", Action::Delete { after_days: 7, delay_minutes: 0 });
+        assert_eq!(test_rule_on_name(&legacy, "a.log").action_preview.as_deref(), Some("Delete after 7 day(s)"));
+        let now = rule("Now", "*.log", Action::Delete { after_days: 0, delay_minutes: 0 });
+        assert_eq!(test_rule_on_name(&now, "a.log").action_preview.as_deref(), Some("Delete"));
+    }
+}
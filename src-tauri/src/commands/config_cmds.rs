@@ -1,10 +1,12 @@
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use tauri::{AppHandle, State};
 use tauri_plugin_autostart::ManagerExt;
 
-use crate::config::{self, AppConfig};
+use crate::config::{self, AppConfig, AppSettings};
+use crate::portable;
+use crate::scheduler;
 use super::AppState;
 
 #[tauri::command]
@@ -19,6 +21,8 @@ pub fn save_config_cmd(
     state: State<AppState>,
     new_config: AppConfig,
 ) -> Result<(), String> {
+    validate_settings(&new_config.settings)?;
+
     // Sync autostart with the start_with_os setting
     let autostart = app.autolaunch();
     if new_config.settings.start_with_os {
@@ -44,6 +48,30 @@ pub fn save_config_cmd(
     Ok(())
 }
 
+/// Reject settings the app can't run with: an unparseable `deletion_cron` or an unknown
+/// `update_mode`.
+fn validate_settings(settings: &AppSettings) -> Result<(), String> {
+    if let Some(expr) = settings.deletion_cron.as_deref().filter(|e| !e.trim().is_empty()) {
+        scheduler::parse_deletion_cron(expr)?;
+    }
+    if !["off", "notify", "auto"].contains(&settings.update_mode.as_str()) {
+        return Err(format!("Unknown update mode: {}", settings.update_mode));
+    }
+    Ok(())
+}
+
+/// Refuse a portable export imported without a base directory, whose `{BASE}/…`
+/// paths would otherwise be saved literally.
+fn reject_unresolved_paths(config: &AppConfig) -> Result<(), String> {
+    if portable::has_unresolved_paths(config) {
+        return Err(format!(
+            "This is a portable config: its {} paths need a base directory to import",
+            portable::BASE_PLACEHOLDER
+        ));
+    }
+    Ok(())
+}
+
 /// Export current config to a user-specified file path.
 #[tauri::command]
 pub fn export_config(state: State<AppState>, path: String) -> Result<(), String> {
@@ -53,19 +81,44 @@ pub fn export_config(state: State<AppState>, path: String) -> Result<(), String>
     Ok(())
 }
 
-/// Import config from a user-specified file path. Validates JSON before applying.
+/// Export the config with paths under `base_dir` written as `{BASE}/…`, for use on
+/// another machine (see `portable::for_each_path` for which fields). Returns how many
+/// paths were templated.
 #[tauri::command]
-pub fn import_config(state: State<AppState>, path: String) -> Result<(), String> {
-    let file_path = PathBuf::from(&path);
+pub fn export_config_portable(state: State<AppState>, path: String, base_dir: String) -> Result<u32, String> {
+    let mut portable = state.config.lock().map_err(|e| e.to_string())?.clone();
+    let templated = portable::make_portable(&mut portable, Path::new(&base_dir));
+    let json = serde_json::to_string_pretty(&portable).map_err(|e| e.to_string())?;
+    fs::write(&path, json).map_err(|e| format!("Failed to write config: {}", e))?;
+    Ok(templated)
+}
+
+/// Read and validate a config file for import. Also returns the upgraded raw JSON,
+/// which tells which fields the file actually contains.
+fn read_import(path: &str) -> Result<(AppConfig, serde_json::Value), String> {
+    let file_path = PathBuf::from(path);
     if !file_path.exists() {
         return Err("File not found".to_string());
     }
 
     let data = config::read_file_strip_bom(&file_path)?;
 
-    // Validate the JSON parses as a valid AppConfig
-    let imported: AppConfig = serde_json::from_str(&data)
+    // Upgrade older shapes, then validate the JSON parses as a valid AppConfig
+    let mut value: serde_json::Value = serde_json::from_str(&data)
+        .map_err(|e| format!("Invalid config format: {}", e))?;
+    config::upgrade_config(&mut value);
+    let imported: AppConfig = serde_json::from_value(value.clone())
         .map_err(|e| format!("Invalid config format: {}", e))?;
+    Ok((imported, value))
+}
+
+/// Import config from a user-specified file path. Validates JSON and settings before
+/// applying; a portable export needs `import_config_portable` instead.
+#[tauri::command]
+pub fn import_config(state: State<AppState>, path: String) -> Result<(), String> {
+    let (imported, _) = read_import(&path)?;
+    reject_unresolved_paths(&imported)?;
+    validate_settings(&imported.settings)?;
 
     // Save to the actual config location (always as clean UTF-8)
     config::save_config(&imported)?;
@@ -77,6 +130,53 @@ pub fn import_config(state: State<AppState>, path: String) -> Result<(), String>
     Ok(())
 }
 
+/// Import a portable config, resolving its `{BASE}/…` paths against `base_dir`.
+/// Replaces the current config like `import_config`.
+#[tauri::command]
+pub fn import_config_portable(state: State<AppState>, path: String, base_dir: String) -> Result<(), String> {
+    let (mut imported, _) = read_import(&path)?;
+    portable::resolve_portable(&mut imported, Path::new(&base_dir));
+    validate_settings(&imported.settings)?;
+    config::save_config(&imported)?;
+    let mut config = state.config.lock().map_err(|e| e.to_string())?;
+    *config = imported;
+    Ok(())
+}
+
+/// Merge a config file into the current one instead of replacing it (see
+/// `config::merge_imported_config`). Validated like `import_config`. For a portable
+/// export, `base_dir` resolves its `{BASE}/…` paths; without one it is rejected.
+#[tauri::command]
+pub fn merge_config(
+    state: State<AppState>,
+    path: String,
+    overwrite_existing: bool,
+    base_dir: Option<String>,
+) -> Result<config::ConfigMergeSummary, String> {
+    let (mut imported, mut value) = read_import(&path)?;
+    if let Some(base) = base_dir.as_deref() {
+        portable::resolve_portable(&mut imported, Path::new(base));
+        if let Some(settings) = value.get_mut("settings").and_then(|s| s.as_object_mut()) {
+            if settings.contains_key("default_sort_root") {
+                settings.insert("default_sort_root".to_string(), serde_json::json!(imported.settings.default_sort_root));
+            }
+        }
+    }
+    reject_unresolved_paths(&imported)?;
+    let mut config = state.config.lock().map_err(|e| e.to_string())?;
+    let mut merged = config.clone();
+    let summary = config::merge_imported_config(
+        &mut merged,
+        imported,
+        value.get("settings").and_then(|s| s.as_object()),
+        overwrite_existing,
+    )?;
+    validate_settings(&merged.settings)?;
+    config::save_config(&merged)?;
+    *config = merged;
+    Ok(summary)
+}
+
 /// Get the config file path so the user knows where it lives.
 #[tauri::command]
 pub fn get_config_path() -> String {
@@ -1,6 +1,12 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
 use tauri::{Emitter, State};
 
-use crate::db::{ActivityLogEntry, FileIndexEntry, RuleExecutionStats, ScheduledDeletion, UndoEntry};
+use crate::db::{ActivityLogEntry, ActivityLogPage, ActivitySummary, BucketSize, Database, FileIndexEntry, RuleExecutionStats, ScheduledDeletion, ScheduledDeletionPage, TimeBucket, UndoBatch, UndoEntry};
+use crate::log_archive::{self, LogArchiveInfo};
+use crate::rules::friendly_io_error;
 use crate::scheduler;
 use super::AppState;
 
@@ -19,6 +25,127 @@ pub fn get_activity_log(
         .map_err(|e| e.to_string())
 }
 
+/// [`get_activity_log`] plus the total row count for the same folder filter, for paging.
+#[tauri::command]
+pub fn get_activity_log_page(
+    state: State<AppState>,
+    limit: Option<u32>,
+    offset: Option<u32>,
+    folder_id: Option<String>,
+) -> Result<ActivityLogPage, String> {
+    let entries = state
+        .db
+        .get_activity_log(limit.unwrap_or(50), offset.unwrap_or(0), folder_id.as_deref())
+        .map_err(|e| e.to_string())?;
+    let total = state.db.count_activity(folder_id.as_deref()).map_err(|e| e.to_string())?;
+    Ok(ActivityLogPage { entries, total })
+}
+
+/// Full-text search of the activity log (file name, path, rule name, details), best
+/// matches first. Every word must match; an empty query lists the newest entries.
+#[tauri::command]
+pub fn search_activity_log(
+    state: State<AppState>,
+    query: String,
+    limit: Option<u32>,
+    offset: Option<u32>,
+) -> Result<Vec<ActivityLogEntry>, String> {
+    state
+        .db
+        .search_activity(&query, limit.unwrap_or(50), offset.unwrap_or(0))
+        .map_err(|e| e.to_string())
+}
+
+/// Write the activity log to a CSV file at `path`, oldest first, optionally limited to
+/// one folder and to entries at or after `since` (ISO 8601 or `YYYY-MM-DD HH:MM:SS`, UTC).
+/// Returns the number of rows exported.
+#[tauri::command]
+pub fn export_activity_log_csv(
+    state: State<AppState>,
+    path: String,
+    folder_id: Option<String>,
+    since: Option<String>,
+) -> Result<u32, String> {
+    let since = since.map(|s| to_db_timestamp(&s));
+    let file = File::create(&path).map_err(|e| format!("Failed to create {}: {}", path, friendly_io_error(&e)))?;
+    let mut out = BufWriter::new(file);
+    let count = write_activity_csv(&state.db, &mut out, folder_id.as_deref(), since.as_deref())?;
+    out.flush().map_err(|e| friendly_io_error(&e))?;
+    Ok(count)
+}
+
+const ACTIVITY_CSV_HEADER: [&str; 9] =
+    ["timestamp", "action", "result", "rule_name", "folder_id", "file_name", "file_path", "details", "id"];
+
+fn write_activity_csv(
+    db: &Database,
+    out: &mut impl Write,
+    folder_id: Option<&str>,
+    since: Option<&str>,
+) -> Result<u32, String> {
+    write_csv_row(out, &ACTIVITY_CSV_HEADER).map_err(|e| friendly_io_error(&e))?;
+    db.iter_activity_for_export(folder_id, since, |entry| {
+        write_csv_row(
+            out,
+            &[
+                &to_iso_timestamp(&entry.timestamp),
+                &entry.action,
+                &entry.result,
+                entry.rule_name.as_deref().unwrap_or_default(),
+                entry.folder_id.as_deref().unwrap_or_default(),
+                &entry.file_name,
+                &entry.file_path,
+                entry.details.as_deref().unwrap_or_default(),
+                &entry.id,
+            ],
+        )
+    })
+}
+
+/// One CSV record. Fields with a comma, quote or line break are quoted, with inner
+/// quotes doubled (RFC 4180).
+fn write_csv_row(out: &mut impl Write, fields: &[&str]) -> std::io::Result<()> {
+    let line: Vec<String> = fields
+        .iter()
+        .map(|field| {
+            if field.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.to_string()
+            }
+        })
+        .collect();
+    write!(out, "{}\r\n", line.join(","))
+}
+
+/// `2026-03-01 09:30:00` (UTC, as stored) → `2026-03-01T09:30:00Z`.
+fn to_iso_timestamp(db_timestamp: &str) -> String {
+    match chrono::NaiveDateTime::parse_from_str(db_timestamp, "%Y-%m-%d %H:%M:%S") {
+        Ok(t) => t.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+        Err(_) => db_timestamp.to_string(),
+    }
+}
+
+/// Accept an ISO 8601 `since` and turn it into the stored format so it compares as text.
+fn to_db_timestamp(since: &str) -> String {
+    match chrono::DateTime::parse_from_rfc3339(since) {
+        Ok(t) => t.with_timezone(&chrono::Utc).format("%Y-%m-%d %H:%M:%S").to_string(),
+        Err(_) => since.to_string(),
+    }
+}
+
+/// Monthly archives of pruned activity log entries, newest first.
+#[tauri::command]
+pub fn list_log_archives() -> Result<Vec<LogArchiveInfo>, String> {
+    log_archive::list_archives(&log_archive::archive_dir())
+}
+
+/// All entries stored in one log archive (by file name from `list_log_archives`).
+#[tauri::command]
+pub fn read_log_archive(name: String) -> Result<Vec<ActivityLogEntry>, String> {
+    log_archive::read_archive(&log_archive::archive_dir(), &name)
+}
+
 #[tauri::command]
 pub fn get_pending_actions(state: State<AppState>) -> Result<Vec<FileIndexEntry>, String> {
     state.db.get_pending_files().map_err(|e| e.to_string())
@@ -36,7 +163,63 @@ pub fn undo_action(state: State<AppState>, undo_id: String) -> Result<(), String
         .iter()
         .find(|e| e.id == undo_id)
         .ok_or("Undo entry not found")?;
+    undo_and_log(&state.db, entry)
+}
+
+/// Undo entries grouped by the scan or scheduler run that created them, newest first.
+#[tauri::command]
+pub fn get_undo_batches(state: State<AppState>) -> Result<Vec<UndoBatch>, String> {
+    state.db.get_undo_batches().map_err(|e| e.to_string())
+}
+
+/// Outcome of a batch undo: how many entries were restored, and `(undo_id, reason)`
+/// for each one that couldn't be.
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct UndoBatchSummary {
+    pub restored: u32,
+    pub failed: Vec<(String, String)>,
+}
+
+/// Undo many entries in one call. Failures don't stop the batch; every entry that
+/// succeeds is marked restored. Unknown or already-restored IDs are reported as failed.
+#[tauri::command]
+pub fn undo_batch(state: State<AppState>, undo_ids: Vec<String>) -> Result<UndoBatchSummary, String> {
+    let entries = state.db.get_undo_entries().map_err(|e| e.to_string())?;
+    let mut summary = undo_entries(
+        &state.db,
+        entries.iter().filter(|e| undo_ids.contains(&e.id)),
+    );
+    for id in undo_ids.iter().filter(|id| !entries.iter().any(|e| &e.id == *id)) {
+        summary.failed.push((id.clone(), "Undo entry not found".to_string()));
+    }
+    Ok(summary)
+}
 
+/// Undo every not-yet-restored action recorded after `timestamp` (`YYYY-MM-DD HH:MM:SS`, UTC),
+/// e.g. everything a misconfigured rule did in the last scan.
+#[tauri::command]
+pub fn undo_all_since(state: State<AppState>, timestamp: String) -> Result<UndoBatchSummary, String> {
+    let entries = state
+        .db
+        .get_undo_entries_since(&timestamp)
+        .map_err(|e| e.to_string())?;
+    Ok(undo_entries(&state.db, entries.iter()))
+}
+
+/// Undo entries in the given order (callers pass newest first, so chained moves unwind).
+fn undo_entries<'a>(db: &Database, entries: impl Iterator<Item = &'a UndoEntry>) -> UndoBatchSummary {
+    let mut summary = UndoBatchSummary::default();
+    for entry in entries {
+        match undo_and_log(db, entry) {
+            Ok(()) => summary.restored += 1,
+            Err(reason) => summary.failed.push((entry.id.clone(), reason)),
+        }
+    }
+    summary
+}
+
+/// Restore one entry, mark it restored, and log the outcome to the activity log.
+fn undo_and_log(db: &Database, entry: &UndoEntry) -> Result<(), String> {
     let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
     let file_name = std::path::Path::new(&entry.original_path)
         .file_name()
@@ -44,8 +227,8 @@ pub fn undo_action(state: State<AppState>, undo_id: String) -> Result<(), String
         .unwrap_or("unknown")
         .to_string();
 
-    if entry.current_path.is_none() {
-        let _ = state.db.insert_activity(
+    if let Err(reason) = restore_undo_file(entry) {
+        let _ = db.insert_activity(
             &uuid::Uuid::new_v4().to_string(),
             &entry.original_path,
             &file_name,
@@ -54,43 +237,14 @@ pub fn undo_action(state: State<AppState>, undo_id: String) -> Result<(), String
             None,
             &now,
             "error",
-            Some("Undo is unavailable for recycle-bin deletions"),
+            Some(&reason),
         );
-        return Err("Undo is unavailable for recycle-bin deletions".to_string());
+        return Err(reason);
     }
 
-    if let Some(ref current_path) = entry.current_path {
-        let from = std::path::Path::new(current_path);
-        let to = std::path::Path::new(&entry.original_path);
-
-        if !from.exists() {
-            let _ = state.db.insert_activity(
-                &uuid::Uuid::new_v4().to_string(),
-                &entry.original_path,
-                &file_name,
-                "undo",
-                None,
-                None,
-                &now,
-                "error",
-                Some("Undo source file no longer exists"),
-            );
-            return Err("Undo source file no longer exists".to_string());
-        }
+    db.mark_restored(&entry.id).map_err(|e| e.to_string())?;
 
-        // Ensure parent dir exists
-        if let Some(parent) = to.parent() {
-            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
-        }
-        std::fs::rename(from, to).map_err(|e| e.to_string())?;
-    }
-
-    state
-        .db
-        .mark_restored(&undo_id)
-        .map_err(|e| e.to_string())?;
-
-    let _ = state.db.insert_activity(
+    let _ = db.insert_activity(
         &uuid::Uuid::new_v4().to_string(),
         &entry.original_path,
         &file_name,
@@ -105,6 +259,86 @@ pub fn undo_action(state: State<AppState>, undo_id: String) -> Result<(), String
     Ok(())
 }
 
+/// Reverse the file operation recorded in an undo entry.
+/// - moves/renames (`auto_move`, `rename`, ...): move `current_path` back to `original_path`
+/// - copies (`auto_copy`): remove the copy; the original was never touched
+/// - links (`auto_link`): remove the link
+/// - staged deletions (`auto_delete`, ... with a `current_path`): move the file back out
+///   of the staging folder; recycle-bin deletions have no path and can't be undone here
+/// - removed empty folders (`remove_empty_dir`): create the folder again
+/// - archives (`auto_archive`): extract the file from the zip (the zip entry is kept;
+///   the removed original has its own `archive_source` deletion entry)
+///
+/// Never overwrites: if something else now occupies the original path, the undo fails.
+fn restore_undo_file(entry: &UndoEntry) -> Result<(), String> {
+    if entry.action == "remove_empty_dir" {
+        return std::fs::create_dir_all(&entry.original_path)
+            .map_err(|e| format!("Failed to recreate folder: {}", friendly_io_error(&e)));
+    }
+    let current_path = entry
+        .current_path
+        .as_ref()
+        .ok_or("Undo is unavailable for recycle-bin deletions")?;
+    if entry.action == "auto_archive" {
+        let (zip_path, entry_name) = crate::archive::parse_undo_location(current_path)
+            .ok_or("Undo entry doesn't point into an archive")?;
+        let to = std::path::Path::new(&entry.original_path);
+        if to.exists() {
+            return Err(format!(
+                "Cannot undo: \"{}\" already exists at the original location",
+                to.file_name().unwrap_or_default().to_string_lossy()
+            ));
+        }
+        return crate::archive::extract_entry(&zip_path, entry_name, to)
+            .map_err(|e| format!("Failed to extract from archive: {}", friendly_io_error(&e)));
+    }
+    if entry.action == "auto_link" {
+        // Links never touched the original — undo just removes the link (even a dangling one)
+        let link = std::path::Path::new(current_path);
+        return std::fs::remove_file(link)
+            .or_else(|_| std::fs::remove_dir(link))
+            .map_err(|e| format!("Failed to remove link: {}", friendly_io_error(&e)));
+    }
+    let from = std::path::Path::new(current_path);
+    let to = std::path::Path::new(&entry.original_path);
+
+    if !from.exists() {
+        return Err("Undo source file no longer exists".to_string());
+    }
+
+    if entry.action == "auto_copy" && to.exists() {
+        let removed = if from.is_dir() {
+            std::fs::remove_dir_all(from)
+        } else {
+            std::fs::remove_file(from)
+        };
+        return removed.map_err(|e| format!("Failed to remove copy: {}", friendly_io_error(&e)));
+    }
+
+    if to.exists() {
+        let name = to.file_name().unwrap_or_default().to_string_lossy();
+        return Err(format!(
+            "Cannot undo: \"{}\" already exists at the original location",
+            name
+        ));
+    }
+
+    // Ensure parent dir exists
+    if let Some(parent) = to.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    std::fs::rename(from, to)
+        .or_else(|e| {
+            // Different drive (e.g. out of the staging folder): copy then remove
+            if !from.is_file() {
+                return Err(e);
+            }
+            crate::rules::copy_file_preserving_times(from, to)?;
+            std::fs::remove_file(from)
+        })
+        .map_err(|e| friendly_io_error(&e))
+}
+
 // ── Scheduled Deletions ─────────────────────────────────────
 
 /// Get all files currently scheduled for deletion.
@@ -116,6 +350,20 @@ pub fn get_scheduled_deletions(state: State<AppState>) -> Result<Vec<ScheduledDe
         .map_err(|e| e.to_string())
 }
 
+/// One page of scheduled deletions plus the total, optionally for one folder.
+#[tauri::command]
+pub fn get_scheduled_deletions_paged(
+    state: State<AppState>,
+    limit: u32,
+    offset: u32,
+    folder_id: Option<String>,
+) -> Result<ScheduledDeletionPage, String> {
+    state
+        .db
+        .get_scheduled_deletions_paged(limit, offset, folder_id.as_deref())
+        .map_err(|e| e.to_string())
+}
+
 /// Cancel a scheduled deletion by ID.
 #[tauri::command]
 pub fn cancel_scheduled_deletion(
@@ -138,9 +386,44 @@ pub fn run_deletions(app: tauri::AppHandle, state: State<AppState>) -> Result<u3
         let guard = state.config.lock().map_err(|e| e.to_string())?;
         guard.clone()
     };
-    let count = scheduler::process_due_deletions_with_config(&state.db, Some(&config));
+    let run = scheduler::process_due_deletions_with_config(&state.db, Some(&config), true);
+    if run.awaiting_approval > 0 {
+        let _ = app.emit("deletions-awaiting-approval", run.awaiting_approval);
+    }
     let _ = app.emit("dashboard-data-changed", ());
-    Ok(count)
+    Ok(run.processed)
+}
+
+/// When the scheduler will next delete a file, as an RFC3339 timestamp: the first
+/// scheduler tick after the earliest pending deletion falls due (or after a global
+/// pause ends), honouring `deletion_cron`. `None` if nothing is scheduled, deletions
+/// wait for approval, or the cron schedule never fires again.
+#[tauri::command]
+pub fn get_next_deletion_run(state: State<AppState>) -> Result<Option<String>, String> {
+    let settings = state.config.lock().map_err(|e| e.to_string())?.settings.clone();
+    if settings.require_deletion_approval {
+        return Ok(None);
+    }
+    let max_attempts = settings.max_deletion_attempts.max(1);
+    let next_due = state.db.get_next_due_deletion(max_attempts, crate::scheduler::retry_backoff);
+    let Some(due) = next_due.map_err(|e| e.to_string())? else {
+        return Ok(None);
+    };
+    let mut due = chrono::NaiveDateTime::parse_from_str(&due, "%Y-%m-%d %H:%M:%S")
+        .map_err(|e| e.to_string())?
+        .and_utc();
+    let now = chrono::Utc::now();
+    if settings.is_paused_at(now) {
+        if let Some(until) = settings.paused_until.as_deref().and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok()) {
+            due = due.max(until.to_utc());
+        }
+    }
+    let next = state
+        .scheduler_clock
+        .lock()
+        .map_err(|e| e.to_string())?
+        .next_deletion_run(due, settings.deletion_cron.as_deref(), now);
+    Ok(next.map(|t| t.to_rfc3339()))
 }
 
 /// Immediately delete selected scheduled deletions by IDs. Returns count deleted.
@@ -150,7 +433,54 @@ pub fn delete_scheduled_now(
     state: State<AppState>,
     deletion_ids: Vec<String>,
 ) -> Result<u32, String> {
-    let count = scheduler::process_selected_deletions_now(&state.db, &deletion_ids);
+    let config = state.config.lock().map_err(|e| e.to_string())?.clone();
+    let count = scheduler::process_selected_deletions_now(&state.db, &deletion_ids, &config);
+    let _ = app.emit("dashboard-data-changed", ());
+    Ok(count)
+}
+
+/// Approve one deletion held back by `require_deletion_approval` and run it now.
+/// Returns 1 if the file was deleted, 0 if it failed (see the activity log).
+#[tauri::command]
+pub fn approve_deletion(
+    app: tauri::AppHandle,
+    state: State<AppState>,
+    deletion_id: String,
+) -> Result<u32, String> {
+    let awaiting = state.db.get_awaiting_approval_ids().map_err(|e| e.to_string())?;
+    if !awaiting.contains(&deletion_id) {
+        return Err("Deletion is not awaiting approval".to_string());
+    }
+    let config = state.config.lock().map_err(|e| e.to_string())?.clone();
+    let count = scheduler::process_selected_deletions_now(&state.db, &[deletion_id], &config);
+    let _ = app.emit("dashboard-data-changed", ());
+    Ok(count)
+}
+
+/// Approve and run every deletion awaiting approval. Returns count deleted.
+#[tauri::command]
+pub fn approve_all_due(app: tauri::AppHandle, state: State<AppState>) -> Result<u32, String> {
+    let awaiting = state.db.get_awaiting_approval_ids().map_err(|e| e.to_string())?;
+    let config = state.config.lock().map_err(|e| e.to_string())?.clone();
+    let count = scheduler::process_selected_deletions_now(&state.db, &awaiting, &config);
+    let _ = app.emit("dashboard-data-changed", ());
+    Ok(count)
+}
+
+/// Move every file a rule has queued in a folder into `destination` instead of letting
+/// the scheduled action run. Returns the number of files archived.
+#[tauri::command]
+pub fn archive_scheduled_deletions(
+    app: tauri::AppHandle,
+    state: State<AppState>,
+    folder_id: String,
+    rule_name: String,
+    destination: String,
+) -> Result<u32, String> {
+    if destination.trim().is_empty() {
+        return Err("Choose a destination folder".to_string());
+    }
+    let count = scheduler::archive_scheduled_deletions(&state.db, &folder_id, &rule_name, Path::new(&destination));
     let _ = app.emit("dashboard-data-changed", ());
     Ok(count)
 }
@@ -171,3 +501,151 @@ pub fn get_rule_execution_stats(
         .get_rule_execution_stats(&folder_id, &since)
         .map_err(|e| e.to_string())
 }
+
+/// Dashboard summary of the last `days` days: files processed and bytes moved per day,
+/// actions by type, and the busiest rules.
+#[tauri::command]
+pub fn get_activity_summary(state: State<AppState>, days: u32) -> Result<ActivitySummary, String> {
+    let since = (chrono::Utc::now() - chrono::Duration::days(days as i64))
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string();
+    state.db.get_activity_summary(&since).map_err(|e| e.to_string())
+}
+
+/// Moved/deleted/error counts per day or hour over the last `days` days, for charts.
+/// Quiet periods come back as zero buckets, so the series is continuous.
+#[tauri::command]
+pub fn get_activity_timeseries(
+    state: State<AppState>,
+    days: u32,
+    bucket: BucketSize,
+    folder_id: Option<String>,
+) -> Result<Vec<TimeBucket>, String> {
+    state
+        .db
+        .get_activity_timeseries(chrono::Utc::now().naive_utc(), days, bucket, folder_id.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn undo_entry(original: &std::path::Path, current: &std::path::Path, action: &str) -> UndoEntry {
+        UndoEntry {
+            id: "undo-1".to_string(),
+            original_path: original.to_string_lossy().to_string(),
+            current_path: Some(current.to_string_lossy().to_string()),
+            action: action.to_string(),
+            timestamp: "2026-01-01 00:00:00".to_string(),
+            expires_at: "2026-01-08 00:00:00".to_string(),
+            restored: false,
+            batch_id: None,
+        }
+    }
+
+    #[test]
+    fn activity_csv_quotes_awkward_paths() {
+        let db = Database::open_in_memory().unwrap();
+        let log = |id: &str, path: &str, folder: &str, ts: &str| {
+            let name = Path::new(path).file_name().unwrap().to_string_lossy().to_string();
+            db.insert_activity(id, path, &name, "moved", Some("PDFs"), Some(folder), ts, "success", None).unwrap();
+        };
+        log("a1", "/docs/old.pdf", "folder-1", "2026-01-01 08:00:00");
+        log("a2", "/docs/a, \"quoted\"\nname.pdf", "folder-1", "2026-02-01 09:30:00");
+        log("a3", "/other/b.pdf", "folder-2", "2026-02-02 10:00:00");
+
+        let mut out = Vec::new();
+        let since = to_db_timestamp("2026-01-15T00:00:00Z");
+        let count = write_activity_csv(&db, &mut out, Some("folder-1"), Some(&since)).unwrap();
+        assert_eq!(count, 1);
+        let csv = String::from_utf8(out).unwrap();
+        assert_eq!(
+            csv,
+            "timestamp,action,result,rule_name,folder_id,file_name,file_path,details,id\r\n\
+             2026-02-01T09:30:00Z,moved,success,PDFs,folder-1,\"a, \"\"quoted\"\"\nname.pdf\",\
+             \"/docs/a, \"\"quoted\"\"\nname.pdf\",,a2\r\n"
+        );
+    }
+
+    #[test]
+    fn batch_undo_continues_past_failures() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::open_in_memory().unwrap();
+        let sorted = dir.path().join("sorted");
+        fs::create_dir_all(&sorted).unwrap();
+        for (id, name, ts) in [("u1", "a.pdf", "2026-01-02 10:00:00"), ("u2", "b.pdf", "2026-01-02 11:00:00")] {
+            let original = dir.path().join(name);
+            let current = sorted.join(name);
+            db.insert_undo(id, &original.to_string_lossy(), Some(&current.to_string_lossy()), "auto_move", ts, "2026-01-09 00:00:00", None)
+                .unwrap();
+        }
+        // Only a.pdf is still at its moved location; b.pdf's undo will fail
+        fs::write(sorted.join("a.pdf"), "a").unwrap();
+
+        let entries = db.get_undo_entries_since("2026-01-01 00:00:00").unwrap();
+        let summary = undo_entries(&db, entries.iter());
+
+        assert_eq!(summary.restored, 1);
+        assert_eq!(summary.failed.len(), 1);
+        assert_eq!(summary.failed[0].0, "u2");
+        assert!(dir.path().join("a.pdf").exists());
+        let remaining: Vec<String> = db.get_undo_entries().unwrap().into_iter().map(|e| e.id).collect();
+        assert_eq!(remaining, vec!["u2"]);
+    }
+
+    #[test]
+    fn undo_since_skips_older_entries() {
+        let db = Database::open_in_memory().unwrap();
+        db.insert_undo("old", "/a", Some("/b"), "auto_move", "2026-01-01 09:00:00", "2026-01-08 00:00:00", None).unwrap();
+        db.insert_undo("new", "/c", Some("/d"), "auto_move", "2026-01-01 12:00:00", "2026-01-08 00:00:00", None).unwrap();
+        let ids: Vec<String> = db
+            .get_undo_entries_since("2026-01-01 10:00:00")
+            .unwrap()
+            .into_iter()
+            .map(|e| e.id)
+            .collect();
+        assert_eq!(ids, vec!["new"]);
+    }
+
+    #[test]
+    fn rename_undo_restores_old_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let old_name = dir.path().join("IMG_0001.jpg");
+        let new_name = dir.path().join("2026-01-01 holiday.jpg");
+        fs::write(&new_name, "photo").unwrap();
+
+        restore_undo_file(&undo_entry(&old_name, &new_name, "rename")).unwrap();
+        assert_eq!(fs::read_to_string(&old_name).unwrap(), "photo");
+        assert!(!new_name.exists());
+    }
+
+    #[test]
+    fn undo_refuses_to_overwrite_taken_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let old_name = dir.path().join("report.pdf");
+        let new_name = dir.path().join("report-final.pdf");
+        fs::write(&new_name, "renamed").unwrap();
+        fs::write(&old_name, "someone else").unwrap();
+
+        let err = restore_undo_file(&undo_entry(&old_name, &new_name, "rename")).unwrap_err();
+        assert!(err.contains("already exists"), "{}", err);
+        assert_eq!(fs::read_to_string(&old_name).unwrap(), "someone else");
+        assert!(new_name.exists());
+    }
+
+    #[test]
+    fn copy_undo_removes_the_copy() {
+        let dir = tempfile::tempdir().unwrap();
+        let original = dir.path().join("a.txt");
+        let copy = dir.path().join("backup").join("a.txt");
+        fs::create_dir_all(copy.parent().unwrap()).unwrap();
+        fs::write(&original, "x").unwrap();
+        fs::write(&copy, "x").unwrap();
+
+        restore_undo_file(&undo_entry(&original, &copy, "auto_copy")).unwrap();
+        assert!(original.exists());
+        assert!(!copy.exists());
+    }
+}
@@ -73,6 +73,17 @@ pub fn enforce_storage_limit(state: State<AppState>) -> Result<u64, String> {
         .map_err(|e| e.to_string())
 }
 
+/// Run SQLite's integrity check on the database. False means it's damaged; the next
+/// launch restores it from the backup taken at the last healthy start.
+#[tauri::command]
+pub fn check_db_integrity(state: State<AppState>) -> Result<bool, String> {
+    let ok = state.db.integrity_check().map_err(|e| e.to_string())?;
+    if ok {
+        log::info!("Database integrity check passed");
+    }
+    Ok(ok)
+}
+
 /// Get the database file path for reference.
 #[tauri::command]
 pub fn get_db_path() -> String {
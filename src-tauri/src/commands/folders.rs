@@ -1,9 +1,12 @@
+use std::collections::HashMap;
 use std::path::PathBuf;
 
+use chrono::Utc;
 use tauri::State;
 use uuid::Uuid;
 
-use crate::config::{self, WatchedFolder};
+use crate::config::{self, Action, AppConfig, WatchedFolder};
+use crate::db::FolderSummary;
 use super::AppState;
 
 #[tauri::command]
@@ -12,19 +15,113 @@ pub fn get_watched_folders(state: State<AppState>) -> Result<Vec<WatchedFolder>,
     Ok(config.folders.clone())
 }
 
+/// Everything the dashboard shows for one folder, gathered in a single call.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct FolderOverview {
+    pub folder: WatchedFolder,
+    pub enabled: bool,
+    /// The folder path currently exists on disk
+    pub available: bool,
+    pub rule_count: u32,
+    pub scheduled_count: u32,
+    pub last_activity_ts: Option<String>,
+    /// The watcher is running and this folder is enabled, available, and not paused
+    pub watch_active: bool,
+}
+
+/// Watched folders with live status, scheduled counts, and last activity in one call.
+#[tauri::command]
+pub fn get_folders_overview(state: State<AppState>) -> Result<Vec<FolderOverview>, String> {
+    let folders = state.config.lock().map_err(|e| e.to_string())?.folders.clone();
+    let watcher_running = state.watcher.lock().map_err(|e| e.to_string())?.is_running();
+    let summaries = state.db.get_folder_summaries().map_err(|e| e.to_string())?;
+    Ok(build_folders_overview(folders, &summaries, watcher_running))
+}
+
+fn build_folders_overview(
+    folders: Vec<WatchedFolder>,
+    summaries: &HashMap<String, FolderSummary>,
+    watcher_running: bool,
+) -> Vec<FolderOverview> {
+    let now = Utc::now();
+    folders
+        .into_iter()
+        .map(|folder| {
+            let summary = summaries.get(&folder.id).cloned().unwrap_or_default();
+            let available = folder.path.is_dir();
+            FolderOverview {
+                enabled: folder.enabled,
+                available,
+                rule_count: folder.rules.len() as u32,
+                scheduled_count: summary.scheduled_count,
+                last_activity_ts: summary.last_activity_ts,
+                watch_active: watcher_running && folder.enabled && available && !folder.is_paused_at(now),
+                folder,
+            }
+        })
+        .collect()
+}
+
 #[tauri::command]
 pub fn add_watched_folder(state: State<AppState>, path: String) -> Result<WatchedFolder, String> {
     let mut config = state.config.lock().map_err(|e| e.to_string())?;
+    let folder = push_new_folder(&mut config, &path)?;
+    config::save_config(&config)?;
+
+    Ok(folder)
+}
+
+/// Result of add_watched_folders.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct FolderAddReport {
+    /// Each path's outcome, in the order given
+    pub outcomes: Vec<Result<WatchedFolder, String>>,
+    /// Why the running watcher couldn't be restarted; the folders are still saved
+    pub watcher_error: Option<String>,
+}
+
+/// Add several folders at once, returning each path's outcome in order. Missing paths
+/// and folders already watched (or listed twice) fail without stopping the rest.
+/// The config is saved once, and a running watcher is restarted to pick them up.
+#[tauri::command]
+pub fn add_watched_folders(state: State<AppState>, paths: Vec<String>) -> Result<FolderAddReport, String> {
+    let mut config = state.config.lock().map_err(|e| e.to_string())?;
+    let outcomes: Vec<_> = paths.iter().map(|path| push_new_folder(&mut config, path)).collect();
+    let mut watcher_error = None;
+    if outcomes.iter().any(|o| o.is_ok()) {
+        config::save_config(&config)?;
+        let mut watcher = state.watcher.lock().map_err(|e| e.to_string())?;
+        if watcher.is_running() {
+            if let Err(e) = watcher.start(&config, state.db.clone(), state.config.clone()) {
+                log::error!("Failed to restart watcher after adding folders: {}", e);
+                watcher_error = Some(e);
+            }
+        }
+    }
+    Ok(FolderAddReport { outcomes, watcher_error })
+}
+
+/// Describes the watched folder `path` contains or lies inside, so the UI can warn
+/// before adding it.
+#[tauri::command]
+pub fn check_folder_overlap(state: State<AppState>, path: String) -> Result<Option<String>, String> {
+    let config = state.config.lock().map_err(|e| e.to_string())?;
+    Ok(config::nested_folder_conflict(&config, &PathBuf::from(path)))
+}
 
-    let folder_path = PathBuf::from(&path);
+/// Validate `path` and append it to `config.folders` as a new, rule-less folder.
+fn push_new_folder(config: &mut AppConfig, path: &str) -> Result<WatchedFolder, String> {
+    let folder_path = PathBuf::from(path);
     if !folder_path.exists() {
         return Err(format!("Folder does not exist: {}", path));
     }
+    let folder_path = config::normalize_folder_path(&folder_path);
 
-    // Check for duplicates
-    if config.folders.iter().any(|f| f.path == folder_path) {
+    // Check for duplicates (folders saved before paths were normalized may be stored differently)
+    if config.folders.iter().any(|f| config::normalize_folder_path(&f.path) == folder_path) {
         return Err("Folder is already being watched".to_string());
     }
+    config::check_nested_folder(config, &folder_path)?;
 
     let folder = WatchedFolder {
         id: Uuid::new_v4().to_string(),
@@ -33,23 +130,70 @@ pub fn add_watched_folder(state: State<AppState>, path: String) -> Result<Watche
         rules: Vec::new(),
         whitelist: Vec::new(),
         watch_subdirectories: false,
+        default_action: None,
+        default_after_days: 0,
+        paused_until: None,
+        auto_watch_new_subdirs: None,
     };
 
     config.folders.push(folder.clone());
-    config::save_config(&config)?;
-
     Ok(folder)
 }
 
+/// The folder a `--watch-folder` launch (the Explorer context menu) asks for: the ID of
+/// the folder already watched at `path`, or of a new one added and saved after the same
+/// checks as `add_watched_folder`.
+pub fn watch_folder_from_cli(config: &mut AppConfig, path: &str) -> Result<String, String> {
+    let normalized = config::normalize_folder_path(std::path::Path::new(path));
+    if let Some(existing) = config.folders.iter().find(|f| config::normalize_folder_path(&f.path) == normalized) {
+        return Ok(existing.id.clone());
+    }
+    let folder = push_new_folder(config, path)?;
+    config::save_config(config)?;
+    Ok(folder.id)
+}
+
+/// Stop watching a folder. It moves to the archive with its rules so it can be restored.
 #[tauri::command]
 pub fn remove_watched_folder(state: State<AppState>, folder_id: String) -> Result<(), String> {
     let mut config = state.config.lock().map_err(|e| e.to_string())?;
-    config.folders.retain(|f| f.id != folder_id);
+    config::archive_folder(&mut config, &folder_id);
     config::save_config(&config)?;
     let _ = state.db.remove_scheduled_deletions_by_folder(&folder_id);
     Ok(())
 }
 
+/// Folders removed from the watch list that can still be restored.
+#[tauri::command]
+pub fn get_archived_folders(state: State<AppState>) -> Result<Vec<WatchedFolder>, String> {
+    let config = state.config.lock().map_err(|e| e.to_string())?;
+    Ok(config.archived_folders.clone())
+}
+
+/// Put an archived folder back on the watch list with its rules.
+/// Restart the watcher afterwards to start watching it.
+#[tauri::command]
+pub fn restore_watched_folder(state: State<AppState>, folder_id: String) -> Result<WatchedFolder, String> {
+    let mut config = state.config.lock().map_err(|e| e.to_string())?;
+    let folder = config::restore_archived_folder(&mut config, &folder_id)?;
+    config::save_config(&config)?;
+    Ok(folder)
+}
+
+/// Permanently delete an archived folder, its rules, and its DB rows.
+#[tauri::command]
+pub fn purge_archived_folder(state: State<AppState>, folder_id: String) -> Result<(), String> {
+    let mut config = state.config.lock().map_err(|e| e.to_string())?;
+    let before = config.archived_folders.len();
+    config.archived_folders.retain(|f| f.id != folder_id);
+    if config.archived_folders.len() == before {
+        return Err("Archived folder not found".to_string());
+    }
+    config::save_config(&config)?;
+    state.db.delete_folder_data(&folder_id).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
 #[tauri::command]
 pub fn toggle_watched_folder(
     state: State<AppState>,
@@ -78,6 +222,163 @@ pub fn toggle_watch_subdirectories(
     Ok(())
 }
 
+/// Set (or clear) the preset applied to new subfolders created directly in this folder.
+/// Restart the watcher afterwards so the folder is watched recursively.
+#[tauri::command]
+pub fn set_auto_watch_new_subdirs(
+    state: State<AppState>,
+    folder_id: String,
+    preset: Option<String>,
+) -> Result<(), String> {
+    let mut config = state.config.lock().map_err(|e| e.to_string())?;
+    if let Some(name) = &preset {
+        if !config.presets.iter().any(|p| &p.name == name) {
+            return Err(format!("Preset '{}' not found", name));
+        }
+    }
+    let folder = config
+        .folders
+        .iter_mut()
+        .find(|f| f.id == folder_id)
+        .ok_or("Folder not found")?;
+    folder.auto_watch_new_subdirs = preset;
+    config::save_config(&config)?;
+    Ok(())
+}
+
+/// Result of checking configured folders against the filesystem.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ReconcileReport {
+    /// Folders whose path no longer exists (as they were before being dropped/disabled)
+    pub missing: Vec<WatchedFolder>,
+    /// Ids of folders whose path exists
+    pub ok: Vec<String>,
+}
+
+/// Find watched folders whose path is gone from disk. With `remove_missing` they're
+/// archived like [`remove_watched_folder`] does (dropping their scheduled actions);
+/// otherwise they're disabled. Unlike the overview's `available` flag, this changes the
+/// saved config, and a running watcher is restarted to match.
+#[tauri::command]
+pub fn reconcile_folders(state: State<AppState>, remove_missing: bool) -> Result<ReconcileReport, String> {
+    let mut config = state.config.lock().map_err(|e| e.to_string())?;
+    let report = reconcile(&mut config, remove_missing);
+    if !report.missing.is_empty() {
+        config::save_config(&config)?;
+        if remove_missing {
+            for folder in &report.missing {
+                let _ = state.db.remove_scheduled_deletions_by_folder(&folder.id);
+            }
+        }
+        let mut watcher = state.watcher.lock().map_err(|e| e.to_string())?;
+        if watcher.is_running() {
+            if let Err(e) = watcher.start(&config, state.db.clone(), state.config.clone()) {
+                log::error!("Failed to restart watcher after reconciling folders: {}", e);
+            }
+        }
+    }
+    Ok(report)
+}
+
+fn reconcile(config: &mut AppConfig, remove_missing: bool) -> ReconcileReport {
+    let (present, missing): (Vec<_>, Vec<_>) =
+        config.folders.iter().partition(|f| f.path.is_dir());
+    let report = ReconcileReport {
+        missing: missing.into_iter().cloned().collect(),
+        ok: present.into_iter().map(|f| f.id.clone()).collect(),
+    };
+    if remove_missing {
+        for folder in &report.missing {
+            config::archive_folder(config, &folder.id);
+        }
+    } else {
+        for folder in config.folders.iter_mut().filter(|f| !report.ok.contains(&f.id)) {
+            folder.enabled = false;
+        }
+    }
+    report
+}
+
+/// Merge one watched folder into another: the source's rules (with fresh ids) and
+/// whitelist move to the target, DB history/schedules are re-pointed, and the source
+/// folder is removed.
+#[tauri::command]
+pub fn merge_folders(
+    state: State<AppState>,
+    source_folder_id: String,
+    target_folder_id: String,
+) -> Result<(), String> {
+    let mut config = state.config.lock().map_err(|e| e.to_string())?;
+    let mut merged = config.clone();
+    let rule_id_map = config::merge_folders(&mut merged, &source_folder_id, &target_folder_id)?;
+    config::save_config(&merged)?;
+    *config = merged;
+
+    state
+        .db
+        .reassign_folder_data(&source_folder_id, &target_folder_id, &rule_id_map)
+        .map_err(|e| format!("Folders merged, but updating history failed: {}", e))?;
+    Ok(())
+}
+
+// ── Folder Pause Commands ───────────────────────────────────
+
+/// Whether a folder is currently paused, and until when.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct FolderPauseStatus {
+    pub paused: bool,
+    /// RFC3339; None when not paused (or the pause has expired)
+    pub paused_until: Option<String>,
+}
+
+/// Stop the watcher and scheduler from touching a folder for `minutes`.
+/// Returns the RFC3339 time the pause ends.
+#[tauri::command]
+pub fn pause_folder(state: State<AppState>, folder_id: String, minutes: u32) -> Result<String, String> {
+    if minutes == 0 {
+        return Err("Pause duration must be at least 1 minute".to_string());
+    }
+    let until = (Utc::now() + chrono::Duration::minutes(minutes as i64)).to_rfc3339();
+    let mut config = state.config.lock().map_err(|e| e.to_string())?;
+    let folder = config
+        .folders
+        .iter_mut()
+        .find(|f| f.id == folder_id)
+        .ok_or("Folder not found")?;
+    folder.paused_until = Some(until.clone());
+    config::save_config(&config)?;
+    Ok(until)
+}
+
+/// Clear a folder's pause early.
+#[tauri::command]
+pub fn resume_folder(state: State<AppState>, folder_id: String) -> Result<(), String> {
+    let mut config = state.config.lock().map_err(|e| e.to_string())?;
+    let folder = config
+        .folders
+        .iter_mut()
+        .find(|f| f.id == folder_id)
+        .ok_or("Folder not found")?;
+    folder.paused_until = None;
+    config::save_config(&config)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_folder_pause_status(state: State<AppState>, folder_id: String) -> Result<FolderPauseStatus, String> {
+    let config = state.config.lock().map_err(|e| e.to_string())?;
+    let folder = config
+        .folders
+        .iter()
+        .find(|f| f.id == folder_id)
+        .ok_or("Folder not found")?;
+    let paused = folder.is_paused_at(Utc::now());
+    Ok(FolderPauseStatus {
+        paused,
+        paused_until: if paused { folder.paused_until.clone() } else { None },
+    })
+}
+
 // ── Folder Whitelist Commands ───────────────────────────────
 
 #[tauri::command]
@@ -110,3 +411,139 @@ pub fn set_folder_whitelist(
     config::save_config(&config)?;
     Ok(())
 }
+
+// ── Folder Default Action ───────────────────────────────────
+
+/// Set (or clear) the catch-all action for files no rule matches.
+/// Pending entries from the previous default action are dropped so the next scan
+/// reschedules them with the new settings.
+#[tauri::command]
+pub fn set_folder_default_action(
+    state: State<AppState>,
+    folder_id: String,
+    default_action: Option<Action>,
+    default_after_days: u32,
+) -> Result<(), String> {
+    let mut config = state.config.lock().map_err(|e| e.to_string())?;
+    let folder = config
+        .folders
+        .iter_mut()
+        .find(|f| f.id == folder_id)
+        .ok_or("Folder not found")?;
+    folder.default_action = default_action;
+    folder.default_after_days = default_after_days;
+    config::save_config(&config)?;
+    let _ = state
+        .db
+        .remove_scheduled_deletions_by_rule(&folder_id, config::DEFAULT_ACTION_RULE_NAME);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Database;
+    use crate::test_support::{folder, move_to, rule, watched_dir};
+
+    #[test]
+    fn overview_reports_rules_schedules_and_status() {
+        let (tmp, watched) = watched_dir();
+        let pdfs = tmp.path().join("pdfs");
+        let f = folder(&watched, vec![rule("PDFs", "*.pdf", move_to(&pdfs)), rule("Logs", "*.log", move_to(&pdfs))]);
+        let mut missing = folder(&tmp.path().join("gone"), Vec::new());
+        missing.id = "folder-2".to_string();
+
+        let db = Database::open_in_memory().unwrap();
+        db.upsert_scheduled_deletion("s1", "/w/a.log", "folder-1", "Logs", "a.log", None, None, "2026-01-01 00:00:00", "2026-01-02 00:00:00", "delete", None, false, 0).unwrap();
+        db.upsert_scheduled_deletion("s2", "/w/b.log", "folder-1", "Logs", "b.log", None, None, "2026-01-01 00:00:00", "2026-01-02 00:00:00", "delete", None, false, 0).unwrap();
+        db.insert_activity("a1", "/w/c.pdf", "c.pdf", "moved", Some("PDFs"), Some("folder-1"), "2026-01-05 10:00:00", "success", None).unwrap();
+
+        let summaries = db.get_folder_summaries().unwrap();
+        let overview = build_folders_overview(vec![f, missing], &summaries, true);
+
+        assert_eq!(overview.len(), 2);
+        let first = &overview[0];
+        assert!(first.enabled && first.available && first.watch_active);
+        assert_eq!(first.rule_count, 2);
+        assert_eq!(first.scheduled_count, 2);
+        assert_eq!(first.last_activity_ts.as_deref(), Some("2026-01-05 10:00:00"));
+
+        let second = &overview[1];
+        assert!(!second.available);
+        assert!(!second.watch_active);
+        assert_eq!(second.scheduled_count, 0);
+        assert!(second.last_activity_ts.is_none());
+
+        let stopped = build_folders_overview(vec![overview[0].folder.clone()], &summaries, false);
+        assert!(!stopped[0].watch_active);
+    }
+
+    #[test]
+    fn bulk_add_reports_each_path() {
+        let (tmp, watched) = watched_dir();
+        let other = tmp.path().join("other");
+        std::fs::create_dir(&other).unwrap();
+        let mut config = AppConfig { folders: vec![folder(&watched, Vec::new())], ..Default::default() };
+
+        let paths = [&other, &other, &watched, &tmp.path().join("gone")];
+        let outcomes: Vec<_> = paths.iter().map(|p| push_new_folder(&mut config, &p.to_string_lossy())).collect();
+
+        assert_eq!(outcomes[0].as_ref().unwrap().path, other);
+        assert_eq!(outcomes[1].as_ref().unwrap_err(), "Folder is already being watched");
+        assert_eq!(outcomes[2].as_ref().unwrap_err(), "Folder is already being watched");
+        assert!(outcomes[3].as_ref().unwrap_err().starts_with("Folder does not exist"));
+        assert_eq!(config.folders.len(), 2);
+    }
+
+    #[test]
+    fn watch_folder_from_cli_respects_the_nested_folder_policy() {
+        let (_tmp, watched) = watched_dir();
+        let inner = watched.join("inner");
+        std::fs::create_dir(&inner).unwrap();
+        let mut config = AppConfig { folders: vec![folder(&watched, Vec::new())], ..Default::default() };
+
+        assert_eq!(watch_folder_from_cli(&mut config, &watched.to_string_lossy()).unwrap(), "folder-1");
+        let err = watch_folder_from_cli(&mut config, &inner.to_string_lossy()).unwrap_err();
+        assert!(err.contains("is inside the watched folder"), "{}", err);
+        assert_eq!(config.folders.len(), 1);
+    }
+
+    fn config_with_missing_folder() -> (tempfile::TempDir, AppConfig) {
+        let (tmp, watched) = watched_dir();
+        let mut missing = folder(&tmp.path().join("gone"), Vec::new());
+        missing.id = "folder-2".to_string();
+        let config = AppConfig {
+            folders: vec![folder(&watched, Vec::new()), missing],
+            settings: Default::default(),
+            presets: Vec::new(),
+            ..Default::default()
+        };
+        (tmp, config)
+    }
+
+    #[test]
+    fn reconcile_disables_missing_folders() {
+        let (_tmp, mut config) = config_with_missing_folder();
+        let report = reconcile(&mut config, false);
+
+        assert_eq!(report.ok, vec!["folder-1"]);
+        assert_eq!(report.missing.len(), 1);
+        assert_eq!(report.missing[0].id, "folder-2");
+        assert!(report.missing[0].enabled, "report shows the folder as it was");
+        assert_eq!(config.folders.len(), 2);
+        assert!(config.folders[0].enabled);
+        assert!(!config.folders[1].enabled);
+    }
+
+    #[test]
+    fn reconcile_can_remove_missing_folders() {
+        let (_tmp, mut config) = config_with_missing_folder();
+        let report = reconcile(&mut config, true);
+
+        assert_eq!(report.missing[0].id, "folder-2");
+        let ids: Vec<&str> = config.folders.iter().map(|f| f.id.as_str()).collect();
+        assert_eq!(ids, vec!["folder-1"]);
+        let archived: Vec<&str> = config.archived_folders.iter().map(|f| f.id.as_str()).collect();
+        assert_eq!(archived, vec!["folder-2"], "removed folders can be restored or purged later");
+    }
+}
@@ -1,8 +1,10 @@
+mod automation;
 mod conditions;
 mod config_cmds;
 mod data;
 mod db_viewer;
 mod folders;
+mod review;
 mod rules;
 mod watcher_cmds;
 
@@ -11,6 +13,7 @@ use std::sync::atomic::AtomicBool;
 
 use crate::config::AppConfig;
 use crate::db::Database;
+use crate::scheduler::{ScanIntervalOverride, SchedulerClock};
 use crate::watcher::FileWatcher;
 
 pub struct AppState {
@@ -18,16 +21,22 @@ pub struct AppState {
     pub db: Arc<Database>,
     pub watcher: Arc<Mutex<FileWatcher>>,
     pub scan_running: Arc<AtomicBool>,
+    /// Temporary scan interval read by the scheduler loop in place of the configured one
+    pub scan_interval_override: Arc<Mutex<Option<ScanIntervalOverride>>>,
+    /// Shared with the scheduler loop so commands can tell when it will next run
+    pub scheduler_clock: Arc<Mutex<SchedulerClock>>,
 }
 
 // ── Re-exports ──────────────────────────────────────────────
 // Wildcard re-exports are needed so tauri::generate_handler! can find
 // the hidden __cmd__ items generated by #[tauri::command].
 
+pub use automation::*;
 pub use conditions::*;
 pub use config_cmds::*;
 pub use data::*;
 pub use db_viewer::*;
 pub use folders::*;
+pub use review::*;
 pub use rules::*;
 pub use watcher_cmds::*;
@@ -0,0 +1,129 @@
+use std::path::Path;
+
+use tauri::{Emitter, State};
+
+use crate::config::{Action, AppConfig};
+use crate::db::{Database, ReviewItem};
+use crate::rules;
+use super::AppState;
+
+/// What the user decided for a flagged file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+pub enum ReviewDecision {
+    /// Run the Review rule's `on_approve` action (if it has one)
+    Approve,
+    /// Leave the file where it is
+    Dismiss,
+}
+
+/// Files flagged by Review rules that are still waiting on a decision, oldest first.
+#[tauri::command]
+pub fn get_review_queue(state: State<AppState>) -> Result<Vec<ReviewItem>, String> {
+    state.db.get_pending_reviews().map_err(|e| e.to_string())
+}
+
+/// Approve or dismiss a flagged file. A failed approved action leaves the item pending.
+#[tauri::command]
+pub fn resolve_review_item(
+    app: tauri::AppHandle,
+    state: State<AppState>,
+    id: String,
+    decision: ReviewDecision,
+) -> Result<(), String> {
+    let config = state.config.lock().map_err(|e| e.to_string())?.clone();
+    resolve_review(&config, &state.db, &id, decision)?;
+    let _ = app.emit("dashboard-data-changed", ());
+    Ok(())
+}
+
+fn resolve_review(config: &AppConfig, db: &Database, id: &str, decision: ReviewDecision) -> Result<(), String> {
+    let item = db
+        .get_review_item(id)
+        .map_err(|e| e.to_string())?
+        .ok_or("Review item not found")?;
+    if item.status != "pending" {
+        return Err(format!("Review item was already {}", item.status));
+    }
+    let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+
+    if decision == ReviewDecision::Approve {
+        let (folder, rule) = config
+            .folders
+            .iter()
+            .filter(|f| f.id == item.folder_id)
+            .find_map(|f| f.rules.iter().find(|r| r.id() == item.rule_id).map(|r| (f, r)))
+            .ok_or("The rule that flagged this file no longer exists")?;
+        if let Action::Review { on_approve: Some(action), .. } = &rule.action {
+            let file_path = Path::new(&item.file_path);
+            if !file_path.exists() {
+                return Err(format!("File no longer exists: {}", item.file_path));
+            }
+            let result =
+                rules::execute_approved_action(file_path, &folder.path, rule, action, db, &config.settings);
+            let _ = db.insert_activity_sized(&crate::scheduler::action_activity(&result, &item.folder_id, &now));
+            if !result.success {
+                return Err(result.details.unwrap_or_else(|| "Approved action failed".to_string()));
+            }
+        }
+    }
+
+    let status = match decision {
+        ReviewDecision::Approve => "approved",
+        ReviewDecision::Dismiss => "dismissed",
+    };
+    db.resolve_review(id, status, &now).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use crate::config::AppSettings;
+    use crate::rules::EvalOutcome;
+    use crate::test_support::{evaluate_file_full, folder, move_to, rule, watched_dir};
+
+    fn review_to(dest: &Path) -> Action {
+        Action::Review {
+            reason: "Looks like a receipt".to_string(),
+            on_approve: Some(Box::new(move_to(dest))),
+        }
+    }
+
+    #[test]
+    fn approving_runs_the_intended_move() {
+        let (tmp, watched) = watched_dir();
+        let receipts = tmp.path().join("receipts");
+        let file = watched.join("scan.pdf");
+        fs::write(&file, "x").unwrap();
+        let f = folder(&watched, vec![rule("Maybe receipts", "*.pdf", review_to(&receipts))]);
+        let db = Database::open_in_memory().unwrap();
+
+        assert!(matches!(evaluate_file_full(&file, &f, &db), EvalOutcome::Action(_)));
+        let item = db.get_pending_reviews().unwrap().remove(0);
+        let config = AppConfig { folders: vec![f], settings: AppSettings::default(), presets: Vec::new(), ..Default::default() };
+        resolve_review(&config, &db, &item.id, ReviewDecision::Approve).unwrap();
+
+        assert!(!file.exists());
+        assert!(receipts.join("scan.pdf").exists());
+        assert!(db.get_pending_reviews().unwrap().is_empty());
+        assert!(resolve_review(&config, &db, &item.id, ReviewDecision::Dismiss).is_err());
+    }
+
+    #[test]
+    fn dismissed_files_stay_put_and_are_not_flagged_again() {
+        let (tmp, watched) = watched_dir();
+        let file = watched.join("scan.pdf");
+        fs::write(&file, "x").unwrap();
+        let f = folder(&watched, vec![rule("Maybe receipts", "*.pdf", review_to(&tmp.path().join("r")))]);
+        let db = Database::open_in_memory().unwrap();
+
+        evaluate_file_full(&file, &f, &db);
+        let item = db.get_pending_reviews().unwrap().remove(0);
+        let config = AppConfig { folders: vec![f.clone()], settings: AppSettings::default(), presets: Vec::new(), ..Default::default() };
+        resolve_review(&config, &db, &item.id, ReviewDecision::Dismiss).unwrap();
+
+        assert!(file.exists());
+        assert!(matches!(evaluate_file_full(&file, &f, &db), EvalOutcome::NoMatch));
+        assert!(db.get_pending_reviews().unwrap().is_empty());
+    }
+}
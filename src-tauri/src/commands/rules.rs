@@ -1,7 +1,9 @@
 use tauri::State;
 
-use crate::config::{self, Rule};
+use crate::condition;
+use crate::config::{self, Condition, Rule, RulePreset};
 use crate::db::RuleMetadata;
+use crate::rules::ShadowWarning;
 use super::AppState;
 
 /// A source rule reference: which folder it lives in and which rule ID to copy.
@@ -11,6 +13,14 @@ pub struct RuleSource {
     pub rule_id: String,
 }
 
+/// A rule together with the folder it belongs to (for cross-folder listings).
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct FolderRule {
+    pub folder_id: String,
+    pub folder_path: std::path::PathBuf,
+    pub rule: Rule,
+}
+
 #[tauri::command]
 pub fn get_rules(state: State<AppState>, folder_id: String) -> Result<Vec<Rule>, String> {
     let config = state.config.lock().map_err(|e| e.to_string())?;
@@ -22,9 +32,39 @@ pub fn get_rules(state: State<AppState>, folder_id: String) -> Result<Vec<Rule>,
     Ok(folder.rules.clone())
 }
 
+/// Check a rule for problems that won't stop it saving: organize loops (destination
+/// inside a recursive watched folder), an invalid regex, or a destination drive that
+/// isn't connected. Returns human-readable warnings; an empty list means the rule looks safe.
+#[tauri::command]
+pub fn validate_move_rule(
+    state: State<AppState>,
+    folder_id: String,
+    rule: Rule,
+) -> Result<Vec<String>, String> {
+    let config = state.config.lock().map_err(|e| e.to_string())?;
+    if !config.folders.iter().any(|f| f.id == folder_id) {
+        return Err("Folder not found".to_string());
+    }
+    Ok(config::validate_rule(&config, &rule))
+}
+
+/// Rules in a folder that can never act because an earlier rule matches every file.
+#[tauri::command]
+pub fn find_shadowed_rules(state: State<AppState>, folder_id: String) -> Result<Vec<ShadowWarning>, String> {
+    let config = state.config.lock().map_err(|e| e.to_string())?;
+    let folder = config
+        .folders
+        .iter()
+        .find(|f| f.id == folder_id)
+        .ok_or("Folder not found")?;
+    Ok(crate::rules::shadowed_rules(folder))
+}
+
+/// Add a rule. Returns the warnings from `validate_move_rule` (the rule is saved regardless).
 #[tauri::command]
-pub fn add_rule(state: State<AppState>, folder_id: String, rule: Rule) -> Result<(), String> {
+pub fn add_rule(state: State<AppState>, folder_id: String, rule: Rule) -> Result<Vec<String>, String> {
     let mut config = state.config.lock().map_err(|e| e.to_string())?;
+    let warnings = config::validate_rule(&config, &rule);
     let folder = config
         .folders
         .iter_mut()
@@ -36,16 +76,18 @@ pub fn add_rule(state: State<AppState>, folder_id: String, rule: Rule) -> Result
     // Record creation timestamp
     let now = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
     let _ = state.db.insert_rule_metadata(&rule_id, &folder_id, &now);
-    Ok(())
+    Ok(warnings)
 }
 
+/// Replace a rule, reconciling its scheduled actions. Returns warnings like `add_rule`.
 #[tauri::command]
 pub fn update_rule(
     state: State<AppState>,
     folder_id: String,
     rule: Rule,
-) -> Result<(), String> {
+) -> Result<Vec<String>, String> {
     let mut config = state.config.lock().map_err(|e| e.to_string())?;
+    let warnings = config::validate_rule(&config, &rule);
     let folder = config
         .folders
         .iter_mut()
@@ -116,9 +158,9 @@ pub fn update_rule(
                 let _ = state.db.remove_scheduled_deletions_by_rule(&folder_id, &old_rule.name);
             }
         }
-        // Delete → Move or Move → Delete: clear all scheduled entries for this rule
-        (config::Action::Delete { .. }, config::Action::Move { .. })
-        | (config::Action::Move { .. }, config::Action::Delete { .. }) => {
+        // Any other change of action type (including to/from Review): clear all scheduled
+        // entries for this rule
+        _ => {
             let _ = state.db.remove_scheduled_deletions_by_rule(&folder_id, &old_rule.name);
             log::info!(
                 "Cleared scheduled actions for rule '{}' (action type changed)",
@@ -127,7 +169,7 @@ pub fn update_rule(
         }
     }
 
-    Ok(())
+    Ok(warnings)
 }
 
 #[tauri::command]
@@ -162,6 +204,44 @@ pub fn delete_rule(
     Ok(())
 }
 
+/// All rules tagged `tag` (case-insensitive) across every watched folder.
+#[tauri::command]
+pub fn get_rules_by_tag(state: State<AppState>, tag: String) -> Result<Vec<FolderRule>, String> {
+    let config = state.config.lock().map_err(|e| e.to_string())?;
+    Ok(config::rules_with_tag(&config, &tag)
+        .into_iter()
+        .map(|(folder, rule)| FolderRule {
+            folder_id: folder.id.clone(),
+            folder_path: folder.path.clone(),
+            rule: rule.clone(),
+        })
+        .collect())
+}
+
+/// Replace a rule's tags. Blank and duplicate tags are dropped; returns the saved list.
+#[tauri::command]
+pub fn set_rule_tags(
+    state: State<AppState>,
+    folder_id: String,
+    rule_id: String,
+    tags: Vec<String>,
+) -> Result<Vec<String>, String> {
+    let mut config = state.config.lock().map_err(|e| e.to_string())?;
+    let rule = config
+        .folders
+        .iter_mut()
+        .find(|f| f.id == folder_id)
+        .ok_or("Folder not found")?
+        .rules
+        .iter_mut()
+        .find(|r| r.id() == rule_id)
+        .ok_or("Rule not found")?;
+    rule.tags = config::normalize_tags(tags);
+    let saved = rule.tags.clone();
+    config::save_config(&config)?;
+    Ok(saved)
+}
+
 #[tauri::command]
 pub fn get_rule_metadata(
     state: State<AppState>,
@@ -192,11 +272,32 @@ pub fn reorder_rules(
             reordered.push(rule.clone());
         }
     }
+    Rule::assign_priorities_in_order(&mut reordered);
     folder.rules = reordered;
     config::save_config(&config)?;
     Ok(())
 }
 
+/// Move a single rule to `new_index` (clamped) without sending the whole order.
+/// Returns the index the rule ended up at.
+#[tauri::command]
+pub fn move_rule(
+    state: State<AppState>,
+    folder_id: String,
+    rule_id: String,
+    new_index: usize,
+) -> Result<usize, String> {
+    let mut config = state.config.lock().map_err(|e| e.to_string())?;
+    let folder = config
+        .folders
+        .iter_mut()
+        .find(|f| f.id == folder_id)
+        .ok_or("Folder not found")?;
+    let index = folder.move_rule(&rule_id, new_index)?;
+    config::save_config(&config)?;
+    Ok(index)
+}
+
 /// Copy rules from other folders into the target folder.
 /// Each source rule gets a new UUID so it's an independent copy.
 #[tauri::command]
@@ -244,6 +345,55 @@ pub fn copy_rules_to_folder(
     Ok(count)
 }
 
+/// Write one folder's rules to a JSON file (just the rule list, no folder paths or
+/// settings), for sharing a rule set. Returns the number of rules written.
+#[tauri::command]
+pub fn export_folder_rules(state: State<AppState>, folder_id: String, path: String) -> Result<u32, String> {
+    let config = state.config.lock().map_err(|e| e.to_string())?;
+    let folder = config
+        .folders
+        .iter()
+        .find(|f| f.id == folder_id)
+        .ok_or("Folder not found")?;
+    let json = serde_json::to_string_pretty(&folder.rules).map_err(|e| e.to_string())?;
+    std::fs::write(&path, json).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    Ok(folder.rules.len() as u32)
+}
+
+/// Append the rules from a file written by `export_folder_rules` to a folder. Each rule
+/// gets a new UUID; if any condition is invalid nothing is imported.
+/// Returns the number of rules imported.
+#[tauri::command]
+pub fn import_folder_rules(state: State<AppState>, folder_id: String, path: String) -> Result<u32, String> {
+    let text = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let rules = rules_from_json(&text)?;
+
+    let mut config = state.config.lock().map_err(|e| e.to_string())?;
+    let folder = config
+        .folders
+        .iter_mut()
+        .find(|f| f.id == folder_id)
+        .ok_or("Folder not found")?;
+    let count = rules.len() as u32;
+    let now = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+    for rule in rules {
+        let _ = state.db.insert_rule_metadata(&rule.id, &folder_id, &now);
+        folder.rules.push(rule);
+    }
+    config::save_config(&config)?;
+    Ok(count)
+}
+
+/// Parse an exported rule list, validating every condition and giving each rule a fresh id.
+fn rules_from_json(text: &str) -> Result<Vec<Rule>, String> {
+    let mut rules: Vec<Rule> = serde_json::from_str(text).map_err(|e| format!("Invalid rules file: {}", e))?;
+    for rule in &mut rules {
+        condition::validate_condition(&rule.condition).map_err(|e| format!("Rule '{}': {}", rule.name, e))?;
+        rule.id = uuid::Uuid::new_v4().to_string();
+    }
+    Ok(rules)
+}
+
 /// Move a rule from one folder to another at a specific position.
 #[tauri::command]
 pub fn move_rule_to_folder(
@@ -299,3 +449,235 @@ pub fn move_rule_to_folder(
 
     Ok(())
 }
+
+// ── Presets ─────────────────────────────────────────────────
+
+#[tauri::command]
+pub fn get_rule_presets(state: State<AppState>) -> Result<Vec<RulePreset>, String> {
+    let config = state.config.lock().map_err(|e| e.to_string())?;
+    Ok(config.presets.clone())
+}
+
+/// Save a folder's rules and whitelist as a named preset, replacing any preset with that name.
+#[tauri::command]
+pub fn save_folder_as_preset(state: State<AppState>, folder_id: String, name: String) -> Result<(), String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Preset name cannot be empty".to_string());
+    }
+    let mut config = state.config.lock().map_err(|e| e.to_string())?;
+    let folder = config
+        .folders
+        .iter()
+        .find(|f| f.id == folder_id)
+        .ok_or("Folder not found")?;
+    let preset = RulePreset { name: name.clone(), rules: folder.rules.clone(), whitelist: folder.whitelist.clone() };
+    config.presets.retain(|p| p.name != name);
+    config.presets.push(preset);
+    config::save_config(&config)?;
+    Ok(())
+}
+
+/// Delete a preset. Folders that auto-watch new subfolders with it stop doing so.
+#[tauri::command]
+pub fn delete_rule_preset(state: State<AppState>, name: String) -> Result<(), String> {
+    let mut config = state.config.lock().map_err(|e| e.to_string())?;
+    config.presets.retain(|p| p.name != name);
+    for folder in &mut config.folders {
+        if folder.auto_watch_new_subdirs.as_deref() == Some(name.as_str()) {
+            folder.auto_watch_new_subdirs = None;
+        }
+    }
+    config::save_config(&config)?;
+    Ok(())
+}
+
+/// Groups of rule IDs in a folder that do the same thing, in list order. Only groups
+/// of two or more are returned; the first ID of each group is the one `dedupe_rules` keeps.
+#[tauri::command]
+pub fn find_duplicate_rules(state: State<AppState>, folder_id: String) -> Result<Vec<Vec<String>>, String> {
+    let config = state.config.lock().map_err(|e| e.to_string())?;
+    let folder = config
+        .folders
+        .iter()
+        .find(|f| f.id == folder_id)
+        .ok_or("Folder not found")?;
+    Ok(duplicate_groups(&folder.rules))
+}
+
+/// Remove every duplicate found by `find_duplicate_rules`, keeping the first rule of
+/// each group. Returns the number of rules removed.
+#[tauri::command]
+pub fn dedupe_rules(state: State<AppState>, folder_id: String) -> Result<u32, String> {
+    let mut config = state.config.lock().map_err(|e| e.to_string())?;
+    let folder = config
+        .folders
+        .iter_mut()
+        .find(|f| f.id == folder_id)
+        .ok_or("Folder not found")?;
+
+    let groups = duplicate_groups(&folder.rules);
+    let mut removed: Vec<Rule> = Vec::new();
+    for group in &groups {
+        let kept_name = folder.rules.iter().find(|r| r.id() == group[0]).map(|r| r.name.clone());
+        for id in &group[1..] {
+            if let Some(idx) = folder.rules.iter().position(|r| r.id() == id) {
+                let rule = folder.rules.remove(idx);
+                // Scheduled entries are keyed by rule name — keep them if the survivor shares it
+                if kept_name.as_deref() != Some(rule.name.as_str()) {
+                    let _ = state.db.remove_scheduled_deletions_by_rule(&folder_id, &rule.name);
+                }
+                removed.push(rule);
+            }
+        }
+    }
+    if removed.is_empty() {
+        return Ok(0);
+    }
+    config::save_config(&config)?;
+
+    for rule in &removed {
+        let _ = state.db.delete_rule_metadata(rule.id(), &folder_id);
+    }
+    Ok(removed.len() as u32)
+}
+
+fn duplicate_groups(rules: &[Rule]) -> Vec<Vec<String>> {
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    for (i, rule) in rules.iter().enumerate() {
+        match groups.iter_mut().find(|g| rules_equivalent(&rules[g[0]], rule)) {
+            Some(group) => group.push(i),
+            None => groups.push(vec![i]),
+        }
+    }
+    groups
+        .into_iter()
+        .filter(|g| g.len() > 1)
+        .map(|g| g.into_iter().map(|i| rules[i].id.clone()).collect())
+        .collect()
+}
+
+/// Same action and matching options, and conditions that are structurally identical once
+/// the operands of AND/OR/XOR are put in a fixed order. Rules are only grouped when that
+/// proves they match the same files, since `dedupe_rules` deletes all but the first.
+/// Enabled state and the active window count too, so a disabled or time-limited copy
+/// never replaces the rule that actually runs. Names, tags and descriptions don't count.
+fn rules_equivalent(a: &Rule, b: &Rule) -> bool {
+    serde_json::to_value(&a.action).ok() == serde_json::to_value(&b.action).ok()
+        && a.enabled == b.enabled
+        && a.active_window == b.active_window
+        && a.whitelist == b.whitelist
+        && a.match_subdirectories == b.match_subdirectories
+        && a.case_sensitive == b.case_sensitive
+        && a.max_per_run == b.max_per_run
+        && a.min_dest_free_bytes == b.min_dest_free_bytes
+        && normalized_condition_text(&a.condition) == normalized_condition_text(&b.condition)
+}
+
+/// `condition::to_explicit_text` with the operands of each group sorted, so
+/// `*.png OR *.jpg` and `*.jpg OR *.png` compare equal.
+fn normalized_condition_text(cond: &Condition) -> String {
+    let group = |conditions: &[Condition], op: &str| {
+        let mut parts: Vec<String> = conditions.iter().map(normalized_condition_text).collect();
+        parts.sort();
+        format!("({})", parts.join(op))
+    };
+    match cond {
+        Condition::Not { condition } => format!("NOT {}", normalized_condition_text(condition)),
+        Condition::And { conditions } => group(conditions, " AND "),
+        Condition::Or { conditions } => group(conditions, " OR "),
+        Condition::Xor { conditions } => group(conditions, " XOR "),
+        leaf => condition::to_explicit_text(leaf),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{move_to, rule};
+    use std::path::Path;
+
+    #[test]
+    fn imported_rules_get_new_ids_and_bad_conditions_reject_the_file() {
+        let exported = vec![
+            rule("PDFs", "*.pdf", move_to(Path::new("/sorted/pdfs"))),
+            rule("Images", "*.jpg OR *.png", move_to(Path::new("/sorted/images"))),
+        ];
+        let json = serde_json::to_string(&exported).unwrap();
+
+        let imported = rules_from_json(&json).unwrap();
+        assert_eq!(imported.iter().map(|r| r.name.as_str()).collect::<Vec<_>>(), vec!["PDFs", "Images"]);
+        assert!(imported.iter().all(|r| r.id != "PDFs-id" && r.id != "Images-id"));
+        assert_ne!(imported[0].id, imported[1].id);
+
+        let mut broken = exported.clone();
+        broken[1].condition = Condition::Regex { pattern: "(unclosed".to_string(), flags: String::new() };
+        let err = rules_from_json(&serde_json::to_string(&broken).unwrap()).unwrap_err();
+        assert!(err.starts_with("Rule 'Images'"), "{}", err);
+        assert!(rules_from_json("{}").is_err());
+    }
+
+    #[test]
+    fn only_true_duplicates_are_grouped() {
+        let pdfs = Path::new("/sorted/pdfs");
+        let mut copy = rule("PDFs", "*.pdf", move_to(pdfs));
+        copy.id = "PDFs-copy".to_string();
+        let rules = vec![
+            rule("PDFs", "*.pdf", move_to(pdfs)),
+            rule("Images", "*.jpg OR *.png", move_to(Path::new("/sorted/images"))),
+            // Looks like the first rule but also matches `report.pdf.bak`
+            rule("PDF-ish", "*.pdf*", move_to(pdfs)),
+            // Same condition, different destination
+            rule("PDFs elsewhere", "*.pdf", move_to(Path::new("/sorted/other"))),
+            copy,
+            // Same files as "Images", written the other way round
+            rule("Pictures", "*.png OR *.jpg", move_to(Path::new("/sorted/images"))),
+        ];
+
+        assert_eq!(
+            duplicate_groups(&rules),
+            vec![
+                vec!["PDFs-id".to_string(), "PDFs-copy".to_string()],
+                vec!["Images-id".to_string(), "Pictures-id".to_string()],
+            ]
+        );
+    }
+
+    #[test]
+    fn case_sensitivity_and_metadata_conditions_keep_rules_apart() {
+        let dest = Path::new("/sorted");
+        let mut strict = rule("Strict", "*.pdf", move_to(dest));
+        strict.case_sensitive = true;
+        let rules = vec![
+            rule("Loose", "*.pdf", move_to(dest)),
+            strict,
+            // Age never matches on sample names, so sampling can't prove these equal
+            rule("Old", "age>30d", move_to(dest)),
+            rule("Older", "age>60d", move_to(dest)),
+        ];
+        assert!(duplicate_groups(&rules).is_empty());
+    }
+
+    #[test]
+    fn disabled_or_windowed_copies_are_not_duplicates() {
+        let dest = Path::new("/sorted");
+        let mut disabled = rule("Off", "*.pdf", move_to(dest));
+        disabled.enabled = false;
+        let mut nightly = rule("Nightly", "*.pdf", move_to(dest));
+        nightly.active_window = Some(crate::config::TimeWindow { start_hour: 22, end_hour: 6, days: Vec::new() });
+        let rules = vec![disabled, nightly, rule("Always", "*.pdf", move_to(dest))];
+        assert!(duplicate_groups(&rules).is_empty());
+    }
+
+    #[test]
+    fn conditions_that_agree_on_sample_names_are_not_grouped() {
+        let dest = Path::new("/sorted");
+        let rules = vec![
+            rule("Drafts", "*.txt AND draft*", move_to(dest)),
+            rule("Finals", "*.txt AND final*", move_to(dest)),
+            rule("Invoices", "/^inv_\\d+\\.pdf$/", move_to(dest)),
+            rule("Receipts", "/^rcpt_\\d+\\.pdf$/", move_to(dest)),
+        ];
+        assert!(duplicate_groups(&rules).is_empty());
+    }
+}
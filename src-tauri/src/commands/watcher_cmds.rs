@@ -1,8 +1,16 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::Ordering;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
 
 use tauri::{Emitter, State};
 
-use crate::scheduler;
+use crate::backoff::RuleBackoff;
+use crate::config::Rule;
+use crate::rules::{friendly_io_error, PlannedAction, RuleProfile};
+use crate::scheduler::{self, CleanlinessScore, RulePreview, ScanIntervalOverride};
+use crate::watcher::WatcherStatus;
 use super::AppState;
 
 #[derive(serde::Serialize, Clone)]
@@ -45,24 +53,133 @@ pub fn open_in_explorer(path: String) -> Result<(), String> {
     Ok(())
 }
 
+/// Open the file explorer with `path` selected. Linux file managers have no common way
+/// to select a file, so there the containing folder is opened instead.
+#[tauri::command]
+pub fn reveal_in_explorer(path: String) -> Result<(), String> {
+    let p = std::path::Path::new(&path);
+    if !p.exists() {
+        return Err(format!("Path '{}' does not exist", path));
+    }
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        // explorer parses its own command line: the path must be quoted inside the /select, switch
+        std::process::Command::new("explorer")
+            .raw_arg(format!("/select,\"{}\"", path))
+            .spawn()
+            .map_err(|e| format!("Failed to open explorer: {}", e))?;
+    }
+    #[cfg(target_os = "macos")]
+    {
+        std::process::Command::new("open")
+            .arg("-R")
+            .arg(&path)
+            .spawn()
+            .map_err(|e| format!("Failed to open Finder: {}", e))?;
+    }
+    #[cfg(target_os = "linux")]
+    {
+        let dir = if p.is_dir() { p } else { p.parent().unwrap_or(p) };
+        std::process::Command::new("xdg-open")
+            .arg(dir)
+            .spawn()
+            .map_err(|e| format!("Failed to open file manager: {}", e))?;
+    }
+    Ok(())
+}
+
 /// Ensure a directory exists, creating it (and parents) if needed.
-/// Returns a clear error if the drive letter doesn't exist.
+/// Returns a clear error for a relative path or one on a missing drive.
 #[tauri::command]
 pub fn ensure_dir(path: String) -> Result<(), String> {
-    let p = std::path::Path::new(&path);
     // Check the root/drive exists first
-    if let Some(root) = p.components().next() {
-        let root_path = std::path::PathBuf::from(root.as_os_str());
-        if !root_path.exists() {
-            return Err(format!("Drive '{}' does not exist", root_path.display()));
-        }
+    if let Some(problem) = crate::config::unreachable_root(std::path::Path::new(&path)) {
+        return Err(problem);
     }
     std::fs::create_dir_all(&path)
         .map_err(|e| format!("Failed to create directory '{}': {}", path, e))
 }
 
+/// How long `probe_destination` waits before reporting a share as unresponsive.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Result of `probe_destination`.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct DestinationProbe {
+    /// The path exists and is a folder
+    pub reachable: bool,
+    /// A probe file could be written and removed
+    pub writable: bool,
+    /// Time to write (and flush) plus delete a small probe file
+    pub write_latency_ms: Option<u64>,
+    pub error: Option<String>,
+}
+
+/// Gauge whether a Move destination (e.g. a network share) is usable and responsive
+/// by timing a small write + delete. Gives up after a few seconds so a hung share
+/// doesn't block the UI.
 #[tauri::command]
-pub fn scan_now(app: tauri::AppHandle, state: State<AppState>) -> Result<(), String> {
+pub fn probe_destination(path: String) -> DestinationProbe {
+    probe_with_timeout(PathBuf::from(path), PROBE_TIMEOUT)
+}
+
+fn probe_with_timeout(path: PathBuf, timeout: Duration) -> DestinationProbe {
+    let (tx, rx) = mpsc::channel();
+    // A stuck share can block the thread indefinitely; it's detached and reports nowhere
+    std::thread::spawn(move || {
+        let _ = tx.send(probe_dir(&path));
+    });
+    rx.recv_timeout(timeout).unwrap_or_else(|_| DestinationProbe {
+        reachable: false,
+        writable: false,
+        write_latency_ms: None,
+        error: Some(format!("No response after {} seconds", timeout.as_secs())),
+    })
+}
+
+fn probe_dir(path: &Path) -> DestinationProbe {
+    if !path.is_dir() {
+        return DestinationProbe {
+            reachable: false,
+            writable: false,
+            write_latency_ms: None,
+            error: Some(format!("'{}' is not an existing folder", path.display())),
+        };
+    }
+    let probe_file = path.join(format!(".folder-organizer-probe-{}", uuid::Uuid::new_v4()));
+    let started = Instant::now();
+    let written = std::fs::File::create(&probe_file)
+        .and_then(|mut f| {
+            f.write_all(&[0u8; 4096])?;
+            f.sync_all()
+        })
+        .and_then(|_| std::fs::remove_file(&probe_file));
+    match written {
+        Ok(()) => DestinationProbe {
+            reachable: true,
+            writable: true,
+            write_latency_ms: Some(started.elapsed().as_millis() as u64),
+            error: None,
+        },
+        Err(e) => {
+            let _ = std::fs::remove_file(&probe_file);
+            DestinationProbe {
+                reachable: true,
+                writable: false,
+                write_latency_ms: None,
+                error: Some(friendly_io_error(&e)),
+            }
+        }
+    }
+}
+
+/// Scan every enabled folder and wait for it to finish; returns the number of files
+/// processed. Runs off the main thread so the window stays responsive. Progress still
+/// goes out as `scan-status` events, and the count as `scan-complete` for listeners
+/// that aren't awaiting the call.
+#[tauri::command(async)]
+pub fn scan_now(app: tauri::AppHandle, state: State<AppState>) -> Result<u32, String> {
     if state.scan_running.swap(true, Ordering::SeqCst) {
         return Err("A scan is already running".to_string());
     }
@@ -86,46 +203,42 @@ pub fn scan_now(app: tauri::AppHandle, state: State<AppState>) -> Result<(), Str
             return Err(e.to_string());
         }
     };
-    let db = state.db.clone();
-    let scan_running = state.scan_running.clone();
 
-    std::thread::spawn(move || {
-        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-            scheduler::scan_existing_files(&config, &db)
-        }));
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        scheduler::scan_existing_files(&config, &state.db)
+    }));
+    state.scan_running.store(false, Ordering::SeqCst);
 
-        match result {
-            Ok(count) => {
-                let _ = app.emit(
-                    "scan-status",
-                    ScanStatusEvent {
-                        scope: "all".to_string(),
-                        folder_id: None,
-                        status: "finished".to_string(),
-                        count: Some(count),
-                        error: None,
-                    },
-                );
-                let _ = app.emit("dashboard-data-changed", ());
-            }
-            Err(_) => {
-                let _ = app.emit(
-                    "scan-status",
-                    ScanStatusEvent {
-                        scope: "all".to_string(),
-                        folder_id: None,
-                        status: "failed".to_string(),
-                        count: None,
-                        error: Some("Scan panicked".to_string()),
-                    },
-                );
-            }
+    match result {
+        Ok(count) => {
+            let _ = app.emit(
+                "scan-status",
+                ScanStatusEvent {
+                    scope: "all".to_string(),
+                    folder_id: None,
+                    status: "finished".to_string(),
+                    count: Some(count),
+                    error: None,
+                },
+            );
+            let _ = app.emit("scan-complete", count);
+            let _ = app.emit("dashboard-data-changed", ());
+            Ok(count)
         }
-
-        scan_running.store(false, Ordering::SeqCst);
-    });
-
-    Ok(())
+        Err(_) => {
+            let _ = app.emit(
+                "scan-status",
+                ScanStatusEvent {
+                    scope: "all".to_string(),
+                    folder_id: None,
+                    status: "failed".to_string(),
+                    count: None,
+                    error: Some("Scan panicked".to_string()),
+                },
+            );
+            Err("Scan panicked".to_string())
+        }
+    }
 }
 
 /// Scan a single folder for existing files and evaluate rules.
@@ -200,6 +313,91 @@ pub fn scan_folder(
     Ok(())
 }
 
+/// Scan one folder right away and wait for it to finish, unlike `scan_folder`,
+/// which reports back through `scan-status` events. Returns the number of files processed.
+#[tauri::command(async)]
+pub fn scan_folder_now(app: tauri::AppHandle, state: State<AppState>, folder_id: String) -> Result<u32, String> {
+    let config = state.config.lock().map_err(|e| e.to_string())?.clone();
+    if !config.folders.iter().any(|f| f.id == folder_id) {
+        return Err("Folder not found".to_string());
+    }
+    if state.scan_running.swap(true, Ordering::SeqCst) {
+        return Err("A scan is already running".to_string());
+    }
+
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        scheduler::scan_single_folder(&config, &state.db, &folder_id)
+    }));
+    state.scan_running.store(false, Ordering::SeqCst);
+
+    let count = result.map_err(|_| "Scan panicked".to_string())?;
+    let _ = app.emit("dashboard-data-changed", ());
+    Ok(count)
+}
+
+/// Preview what a scan of one folder would do (moves, copies, scheduled deletes)
+/// without touching any files or scheduling anything.
+#[tauri::command]
+pub fn scan_dry_run(state: State<AppState>, folder_id: String) -> Result<Vec<PlannedAction>, String> {
+    let config = state.config.lock().map_err(|e| e.to_string())?.clone();
+    if !config.folders.iter().any(|f| f.id == folder_id) {
+        return Err("Folder not found".to_string());
+    }
+    Ok(scheduler::scan_dry_run(&config, &folder_id))
+}
+
+/// The folder's current files a rule would match (up to 1000), without acting on any.
+/// The rule doesn't have to be saved, so it can be tried out while editing.
+#[tauri::command(async)]
+pub fn preview_rule_on_folder(state: State<AppState>, folder_id: String, rule: Rule) -> Result<RulePreview, String> {
+    let config = state.config.lock().map_err(|e| e.to_string())?.clone();
+    if !config.folders.iter().any(|f| f.id == folder_id) {
+        return Err("Folder not found".to_string());
+    }
+    Ok(scheduler::preview_rule_on_folder(&config, &folder_id, &rule))
+}
+
+/// Time each of a folder's rules over its current files (no actions are taken).
+#[tauri::command]
+pub fn profile_folder_scan(state: State<AppState>, folder_id: String) -> Result<Vec<RuleProfile>, String> {
+    let config = state.config.lock().map_err(|e| e.to_string())?.clone();
+    if !config.folders.iter().any(|f| f.id == folder_id) {
+        return Err("Folder not found".to_string());
+    }
+    Ok(scheduler::profile_folder_scan(&config, &folder_id))
+}
+
+/// Score how much of a folder's current contents its rules handle, with suggestions
+/// for the most common unmatched file types.
+#[tauri::command]
+pub fn get_folder_cleanliness(state: State<AppState>, folder_id: String) -> Result<CleanlinessScore, String> {
+    let config = state.config.lock().map_err(|e| e.to_string())?.clone();
+    if !config.folders.iter().any(|f| f.id == folder_id) {
+        return Err("Folder not found".to_string());
+    }
+    scheduler::folder_cleanliness(&config, &folder_id).ok_or_else(|| "Folder path does not exist".to_string())
+}
+
+/// Temporarily override the scheduler interval (e.g. 1 minute for the next 15 minutes)
+/// to catch up on a backlog. Reverts to `scan_interval_minutes` automatically;
+/// `for_minutes == 0` clears an active override. While the override is active, every
+/// scheduler cycle also runs a full folder scan.
+#[tauri::command]
+pub fn set_temporary_scan_interval(
+    state: State<AppState>,
+    minutes: u32,
+    for_minutes: u32,
+) -> Result<(), String> {
+    let mut slot = state.scan_interval_override.lock().map_err(|e| e.to_string())?;
+    *slot = if for_minutes == 0 {
+        None
+    } else {
+        Some(ScanIntervalOverride::new(minutes.max(1), for_minutes, Instant::now()))
+    };
+    log::info!("Temporary scan interval set to {} min for {} min", minutes, for_minutes);
+    Ok(())
+}
+
 #[tauri::command]
 pub fn restart_watcher(state: State<AppState>) -> Result<(), String> {
     let config = state.config.lock().map_err(|e| e.to_string())?;
@@ -220,3 +418,41 @@ pub fn get_watcher_status(state: State<AppState>) -> Result<bool, String> {
     let watcher = state.watcher.lock().map_err(|e| e.to_string())?;
     Ok(watcher.is_running())
 }
+
+/// Per-folder watch registration: which folders are really watched and why others aren't.
+#[tauri::command]
+pub fn get_watcher_details(state: State<AppState>) -> Result<WatcherStatus, String> {
+    let watcher = state.watcher.lock().map_err(|e| e.to_string())?;
+    Ok(watcher.status())
+}
+
+/// Rules whose actions have been failing, and how long each is suppressed for.
+#[tauri::command]
+pub fn get_rule_backoffs() -> Vec<RuleBackoff> {
+    crate::backoff::current_backoffs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn probe_local_dir_is_fast_and_writable() {
+        let tmp = tempfile::tempdir().unwrap();
+        let probe = probe_with_timeout(tmp.path().to_path_buf(), PROBE_TIMEOUT);
+        assert!(probe.reachable);
+        assert!(probe.writable);
+        assert!(probe.write_latency_ms.unwrap() < 1000);
+        // The probe file is cleaned up
+        assert_eq!(std::fs::read_dir(tmp.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn probe_missing_dir_is_unreachable() {
+        let tmp = tempfile::tempdir().unwrap();
+        let probe = probe_with_timeout(tmp.path().join("nope"), PROBE_TIMEOUT);
+        assert!(!probe.reachable);
+        assert!(!probe.writable);
+        assert!(probe.error.is_some());
+    }
+}
@@ -7,40 +7,399 @@
 //!   `invoice*`        — glob, matches files starting with "invoice"
 //!   `*report*`        — glob, contains "report"
 //!   `/^IMG_\d+/`      — regex (wrapped in `/`)
+//!   `"annual report*"` — glob in double quotes: may contain spaces and parens, and
+//!                        is always a plain glob (`"tag:x"` matches the name `tag:x`)
+//!   `report\ \(final\).pdf` — backslash escapes: `\*` / `\?` match a literal `*` / `?`,
+//!                        and `\(`, `\)`, `\ `, `\"` don't end the word or quoted pattern
 //!
 //! Combinators:
 //!   `*.pdf AND *invoice*`               — both must match
 //!   `*.jpg OR *.png OR *.gif`           — any must match
 //!   `NOT *.tmp`                         — negation
+//!   `*.pdf XOR *draft*`                 — exactly one must match (same precedence as OR)
 //!   `(*.pdf OR *.docx) AND *report*`    — grouping with parens
 //!   `*`                                 — matches everything (Always)
+//!
+//! File age (whole days):
+//!   `modified>30d`                      — last modified more than 30 days ago
+//!   `created<7d`                        — created less than 7 days ago
+//!
+//! Size ranking (within the file's directory):
+//!   `largest:1`                         — the single largest file
+//!   `smallest:3`                        — the three smallest files
+//!
+//! OS file tags (macOS Finder tags; never match elsewhere):
+//!   `tag:Red`                           — file carries the "Red" tag
+//!
+//! Parent folder (the file's immediate folder name, glob):
+//!   `parent:to-sort`                    — file sits directly in a folder named to-sort
+//!
+//! Content type (sniffed from the file's magic bytes):
+//!   `content:image/png`                 — file really is a PNG
+//!   `content:image/*`                   — any image, whatever its extension
+//!
+//! Date in the file name (regex capture, compared with today):
+//!   `dated>30d/(\d{4}-\d{2}-\d{2})/`   — embedded date is over 30 days old
+//!
+//! Duplicate content (SHA-256, only hashed when a same-size file exists):
+//!   `duplicate:D:\Archive`              — an identical file exists under D:\Archive
+//!
+//! Document metadata (PDF Info dictionary, JPEG EXIF; `=` equals, `~` contains, `>`/`<`):
+//!   `meta:author~"ACME"`               — PDF author contains "ACME"
+//!   `meta:datetaken<"2020-01-01"`      — photo taken before 2020
+
+use std::cell::OnceCell;
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
 
-use regex::Regex;
+use chrono::NaiveDate;
 
-use crate::config::Condition;
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+
+use crate::config::{AgeOp, Condition, MetaOp, SizeExtreme, TimeField};
 
 // ── Evaluation ──────────────────────────────────────────────
 
-/// Test whether a filename matches a condition tree.
+/// File metadata available to metadata-based conditions (e.g. `Age`).
+/// A default context has no timestamps, so metadata conditions never match.
+#[derive(Debug, Clone, Default)]
+pub struct FileContext {
+    pub modified: Option<SystemTime>,
+    pub created: Option<SystemTime>,
+    /// True when the platform has no creation time and `created` was filled from `modified`.
+    pub created_is_fallback: bool,
+    /// The file's size rank among its siblings, if the caller computed one.
+    pub size_rank: Option<SizeRank>,
+    /// Path on disk, for conditions that read more than metadata (e.g. file tags).
+    pub path: Option<PathBuf>,
+    /// Result of each `Duplicate` condition by reference dir, filled in by the caller
+    /// (rules.rs hashes against its cache); missing entries don't match.
+    pub duplicate_in: HashMap<PathBuf, bool>,
+    /// Sniffed MIME type, read from the file header at most once per file.
+    /// Inner None = unknown type or unreadable file.
+    content_mime: OnceCell<Option<&'static str>>,
+    /// Document metadata fields (see `doc_meta`), extracted at most once per file.
+    meta_fields: OnceCell<HashMap<String, String>>,
+}
+
+/// How much of the file header is read for content sniffing.
+const CONTENT_SNIFF_BYTES: u64 = 8192;
+
+/// 1-based position of a file when its directory is sorted by size
+/// (`largest == 1` is the biggest file, `smallest == 1` the smallest).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SizeRank {
+    pub largest: u32,
+    pub smallest: u32,
+}
+
+impl FileContext {
+    /// Read timestamps for `path`. Missing metadata yields an empty context.
+    pub fn from_path(path: &Path) -> Self {
+        let ctx = match fs::metadata(path) {
+            Ok(meta) => Self::from_metadata(&meta),
+            Err(_) => Self::default(),
+        };
+        Self { path: Some(path.to_path_buf()), ..ctx }
+    }
+
+    pub fn from_metadata(meta: &fs::Metadata) -> Self {
+        Self::from_times(meta.modified().ok(), meta.created().ok())
+    }
+
+    /// Build a context, filling a missing creation time from the modified time.
+    pub fn from_times(modified: Option<SystemTime>, created: Option<SystemTime>) -> Self {
+        match created {
+            Some(created) => Self {
+                modified,
+                created: Some(created),
+                ..Self::default()
+            },
+            None => Self {
+                modified,
+                created: modified,
+                created_is_fallback: modified.is_some(),
+                ..Self::default()
+            },
+        }
+    }
+
+    /// The file's real MIME type from its magic bytes. Unreadable or locked files
+    /// yield None (so content conditions just don't match).
+    pub fn content_mime(&self) -> Option<&'static str> {
+        *self.content_mime.get_or_init(|| {
+            let path = self.path.as_ref()?;
+            let mut header = Vec::new();
+            fs::File::open(path)
+                .and_then(|f| f.take(CONTENT_SNIFF_BYTES).read_to_end(&mut header))
+                .ok()?;
+            infer::get(&header).map(|t| t.mime_type())
+        })
+    }
+
+    /// A document metadata field (lowercase name), if the file has a readable one.
+    pub fn meta_field(&self, field: &str) -> Option<&str> {
+        self.meta_fields
+            .get_or_init(|| self.path.as_deref().map(crate::doc_meta::read_fields).unwrap_or_default())
+            .get(&field.to_lowercase())
+            .map(String::as_str)
+    }
+}
+
+/// Test whether a filename matches a condition tree (name-only; metadata conditions don't match).
 pub fn evaluate(condition: &Condition, file_name: &str) -> bool {
+    evaluate_with_context(condition, file_name, &FileContext::default())
+}
+
+/// Per-rule switches that change how conditions match.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EvalOptions {
+    /// Glob patterns compare case exactly instead of lowercasing both sides.
+    pub case_sensitive: bool,
+}
+
+/// Test whether a file matches a condition tree, using `ctx` for metadata conditions.
+pub fn evaluate_with_context(condition: &Condition, file_name: &str, ctx: &FileContext) -> bool {
+    evaluate_with_options(condition, file_name, ctx, EvalOptions::default())
+}
+
+/// Like [`evaluate_with_context`], with explicit matching options.
+pub fn evaluate_with_options(
+    condition: &Condition,
+    file_name: &str,
+    ctx: &FileContext,
+    opts: EvalOptions,
+) -> bool {
     match condition {
-        Condition::Glob { pattern } => glob_match(pattern, file_name),
-        Condition::Regex { pattern } => {
-            Regex::new(pattern)
-                .map(|re| re.is_match(file_name))
-                .unwrap_or(false)
+        Condition::Glob { pattern } => {
+            if opts.case_sensitive {
+                glob_match_impl(pattern.as_bytes(), file_name.as_bytes())
+            } else {
+                glob_match(pattern, file_name)
+            }
         }
+        Condition::Regex { pattern, flags } => build_regex(pattern, flags)
+            .map(|re| re.is_match(file_name))
+            .unwrap_or(false),
         Condition::And { conditions } => {
-            conditions.iter().all(|c| evaluate(c, file_name))
+            conditions.iter().all(|c| evaluate_with_options(c, file_name, ctx, opts))
         }
         Condition::Or { conditions } => {
-            conditions.iter().any(|c| evaluate(c, file_name))
+            conditions.iter().any(|c| evaluate_with_options(c, file_name, ctx, opts))
+        }
+        Condition::Xor { conditions } => {
+            conditions
+                .iter()
+                .filter(|c| evaluate_with_options(c, file_name, ctx, opts))
+                .take(2)
+                .count()
+                == 1
         }
-        Condition::Not { condition } => !evaluate(condition, file_name),
+        Condition::Not { condition } => !evaluate_with_options(condition, file_name, ctx, opts),
+        Condition::Age { op, days, field } => age_matches(*op, *days, *field, ctx),
+        Condition::ExtremeSize { which, n } => match ctx.size_rank {
+            Some(rank) => match which {
+                SizeExtreme::Largest => rank.largest <= *n,
+                SizeExtreme::Smallest => rank.smallest <= *n,
+            },
+            None => false,
+        },
+        Condition::ContentType { mime } => match ctx.content_mime() {
+            Some(actual) => glob_match(mime, actual),
+            None => false,
+        },
+        Condition::HasTag { tag } => match &ctx.path {
+            Some(path) => read_file_tags(path).iter().any(|t| t.eq_ignore_ascii_case(tag)),
+            None => false,
+        },
+        Condition::ParentDir { pattern } => {
+            let parent = ctx.path.as_deref().and_then(Path::parent).and_then(Path::file_name);
+            match parent.map(|name| name.to_string_lossy()) {
+                Some(name) if opts.case_sensitive => glob_match_impl(pattern.as_bytes(), name.as_bytes()),
+                Some(name) => glob_match(pattern, &name),
+                None => false,
+            }
+        }
+        Condition::EmbeddedDateOlderThan { regex_with_date_group, days } => embedded_date_older_than(
+            regex_with_date_group,
+            *days,
+            file_name,
+            chrono::Local::now().date_naive(),
+        ),
+        Condition::Duplicate { reference_dir } => ctx.duplicate_in.get(reference_dir).copied().unwrap_or(false),
+        Condition::MetaField { field, op, value } => ctx
+            .meta_field(field)
+            .is_some_and(|actual| meta_matches(*op, actual, value)),
         Condition::Always => true,
     }
 }
 
+/// Compare a metadata value against the condition's value.
+fn meta_matches(op: MetaOp, actual: &str, expected: &str) -> bool {
+    let ordering = match (actual.trim().parse::<f64>(), expected.trim().parse::<f64>()) {
+        (Ok(a), Ok(b)) => a.partial_cmp(&b),
+        _ => Some(actual.to_lowercase().cmp(&expected.to_lowercase())),
+    };
+    match op {
+        MetaOp::Equals => actual.eq_ignore_ascii_case(expected),
+        MetaOp::Contains => actual.to_lowercase().contains(&expected.to_lowercase()),
+        MetaOp::GreaterThan => ordering == Some(std::cmp::Ordering::Greater),
+        MetaOp::LessThan => ordering == Some(std::cmp::Ordering::Less),
+    }
+}
+
+/// Date layouts accepted from an embedded-date capture.
+const EMBEDDED_DATE_FORMATS: &[&str] = &["%Y-%m-%d", "%Y_%m_%d", "%Y.%m.%d", "%Y%m%d"];
+
+/// Whether the date captured from `file_name` is more than `days` before `today`.
+/// No match, a missing group, or an unparseable capture never matches.
+fn embedded_date_older_than(pattern: &str, days: u32, file_name: &str, today: NaiveDate) -> bool {
+    let Ok(re) = build_regex(pattern, "") else {
+        return false;
+    };
+    let Some(caps) = re.captures(file_name) else {
+        return false;
+    };
+    let Some(captured) = caps.name("date").or_else(|| caps.get(1)) else {
+        return false;
+    };
+    let Some(date) = EMBEDDED_DATE_FORMATS
+        .iter()
+        .find_map(|fmt| NaiveDate::parse_from_str(captured.as_str(), fmt).ok())
+    else {
+        return false;
+    };
+    (today - date).num_days() > days as i64
+}
+
+fn age_matches(op: AgeOp, days: u32, field: TimeField, ctx: &FileContext) -> bool {
+    let time = match field {
+        TimeField::Modified => ctx.modified,
+        TimeField::Created => ctx.created,
+    };
+    let Some(time) = time else {
+        return false;
+    };
+    // Timestamps in the future count as age zero
+    let age = SystemTime::now().duration_since(time).unwrap_or_default();
+    let threshold = Duration::from_secs(days as u64 * 86_400);
+    match op {
+        AgeOp::GreaterThan => age > threshold,
+        AgeOp::LessThan => age < threshold,
+    }
+}
+
+/// Finder tag names from the `_kMDItemUserTags` xattr (a binary plist of strings).
+/// Each entry is `Name` or `Name\n<color index>`; only the name is returned.
+#[cfg(target_os = "macos")]
+pub fn read_file_tags(path: &Path) -> Vec<String> {
+    let bytes = match xattr::get(path, "com.apple.metadata:_kMDItemUserTags") {
+        Ok(Some(bytes)) => bytes,
+        _ => return Vec::new(),
+    };
+    plist::from_bytes::<Vec<String>>(&bytes)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|t| t.split('\n').next().unwrap_or_default().to_string())
+        .collect()
+}
+
+/// File tags aren't supported on this platform — nothing is ever tagged.
+#[cfg(not(target_os = "macos"))]
+pub fn read_file_tags(_path: &Path) -> Vec<String> {
+    Vec::new()
+}
+
+/// Whether the condition matches every file, judged from its structure alone:
+/// `*` (or `Always`), a glob of only `*`s, an empty or `.*` regex, an `OR` with such a
+/// branch, or an `AND` of nothing else. Conservative — `false` means "can't tell".
+pub fn always_matches(condition: &Condition) -> bool {
+    match condition {
+        Condition::Always => true,
+        Condition::Glob { pattern } => !pattern.is_empty() && pattern.chars().all(|c| c == '*'),
+        Condition::Regex { pattern, .. } => matches!(pattern.as_str(), "" | ".*" | "^.*" | "^.*$" | ".*$"),
+        Condition::Or { conditions } => conditions.iter().any(always_matches),
+        Condition::And { conditions } => !conditions.is_empty() && conditions.iter().all(always_matches),
+        _ => false,
+    }
+}
+
+/// Whether the condition needs a size ranking of the file's directory.
+pub fn uses_size_rank(condition: &Condition) -> bool {
+    match condition {
+        Condition::ExtremeSize { .. } => true,
+        Condition::And { conditions } | Condition::Or { conditions } | Condition::Xor { conditions } => {
+            conditions.iter().any(uses_size_rank)
+        }
+        Condition::Not { condition } => uses_size_rank(condition),
+        _ => false,
+    }
+}
+
+/// Reference dirs of every `Duplicate` condition in the tree.
+pub fn duplicate_reference_dirs(condition: &Condition) -> Vec<PathBuf> {
+    match condition {
+        Condition::Duplicate { reference_dir } => vec![reference_dir.clone()],
+        Condition::And { conditions } | Condition::Or { conditions } | Condition::Xor { conditions } => {
+            conditions.iter().flat_map(duplicate_reference_dirs).collect()
+        }
+        Condition::Not { condition } => duplicate_reference_dirs(condition),
+        _ => Vec::new(),
+    }
+}
+
+/// Whether the condition reads the file's creation time anywhere in its tree.
+pub fn uses_created_time(condition: &Condition) -> bool {
+    match condition {
+        Condition::Age { field, .. } => *field == TimeField::Created,
+        Condition::And { conditions } | Condition::Or { conditions } | Condition::Xor { conditions } => {
+            conditions.iter().any(uses_created_time)
+        }
+        Condition::Not { condition } => uses_created_time(condition),
+        _ => false,
+    }
+}
+
+/// Capture groups of the condition's regex against `file_name`, for conditions that are
+/// a regex or an `AND` containing one (the first such regex wins). Index 0 is the whole
+/// match; groups that didn't take part are None. None if there's no regex or it didn't match.
+pub fn regex_captures(condition: &Condition, file_name: &str) -> Option<Vec<Option<String>>> {
+    match condition {
+        Condition::Regex { pattern, flags } => {
+            let re = build_regex(pattern, flags).ok()?;
+            let caps = re.captures(file_name)?;
+            Some(caps.iter().map(|m| m.map(|m| m.as_str().to_string())).collect())
+        }
+        Condition::And { conditions } => conditions.iter().find_map(|c| regex_captures(c, file_name)),
+        _ => None,
+    }
+}
+
+/// Compile a regex condition, applying `/pattern/flags` letters:
+/// `i` case-insensitive, `m` multi-line, `s` dot matches newline, `x` verbose.
+fn build_regex(pattern: &str, flags: &str) -> Result<Regex, String> {
+    let mut builder = RegexBuilder::new(pattern);
+    for flag in flags.chars() {
+        match flag {
+            'i' => builder.case_insensitive(true),
+            'm' => builder.multi_line(true),
+            's' => builder.dot_matches_new_line(true),
+            'x' => builder.ignore_whitespace(true),
+            other => {
+                return Err(format!(
+                    "Unknown regex flag '{}' (supported: i, m, s, x)",
+                    other
+                ))
+            }
+        };
+    }
+    builder.build().map_err(|e| format!("Invalid regex: {}", e))
+}
+
 /// Simple glob matching: `*` = any chars, `?` = single char. Case-insensitive.
 fn glob_match(pattern: &str, text: &str) -> bool {
     let pat = pattern.to_lowercase();
@@ -55,10 +414,15 @@ fn glob_match_impl(pat: &[u8], txt: &[u8]) -> bool {
     let mut star_tx = 0;
 
     while tx < txt.len() {
-        if px < pat.len() && (pat[px] == b'?' || pat[px] == txt[tx]) {
+        // `\x` matches x literally, so `\*` is an asterisk and `\?` a question mark
+        let escaped = px + 1 < pat.len() && pat[px] == b'\\';
+        if escaped && pat[px + 1] == txt[tx] {
+            px += 2;
+            tx += 1;
+        } else if !escaped && px < pat.len() && (pat[px] == b'?' || pat[px] == txt[tx]) {
             px += 1;
             tx += 1;
-        } else if px < pat.len() && pat[px] == b'*' {
+        } else if !escaped && px < pat.len() && pat[px] == b'*' {
             star_px = px;
             star_tx = tx;
             px += 1;
@@ -100,8 +464,39 @@ pub fn parse(input: &str) -> Result<Condition, String> {
 pub fn to_text(cond: &Condition) -> String {
     match cond {
         Condition::Always => "*".to_string(),
-        Condition::Glob { pattern } => pattern.clone(),
-        Condition::Regex { pattern } => format!("/{}/", pattern),
+        Condition::Glob { pattern } => glob_text(pattern),
+        Condition::Regex { pattern, flags } => format!("/{}/{}", pattern, flags),
+        Condition::Age { op, days, field } => {
+            let field = match field {
+                TimeField::Modified => "modified",
+                TimeField::Created => "created",
+            };
+            let op = match op {
+                AgeOp::GreaterThan => ">",
+                AgeOp::LessThan => "<",
+            };
+            format!("{}{}{}d", field, op, days)
+        }
+        Condition::ExtremeSize { which, n } => match which {
+            SizeExtreme::Largest => format!("largest:{}", n),
+            SizeExtreme::Smallest => format!("smallest:{}", n),
+        },
+        Condition::HasTag { tag } => format!("tag:{}", tag),
+        Condition::ParentDir { pattern } => format!("parent:{}", pattern),
+        Condition::ContentType { mime } => format!("content:{}", mime),
+        Condition::Duplicate { reference_dir } => format!("duplicate:{}", reference_dir.display()),
+        Condition::MetaField { field, op, value } => {
+            let op = match op {
+                MetaOp::Equals => '=',
+                MetaOp::Contains => '~',
+                MetaOp::GreaterThan => '>',
+                MetaOp::LessThan => '<',
+            };
+            format!("meta:{}{}\"{}\"", field, op, value)
+        }
+        Condition::EmbeddedDateOlderThan { regex_with_date_group, days } => {
+            format!("dated>{}d/{}/", days, regex_with_date_group)
+        }
         Condition::Not { condition } => {
             let inner = to_text(condition);
             if needs_parens(condition) {
@@ -114,7 +509,7 @@ pub fn to_text(cond: &Condition) -> String {
             conditions
                 .iter()
                 .map(|c| {
-                    if matches!(c, Condition::Or { .. }) {
+                    if matches!(c, Condition::Or { .. } | Condition::Xor { .. }) {
                         format!("({})", to_text(c))
                     } else {
                         to_text(c)
@@ -126,15 +521,78 @@ pub fn to_text(cond: &Condition) -> String {
         Condition::Or { conditions } => {
             conditions
                 .iter()
-                .map(|c| to_text(c))
+                .map(|c| {
+                    if matches!(c, Condition::Xor { .. }) {
+                        format!("({})", to_text(c))
+                    } else {
+                        to_text(c)
+                    }
+                })
                 .collect::<Vec<_>>()
                 .join(" OR ")
         }
+        Condition::Xor { conditions } => {
+            conditions
+                .iter()
+                .map(|c| {
+                    if matches!(c, Condition::Or { .. } | Condition::Xor { .. }) {
+                        format!("({})", to_text(c))
+                    } else {
+                        to_text(c)
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(" XOR ")
+        }
     }
 }
 
+/// Serialize a Condition tree with every AND/OR/XOR group parenthesized, so the
+/// grouping the parser chose is visible: `*.pdf OR *.docx AND *report*` becomes
+/// `(*.pdf OR (*.docx AND *report*))`. Parses back to the same tree.
+pub fn to_explicit_text(cond: &Condition) -> String {
+    let group = |conditions: &[Condition], op: &str| {
+        let parts: Vec<String> = conditions.iter().map(to_explicit_text).collect();
+        format!("({})", parts.join(op))
+    };
+    match cond {
+        Condition::Not { condition } => format!("NOT {}", to_explicit_text(condition)),
+        Condition::And { conditions } => group(conditions, " AND "),
+        Condition::Or { conditions } => group(conditions, " OR "),
+        Condition::Xor { conditions } => group(conditions, " XOR "),
+        leaf => to_text(leaf),
+    }
+}
+
+/// A glob as condition text, quoted when the bare word would tokenize differently:
+/// it has spaces or parens, is a keyword, or would read as another condition
+/// (`*` alone, a regex, `tag:x`, `modified>30d`, …). Escapes already in the pattern
+/// are kept; unescaped quotes are escaped inside the quoted form.
+fn glob_text(pattern: &str) -> String {
+    let bare_parses_as_glob = parse(pattern).is_ok_and(|c| matches!(c, Condition::Glob { pattern: p } if p == pattern));
+    if bare_parses_as_glob {
+        return pattern.to_string();
+    }
+    let mut quoted = String::with_capacity(pattern.len() + 2);
+    quoted.push('"');
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                quoted.push(c);
+                // A trailing backslash is a literal one; double it so it can't eat the closing quote
+                quoted.push(chars.next().unwrap_or('\\'));
+            }
+            '"' => quoted.push_str("\\\""),
+            _ => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
 fn needs_parens(cond: &Condition) -> bool {
-    matches!(cond, Condition::And { .. } | Condition::Or { .. })
+    matches!(cond, Condition::And { .. } | Condition::Or { .. } | Condition::Xor { .. })
 }
 
 // ── Tokenizer ───────────────────────────────────────────────
@@ -143,14 +601,83 @@ fn needs_parens(cond: &Condition) -> bool {
 enum Token {
     And,
     Or,
+    Xor,
     Not,
     LParen,
     RParen,
     Glob(String),
-    Regex(String),
+    /// `"pattern"`: a glob taken literally, never read as a keyword or prefix form
+    QuotedGlob(String),
+    /// Pattern and trailing flag letters
+    Regex(String, String),
+    /// `dated>Nd/pattern/`: day threshold and capture pattern
+    EmbeddedDate(u32, String),
+    /// `meta:field~"value"`: field, operator and value
+    Meta(String, MetaOp, String),
+}
+
+/// Public, serializable token category for editor syntax highlighting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum TokenKind {
+    And,
+    Or,
+    Xor,
+    Not,
+    LParen,
+    RParen,
+    Glob,
+    Regex,
+}
+
+/// A token with its position in the source text.
+/// `start`/`end` are character (not byte) offsets, end-exclusive.
+/// `text` is the raw source slice, e.g. `/^IMG_\d+/` including the slashes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TokenSpan {
+    pub kind: TokenKind,
+    pub start: usize,
+    pub end: usize,
+    pub text: String,
+}
+
+impl Token {
+    fn kind(&self) -> TokenKind {
+        match self {
+            Token::And => TokenKind::And,
+            Token::Or => TokenKind::Or,
+            Token::Xor => TokenKind::Xor,
+            Token::Not => TokenKind::Not,
+            Token::LParen => TokenKind::LParen,
+            Token::RParen => TokenKind::RParen,
+            Token::Glob(_) | Token::QuotedGlob(_) | Token::Meta(..) => TokenKind::Glob,
+            Token::Regex(..) | Token::EmbeddedDate(..) => TokenKind::Regex,
+        }
+    }
+}
+
+/// Tokenize condition text into spans (for syntax highlighting in the UI).
+pub fn tokenize_spans(input: &str) -> Result<Vec<TokenSpan>, String> {
+    let chars: Vec<char> = input.chars().collect();
+    Ok(tokenize_with_spans(input)?
+        .into_iter()
+        .map(|(token, start, end)| TokenSpan {
+            kind: token.kind(),
+            start,
+            end,
+            text: chars[start..end].iter().collect(),
+        })
+        .collect())
 }
 
 fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    Ok(tokenize_with_spans(input)?
+        .into_iter()
+        .map(|(token, _, _)| token)
+        .collect())
+}
+
+/// Tokenize, returning each token with its (start, end) character offsets.
+fn tokenize_with_spans(input: &str) -> Result<Vec<(Token, usize, usize)>, String> {
     let mut tokens = Vec::new();
     let chars: Vec<char> = input.chars().collect();
     let mut i = 0;
@@ -164,18 +691,39 @@ fn tokenize(input: &str) -> Result<Vec<Token>, String> {
 
         // Parentheses
         if chars[i] == '(' {
-            tokens.push(Token::LParen);
+            tokens.push((Token::LParen, i, i + 1));
             i += 1;
             continue;
         }
         if chars[i] == ')' {
-            tokens.push(Token::RParen);
+            tokens.push((Token::RParen, i, i + 1));
             i += 1;
             continue;
         }
 
-        // Regex literal: /pattern/
+        // Quoted glob: "annual report*" — may hold spaces and parens
+        if chars[i] == '"' {
+            let token_start = i;
+            i += 1;
+            let start = i;
+            while i < chars.len() && chars[i] != '"' {
+                i += if chars[i] == '\\' { 2 } else { 1 };
+            }
+            if i >= chars.len() {
+                return Err("Unterminated pattern: missing closing \"".to_string());
+            }
+            let pattern: String = chars[start..i].iter().collect();
+            i += 1;
+            if pattern.is_empty() {
+                return Err("Empty quoted pattern".to_string());
+            }
+            tokens.push((Token::QuotedGlob(pattern), token_start, i));
+            continue;
+        }
+
+        // Regex literal: /pattern/ with optional trailing flags (/pattern/i)
         if chars[i] == '/' {
+            let token_start = i;
             i += 1;
             let start = i;
             while i < chars.len() && chars[i] != '/' {
@@ -185,21 +733,103 @@ fn tokenize(input: &str) -> Result<Vec<Token>, String> {
                 return Err("Unterminated regex: missing closing /".to_string());
             }
             let pattern: String = chars[start..i].iter().collect();
-            tokens.push(Token::Regex(pattern));
             i += 1; // skip closing /
+            let flags_start = i;
+            while i < chars.len() && chars[i].is_ascii_alphabetic() {
+                i += 1;
+            }
+            let flags: String = chars[flags_start..i].iter().collect();
+            tokens.push((Token::Regex(pattern, flags), token_start, i));
+            continue;
+        }
+
+        // Embedded date: dated>30d/pattern/ — the pattern may hold parens, so it's read
+        // here rather than as a glob word
+        if starts_with_ignore_case(&chars[i..], "dated>") {
+            let token_start = i;
+            i += "dated>".len();
+            let digits_start = i;
+            while i < chars.len() && chars[i].is_ascii_digit() {
+                i += 1;
+            }
+            let days = chars[digits_start..i].iter().collect::<String>().parse::<u32>().ok();
+            let (Some(days), Some('d'), Some('/')) = (days, chars.get(i), chars.get(i + 1)) else {
+                return Err("Invalid embedded date: expected dated>30d/pattern/".to_string());
+            };
+            i += 2;
+            let start = i;
+            while i < chars.len() && chars[i] != '/' {
+                i += 1;
+            }
+            if i >= chars.len() {
+                return Err("Unterminated regex: missing closing /".to_string());
+            }
+            let pattern: String = chars[start..i].iter().collect();
+            i += 1;
+            tokens.push((Token::EmbeddedDate(days, pattern), token_start, i));
+            continue;
+        }
+
+        // Metadata: meta:field~"value" — the quoted value may hold spaces and parens
+        if starts_with_ignore_case(&chars[i..], "meta:") {
+            let token_start = i;
+            i += "meta:".len();
+            let field_start = i;
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let field: String = chars[field_start..i].iter().collect();
+            let op = match chars.get(i) {
+                Some('=') => MetaOp::Equals,
+                Some('~') => MetaOp::Contains,
+                Some('>') => MetaOp::GreaterThan,
+                Some('<') => MetaOp::LessThan,
+                _ => return Err("Invalid metadata condition: expected meta:field~\"value\"".to_string()),
+            };
+            if field.is_empty() {
+                return Err("Invalid metadata condition: expected meta:field~\"value\"".to_string());
+            }
+            i += 1;
+            let value: String = if chars.get(i) == Some(&'"') {
+                i += 1;
+                let start = i;
+                while i < chars.len() && chars[i] != '"' {
+                    i += 1;
+                }
+                if i >= chars.len() {
+                    return Err("Unterminated metadata value: missing closing \"".to_string());
+                }
+                i += 1;
+                chars[start..i - 1].iter().collect()
+            } else {
+                let start = i;
+                while i < chars.len() && !chars[i].is_whitespace() && chars[i] != '(' && chars[i] != ')' {
+                    i += 1;
+                }
+                chars[start..i].iter().collect()
+            };
+            if value.is_empty() {
+                return Err(format!("Missing value after 'meta:{}'", field));
+            }
+            tokens.push((Token::Meta(field.to_lowercase(), op, value), token_start, i));
             continue;
         }
 
-        // Keywords: AND, OR, NOT — must be followed by whitespace or paren or end
+        // Keywords: AND, OR, XOR, NOT — must be followed by whitespace or paren or end
         if i + 3 <= chars.len() {
             let word3: String = chars[i..i + 3].iter().collect();
             if word3.eq_ignore_ascii_case("AND") && is_word_boundary(&chars, i + 3) {
-                tokens.push(Token::And);
+                tokens.push((Token::And, i, i + 3));
                 i += 3;
                 continue;
             }
             if word3.eq_ignore_ascii_case("NOT") && is_word_boundary(&chars, i + 3) {
-                tokens.push(Token::Not);
+                tokens.push((Token::Not, i, i + 3));
+                i += 3;
+                continue;
+            }
+            if word3.eq_ignore_ascii_case("XOR") && is_word_boundary(&chars, i + 3) {
+                tokens.push((Token::Xor, i, i + 3));
                 i += 3;
                 continue;
             }
@@ -207,30 +837,37 @@ fn tokenize(input: &str) -> Result<Vec<Token>, String> {
         if i + 2 <= chars.len() {
             let word2: String = chars[i..i + 2].iter().collect();
             if word2.eq_ignore_ascii_case("OR") && is_word_boundary(&chars, i + 2) {
-                tokens.push(Token::Or);
+                tokens.push((Token::Or, i, i + 2));
                 i += 2;
                 continue;
             }
         }
 
-        // Glob pattern — collect until whitespace, paren, or end
+        // Glob pattern — collect until whitespace, paren, or end (a backslash keeps the
+        // next character in the word; the escape itself stays in the pattern)
         let start = i;
         while i < chars.len()
             && !chars[i].is_whitespace()
             && chars[i] != '('
             && chars[i] != ')'
         {
-            i += 1;
+            i += if chars[i] == '\\' { 2 } else { 1 };
         }
+        i = i.min(chars.len()); // a trailing backslash steps past the end
         let glob: String = chars[start..i].iter().collect();
         if !glob.is_empty() {
-            tokens.push(Token::Glob(glob));
+            tokens.push((Token::Glob(glob), start, i));
         }
     }
 
     Ok(tokens)
 }
 
+fn starts_with_ignore_case(chars: &[char], word: &str) -> bool {
+    chars.len() >= word.len()
+        && chars.iter().zip(word.chars()).all(|(c, w)| c.eq_ignore_ascii_case(&w))
+}
+
 fn is_word_boundary(chars: &[char], pos: usize) -> bool {
     pos >= chars.len() || chars[pos].is_whitespace() || chars[pos] == '(' || chars[pos] == ')'
 }
@@ -238,25 +875,41 @@ fn is_word_boundary(chars: &[char], pos: usize) -> bool {
 // ── Recursive Descent Parser ────────────────────────────────
 // Grammar:
 //   expr     = or_expr
-//   or_expr  = and_expr ("OR" and_expr)*
+//   or_expr  = and_expr (("OR" | "XOR") and_expr)*   — left-associative
 //   and_expr = not_expr ("AND" not_expr)*
 //   not_expr = "NOT" not_expr | primary
-//   primary  = "(" or_expr ")" | glob | regex
+//   primary  = "(" or_expr ")" | glob | regex | dated | meta
 
 fn parse_or<'a>(tokens: &'a [Token]) -> Result<(Condition, &'a [Token]), String> {
     let (left, mut rest) = parse_and(tokens)?;
     let mut parts = vec![left];
+    // Operator of the current run of `parts`; a switch between OR and XOR closes the run
+    // into a single condition (so `A OR B XOR C` is `(A OR B) XOR C`).
+    let mut op: Option<Token> = None;
 
-    while !rest.is_empty() && rest[0] == Token::Or {
+    while !rest.is_empty() && (rest[0] == Token::Or || rest[0] == Token::Xor) {
+        let next_op = rest[0].clone();
+        if let Some(prev) = op.replace(next_op.clone()) {
+            if prev != next_op {
+                parts = vec![combine_or(prev, std::mem::take(&mut parts))];
+            }
+        }
         let (right, r) = parse_and(&rest[1..])?;
         parts.push(right);
         rest = r;
     }
 
-    if parts.len() == 1 {
-        Ok((parts.remove(0), rest))
+    match op {
+        Some(op) => Ok((combine_or(op, parts), rest)),
+        None => Ok((parts.remove(0), rest)),
+    }
+}
+
+fn combine_or(op: Token, conditions: Vec<Condition>) -> Condition {
+    if op == Token::Xor {
+        Condition::Xor { conditions }
     } else {
-        Ok((Condition::Or { conditions: parts }, rest))
+        Condition::Or { conditions }
     }
 }
 
@@ -309,7 +962,19 @@ fn parse_primary<'a>(tokens: &'a [Token]) -> Result<(Condition, &'a [Token]), St
             Ok((cond, &rest[1..]))
         }
         Token::Glob(pattern) => {
-            if pattern == "*" {
+            if let Some(age) = parse_age(pattern)? {
+                Ok((age, &tokens[1..]))
+            } else if let Some(extreme) = parse_extreme_size(pattern)? {
+                Ok((extreme, &tokens[1..]))
+            } else if let Some(tag) = parse_tag(pattern)? {
+                Ok((tag, &tokens[1..]))
+            } else if let Some(parent) = parse_parent_dir(pattern)? {
+                Ok((parent, &tokens[1..]))
+            } else if let Some(content) = parse_content_type(pattern)? {
+                Ok((content, &tokens[1..]))
+            } else if let Some(duplicate) = parse_duplicate(pattern)? {
+                Ok((duplicate, &tokens[1..]))
+            } else if pattern == "*" {
                 Ok((Condition::Always, &tokens[1..]))
             } else {
                 Ok((
@@ -320,9 +985,31 @@ fn parse_primary<'a>(tokens: &'a [Token]) -> Result<(Condition, &'a [Token]), St
                 ))
             }
         }
-        Token::Regex(pattern) => Ok((
+        Token::QuotedGlob(pattern) => Ok((
+            Condition::Glob {
+                pattern: pattern.clone(),
+            },
+            &tokens[1..],
+        )),
+        Token::Regex(pattern, flags) => Ok((
             Condition::Regex {
                 pattern: pattern.clone(),
+                flags: flags.clone(),
+            },
+            &tokens[1..],
+        )),
+        Token::EmbeddedDate(days, pattern) => Ok((
+            Condition::EmbeddedDateOlderThan {
+                regex_with_date_group: pattern.clone(),
+                days: *days,
+            },
+            &tokens[1..],
+        )),
+        Token::Meta(field, op, value) => Ok((
+            Condition::MetaField {
+                field: field.clone(),
+                op: *op,
+                value: value.clone(),
             },
             &tokens[1..],
         )),
@@ -330,32 +1017,192 @@ fn parse_primary<'a>(tokens: &'a [Token]) -> Result<(Condition, &'a [Token]), St
     }
 }
 
+/// Parse an age word like `modified>30d` or `created<7d`.
+/// Returns Ok(None) if the word isn't an age expression (so it's treated as a glob).
+fn parse_age(word: &str) -> Result<Option<Condition>, String> {
+    let lower = word.to_lowercase();
+    let (field, rest) = if let Some(rest) = lower.strip_prefix("modified") {
+        (TimeField::Modified, rest)
+    } else if let Some(rest) = lower.strip_prefix("created") {
+        (TimeField::Created, rest)
+    } else {
+        return Ok(None);
+    };
+    let (op, rest) = if let Some(rest) = rest.strip_prefix('>') {
+        (AgeOp::GreaterThan, rest)
+    } else if let Some(rest) = rest.strip_prefix('<') {
+        (AgeOp::LessThan, rest)
+    } else {
+        return Ok(None);
+    };
+    let days = rest
+        .strip_suffix('d')
+        .and_then(|n| n.parse::<u32>().ok())
+        .ok_or_else(|| format!("Invalid age '{}': expected a number of days like 30d", word))?;
+    Ok(Some(Condition::Age { op, days, field }))
+}
+
+/// Parse a size-ranking word like `largest:1` or `smallest:3`.
+/// Returns Ok(None) if the word isn't a size-ranking expression.
+fn parse_extreme_size(word: &str) -> Result<Option<Condition>, String> {
+    let lower = word.to_lowercase();
+    let (which, n) = if let Some(n) = lower.strip_prefix("largest:") {
+        (SizeExtreme::Largest, n)
+    } else if let Some(n) = lower.strip_prefix("smallest:") {
+        (SizeExtreme::Smallest, n)
+    } else {
+        return Ok(None);
+    };
+    match n.parse::<u32>() {
+        Ok(n) if n > 0 => Ok(Some(Condition::ExtremeSize { which, n })),
+        _ => Err(format!("Invalid size ranking '{}': expected a positive count like largest:1", word)),
+    }
+}
+
+/// Parse a file-tag word like `tag:Red`. Returns Ok(None) if the word isn't a tag expression.
+fn parse_tag(word: &str) -> Result<Option<Condition>, String> {
+    match word.get(..4) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("tag:") => {
+            let tag = &word[4..];
+            if tag.is_empty() {
+                return Err("Missing tag name after 'tag:'".to_string());
+            }
+            Ok(Some(Condition::HasTag { tag: tag.to_string() }))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Parse a parent-folder word like `parent:to-sort`.
+/// Returns Ok(None) if the word isn't a parent-folder expression.
+fn parse_parent_dir(word: &str) -> Result<Option<Condition>, String> {
+    match word.get(..7) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("parent:") => {
+            let pattern = &word[7..];
+            if pattern.is_empty() {
+                return Err("Missing folder name after 'parent:'".to_string());
+            }
+            Ok(Some(Condition::ParentDir { pattern: pattern.to_string() }))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Parse a content-type word like `content:image/png`.
+/// Returns Ok(None) if the word isn't a content-type expression.
+fn parse_content_type(word: &str) -> Result<Option<Condition>, String> {
+    match word.get(..8) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("content:") => {
+            let mime = &word[8..];
+            if !mime.contains('/') {
+                return Err(format!(
+                    "Invalid content type '{}': expected a MIME type like content:image/png",
+                    word
+                ));
+            }
+            Ok(Some(Condition::ContentType { mime: mime.to_string() }))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Parse a duplicate word like `duplicate:D:\Archive`.
+/// Returns Ok(None) if the word isn't a duplicate expression.
+fn parse_duplicate(word: &str) -> Result<Option<Condition>, String> {
+    match word.get(..10) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("duplicate:") => {
+            let dir = &word[10..];
+            if dir.is_empty() {
+                return Err("Missing folder after 'duplicate:'".to_string());
+            }
+            Ok(Some(Condition::Duplicate { reference_dir: PathBuf::from(dir) }))
+        }
+        _ => Ok(None),
+    }
+}
+
 // ── Validate ────────────────────────────────────────────────
 
 /// Validate a condition text string. Returns Ok(()) or Err with message.
 pub fn validate_text(input: &str) -> Result<(), String> {
-    parse(input).map(|_| ())
+    validate_condition(&parse(input)?)
 }
 
 /// Validate a condition tree (check regex patterns are valid, etc.)
 #[allow(dead_code)]
 pub fn validate_condition(cond: &Condition) -> Result<(), String> {
     match cond {
-        Condition::Regex { pattern } => {
-            Regex::new(pattern).map_err(|e| format!("Invalid regex: {}", e))?;
+        Condition::Regex { pattern, flags } => {
+            build_regex(pattern, flags)?;
             Ok(())
         }
-        Condition::And { conditions } | Condition::Or { conditions } => {
+        Condition::And { conditions } | Condition::Or { conditions } | Condition::Xor { conditions } => {
             for c in conditions {
                 validate_condition(c)?;
             }
             Ok(())
         }
         Condition::Not { condition } => validate_condition(condition),
+        Condition::EmbeddedDateOlderThan { regex_with_date_group, .. } => {
+            let re = build_regex(regex_with_date_group, "")?;
+            if re.captures_len() < 2 {
+                return Err("Embedded date regex needs a capture group around the date".to_string());
+            }
+            Ok(())
+        }
         _ => Ok(()),
     }
 }
 
+// ── Golden tests ────────────────────────────────────────────
+
+/// One expectation from a golden file: `condition_text` should (or should not) match `filename`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct GoldenCase {
+    pub condition_text: String,
+    pub filename: String,
+    pub expected_match: bool,
+}
+
+/// Outcome of one golden case. `actual_match` is None when the condition failed to parse.
+#[derive(Debug, Clone, Serialize)]
+pub struct GoldenResult {
+    pub condition_text: String,
+    pub filename: String,
+    pub expected_match: bool,
+    pub actual_match: Option<bool>,
+    pub passed: bool,
+    pub error: Option<String>,
+}
+
+/// Read a golden file: a JSON array of `{ condition_text, filename, expected_match }`.
+pub fn load_golden_file(path: &Path) -> Result<Vec<GoldenCase>, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    serde_json::from_str(&text).map_err(|e| format!("Invalid golden file {}: {}", path.display(), e))
+}
+
+/// Parse and evaluate every case (name-only, like the rule tester). A condition that
+/// doesn't parse fails its case rather than stopping the run.
+pub fn run_golden_cases(cases: &[GoldenCase]) -> Vec<GoldenResult> {
+    cases
+        .iter()
+        .map(|case| {
+            let (actual_match, error) = match parse(&case.condition_text).and_then(|c| validate_condition(&c).map(|_| c)) {
+                Ok(cond) => (Some(evaluate(&cond, &case.filename)), None),
+                Err(e) => (None, Some(e)),
+            };
+            GoldenResult {
+                condition_text: case.condition_text.clone(),
+                filename: case.filename.clone(),
+                expected_match: case.expected_match,
+                actual_match,
+                passed: actual_match == Some(case.expected_match),
+                error,
+            }
+        })
+        .collect()
+}
+
 // ── Tests ───────────────────────────────────────────────────
 
 #[cfg(test)]
@@ -374,6 +1221,19 @@ mod tests {
         assert!(glob_match("*", "anything.xyz"));
     }
 
+    #[test]
+    fn test_case_sensitive_option() {
+        let c = parse("README*").unwrap();
+        let ctx = FileContext::default();
+        let exact = EvalOptions { case_sensitive: true };
+        assert!(evaluate_with_options(&c, "README.md", &ctx, exact));
+        assert!(!evaluate_with_options(&c, "readme.md", &ctx, exact));
+        assert!(evaluate_with_options(&c, "readme.md", &ctx, EvalOptions::default()));
+        // Options reach nested conditions
+        let nested = parse("NOT README* AND *.md").unwrap();
+        assert!(evaluate_with_options(&nested, "readme.md", &ctx, exact));
+    }
+
     #[test]
     fn test_parse_simple() {
         let c = parse("*.pdf").unwrap();
@@ -420,6 +1280,84 @@ mod tests {
         assert!(!evaluate(&c, "photo.jpg"));
     }
 
+    #[test]
+    fn test_regex_flags() {
+        let c = parse(r"/^img_\d+/i").unwrap();
+        assert!(evaluate(&c, "IMG_1234.jpg"));
+        assert_eq!(to_text(&c), r"/^img_\d+/i");
+        assert!(!evaluate(&parse(r"/^img_\d+/").unwrap(), "IMG_1234.jpg"));
+
+        // Flags end at whitespace, so keywords still tokenize
+        let c = parse(r"/^a/i AND *.txt").unwrap();
+        assert!(evaluate(&c, "Abc.txt"));
+
+        let err = validate_text("/abc/q").unwrap_err();
+        assert!(err.contains("Unknown regex flag 'q'"), "{}", err);
+        assert!(validate_text("/a b/x").is_ok());
+    }
+
+    #[test]
+    fn quoted_globs_may_contain_spaces() {
+        let cond = parse(r#""my file*" AND *.pdf"#).unwrap();
+        match &cond {
+            Condition::And { conditions } => {
+                assert!(matches!(&conditions[0], Condition::Glob { pattern } if pattern == "my file*"));
+            }
+            other => panic!("expected AND, got {:?}", other),
+        }
+        assert!(evaluate(&cond, "my file 2024.pdf"));
+        assert!(!evaluate(&cond, "my file 2024.txt"));
+        assert_eq!(to_text(&cond), r#""my file*" AND *.pdf"#);
+
+        // Quoted words are literal globs, not keywords or prefix forms
+        assert!(matches!(parse(r#""tag:Red""#).unwrap(), Condition::Glob { .. }));
+        assert_eq!(to_text(&Condition::Glob { pattern: "AND".into() }), r#""AND""#);
+        assert_eq!(to_text(&Condition::Glob { pattern: "(draft)*".into() }), r#""(draft)*""#);
+        assert!(parse(r#""unterminated AND *.pdf"#).is_err());
+
+        let spans = tokenize_spans(r#"NOT "a b""#).unwrap();
+        assert_eq!(spans[1].text, r#""a b""#);
+        assert_eq!(spans[1].kind, TokenKind::Glob);
+    }
+
+    #[test]
+    fn escapes_match_literal_metacharacters() {
+        let quoted = parse(r#""report \(final\).pdf""#).unwrap();
+        let bare = parse(r"report\ \(final\).pdf").unwrap();
+        for cond in [&quoted, &bare] {
+            assert!(evaluate(cond, "report (final).pdf"));
+            assert!(!evaluate(cond, "report final.pdf"));
+        }
+
+        let star = parse(r"\*important\**").unwrap();
+        assert!(evaluate(&star, "*important* notes.txt"));
+        assert!(!evaluate(&star, "very important notes.txt"));
+        assert!(evaluate(&parse(r"why\?.txt").unwrap(), "why?.txt"));
+        assert!(!evaluate(&parse(r"why\?.txt").unwrap(), "whyX.txt"));
+
+        // Escapes survive a round trip, and a quote inside a quoted pattern is escaped
+        assert_eq!(to_text(&bare), r"report\ \(final\).pdf");
+        let with_quote = Condition::Glob { pattern: r#"say "hi" *"#.into() };
+        let text = to_text(&with_quote);
+        assert_eq!(text, r#""say \"hi\" *""#);
+        assert!(evaluate(&parse(&text).unwrap(), r#"say "hi" there"#));
+    }
+
+    #[test]
+    fn explicit_text_shows_precedence() {
+        let cases = [
+            ("*.pdf OR *.docx AND *report*", "(*.pdf OR (*.docx AND *report*))"),
+            ("(*.pdf OR *.docx) AND *report*", "((*.pdf OR *.docx) AND *report*)"),
+            ("NOT *.tmp AND *.log", "(NOT *.tmp AND *.log)"),
+            ("*.pdf", "*.pdf"),
+        ];
+        for (input, expected) in cases {
+            let cond = parse(input).unwrap();
+            assert_eq!(to_explicit_text(&cond), expected, "for {}", input);
+            assert_eq!(to_text(&parse(expected).unwrap()), to_text(&cond));
+        }
+    }
+
     #[test]
     fn test_roundtrip() {
         let cases = vec![
@@ -428,6 +1366,9 @@ mod tests {
             "*.jpg OR *.png",
             "NOT *.tmp",
             "(*.pdf OR *.docx) AND *report*",
+            "*.pdf XOR *invoice*",
+            "(*.pdf OR *.doc) XOR *test*",
+            "*.pdf XOR *invoice* OR *.txt",
         ];
         for input in cases {
             let cond = parse(input).unwrap();
@@ -443,6 +1384,227 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_tokenize_spans() {
+        let spans = tokenize_spans(r"(*.pdf OR /^IMG_\d+/) AND NOT *tmp*").unwrap();
+        let got: Vec<(TokenKind, usize, usize, &str)> = spans
+            .iter()
+            .map(|t| (t.kind, t.start, t.end, t.text.as_str()))
+            .collect();
+        assert_eq!(
+            got,
+            vec![
+                (TokenKind::LParen, 0, 1, "("),
+                (TokenKind::Glob, 1, 6, "*.pdf"),
+                (TokenKind::Or, 7, 9, "OR"),
+                (TokenKind::Regex, 10, 20, r"/^IMG_\d+/"),
+                (TokenKind::RParen, 20, 21, ")"),
+                (TokenKind::And, 22, 25, "AND"),
+                (TokenKind::Not, 26, 29, "NOT"),
+                (TokenKind::Glob, 30, 35, "*tmp*"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_spans_unterminated_regex() {
+        assert!(tokenize_spans("/abc").is_err());
+    }
+
+    fn ctx_aged(days: u64) -> FileContext {
+        let time = SystemTime::now() - Duration::from_secs(days * 86_400);
+        FileContext { modified: Some(time), created: Some(time), ..Default::default() }
+    }
+
+    #[test]
+    fn test_parse_age() {
+        let c = parse("modified>30d").unwrap();
+        assert!(matches!(
+            c,
+            Condition::Age { op: AgeOp::GreaterThan, days: 30, field: TimeField::Modified }
+        ));
+        assert_eq!(to_text(&c), "modified>30d");
+
+        let c = parse("*.png AND created<7d").unwrap();
+        assert_eq!(to_text(&c), "*.png AND created<7d");
+
+        assert!(parse("modified>abcd").is_err());
+    }
+
+    #[test]
+    fn test_evaluate_age() {
+        let older = parse("*.png AND modified>30d").unwrap();
+        assert!(evaluate_with_context(&older, "shot.png", &ctx_aged(45)));
+        assert!(!evaluate_with_context(&older, "shot.png", &ctx_aged(10)));
+
+        let newer = parse("created<7d").unwrap();
+        assert!(evaluate_with_context(&newer, "a.txt", &ctx_aged(2)));
+        assert!(!evaluate_with_context(&newer, "a.txt", &ctx_aged(8)));
+
+        // Without metadata, age conditions never match
+        assert!(!evaluate(&older, "shot.png"));
+    }
+
+    #[test]
+    fn test_created_falls_back_to_modified() {
+        let time = SystemTime::now() - Duration::from_secs(40 * 86_400);
+        let ctx = FileContext::from_times(Some(time), None);
+        assert!(ctx.created_is_fallback);
+        assert!(evaluate_with_context(&parse("created>30d").unwrap(), "a", &ctx));
+
+        let ctx = FileContext::from_times(Some(time), Some(SystemTime::now()));
+        assert!(!ctx.created_is_fallback);
+        assert!(!evaluate_with_context(&parse("created>30d").unwrap(), "a", &ctx));
+
+        assert!(uses_created_time(&parse("NOT created>1d").unwrap()));
+        assert!(!uses_created_time(&parse("modified>1d").unwrap()));
+    }
+
+    #[test]
+    fn test_parse_extreme_size() {
+        let c = parse("largest:2").unwrap();
+        assert!(matches!(c, Condition::ExtremeSize { which: SizeExtreme::Largest, n: 2 }));
+        assert_eq!(to_text(&c), "largest:2");
+        assert_eq!(to_text(&parse("*.zip AND smallest:1").unwrap()), "*.zip AND smallest:1");
+        assert!(parse("largest:0").is_err());
+        assert!(parse("largest:x").is_err());
+    }
+
+    #[test]
+    fn test_evaluate_extreme_size() {
+        let c = parse("largest:2").unwrap();
+        let ranked = |largest, smallest| FileContext {
+            size_rank: Some(SizeRank { largest, smallest }),
+            ..Default::default()
+        };
+        assert!(evaluate_with_context(&c, "a", &ranked(1, 3)));
+        assert!(evaluate_with_context(&c, "a", &ranked(2, 2)));
+        assert!(!evaluate_with_context(&c, "a", &ranked(3, 1)));
+        // No ranking computed → never matches
+        assert!(!evaluate(&c, "a"));
+    }
+
+    #[test]
+    fn test_parse_tag() {
+        let c = parse("tag:Red AND *.pdf").unwrap();
+        assert_eq!(to_text(&c), "tag:Red AND *.pdf");
+        assert!(matches!(parse("tag:Red").unwrap(), Condition::HasTag { ref tag } if tag == "Red"));
+        assert!(parse("tag:").is_err());
+        // No path in context → never matches
+        assert!(!evaluate(&parse("tag:Red").unwrap(), "a.pdf"));
+    }
+
+    #[test]
+    fn test_parse_parent_dir() {
+        let c = parse("parent:to-sort AND *.pdf").unwrap();
+        assert_eq!(to_text(&c), "parent:to-sort AND *.pdf");
+        assert!(parse("parent:").is_err());
+
+        let cond = parse("parent:to-*").unwrap();
+        let nested = FileContext::from_path(Path::new("/inbox/a/To-Sort/x.pdf"));
+        let deeper = FileContext::from_path(Path::new("/inbox/to-sort/b/x.pdf"));
+        assert!(evaluate_with_context(&cond, "x.pdf", &nested));
+        assert!(!evaluate_with_context(&cond, "x.pdf", &deeper));
+        // No path in context → never matches
+        assert!(!evaluate(&cond, "x.pdf"));
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    #[test]
+    fn test_tag_never_matches_without_platform_support() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.pdf");
+        fs::write(&path, "x").unwrap();
+        let ctx = FileContext::from_path(&path);
+        assert!(!evaluate_with_context(&parse("tag:Red").unwrap(), "a.pdf", &ctx));
+    }
+
+    #[cfg(target_os = "macos")]
+    #[test]
+    fn test_tag_matches_finder_tag() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.pdf");
+        fs::write(&path, "x").unwrap();
+        let mut plist_bytes = Vec::new();
+        plist::to_writer_binary(&mut plist_bytes, &vec!["Red\n6".to_string(), "Work".to_string()]).unwrap();
+        xattr::set(&path, "com.apple.metadata:_kMDItemUserTags", &plist_bytes).unwrap();
+
+        assert_eq!(read_file_tags(&path), vec!["Red".to_string(), "Work".to_string()]);
+        let ctx = FileContext::from_path(&path);
+        assert!(evaluate_with_context(&parse("tag:red").unwrap(), "a.pdf", &ctx));
+        assert!(!evaluate_with_context(&parse("tag:Blue").unwrap(), "a.pdf", &ctx));
+    }
+
+    const PNG_HEADER: &[u8] = &[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, 0, 0, 0, 0x0D, b'I', b'H', b'D', b'R'];
+
+    #[test]
+    fn test_parse_content_type() {
+        let c = parse("*.jpg AND NOT content:image/jpeg").unwrap();
+        assert_eq!(to_text(&c), "*.jpg AND NOT content:image/jpeg");
+        assert!(parse("content:png").is_err());
+    }
+
+    #[test]
+    fn test_content_type_sniffs_real_type() {
+        let dir = tempfile::tempdir().unwrap();
+        let fake_jpg = dir.path().join("photo.jpg");
+        fs::write(&fake_jpg, PNG_HEADER).unwrap();
+        let text = dir.path().join("notes.png");
+        fs::write(&text, "just text").unwrap();
+
+        let ctx = FileContext::from_path(&fake_jpg);
+        assert!(evaluate_with_context(&parse("content:image/png").unwrap(), "photo.jpg", &ctx));
+        assert!(evaluate_with_context(&parse("content:image/*").unwrap(), "photo.jpg", &ctx));
+        assert!(!evaluate_with_context(&parse("content:image/jpeg").unwrap(), "photo.jpg", &ctx));
+
+        let ctx = FileContext::from_path(&text);
+        assert!(!evaluate_with_context(&parse("content:image/*").unwrap(), "notes.png", &ctx));
+    }
+
+    #[test]
+    fn test_content_type_reads_header_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.bin");
+        fs::write(&path, PNG_HEADER).unwrap();
+
+        let ctx = FileContext::from_path(&path);
+        assert_eq!(ctx.content_mime(), Some("image/png"));
+        // Cached: still answers after the file is gone
+        fs::remove_file(&path).unwrap();
+        let c = parse("content:image/png OR content:image/gif").unwrap();
+        assert!(evaluate_with_context(&c, "a.bin", &ctx));
+
+        // Unreadable file → no match, no error
+        let missing = FileContext::from_path(&path);
+        assert!(!evaluate_with_context(&c, "a.bin", &missing));
+    }
+
+    #[test]
+    fn test_parse_xor() {
+        let c = parse("*.pdf XOR *invoice*").unwrap();
+        assert!(matches!(c, Condition::Xor { ref conditions } if conditions.len() == 2));
+        assert!(evaluate(&c, "report.pdf"));
+        assert!(evaluate(&c, "invoice.doc"));
+        assert!(!evaluate(&c, "invoice.pdf")); // both match
+        assert!(!evaluate(&c, "notes.txt")); // neither matches
+
+        // Three-way XOR means exactly one, not an odd count
+        let c = parse("a* XOR *b* XOR *c").unwrap();
+        assert!(evaluate(&c, "a.txt"));
+        assert!(!evaluate(&c, "abc"));
+    }
+
+    #[test]
+    fn test_xor_or_precedence_is_left_associative() {
+        let c = parse("*.pdf OR *.doc XOR *draft*").unwrap();
+        match &c {
+            Condition::Xor { conditions } => assert!(matches!(conditions[0], Condition::Or { .. })),
+            other => panic!("expected Xor at the top, got {:?}", other),
+        }
+        assert_eq!(to_text(&c), "(*.pdf OR *.doc) XOR *draft*");
+        assert_eq!(to_text(&parse("*.pdf XOR *.doc OR *.txt").unwrap()), "(*.pdf XOR *.doc) OR *.txt");
+    }
+
     #[test]
     fn test_always() {
         let c = parse("*").unwrap();
@@ -452,4 +1614,101 @@ mod tests {
         let c = parse("").unwrap();
         assert!(matches!(c, Condition::Always));
     }
+
+    #[test]
+    fn test_embedded_date_older_than() {
+        let today = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        let pattern = r"(\d{4}-\d{2}-\d{2})";
+        assert!(embedded_date_older_than(pattern, 30, "app-2024-01-05.log", today));
+        assert!(!embedded_date_older_than(pattern, 30, "app-2024-02-20.log", today));
+        // Exactly on the threshold isn't older yet
+        assert!(!embedded_date_older_than(pattern, 30, "app-2024-01-31.log", today));
+        // Named group wins over the first group; compact dates parse too
+        let named = r"^(app)-(?P<date>\d{8})";
+        assert!(embedded_date_older_than(named, 30, "app-20231201.log", today));
+    }
+
+    #[test]
+    fn test_embedded_date_unparseable_is_no_match() {
+        let today = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        let pattern = r"(\d{4}-\d{2}-\d{2})";
+        assert!(!embedded_date_older_than(pattern, 1, "app-2023-13-45.log", today));
+        assert!(!embedded_date_older_than(pattern, 1, "app.log", today));
+        assert!(!embedded_date_older_than(r"app-\d+", 1, "app-20200101.log", today));
+    }
+
+    #[test]
+    fn test_embedded_date_text_round_trip() {
+        let text = r"dated>30d/app-(\d{4}-\d{2}-\d{2})/ AND *.log";
+        let c = parse(text).unwrap();
+        match &c {
+            Condition::And { conditions } => assert!(matches!(
+                &conditions[0],
+                Condition::EmbeddedDateOlderThan { days: 30, regex_with_date_group } if regex_with_date_group == r"app-(\d{4}-\d{2}-\d{2})"
+            )),
+            other => panic!("expected And, got {:?}", other),
+        }
+        assert_eq!(to_text(&c), text);
+        assert!(validate_text(text).is_ok());
+        assert!(validate_text(r"dated>30d/app-\d+/").is_err());
+        assert!(parse("dated>x/a/").is_err());
+    }
+
+    #[test]
+    fn duplicate_text_round_trip() {
+        let c = parse(r"duplicate:D:\Archive AND *.jpg").unwrap();
+        assert_eq!(duplicate_reference_dirs(&c), vec![PathBuf::from(r"D:\Archive")]);
+        assert_eq!(to_text(&c), r"duplicate:D:\Archive AND *.jpg");
+        assert!(parse("duplicate:").is_err());
+
+        // Nothing recorded for the reference dir → no match
+        let mut ctx = FileContext::default();
+        assert!(!evaluate_with_context(&c, "a.jpg", &ctx));
+        ctx.duplicate_in.insert(PathBuf::from(r"D:\Archive"), true);
+        assert!(evaluate_with_context(&c, "a.jpg", &ctx));
+    }
+
+    #[test]
+    fn meta_field_parses_and_matches_pdf_title() {
+        let c = parse(r#"meta:title~"quarterly report" AND *.pdf"#).unwrap();
+        assert_eq!(to_text(&c), r#"meta:title~"quarterly report" AND *.pdf"#);
+        assert_eq!(to_text(&parse("meta:Author=ACME").unwrap()), r#"meta:author="ACME""#);
+        assert!(parse("meta:title").is_err());
+        assert!(parse(r#"meta:title~"open"#).is_err());
+
+        let dir = tempfile::tempdir().unwrap();
+        let pdf = dir.path().join("q3.pdf");
+        fs::write(&pdf, "%PDF-1.4\ntrailer\n<< /Info << /Title (Q3 Quarterly Report) >> >>\n%%EOF\n").unwrap();
+        let ctx = FileContext::from_path(&pdf);
+        assert!(evaluate_with_context(&c, "q3.pdf", &ctx));
+        assert!(!evaluate_with_context(&parse("meta:author~ACME").unwrap(), "q3.pdf", &ctx));
+
+        // Unreadable metadata never matches, even negated comparisons
+        assert!(!evaluate_with_context(&c, "q3.pdf", &FileContext::default()));
+        assert!(meta_matches(MetaOp::LessThan, "2019-12-31 23:59:59", "2020-01-01"));
+        assert!(meta_matches(MetaOp::GreaterThan, "400", "80"));
+    }
+
+    #[test]
+    fn bundled_golden_cases_all_pass() {
+        let cases: Vec<GoldenCase> = serde_json::from_str(include_str!("../golden/conditions.json")).unwrap();
+        assert!(!cases.is_empty());
+        let failures: Vec<_> = run_golden_cases(&cases).into_iter().filter(|r| !r.passed).collect();
+        assert!(failures.is_empty(), "golden mismatches: {:?}", failures);
+    }
+
+    #[test]
+    fn golden_reports_mismatches_and_parse_errors() {
+        let case = |condition_text: &str, filename: &str, expected_match| GoldenCase {
+            condition_text: condition_text.to_string(),
+            filename: filename.to_string(),
+            expected_match,
+        };
+        let results = run_golden_cases(&[case("*.pdf", "a.pdf", false), case("(*.pdf", "a.pdf", true)]);
+        assert_eq!(results[0].actual_match, Some(true));
+        assert!(!results[0].passed);
+        assert_eq!(results[1].actual_match, None);
+        assert!(results[1].error.is_some());
+        assert!(!results[1].passed);
+    }
 }
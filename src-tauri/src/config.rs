@@ -1,6 +1,9 @@
+use chrono::{DateTime, Datelike, Duration, NaiveDateTime, Timelike, Utc, Weekday};
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
 
 /// Get the app data directory: %APPDATA%/folder-organizer/
 pub fn app_data_dir() -> PathBuf {
@@ -15,12 +18,38 @@ fn config_path() -> PathBuf {
     app_data_dir().join("config.json")
 }
 
+/// Current `AppConfig::schema_version`. Bump it together with a new step in
+/// `upgrade_config` whenever the on-disk shape changes.
+pub const CONFIG_SCHEMA_VERSION: u32 = 1;
+
 // ── Data types ──────────────────────────────────────────────
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppConfig {
+    /// Shape of this config file; older files are upgraded by `migrate_config` on load.
+    #[serde(default)]
+    pub schema_version: u32,
     pub folders: Vec<WatchedFolder>,
     pub settings: AppSettings,
+    /// Named rule sets that can be applied to new folders (see `auto_watch_new_subdirs`)
+    #[serde(default)]
+    pub presets: Vec<RulePreset>,
+    /// Folders removed from the watch list, kept with their rules until purged.
+    /// Not watched or scanned.
+    #[serde(default)]
+    pub archived_folders: Vec<WatchedFolder>,
+    /// Top-level fields this version doesn't know about, kept so they survive a save.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// A saved set of rules (and folder whitelist) applied to folders as a template.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RulePreset {
+    pub name: String,
+    pub rules: Vec<Rule>,
+    #[serde(default)]
+    pub whitelist: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -32,8 +61,67 @@ pub struct AppSettings {
     /// Minimize to tray on close
     pub minimize_to_tray: bool,
     /// Show toast notifications on actions
+    #[serde(default = "default_true")]
+    pub show_notifications: bool,
     /// Days to keep activity log entries
     pub log_retention_days: u32,
+    /// Before pruning expired log entries, append them to monthly gzip archives
+    /// under `log_archives/` instead of discarding them.
+    #[serde(default)]
+    pub archive_logs_before_pruning: bool,
+    /// Scheduled actions run per batch before pausing (0 = no batching)
+    #[serde(default)]
+    pub deletion_batch_size: u32,
+    /// Pause between batches of scheduled actions, in milliseconds
+    #[serde(default = "default_deletion_batch_pause_ms")]
+    pub deletion_batch_pause_ms: u32,
+    /// Where deleted files go: the OS recycle bin, or the app's own staging folder
+    #[serde(default)]
+    pub deletion_mode: DeletionMode,
+    /// Days staged deletions are kept before being removed for good
+    #[serde(default = "default_staging_retention_days")]
+    pub staging_retention_days: u32,
+    /// Failed attempts before a scheduled action is given up on. Retries back off
+    /// exponentially in between.
+    #[serde(default = "default_max_deletion_attempts")]
+    pub max_deletion_attempts: u32,
+    /// Keep given-up entries in the schedule (not retried) instead of dropping them
+    #[serde(default)]
+    pub keep_failed_deletions: bool,
+    /// Hold due deletions for the user's approval instead of running them
+    #[serde(default)]
+    pub require_deletion_approval: bool,
+    /// When due scheduled actions run, as a cron expression in local time
+    /// (e.g. `0 9,17 * * Mon-Fri`). `None` runs them on every scheduler tick.
+    #[serde(default)]
+    pub deletion_cron: Option<String>,
+    /// How long the watcher waits for a file's size to stop changing before
+    /// evaluating it, in seconds (0 = don't wait). Files still growing are left
+    /// for the next scan.
+    #[serde(default = "default_stability_timeout_secs")]
+    pub stability_timeout_secs: u32,
+    /// Extensions (without the dot) never touched by any rule — partial downloads
+    /// and temp files. Matched case-insensitively against the last extension.
+    #[serde(default = "default_global_ignore_extensions")]
+    pub global_ignore_extensions: Vec<String>,
+    /// Emit every raw watcher event to the frontend (`raw-fs-event`) for debugging
+    #[serde(default)]
+    pub debug_watcher_events: bool,
+    /// Allow RunCommand rules to start external programs. Off by default; while off,
+    /// those rules match but log an error instead of running anything.
+    #[serde(default)]
+    pub allow_run_command: bool,
+    /// What adding a folder that contains, or sits inside, a watched folder does
+    #[serde(default)]
+    pub nested_folder_policy: NestedFolderPolicy,
+    /// After scanning a recursive folder, recycle subdirectories left empty (never the
+    /// watched folder itself)
+    #[serde(default)]
+    pub remove_empty_dirs: bool,
+    /// RFC3339 time until which all automation (watcher, scans, scheduled actions) is
+    /// paused. Expired values are simply ignored.
+    #[serde(default)]
+    pub paused_until: Option<String>,
     /// Maximum database size in MB (0 = unlimited)
     #[serde(default = "default_max_storage_mb")]
     pub max_storage_mb: u32,
@@ -55,6 +143,44 @@ pub struct AppSettings {
     pub context_menu_prompted: bool,
 }
 
+/// What to do when a new watched folder overlaps an existing one, where both folders'
+/// rules would process the same files.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NestedFolderPolicy {
+    /// Refuse to add the folder.
+    #[default]
+    Reject,
+    /// Add it anyway and log a warning.
+    Warn,
+}
+
+/// How deletions dispose of files.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DeletionMode {
+    /// Send files to the OS recycle bin; falls back to staging when that fails
+    /// (common on headless Linux without a trash implementation).
+    #[default]
+    Trash,
+    /// Move files into the app's `trash_staging` folder (see `staging`), where they
+    /// can be undone until `staging_retention_days` passes.
+    Staging,
+}
+
+impl AppSettings {
+    /// Whether all automation is paused at `now` (see `paused_until`).
+    pub fn is_paused_at(&self, now: DateTime<Utc>) -> bool {
+        pause_active(self.paused_until.as_deref(), now)
+    }
+}
+
+/// Whether an RFC3339 `paused_until` lies after `now`; unparseable values count as not paused.
+fn pause_active(paused_until: Option<&str>, now: DateTime<Utc>) -> bool {
+    paused_until
+        .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+        .map(|until| now < until)
+        .unwrap_or(false)
+}
+
 fn default_true() -> bool {
     true
 }
@@ -69,6 +195,29 @@ fn default_max_storage_mb() -> u32 {
     2048
 }
 
+fn default_deletion_batch_pause_ms() -> u32 {
+    250
+}
+
+fn default_staging_retention_days() -> u32 {
+    30
+}
+
+fn default_max_deletion_attempts() -> u32 {
+    5
+}
+
+fn default_stability_timeout_secs() -> u32 {
+    30
+}
+
+fn default_global_ignore_extensions() -> Vec<String> {
+    ["crdownload", "part", "partial", "tmp", "download"]
+        .iter()
+        .map(|e| e.to_string())
+        .collect()
+}
+
 fn default_update_mode() -> String {
     "notify".to_string()
 }
@@ -83,8 +232,25 @@ impl Default for AppSettings {
             scan_interval_minutes: 5,
             start_with_os: true,
             minimize_to_tray: true,
+            show_notifications: true,
             // ...existing code...
             log_retention_days: 30,
+            archive_logs_before_pruning: false,
+            deletion_batch_size: 0,
+            deletion_batch_pause_ms: default_deletion_batch_pause_ms(),
+            deletion_mode: DeletionMode::default(),
+            staging_retention_days: default_staging_retention_days(),
+            max_deletion_attempts: default_max_deletion_attempts(),
+            keep_failed_deletions: false,
+            require_deletion_approval: false,
+            deletion_cron: None,
+            stability_timeout_secs: default_stability_timeout_secs(),
+            global_ignore_extensions: default_global_ignore_extensions(),
+            debug_watcher_events: false,
+            allow_run_command: false,
+            nested_folder_policy: NestedFolderPolicy::default(),
+            remove_empty_dirs: false,
+            paused_until: None,
             max_storage_mb: default_max_storage_mb(),
             default_sort_root: default_sort_root(),
             update_mode: default_update_mode(),
@@ -107,6 +273,71 @@ pub struct WatchedFolder {
     /// Whether to watch subdirectories recursively (default: false = top-level only)
     #[serde(default)]
     pub watch_subdirectories: bool,
+    /// Catch-all action for files that no rule matched (lowest priority).
+    #[serde(default)]
+    pub default_action: Option<Action>,
+    /// Minimum age in days (by modified time) before the default action applies. 0 = any age.
+    #[serde(default)]
+    pub default_after_days: u32,
+    /// RFC3339 time until which the watcher and scheduler leave this folder alone.
+    /// Expired values are simply ignored.
+    #[serde(default)]
+    pub paused_until: Option<String>,
+    /// Name of a preset: new directories created directly in this folder are added as
+    /// watched folders with that preset's rules. Watching becomes recursive so the new
+    /// folders' files are seen right away.
+    #[serde(default)]
+    pub auto_watch_new_subdirs: Option<String>,
+}
+
+/// Rule name used for activity/scheduling entries created by a folder's default action.
+pub const DEFAULT_ACTION_RULE_NAME: &str = "Default action";
+
+impl WatchedFolder {
+    /// Build a synthetic catch-all rule from the folder's default action, if one is set.
+    /// It is evaluated after all explicit rules and only when none of them matched.
+    pub fn default_rule(&self) -> Option<Rule> {
+        self.default_action.as_ref().map(|action| Rule {
+            id: format!("default-{}", self.id),
+            name: DEFAULT_ACTION_RULE_NAME.to_string(),
+            description: String::new(),
+            enabled: true,
+            condition: Condition::Always,
+            condition_text: "*".to_string(),
+            action: action.clone(),
+            whitelist: Vec::new(),
+            match_subdirectories: false,
+            tags: Vec::new(),
+            case_sensitive: false,
+            max_per_run: None,
+            min_dest_free_bytes: None,
+            active_window: None,
+            priority: i32::MIN,
+        })
+    }
+
+    /// Rules in evaluation order: priority descending, ties in list order.
+    pub fn rules_by_priority(&self) -> Vec<&Rule> {
+        let mut ordered: Vec<&Rule> = self.rules.iter().collect();
+        ordered.sort_by_key(|r| std::cmp::Reverse(r.priority));
+        ordered
+    }
+
+    /// Move one rule to `new_index` in the list (clamped to the last position) and
+    /// renumber priorities to follow the new order. Returns the index it ended up at.
+    pub fn move_rule(&mut self, rule_id: &str, new_index: usize) -> Result<usize, String> {
+        let from = self.rules.iter().position(|r| r.id == rule_id).ok_or("Rule not found")?;
+        let rule = self.rules.remove(from);
+        let to = new_index.min(self.rules.len());
+        self.rules.insert(to, rule);
+        Rule::assign_priorities_in_order(&mut self.rules);
+        Ok(to)
+    }
+
+    /// Whether the folder is paused at `now` (a pause auto-expires once `paused_until` passes).
+    pub fn is_paused_at(&self, now: DateTime<Utc>) -> bool {
+        pause_active(self.paused_until.as_deref(), now)
+    }
 }
 
 // ── Composable Rule System ──────────────────────────────────
@@ -135,6 +366,59 @@ pub struct Rule {
     /// (e.g. `subdir/*.pdf`) instead of just the filename. Default: false (filename only).
     #[serde(default)]
     pub match_subdirectories: bool,
+    /// Free-form labels for grouping rules ("tax", "media"). Organizational only.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// When true, glob patterns distinguish case (`README` ≠ `readme`). Default: false.
+    #[serde(default)]
+    pub case_sensitive: bool,
+    /// Maximum number of immediate actions this rule takes per scan; further matching
+    /// files are left for the next scan. None = unlimited.
+    #[serde(default)]
+    pub max_per_run: Option<u32>,
+    /// Move/copy only if the destination drive has at least this many bytes free;
+    /// otherwise the file is left in place and logged as `skipped_low_space`.
+    #[serde(default)]
+    pub min_dest_free_bytes: Option<u64>,
+    /// Evaluation order within the folder: higher runs first. Rules with equal
+    /// priority keep their list order. Default 0.
+    #[serde(default)]
+    pub priority: i32,
+    /// Local hours/weekdays the rule may run in; outside it the rule is skipped.
+    /// None = always.
+    #[serde(default)]
+    pub active_window: Option<TimeWindow>,
+}
+
+/// A daily span of local hours, optionally limited to some weekdays.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimeWindow {
+    /// 0–23; the window opens at the top of this hour
+    pub start_hour: u32,
+    /// 0–24, exclusive. Below `start_hour` the window wraps past midnight (22 → 6);
+    /// equal to it means all day.
+    pub end_hour: u32,
+    /// Weekdays the window opens on (a wrapping window runs into the next morning).
+    /// Empty = every day.
+    #[serde(default)]
+    pub days: Vec<Weekday>,
+}
+
+impl TimeWindow {
+    pub fn contains(&self, now: NaiveDateTime) -> bool {
+        let hour = now.hour();
+        let opens_on = |day: NaiveDateTime| self.days.is_empty() || self.days.contains(&day.weekday());
+        if self.start_hour == self.end_hour {
+            opens_on(now)
+        } else if self.start_hour < self.end_hour {
+            (self.start_hour..self.end_hour).contains(&hour) && opens_on(now)
+        } else if hour >= self.start_hour {
+            opens_on(now)
+        } else {
+            // Early-morning part of a window that opened the evening before
+            hour < self.end_hour && opens_on(now - Duration::days(1))
+        }
+    }
 }
 
 impl Rule {
@@ -142,9 +426,22 @@ impl Rule {
         &self.id
     }
 
+    /// Rewrite priorities so evaluation follows the given list order (first = highest).
+    pub fn assign_priorities_in_order(rules: &mut [Rule]) {
+        let count = rules.len() as i32;
+        for (i, rule) in rules.iter_mut().enumerate() {
+            rule.priority = count - i as i32;
+        }
+    }
+
     pub fn is_enabled(&self) -> bool {
         self.enabled
     }
+
+    /// Whether `now` (local time) is inside the rule's active window, if it has one.
+    pub fn is_active_at(&self, now: NaiveDateTime) -> bool {
+        self.active_window.as_ref().is_none_or(|w| w.contains(now))
+    }
 }
 
 /// Condition tree — composable file matchers.
@@ -155,23 +452,123 @@ pub enum Condition {
     /// Uses `*` (any chars) and `?` (single char). Case-insensitive.
     Glob { pattern: String },
     /// Regex pattern for power users: `^IMG_\d+\.jpg$`
-    Regex { pattern: String },
+    Regex {
+        pattern: String,
+        /// Flag letters from the `/pattern/flags` syntax: `i`, `m`, `s`, `x`.
+        #[serde(default)]
+        flags: String,
+    },
     /// All sub-conditions must match
     And { conditions: Vec<Condition> },
     /// Any sub-condition must match
     Or { conditions: Vec<Condition> },
+    /// Exactly one sub-condition must match (not "an odd number")
+    Xor { conditions: Vec<Condition> },
     /// Negates the inner condition
     Not { condition: Box<Condition> },
+    /// File age in whole days, measured from the modified or created time.
+    /// Text form: `modified>30d`, `created<7d`.
+    Age { op: AgeOp, days: u32, field: TimeField },
+    /// Only the N largest (or smallest) files in the file's directory.
+    /// Text form: `largest:1`, `smallest:3`.
+    ExtremeSize { which: SizeExtreme, n: u32 },
+    /// OS-level file tag/label (macOS Finder tags). Case-insensitive.
+    /// Never matches on platforms without file tags. Text form: `tag:Red`.
+    HasTag { tag: String },
+    /// Glob on the name of the file's immediate parent folder, however deep it is.
+    /// Text form: `parent:to-sort`.
+    ParentDir { pattern: String },
+    /// Real file type sniffed from the file's leading bytes, regardless of extension.
+    /// `mime` may use wildcards (`image/*`). Text form: `content:image/png`.
+    ContentType { mime: String },
+    /// Date embedded in the file name (e.g. `app-2024-01-05.log`) is more than `days` old.
+    /// The date is read from the regex's `date` named group, or its first group.
+    /// Text form: `dated>30d/(\d{4}-\d{2}-\d{2})/`.
+    EmbeddedDateOlderThan { regex_with_date_group: String, days: u32 },
+    /// A file with identical content (same size, same SHA-256) exists somewhere under
+    /// `reference_dir`. Files are only hashed when a reference file has the same size.
+    /// Text form: `duplicate:D:\Archive` (the path can't contain spaces).
+    Duplicate { reference_dir: PathBuf },
+    /// A field read from the file's own metadata (PDF Info `title`/`author`, EXIF
+    /// `model`/`datetaken`, ...). Files without the field never match.
+    /// Text form: `meta:author~"ACME"`.
+    MetaField { field: String, op: MetaOp, value: String },
     /// Always matches (used as default / catch-all)
     Always,
 }
 
+/// One destination of a multi-destination Move.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuotaDestination {
+    /// May contain the same placeholders as `Action::Move::destination`.
+    pub path: PathBuf,
+    /// Most bytes this destination may hold, measured on disk at the start of each run and
+    /// counted up as files move in; None = no quota, only free space counts.
+    #[serde(default)]
+    pub max_bytes: Option<u64>,
+}
+
+/// How a Move handles a same-named file already at the destination.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ConflictPolicy {
+    /// Append ` (n)` to the incoming file's name
+    #[default]
+    Rename,
+    /// Leave the source where it is
+    Skip,
+    /// Replace the existing file (a folder in the way is never replaced; the move is skipped)
+    Overwrite,
+    /// Replace the existing file only if the incoming one was modified more recently
+    KeepNewer,
+}
+
+/// Comparison used by `Condition::Age`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AgeOp {
+    /// Older than N days (`>`)
+    GreaterThan,
+    /// Newer than N days (`<`)
+    LessThan,
+}
+
+/// Comparison used by `Condition::MetaField`. Text comparisons ignore case; `>`/`<`
+/// compare numerically when both sides are numbers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MetaOp {
+    /// `=`
+    Equals,
+    /// `~`
+    Contains,
+    /// `>`
+    GreaterThan,
+    /// `<`
+    LessThan,
+}
+
+/// Which end of the size ranking `Condition::ExtremeSize` matches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SizeExtreme {
+    Largest,
+    Smallest,
+}
+
+/// Which file timestamp an age condition reads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TimeField {
+    Modified,
+    /// Creation time; falls back to modified on platforms/filesystems that don't record it.
+    Created,
+}
+
 /// What to do when the condition matches.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum Action {
     /// Move file to destination folder (optionally after a delay)
     Move {
+        /// May contain `{year}`/`{month}`/`{day}` (file modified date) and `{ext}`
+        /// placeholders, plus `{1}`, `{2}`, … for the capture groups of a regex condition;
+        /// all are expanded per file by `rules::resolve_destination`.
         destination: PathBuf,
         /// Delay in minutes before the move executes. 0 = immediate (default).
         #[serde(default)]
@@ -180,28 +577,589 @@ pub enum Action {
         /// When false (default), remove the source after moving (cut mode).
         #[serde(default)]
         keep_source: bool,
+        /// What to do when the destination already has a file with the same name.
+        /// Applies to immediate moves; delayed moves always rename.
+        #[serde(default)]
+        on_conflict: ConflictPolicy,
+        /// Quota'd destinations tried in order instead of `destination` for immediate
+        /// moves and copies: the first one under its quota with room for the file wins.
+        /// Delayed moves still go to `destination`.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        destinations: Vec<QuotaDestination>,
+        /// Recreate the file's subfolders (relative to the watched folder) under the
+        /// destination: `watched/a/b/c.pdf` → `destination/a/b/c.pdf`.
+        #[serde(default)]
+        preserve_structure: bool,
     },
     /// Schedule file for deletion after a delay (0 = immediate on next scan)
     Delete {
         /// DEPRECATED — kept for backward-compat deserialization.
-        /// Converted to delay_minutes on config load via migrate_config().
+        /// Converted to delay_minutes on config load via migrate_config() (schema v0 → v1).
         #[serde(default, skip_serializing)]
         after_days: u32,
         /// Delay in minutes before deletion. Default 1440 (1 day).
         #[serde(default)]
         delay_minutes: u32,
     },
+    /// Move the file into a category folder under `root` picked from its extension
+    /// (`root/Images`, `root/Documents`, …, `root/Other`; see `rules::category_for_extension`).
+    /// Runs immediately and stops evaluation, like an immediate move; name collisions are renamed.
+    MoveByCategory { root: PathBuf },
+    /// Add the file to a zip in `destination`, then send the original to the recycle bin.
+    /// Runs immediately and stops evaluation, like an immediate move.
+    Archive {
+        destination: PathBuf,
+        /// Zip file name; may contain `{year}`/`{month}`/`{day}` (file modified date)
+        /// and `{ext}`. `.zip` is appended if missing.
+        archive_name: String,
+    },
+    /// Create a link to the file in `destination`, leaving the original in place.
+    /// Symbolic by default; `hard` makes a hard link (files only, same volume).
+    /// Non-destructive like an immediate copy; files already linked there are skipped.
+    Link {
+        destination: PathBuf,
+        #[serde(default)]
+        hard: bool,
+    },
+    /// Leave the file alone and just log it (action "notified"), with a desktop
+    /// notification from the watcher. `{name}` in `message` becomes the file name.
+    /// Evaluation continues; each file is only notified about once per rule.
+    Notify { message: String },
+    /// Run an external program on the file. `{path}`, `{name}` and `{dir}` in `program`
+    /// and `args` are replaced per file. Runs immediately and stops evaluation (the
+    /// program is expected to take care of the file); a file it already ran on
+    /// successfully isn't run again. Requires `AppSettings::allow_run_command`.
+    RunCommand {
+        program: String,
+        #[serde(default)]
+        args: Vec<String>,
+    },
+    /// Leave the file alone and add it to the review queue for the user to decide.
+    /// Stops evaluation like an immediate move. Approving the item runs `on_approve`
+    /// right away (any delay on it is ignored); dismissing just clears it.
+    Review {
+        /// Shown next to the file in the review queue
+        #[serde(default)]
+        reason: String,
+        #[serde(default)]
+        on_approve: Option<Box<Action>>,
+    },
 }
 
 impl Default for AppConfig {
     fn default() -> Self {
         Self {
+            schema_version: CONFIG_SCHEMA_VERSION,
             folders: Vec::new(),
             settings: AppSettings::default(),
+            presets: Vec::new(),
+            archived_folders: Vec::new(),
+            extra: serde_json::Map::new(),
+        }
+    }
+}
+
+// ── Global ignore list ──────────────────────────────────────
+
+/// Whether `file_name`'s last extension is in `extensions` (case-insensitive;
+/// entries may be written with or without a leading dot).
+pub fn has_ignored_extension(file_name: &str, extensions: &[String]) -> bool {
+    let ext = match file_name.rsplit_once('.') {
+        Some((_, ext)) if !ext.is_empty() => ext,
+        _ => return false,
+    };
+    extensions
+        .iter()
+        .any(|e| e.trim_start_matches('.').eq_ignore_ascii_case(ext))
+}
+
+// ── Folder merging ──────────────────────────────────────────
+
+/// Merge `source_id`'s rules and whitelist into `target_id`, then remove the source folder.
+/// Rules get fresh UUIDs and are appended after the target's rules (lower priority);
+/// whitelist patterns already on the target are not duplicated.
+/// Returns the `(old_rule_id, new_rule_id)` mapping so DB rows can be re-pointed.
+pub fn merge_folders(
+    config: &mut AppConfig,
+    source_id: &str,
+    target_id: &str,
+) -> Result<Vec<(String, String)>, String> {
+    if source_id == target_id {
+        return Err("Cannot merge a folder into itself".to_string());
+    }
+    if !config.folders.iter().any(|f| f.id == target_id) {
+        return Err("Target folder not found".to_string());
+    }
+    let source_idx = config
+        .folders
+        .iter()
+        .position(|f| f.id == source_id)
+        .ok_or("Source folder not found")?;
+    let source = config.folders.remove(source_idx);
+
+    let target = config
+        .folders
+        .iter_mut()
+        .find(|f| f.id == target_id)
+        .ok_or("Target folder not found")?;
+
+    let mut id_map = Vec::new();
+    for mut rule in source.rules {
+        let new_id = uuid::Uuid::new_v4().to_string();
+        id_map.push((rule.id.clone(), new_id.clone()));
+        rule.id = new_id;
+        target.rules.push(rule);
+    }
+    for pattern in source.whitelist {
+        if !target.whitelist.contains(&pattern) {
+            target.whitelist.push(pattern);
+        }
+    }
+    target.watch_subdirectories |= source.watch_subdirectories;
+
+    Ok(id_map)
+}
+
+// ── Config import merging ───────────────────────────────────
+
+/// What [`merge_imported_config`] changed.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ConfigMergeSummary {
+    pub folders_added: u32,
+    pub rules_added: u32,
+    /// Existing rules replaced by the imported version (only with `overwrite_existing`)
+    pub rules_replaced: u32,
+    pub presets_added: u32,
+    /// Settings fields taken from the import (only with `overwrite_existing`)
+    pub settings_changed: Vec<String>,
+}
+
+/// Union an imported config into `config` instead of replacing it. Folders match by ID,
+/// or by path for the same folder exported from another machine; their rules match by
+/// ID and presets by name. Items only in the import are added. For items in both, the
+/// existing one is kept unless `overwrite_existing` is set. Whitelist patterns are
+/// always unioned.
+/// `imported_settings` is the import's raw `settings` object: with `overwrite_existing`,
+/// only the fields it actually contains are applied, the rest keep their current value.
+pub fn merge_imported_config(
+    config: &mut AppConfig,
+    imported: AppConfig,
+    imported_settings: Option<&serde_json::Map<String, serde_json::Value>>,
+    overwrite_existing: bool,
+) -> Result<ConfigMergeSummary, String> {
+    let mut summary = ConfigMergeSummary::default();
+
+    for incoming in imported.folders {
+        let incoming_path = normalize_folder_path(&incoming.path);
+        let existing = config
+            .folders
+            .iter_mut()
+            .find(|f| f.id == incoming.id || normalize_folder_path(&f.path) == incoming_path);
+        let Some(existing) = existing else {
+            summary.folders_added += 1;
+            summary.rules_added += incoming.rules.len() as u32;
+            config.folders.push(incoming);
+            continue;
+        };
+        for pattern in &incoming.whitelist {
+            if !existing.whitelist.contains(pattern) {
+                existing.whitelist.push(pattern.clone());
+            }
+        }
+        if overwrite_existing {
+            existing.enabled = incoming.enabled;
+            existing.watch_subdirectories = incoming.watch_subdirectories;
+            existing.default_action = incoming.default_action.clone();
+            existing.default_after_days = incoming.default_after_days;
+            existing.auto_watch_new_subdirs = incoming.auto_watch_new_subdirs.clone();
         }
+        for rule in incoming.rules {
+            match existing.rules.iter_mut().find(|r| r.id == rule.id) {
+                Some(current) if overwrite_existing => {
+                    *current = rule;
+                    summary.rules_replaced += 1;
+                }
+                Some(_) => {}
+                None => {
+                    existing.rules.push(rule);
+                    summary.rules_added += 1;
+                }
+            }
+        }
+    }
+
+    for preset in imported.presets {
+        match config.presets.iter_mut().find(|p| p.name == preset.name) {
+            Some(current) if overwrite_existing => *current = preset,
+            Some(_) => {}
+            None => {
+                config.presets.push(preset);
+                summary.presets_added += 1;
+            }
+        }
+    }
+
+    if let Some(fields) = imported_settings.filter(|_| overwrite_existing) {
+        let mut settings = match serde_json::to_value(&config.settings).map_err(|e| e.to_string())? {
+            serde_json::Value::Object(map) => map,
+            _ => return Err("Settings are not an object".to_string()),
+        };
+        for (key, value) in fields {
+            if settings.get(key) != Some(value) {
+                settings.insert(key.clone(), value.clone());
+                summary.settings_changed.push(key.clone());
+            }
+        }
+        config.settings = serde_json::from_value(serde_json::Value::Object(settings))
+            .map_err(|e| format!("Invalid settings in import: {}", e))?;
+    }
+
+    Ok(summary)
+}
+
+// ── Rule tags ───────────────────────────────────────────────
+
+/// Trim tags, drop empty ones, and remove case-insensitive duplicates (first spelling wins).
+pub fn normalize_tags(tags: Vec<String>) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim();
+        if !tag.is_empty() && !out.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+            out.push(tag.to_string());
+        }
+    }
+    out
+}
+
+/// Every rule across all folders carrying `tag` (case-insensitive), in folder order.
+pub fn rules_with_tag<'a>(config: &'a AppConfig, tag: &str) -> Vec<(&'a WatchedFolder, &'a Rule)> {
+    let tag = tag.trim();
+    config
+        .folders
+        .iter()
+        .flat_map(|f| f.rules.iter().map(move |r| (f, r)))
+        .filter(|(_, r)| r.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)))
+        .collect()
+}
+
+// ── Rule validation ─────────────────────────────────────────
+
+/// Warnings for a Move rule whose destination lies inside a recursively watched
+/// folder, where moved files would be picked up and re-processed. Only the static
+/// part of a templated destination (before the first `{placeholder}`) is checked.
+/// MoveByCategory rules are checked by their root; other non-Move rules never warn.
+pub fn move_rule_warnings(config: &AppConfig, rule: &Rule) -> Vec<String> {
+    let destination = match &rule.action {
+        Action::Move { destination, .. } => destination,
+        Action::MoveByCategory { root } => root,
+        _ => return Vec::new(),
+    };
+    let static_dest = static_destination(destination);
+
+    config
+        .folders
+        .iter()
+        .filter(|f| f.watch_subdirectories && static_dest.starts_with(&f.path))
+        .map(|f| {
+            format!(
+                "Destination is inside watched folder {} which recurses — files may be re-processed.",
+                f.path.display()
+            )
+        })
+        .collect()
+}
+
+/// Non-fatal problems with a rule that `add_rule` / `update_rule` return for the UI to
+/// surface: an invalid regex (the condition never matches), a relative destination or
+/// one on a drive that isn't connected, and the loop warnings from `move_rule_warnings`.
+pub fn validate_rule(config: &AppConfig, rule: &Rule) -> Vec<String> {
+    let mut warnings = Vec::new();
+    if let Err(e) = crate::condition::validate_condition(&rule.condition) {
+        warnings.push(format!("Condition will never match: {}", e));
+    }
+    for destination in action_destinations(&rule.action) {
+        if let Some(problem) = unreachable_root(&static_destination(destination)) {
+            warnings.push(format!(
+                "{} (destination {}) — the rule will fail until this is fixed.",
+                problem,
+                destination.display()
+            ));
+        }
+    }
+    warnings.extend(move_rule_warnings(config, rule));
+    warnings
+}
+
+/// Every folder an action writes files into.
+fn action_destinations(action: &Action) -> Vec<&Path> {
+    match action {
+        Action::Move { destination, destinations, .. } => std::iter::once(destination.as_path())
+            .chain(destinations.iter().map(|d| d.path.as_path()))
+            .collect(),
+        Action::MoveByCategory { root } => vec![root.as_path()],
+        Action::Archive { destination, .. } | Action::Link { destination, .. } => vec![destination.as_path()],
+        _ => Vec::new(),
+    }
+}
+
+/// Folders whose children are the mount points of separate volumes (`/mnt/backup`,
+/// `/Volumes/Backup`, `/media/usb` or `/media/<user>/usb`).
+const MOUNT_PARENTS: [&str; 4] = ["/mnt", "/media", "/Volumes", "/run/media"];
+
+/// Why nothing can be written under `path`, if so: it's relative (it would resolve
+/// against wherever the app was started), or the drive (on Windows) or mounted volume
+/// it lives on isn't there, e.g. an unplugged USB drive or an unmapped network drive.
+pub fn unreachable_root(path: &Path) -> Option<String> {
+    if let Some(Component::Prefix(prefix)) = path.components().next() {
+        let drive = PathBuf::from(prefix.as_os_str());
+        if !drive.exists() {
+            return Some(format!("Drive '{}' does not exist", drive.display()));
+        }
+    }
+    if !path.is_absolute() {
+        return Some(format!("'{}' is not a full path", path.display()));
+    }
+    let volume = volume_root(path)?;
+    (!is_mount_point(&volume)).then(|| format!("Drive '{}' is not mounted", volume.display()))
+}
+
+/// The mount point `path` lies under when it's inside one of [`MOUNT_PARENTS`].
+fn volume_root(path: &Path) -> Option<PathBuf> {
+    let parent = MOUNT_PARENTS.iter().map(Path::new).find(|p| path.starts_with(p))?;
+    let mut names = path.strip_prefix(parent).ok()?.components();
+    let first = parent.join(names.next()?);
+    // Per-user folders: always under /run/media, and under /media unless it's a volume itself
+    let per_user = parent == Path::new("/run/media")
+        || (parent == Path::new("/media") && first.is_dir() && !is_mount_point(&first));
+    if per_user {
+        Some(first.join(names.next()?))
+    } else {
+        Some(first)
+    }
+}
+
+/// Whether `dir` exists and is the root of another filesystem than its parent.
+#[cfg(unix)]
+fn is_mount_point(dir: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    let parent = dir.parent().unwrap_or(dir);
+    match (fs::metadata(dir), fs::metadata(parent)) {
+        (Ok(dir), Ok(parent)) => dir.is_dir() && dir.dev() != parent.dev(),
+        _ => false,
+    }
+}
+
+#[cfg(not(unix))]
+fn is_mount_point(dir: &Path) -> bool {
+    dir.is_dir()
+}
+
+/// The part of a destination before its first `{placeholder}` component.
+pub fn static_destination(destination: &Path) -> PathBuf {
+    destination
+        .components()
+        .take_while(|c| !c.as_os_str().to_string_lossy().contains('{'))
+        .collect()
+}
+
+/// The form a folder path is stored in: absolute, with `..`, symlinks, separators and
+/// (on Windows) letter case resolved as on disk, so one folder always compares equal to
+/// itself. The `\\?\` prefix Windows adds is dropped again to keep the path readable.
+/// A path that can't be resolved (e.g. missing) is returned as given.
+pub fn normalize_folder_path(path: &Path) -> PathBuf {
+    match path.canonicalize() {
+        Ok(canonical) => strip_verbatim_prefix(canonical),
+        Err(_) => path.to_path_buf(),
+    }
+}
+
+/// `\\?\C:\x` → `C:\x` and `\\?\UNC\server\share` → `\\server\share`.
+fn strip_verbatim_prefix(path: PathBuf) -> PathBuf {
+    let Some(text) = path.to_str() else {
+        return path;
+    };
+    if let Some(rest) = text.strip_prefix(r"\\?\UNC\") {
+        PathBuf::from(format!(r"\\{}", rest))
+    } else if let Some(rest) = text.strip_prefix(r"\\?\") {
+        PathBuf::from(rest)
+    } else {
+        path
     }
 }
 
+/// Describes the watched folder that `path` contains or lies inside, if any. Paths are
+/// compared canonicalized where they exist, so `D:\Downloads` and `D:/Downloads/` match.
+pub fn nested_folder_conflict(config: &AppConfig, path: &Path) -> Option<String> {
+    let new = normalize_folder_path(path);
+    config.folders.iter().find_map(|f| {
+        let existing = normalize_folder_path(&f.path);
+        if existing == new {
+            None
+        } else if new.starts_with(&existing) {
+            Some(format!("'{}' is inside the watched folder '{}'", path.display(), f.path.display()))
+        } else if existing.starts_with(&new) {
+            Some(format!("'{}' contains the watched folder '{}'", path.display(), f.path.display()))
+        } else {
+            None
+        }
+    })
+}
+
+/// The enabled watched folder whose rules handle `path`: a folder it sits directly in,
+/// or one watching subdirectories that contains it. The deepest such folder wins, so a
+/// subfolder watched in its own right isn't handled by a recursive parent's rules.
+pub fn owning_folder<'a>(config: &'a AppConfig, path: &Path) -> Option<&'a WatchedFolder> {
+    config
+        .folders
+        .iter()
+        .filter(|f| {
+            f.enabled
+                && if f.watch_subdirectories {
+                    path.starts_with(&f.path)
+                } else {
+                    // Direct child file or direct child directory
+                    path.parent() == Some(f.path.as_path())
+                }
+        })
+        .max_by_key(|f| f.path.components().count())
+}
+
+/// Apply `nested_folder_policy` to a folder about to be watched at `path`: Err with the
+/// conflict under `Reject`, a logged warning under `Warn`.
+pub fn check_nested_folder(config: &AppConfig, path: &Path) -> Result<(), String> {
+    if let Some(conflict) = nested_folder_conflict(config, path) {
+        match config.settings.nested_folder_policy {
+            NestedFolderPolicy::Reject => return Err(conflict),
+            NestedFolderPolicy::Warn => log::warn!("Adding overlapping folder: {}", conflict),
+        }
+    }
+    Ok(())
+}
+
+/// Whether `path`, found in the watched folder `folder`, lies inside any enabled Move
+/// rule's destination, in any enabled folder. The watcher and scans skip such files so a
+/// destination that is also watched (by another folder, or a recursive one) can't bounce
+/// files between rules forever. Templated destinations are matched on their static prefix.
+/// `folder`'s own destinations only count when strictly below it: one that is the folder
+/// itself or one of its ancestors would keep its rules from ever firing.
+pub fn is_managed_destination(path: &Path, folder: &Path, config: &AppConfig) -> bool {
+    config.folders.iter().filter(|f| f.enabled).any(|f| {
+        let own = f.path == folder;
+        f.rules
+            .iter()
+            .filter(|r| r.is_enabled())
+            .flat_map(|r| match &r.action {
+                Action::Move { destination, destinations, .. } => std::iter::once(destination.as_path())
+                    .chain(destinations.iter().map(|d| d.path.as_path()))
+                    .map(static_destination)
+                    .collect(),
+                Action::MoveByCategory { root } => {
+                    crate::rules::CATEGORY_FOLDERS.iter().map(|category| root.join(category)).collect()
+                }
+                _ => Vec::new(),
+            })
+            .any(|dest| {
+                let counts = if own { dest.starts_with(folder) && dest != folder } else { !dest.as_os_str().is_empty() };
+                counts && path.starts_with(&dest)
+            })
+    })
+}
+
+// ── Auto-watched subfolders ─────────────────────────────────
+
+/// The enabled folder with `auto_watch_new_subdirs` that `dir` sits directly inside.
+fn auto_watching_parent<'a>(config: &'a AppConfig, dir: &Path) -> Option<&'a WatchedFolder> {
+    let parent = dir.parent()?;
+    config
+        .folders
+        .iter()
+        .find(|f| f.enabled && f.path == parent && f.auto_watch_new_subdirs.is_some())
+}
+
+/// Whether `dir` is an unwatched directory directly inside an auto-watching folder,
+/// i.e. one [`auto_watch_new_subdir`] would add.
+pub fn is_auto_watch_candidate(config: &AppConfig, dir: &Path) -> bool {
+    !config.folders.iter().any(|f| f.path == dir) && auto_watching_parent(config, dir).is_some()
+}
+
+/// If `dir` sits directly inside an enabled folder with `auto_watch_new_subdirs`, add it
+/// as a watched folder with that preset's rules (under fresh rule IDs) and return it.
+/// None if it isn't such a directory, is already watched, or the preset is gone.
+pub fn auto_watch_new_subdir(config: &mut AppConfig, dir: &Path) -> Option<WatchedFolder> {
+    if !is_auto_watch_candidate(config, dir) {
+        return None;
+    }
+    let preset_name = auto_watching_parent(config, dir)?.auto_watch_new_subdirs.as_ref()?;
+    let Some(preset) = config.presets.iter().find(|p| &p.name == preset_name) else {
+        log::warn!("Not auto-watching {}: preset '{}' not found", dir.display(), preset_name);
+        return None;
+    };
+
+    let rules = preset
+        .rules
+        .iter()
+        .map(|rule| Rule { id: uuid::Uuid::new_v4().to_string(), ..rule.clone() })
+        .collect();
+    let folder = WatchedFolder {
+        id: uuid::Uuid::new_v4().to_string(),
+        path: dir.to_path_buf(),
+        enabled: true,
+        rules,
+        whitelist: preset.whitelist.clone(),
+        watch_subdirectories: false,
+        default_action: None,
+        default_after_days: 0,
+        paused_until: None,
+        auto_watch_new_subdirs: None,
+    };
+    config.folders.push(folder.clone());
+    Some(folder)
+}
+
+/// Point the auto-watched folder at `from` to `to` after its directory was renamed
+/// (e.g. Explorer's "New folder" getting its real name), keeping its ID and rules.
+/// None unless `from` is a watched folder inside an auto-watching folder and `to` is an
+/// unwatched sibling.
+pub fn rename_auto_watched_folder(config: &mut AppConfig, from: &Path, to: &Path) -> Option<WatchedFolder> {
+    if from.parent() != to.parent() || auto_watching_parent(config, from).is_none() {
+        return None;
+    }
+    if config.folders.iter().any(|f| f.path == to) {
+        return None;
+    }
+    let folder = config.folders.iter_mut().find(|f| f.path == from)?;
+    folder.path = to.to_path_buf();
+    Some(folder.clone())
+}
+
+/// Move a watched folder (with its rules) to `archived_folders`. Returns false if no
+/// watched folder has that ID.
+pub fn archive_folder(config: &mut AppConfig, folder_id: &str) -> bool {
+    let Some(index) = config.folders.iter().position(|f| f.id == folder_id) else {
+        return false;
+    };
+    let folder = config.folders.remove(index);
+    config.archived_folders.retain(|f| f.id != folder.id);
+    config.archived_folders.push(folder);
+    true
+}
+
+/// Move an archived folder back to the watch list and return it.
+pub fn restore_archived_folder(config: &mut AppConfig, folder_id: &str) -> Result<WatchedFolder, String> {
+    let index = config
+        .archived_folders
+        .iter()
+        .position(|f| f.id == folder_id)
+        .ok_or_else(|| "Archived folder not found".to_string())?;
+    if config.folders.iter().any(|f| f.path == config.archived_folders[index].path) {
+        return Err("Folder is already being watched".to_string());
+    }
+    check_nested_folder(config, &config.archived_folders[index].path)?;
+    let folder = config.archived_folders.remove(index);
+    config.folders.push(folder.clone());
+    Ok(folder)
+}
+
 // ── Load / Save ─────────────────────────────────────────────
 
 /// Read a file to string, handling BOM (UTF-8 BOM and UTF-16 LE/BE).
@@ -230,42 +1188,691 @@ pub fn read_file_strip_bom(path: &std::path::Path) -> Result<String, String> {
 
 pub fn load_config() -> AppConfig {
     let path = config_path();
-    if path.exists() {
-        let data = read_file_strip_bom(&path).unwrap_or_default();
-        let mut config: AppConfig = serde_json::from_str(&data).unwrap_or_default();
-        if migrate_config(&mut config) {
+    if !path.exists() {
+        let config = AppConfig::default();
+        save_config(&config).ok();
+        return config;
+    }
+
+    let parsed = read_file_strip_bom(&path)
+        .and_then(|data| serde_json::from_str::<serde_json::Value>(&data).map_err(|e| e.to_string()));
+    let value = match parsed {
+        Ok(value) => value,
+        Err(e) => {
+            log::error!("Config file {} is unreadable ({}); starting from defaults", path.display(), e);
+            backup_config(&path);
+            let config = AppConfig::default();
             save_config(&config).ok();
+            return config;
         }
-        config
-    } else {
-        let config = AppConfig::default();
+    };
+
+    let from_version = stored_schema_version(&value);
+    let intact = serde_json::from_value::<AppConfig>(value.clone()).is_ok();
+    let config = migrate_config(value);
+    if !intact || from_version < CONFIG_SCHEMA_VERSION {
+        // Keep the file as it was before rewriting it in the current shape.
+        backup_config(&path);
         save_config(&config).ok();
-        config
-    }
-}
-
-/// Migrate legacy config fields. Returns true if any migration was applied.
-fn migrate_config(config: &mut AppConfig) -> bool {
-    let mut changed = false;
-    for folder in &mut config.folders {
-        for rule in &mut folder.rules {
-            if let Action::Delete { after_days, delay_minutes } = &mut rule.action {
-                // If we deserialized an old config with after_days but no delay_minutes,
-                // convert days → minutes.
-                if *after_days > 0 && *delay_minutes == 0 {
-                    *delay_minutes = *after_days * 24 * 60;
-                    *after_days = 0;
-                    changed = true;
-                }
+    }
+    config
+}
+
+/// Copy the config file to `config.json.bak` next to it, replacing any older backup.
+fn backup_config(path: &Path) {
+    let backup = path.with_extension("json.bak");
+    match fs::copy(path, &backup) {
+        Ok(_) => log::info!("Backed up config to {}", backup.display()),
+        Err(e) => log::error!("Failed to back up config to {}: {}", backup.display(), e),
+    }
+}
+
+fn stored_schema_version(value: &serde_json::Value) -> u32 {
+    value.get("schema_version").and_then(|v| v.as_u64()).unwrap_or(0) as u32
+}
+
+/// Upgrade a raw config value to `CONFIG_SCHEMA_VERSION` and deserialize it.
+/// Parts that still don't fit (a malformed rule, folder, or settings block) are
+/// dropped or reset to defaults with a warning rather than discarding the whole file.
+pub fn migrate_config(mut value: serde_json::Value) -> AppConfig {
+    upgrade_config(&mut value);
+    match serde_json::from_value::<AppConfig>(value.clone()) {
+        Ok(config) => config,
+        Err(e) => {
+            log::warn!("Config doesn't match the expected shape ({}); keeping what can be read", e);
+            salvage_config(value)
+        }
+    }
+}
+
+/// Apply each schema step from the value's `schema_version` up to the current one.
+/// Returns the version the value started at.
+pub fn upgrade_config(value: &mut serde_json::Value) -> u32 {
+    let from_version = stored_schema_version(value);
+    if from_version > CONFIG_SCHEMA_VERSION {
+        log::warn!(
+            "Config schema v{} is newer than this build supports (v{}); loading it as-is",
+            from_version, CONFIG_SCHEMA_VERSION
+        );
+        return from_version;
+    }
+    let Some(root) = value.as_object_mut() else {
+        return from_version;
+    };
+    if from_version < 1 {
+        let converted = migrate_delete_after_days(root);
+        log::info!("Migrated config v0 → v1: {} delete rule(s) converted from days to minutes", converted);
+    }
+    root.insert("schema_version".to_string(), CONFIG_SCHEMA_VERSION.into());
+    from_version
+}
+
+/// v0 → v1: `Delete { after_days }` becomes `Delete { delay_minutes }`, in folder
+/// rules and presets alike. Returns how many rules were converted.
+fn migrate_delete_after_days(root: &mut serde_json::Map<String, serde_json::Value>) -> usize {
+    let mut converted = 0;
+    for key in ["folders", "presets"] {
+        let Some(items) = root.get_mut(key).and_then(|v| v.as_array_mut()) else {
+            continue;
+        };
+        let rules = items
+            .iter_mut()
+            .filter_map(|item| item.get_mut("rules").and_then(|v| v.as_array_mut()))
+            .flat_map(|rules| rules.iter_mut());
+        for action in rules.filter_map(|r| r.get_mut("action").and_then(|a| a.as_object_mut())) {
+            if action.get("type").and_then(|t| t.as_str()) != Some("Delete") {
+                continue;
+            }
+            let after_days = action.remove("after_days").and_then(|v| v.as_u64()).unwrap_or(0);
+            let delay_minutes = action.get("delay_minutes").and_then(|v| v.as_u64()).unwrap_or(0);
+            if after_days > 0 && delay_minutes == 0 {
+                action.insert("delay_minutes".to_string(), (after_days * 24 * 60).into());
+                converted += 1;
             }
         }
     }
-    changed
+    converted
 }
 
+/// Best-effort deserialization of a config that failed as a whole: invalid rules,
+/// folders and presets are dropped one by one, invalid settings fall back to defaults.
+fn salvage_config(value: serde_json::Value) -> AppConfig {
+    let serde_json::Value::Object(mut root) = value else {
+        log::error!("Config is not a JSON object; starting from defaults");
+        return AppConfig::default();
+    };
+
+    if let Some(settings) = root.get("settings") {
+        if let Err(e) = serde_json::from_value::<AppSettings>(settings.clone()) {
+            log::warn!("Dropping invalid settings, using defaults: {}", e);
+            root.remove("settings");
+        }
+    }
+    if !root.contains_key("settings") {
+        if let Ok(defaults) = serde_json::to_value(AppSettings::default()) {
+            root.insert("settings".to_string(), defaults);
+        }
+    }
+    if let Some(serde_json::Value::Array(folders)) = root.get_mut("folders") {
+        folders.retain_mut(|folder| {
+            if let Some(serde_json::Value::Array(rules)) = folder.get_mut("rules") {
+                rules.retain(|rule| keep_if_valid::<Rule>(rule, "rule"));
+            }
+            keep_if_valid::<WatchedFolder>(folder, "watched folder")
+        });
+    } else {
+        root.insert("folders".to_string(), serde_json::Value::Array(Vec::new()));
+    }
+    if let Some(serde_json::Value::Array(archived)) = root.get_mut("archived_folders") {
+        archived.retain(|folder| keep_if_valid::<WatchedFolder>(folder, "archived folder"));
+    }
+    if let Some(serde_json::Value::Array(presets)) = root.get_mut("presets") {
+        presets.retain(|preset| keep_if_valid::<RulePreset>(preset, "preset"));
+    }
+
+    serde_json::from_value(serde_json::Value::Object(root)).unwrap_or_else(|e| {
+        log::error!("Config could not be salvaged ({}); starting from defaults", e);
+        AppConfig::default()
+    })
+}
+
+fn keep_if_valid<T: serde::de::DeserializeOwned>(item: &serde_json::Value, what: &str) -> bool {
+    match serde_json::from_value::<T>(item.clone()) {
+        Ok(_) => true,
+        Err(e) => {
+            let name = item.get("name").or_else(|| item.get("path")).and_then(|v| v.as_str()).unwrap_or("?");
+            log::warn!("Dropping invalid {} '{}' from config: {}", what, name, e);
+            false
+        }
+    }
+}
+
+/// Serializes config writes within the process so concurrent saves can't interleave.
+static SAVE_LOCK: Mutex<()> = Mutex::new(());
+
 pub fn save_config(config: &AppConfig) -> Result<(), String> {
-    let path = config_path();
     let json = serde_json::to_string_pretty(config).map_err(|e| e.to_string())?;
-    fs::write(&path, json).map_err(|e| e.to_string())?;
+    write_atomically(&config_path(), |file| file.write_all(json.as_bytes()))
+}
+
+/// Replace `path` with whatever `write` produces, all or nothing: the content goes to a
+/// temp file in the same directory which is only renamed over `path` once it has been
+/// fully written and flushed. A crash or error mid-write leaves the old file untouched.
+fn write_atomically(path: &Path, write: impl FnOnce(&mut fs::File) -> std::io::Result<()>) -> Result<(), String> {
+    let _guard = SAVE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let tmp = path.with_extension("json.tmp");
+    let result = fs::File::create(&tmp).and_then(|mut file| {
+        write(&mut file)?;
+        file.sync_all()
+    });
+    if let Err(e) = result.and_then(|_| fs::rename(&tmp, path)) {
+        let _ = fs::remove_file(&tmp);
+        return Err(format!("Failed to write {}: {}", path.display(), e));
+    }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{folder, move_to, rule};
+
+    #[test]
+    fn imported_config_is_merged_not_replaced() {
+        let mine = folder(Path::new("/data/mine"), vec![rule("Keep", "*.pdf", move_to(Path::new("/docs")))]);
+        let mut config = AppConfig { folders: vec![mine.clone()], ..Default::default() };
+        config.settings.scan_interval_minutes = 7;
+
+        let mut shared = mine.clone();
+        shared.rules[0].condition_text = "*.txt".to_string();
+        shared.rules.push(rule("New", "*.zip", move_to(Path::new("/zips"))));
+        shared.whitelist.push("*.lock".to_string());
+        let mut theirs = folder(Path::new("/data/theirs"), Vec::new());
+        theirs.id = "folder-2".to_string();
+        let imported = AppConfig { folders: vec![shared, theirs], ..Default::default() };
+        let settings = serde_json::json!({ "log_retention_days": 90 });
+
+        let mut kept = config.clone();
+        let summary = merge_imported_config(&mut kept, imported.clone(), settings.as_object(), false).unwrap();
+        assert_eq!((summary.folders_added, summary.rules_added, summary.rules_replaced), (1, 1, 0));
+        assert_eq!(kept.folders.len(), 2);
+        assert_eq!(kept.folders[0].rules[0].condition_text, "*.pdf", "existing rule wins");
+        assert_eq!(kept.folders[0].whitelist, vec!["*.lock".to_string()]);
+        assert!(summary.settings_changed.is_empty());
+        assert_eq!(kept.settings.log_retention_days, config.settings.log_retention_days);
+
+        let summary = merge_imported_config(&mut config, imported, settings.as_object(), true).unwrap();
+        assert_eq!(summary.rules_replaced, 1);
+        assert_eq!(config.folders[0].rules[0].condition_text, "*.txt");
+        assert_eq!(summary.settings_changed, vec!["log_retention_days".to_string()]);
+        assert_eq!(config.settings.log_retention_days, 90);
+        assert_eq!(config.settings.scan_interval_minutes, 7, "fields missing from the import are kept");
+    }
+
+    #[test]
+    fn folder_paths_are_normalized() {
+        let (_tmp, watched) = crate::test_support::watched_dir();
+        let canonical = normalize_folder_path(&watched);
+        assert!(canonical.is_absolute());
+        fs::create_dir(watched.join("sub")).unwrap();
+        assert_eq!(normalize_folder_path(&watched.join("sub").join("..")), canonical);
+        let mut trailing = watched.clone().into_os_string();
+        trailing.push(std::path::MAIN_SEPARATOR_STR);
+        assert_eq!(normalize_folder_path(Path::new(&trailing)), canonical);
+        assert_eq!(normalize_folder_path(Path::new("/no/such/dir")), PathBuf::from("/no/such/dir"));
+
+        assert_eq!(strip_verbatim_prefix(PathBuf::from(r"\\?\D:\Downloads")), PathBuf::from(r"D:\Downloads"));
+        assert_eq!(strip_verbatim_prefix(PathBuf::from(r"\\?\UNC\nas\share")), PathBuf::from(r"\\nas\share"));
+    }
+
+    #[test]
+    fn nested_watched_folders_are_detected_both_ways() {
+        let config = AppConfig {
+            folders: vec![folder(Path::new("/data/Downloads"), Vec::new())],
+            ..Default::default()
+        };
+        let inside = nested_folder_conflict(&config, Path::new("/data/Downloads/sub")).unwrap();
+        assert!(inside.contains("is inside the watched folder '/data/Downloads'"), "{}", inside);
+        let outside = nested_folder_conflict(&config, Path::new("/data")).unwrap();
+        assert!(outside.contains("contains the watched folder"), "{}", outside);
+        assert!(nested_folder_conflict(&config, Path::new("/data/Downloads")).is_none());
+        assert!(nested_folder_conflict(&config, Path::new("/data/Downloads2")).is_none());
+    }
+
+    #[test]
+    fn removed_folder_is_archived_with_its_rules_and_restorable() {
+        let downloads = Path::new("/home/me/Downloads");
+        let mut config = AppConfig {
+            folders: vec![folder(downloads, vec![rule("PDFs", "*.pdf", move_to(Path::new("/docs")))])],
+            ..Default::default()
+        };
+
+        assert!(archive_folder(&mut config, "folder-1"));
+        assert!(config.folders.is_empty());
+        assert_eq!(config.archived_folders[0].rules[0].name, "PDFs");
+        assert!(!archive_folder(&mut config, "folder-1"));
+
+        // The same path was watched again in the meantime: restoring would duplicate it
+        config.folders.push(WatchedFolder { id: "other".to_string(), ..folder(downloads, Vec::new()) });
+        assert!(restore_archived_folder(&mut config, "folder-1").is_err());
+        config.folders.clear();
+
+        let restored = restore_archived_folder(&mut config, "folder-1").unwrap();
+        assert_eq!(restored.rules.len(), 1);
+        assert_eq!(config.folders.len(), 1);
+        assert!(config.archived_folders.is_empty());
+        assert!(restore_archived_folder(&mut config, "folder-1").is_err());
+    }
+
+    #[test]
+    fn new_subdirectory_of_auto_watching_folder_becomes_watched() {
+        let projects = Path::new("/work/projects");
+        let mut parent = folder(projects, Vec::new());
+        parent.auto_watch_new_subdirs = Some("Project".to_string());
+        let mut config = AppConfig {
+            folders: vec![parent],
+            settings: AppSettings::default(),
+            presets: vec![RulePreset {
+                name: "Project".to_string(),
+                rules: vec![rule("Builds", "*.zip", move_to(Path::new("/work/builds")))],
+                whitelist: vec!["*.lock".to_string()],
+            }],
+            ..Default::default()
+        };
+
+        let added = auto_watch_new_subdir(&mut config, &projects.join("acme")).unwrap();
+        assert_eq!(added.path, projects.join("acme"));
+        assert_eq!(added.rules[0].name, "Builds");
+        assert_ne!(added.rules[0].id, "Builds-id", "preset rules get fresh IDs");
+        assert_eq!(added.whitelist, vec!["*.lock".to_string()]);
+        assert_eq!(config.folders.len(), 2);
+
+        // Already watched, nested deeper, or no preset configured: nothing happens
+        assert!(auto_watch_new_subdir(&mut config, &projects.join("acme")).is_none());
+        assert!(auto_watch_new_subdir(&mut config, &projects.join("acme").join("src")).is_none());
+        config.folders[0].auto_watch_new_subdirs = Some("Missing".to_string());
+        assert!(auto_watch_new_subdir(&mut config, &projects.join("globex")).is_none());
+        assert_eq!(config.folders.len(), 2);
+    }
+
+    #[test]
+    fn renamed_auto_watched_folder_keeps_its_rules() {
+        let projects = Path::new("/work/projects");
+        let mut parent = folder(projects, Vec::new());
+        parent.auto_watch_new_subdirs = Some("Project".to_string());
+        let mut child = folder(&projects.join("New folder"), vec![rule("Builds", "*.zip", move_to(Path::new("/b")))]);
+        child.id = "child".to_string();
+        let mut config = AppConfig { folders: vec![parent, child], ..Default::default() };
+
+        let renamed = rename_auto_watched_folder(&mut config, &projects.join("New folder"), &projects.join("acme")).unwrap();
+        assert_eq!(renamed.id, "child");
+        assert_eq!(config.folders[1].path, projects.join("acme"));
+        assert_eq!(config.folders[1].rules[0].name, "Builds");
+
+        // Only siblings inside an auto-watching folder are followed
+        assert!(rename_auto_watched_folder(&mut config, &projects.join("acme"), Path::new("/elsewhere/acme")).is_none());
+        assert!(rename_auto_watched_folder(&mut config, projects, Path::new("/work/renamed")).is_none());
+        assert_eq!(config.folders[1].path, projects.join("acme"));
+    }
+
+    #[test]
+    fn restoring_a_nested_folder_follows_the_policy() {
+        let projects = Path::new("/work/projects");
+        let mut archived = folder(&projects.join("acme"), Vec::new());
+        archived.id = "archived".to_string();
+        let mut config = AppConfig {
+            folders: vec![folder(projects, Vec::new())],
+            archived_folders: vec![archived],
+            ..Default::default()
+        };
+
+        let err = restore_archived_folder(&mut config, "archived").unwrap_err();
+        assert!(err.contains("is inside the watched folder"), "{}", err);
+        assert_eq!(config.archived_folders.len(), 1);
+
+        config.settings.nested_folder_policy = NestedFolderPolicy::Warn;
+        assert_eq!(restore_archived_folder(&mut config, "archived").unwrap().id, "archived");
+        assert_eq!(config.folders.len(), 2);
+    }
+
+    #[test]
+    fn deepest_watched_folder_owns_a_file() {
+        let projects = Path::new("/work/projects");
+        let mut parent = folder(projects, Vec::new());
+        parent.watch_subdirectories = true;
+        let mut child = folder(&projects.join("acme"), Vec::new());
+        child.id = "child".to_string();
+        let config = AppConfig { folders: vec![parent, child], ..Default::default() };
+
+        assert_eq!(owning_folder(&config, &projects.join("acme").join("a.zip")).unwrap().id, "child");
+        assert_eq!(owning_folder(&config, &projects.join("a.zip")).unwrap().id, "folder-1");
+        // The child doesn't watch its subdirectories, so the recursive parent handles them
+        assert_eq!(owning_folder(&config, &projects.join("acme").join("src").join("a.rs")).unwrap().id, "folder-1");
+        assert!(owning_folder(&config, Path::new("/work/other.zip")).is_none());
+    }
+
+    #[test]
+    fn ignored_extensions_match_case_insensitively() {
+        let exts = AppSettings::default().global_ignore_extensions;
+        assert!(has_ignored_extension("movie.mkv.crdownload", &exts));
+        assert!(has_ignored_extension("setup.EXE.PART", &exts));
+        assert!(!has_ignored_extension("report.pdf", &exts));
+        assert!(!has_ignored_extension("tmp", &exts));
+        assert!(has_ignored_extension("a.bak", &[".BAK".to_string()]));
+    }
+
+    #[test]
+    fn managed_destinations_cover_every_folders_move_rules() {
+        let downloads = folder(Path::new("/downloads"), vec![rule("PDFs", "*.pdf", move_to(Path::new("/inbox")))]);
+        let mut inbox = folder(Path::new("/inbox"), vec![rule("Back", "*", move_to(Path::new("/sorted/{year}")))]);
+        inbox.id = "folder-2".to_string();
+        let mut config = AppConfig {
+            folders: vec![downloads, inbox],
+            settings: AppSettings::default(),
+            presets: Vec::new(),
+            ..Default::default()
+        };
+
+        // The inbox is another folder's destination, so its files are left alone
+        assert!(is_managed_destination(Path::new("/inbox/a.pdf"), Path::new("/inbox"), &config));
+        assert!(is_managed_destination(Path::new("/sorted/2026/a.pdf"), Path::new("/sorted"), &config));
+        assert!(!is_managed_destination(Path::new("/downloads/a.pdf"), Path::new("/downloads"), &config));
+
+        config.folders[0].enabled = false;
+        assert!(!is_managed_destination(Path::new("/inbox/a.pdf"), Path::new("/inbox"), &config));
+    }
+
+    #[test]
+    fn another_folders_destination_below_this_one_is_managed() {
+        let downloads = folder(Path::new("/downloads"), vec![rule("PDFs", "*.pdf", move_to(Path::new("/sorted/pdfs")))]);
+        let mut sorted = folder(Path::new("/sorted"), vec![rule("Back", "*", move_to(Path::new("/downloads/{year}")))]);
+        sorted.id = "folder-2".to_string();
+        let mut config = AppConfig {
+            folders: vec![downloads, sorted],
+            settings: AppSettings::default(),
+            presets: Vec::new(),
+            ..Default::default()
+        };
+
+        // Another folder's destination below this one is left alone
+        assert!(is_managed_destination(Path::new("/sorted/pdfs/a.pdf"), Path::new("/sorted"), &config));
+        assert!(!is_managed_destination(Path::new("/sorted/a.pdf"), Path::new("/sorted"), &config));
+
+        config.folders[0].enabled = false;
+        assert!(!is_managed_destination(Path::new("/sorted/pdfs/a.pdf"), Path::new("/sorted"), &config));
+    }
+
+    #[test]
+    fn destinations_at_or_above_the_folder_are_not_managed() {
+        let inbox = folder(
+            Path::new("/data/inbox"),
+            vec![
+                rule("By year", "*.jpg", move_to(Path::new("/data/inbox/{year}"))),
+                rule("Up", "*.pdf", move_to(Path::new("/data"))),
+                rule("Here", "*.txt", move_to(Path::new("/data/inbox"))),
+            ],
+        );
+        let config = AppConfig { folders: vec![inbox], ..Default::default() };
+
+        // The templated destination's static prefix is the watched folder itself
+        let folder_path = Path::new("/data/inbox");
+        assert!(!is_managed_destination(Path::new("/data/inbox/a.jpg"), folder_path, &config));
+        assert!(!is_managed_destination(Path::new("/data/inbox/a.pdf"), folder_path, &config));
+        assert!(!is_managed_destination(Path::new("/data/inbox/2026/a.jpg"), folder_path, &config));
+    }
+
+    #[test]
+    fn category_folders_inside_the_watched_folder_are_managed() {
+        let downloads = folder(
+            Path::new("/downloads"),
+            vec![rule("Sort", "*", Action::MoveByCategory { root: PathBuf::from("/downloads") })],
+        );
+        let config = AppConfig { folders: vec![downloads], ..Default::default() };
+
+        let folder_path = Path::new("/downloads");
+        assert!(!is_managed_destination(Path::new("/downloads/a.jpg"), folder_path, &config));
+        assert!(is_managed_destination(Path::new("/downloads/Images/a.jpg"), folder_path, &config));
+        assert!(!is_managed_destination(Path::new("/downloads/Projects/a.jpg"), folder_path, &config));
+    }
+
+    #[test]
+    fn merge_folders_combines_rules_and_whitelist() {
+        let mut parent = folder(Path::new("/data"), vec![rule("PDFs", "*.pdf", move_to(Path::new("/pdfs")))]);
+        parent.whitelist = vec!["*.tmp".to_string()];
+        let mut child = folder(Path::new("/data/sub"), vec![rule("Images", "*.jpg", move_to(Path::new("/img")))]);
+        child.id = "folder-2".to_string();
+        child.whitelist = vec!["*.tmp".to_string(), "keep*".to_string()];
+        let mut config = AppConfig {
+            folders: vec![parent, child],
+            settings: AppSettings::default(),
+            presets: Vec::new(),
+            ..Default::default()
+        };
+
+        let id_map = merge_folders(&mut config, "folder-2", "folder-1").unwrap();
+
+        assert_eq!(config.folders.len(), 1);
+        let merged = &config.folders[0];
+        let names: Vec<&str> = merged.rules.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["PDFs", "Images"]);
+        assert_eq!(merged.whitelist, vec!["*.tmp".to_string(), "keep*".to_string()]);
+        assert_eq!(id_map.len(), 1);
+        assert_eq!(id_map[0].0, "Images-id");
+        assert_eq!(merged.rules[1].id, id_map[0].1);
+        assert_ne!(id_map[0].0, id_map[0].1);
+    }
+
+    #[test]
+    fn rules_filtered_by_tag_across_folders() {
+        let mut taxes = rule("Taxes", "*tax*", move_to(Path::new("/tax")));
+        taxes.tags = vec!["finance".to_string()];
+        let mut photos = rule("Photos", "*.jpg", move_to(Path::new("/img")));
+        photos.tags = vec!["media".to_string()];
+        let mut invoices = rule("Invoices", "*invoice*", move_to(Path::new("/inv")));
+        invoices.tags = vec!["Finance".to_string(), "work".to_string()];
+        let mut second = folder(Path::new("/work"), vec![invoices]);
+        second.id = "folder-2".to_string();
+        let config = AppConfig {
+            folders: vec![folder(Path::new("/data"), vec![taxes, photos]), second],
+            settings: AppSettings::default(),
+            presets: Vec::new(),
+            ..Default::default()
+        };
+
+        let found: Vec<(&str, &str)> = rules_with_tag(&config, "finance")
+            .into_iter()
+            .map(|(f, r)| (f.id.as_str(), r.name.as_str()))
+            .collect();
+        assert_eq!(found, vec![("folder-1", "Taxes"), ("folder-2", "Invoices")]);
+        assert!(rules_with_tag(&config, "nope").is_empty());
+    }
+
+    #[test]
+    fn tags_are_trimmed_and_deduped() {
+        let tags = normalize_tags(vec![" Tax ".into(), "".into(), "tax".into(), "media".into()]);
+        assert_eq!(tags, vec!["Tax".to_string(), "media".to_string()]);
+    }
+
+    #[test]
+    fn move_into_recursive_folder_warns() {
+        let mut downloads = folder(Path::new("/data/downloads"), Vec::new());
+        downloads.watch_subdirectories = true;
+        let config = AppConfig {
+            folders: vec![downloads],
+            settings: AppSettings::default(),
+            presets: Vec::new(),
+            ..Default::default()
+        };
+
+        let looping = rule("Sort", "*.pdf", move_to(Path::new("/data/downloads/pdfs/{year}")));
+        let warnings = move_rule_warnings(&config, &looping);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("/data/downloads"));
+
+        let safe = rule("Sort", "*.pdf", move_to(Path::new("/data/archive")));
+        assert!(move_rule_warnings(&config, &safe).is_empty());
+    }
+
+    #[test]
+    fn validate_rule_flags_bad_regex_and_missing_drive() {
+        let config = AppConfig::default();
+        let tmp = tempfile::tempdir().unwrap();
+        assert!(validate_rule(&config, &rule("Sort", "*.pdf", move_to(tmp.path()))).is_empty());
+
+        let mut bad = rule("Sort", "*.pdf", move_to(Path::new("/mnt/no-such-drive-7f3a/sorted/{year}")));
+        bad.condition = Condition::Regex { pattern: "([a-z".into(), flags: String::new() };
+        let warnings = validate_rule(&config, &bad);
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].starts_with("Condition will never match"));
+        assert!(warnings[1].contains("'/mnt/no-such-drive-7f3a' is not mounted"));
+
+        let relative = rule("Sort", "*.pdf", move_to(Path::new("sorted")));
+        assert!(validate_rule(&config, &relative)[0].contains("not a full path"));
+    }
+
+    #[test]
+    fn volume_roots_sit_below_the_mount_parents() {
+        assert_eq!(volume_root(Path::new("/mnt/usb/photos")), Some(PathBuf::from("/mnt/usb")));
+        assert_eq!(volume_root(Path::new("/Volumes/Backup/2024")), Some(PathBuf::from("/Volumes/Backup")));
+        assert_eq!(volume_root(Path::new("/run/media/ann/usb/x")), Some(PathBuf::from("/run/media/ann/usb")));
+        assert_eq!(volume_root(Path::new("/home/ann/sorted")), None);
+        assert_eq!(volume_root(Path::new("/mnt")), None);
+    }
+
+    #[test]
+    fn move_into_non_recursive_subfolder_is_safe() {
+        let config = AppConfig {
+            folders: vec![folder(Path::new("/data/downloads"), Vec::new())],
+            settings: AppSettings::default(),
+            presets: Vec::new(),
+            ..Default::default()
+        };
+        let r = rule("Sort", "*.pdf", move_to(Path::new("/data/downloads/pdfs")));
+        assert!(move_rule_warnings(&config, &r).is_empty());
+    }
+
+    #[test]
+    fn move_rule_repositions_and_clamps() {
+        let dest = Path::new("/sorted");
+        let mut f = folder(
+            Path::new("/inbox"),
+            ["a", "b", "c", "d"].iter().map(|n| rule(n, "*", move_to(dest))).collect(),
+        );
+        let order = |f: &WatchedFolder| f.rules.iter().map(|r| r.name.as_str()).collect::<Vec<_>>().join("");
+
+        assert_eq!(f.move_rule("c-id", 0), Ok(0));
+        assert_eq!(order(&f), "cabd");
+        assert_eq!(f.move_rule("c-id", 3), Ok(3));
+        assert_eq!(order(&f), "abdc");
+        assert_eq!(f.move_rule("a-id", 99), Ok(3));
+        assert_eq!(order(&f), "bdca");
+        assert!(f.move_rule("missing", 0).is_err());
+
+        // Priorities follow the new order
+        let by_priority: String = f.rules_by_priority().iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(by_priority, "bdca");
+    }
+
+    #[test]
+    fn folder_pause_expires() {
+        let now = Utc::now();
+        let mut f = folder(Path::new("/data"), Vec::new());
+        assert!(!f.is_paused_at(now));
+
+        f.paused_until = Some((now + chrono::Duration::minutes(120)).to_rfc3339());
+        assert!(f.is_paused_at(now));
+        assert!(!f.is_paused_at(now + chrono::Duration::minutes(121)));
+
+        f.paused_until = Some("not a time".to_string());
+        assert!(!f.is_paused_at(now));
+    }
+
+    #[test]
+    fn rules_sorted_by_priority_then_list_order() {
+        let rules = ["a", "b", "c"]
+            .iter()
+            .map(|name| rule(name, "*", move_to(Path::new("/out"))))
+            .collect();
+        let mut f = folder(Path::new("/data"), rules);
+        f.rules[2].priority = 5;
+        let names: Vec<&str> = f.rules_by_priority().iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["c", "a", "b"]);
+
+        Rule::assign_priorities_in_order(&mut f.rules);
+        let names: Vec<&str> = f.rules_by_priority().iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn merge_folders_rejects_unknown_or_same_folder() {
+        let mut config = AppConfig {
+            folders: vec![folder(Path::new("/data"), Vec::new())],
+            settings: AppSettings::default(),
+            presets: Vec::new(),
+            ..Default::default()
+        };
+        assert!(merge_folders(&mut config, "folder-1", "folder-1").is_err());
+        assert!(merge_folders(&mut config, "missing", "folder-1").is_err());
+        assert_eq!(config.folders.len(), 1);
+    }
+
+    #[test]
+    fn v0_config_is_upgraded_and_keeps_unknown_fields() {
+        let v0 = serde_json::json!({
+            "folders": [{
+                "id": "f1", "path": "/data", "enabled": true,
+                "rules": [{
+                    "id": "r1", "name": "Old", "enabled": true, "condition_text": "*.log",
+                    "condition": { "type": "Glob", "pattern": "*.log" },
+                    "action": { "type": "Delete", "after_days": 2 }
+                }]
+            }],
+            "settings": AppSettings::default(),
+            "future_feature": { "on": true }
+        });
+
+        let config = migrate_config(v0);
+        assert_eq!(config.schema_version, CONFIG_SCHEMA_VERSION);
+        assert!(matches!(config.folders[0].rules[0].action, Action::Delete { delay_minutes: 2880, .. }));
+        let saved = serde_json::to_value(&config).unwrap();
+        assert_eq!(saved["future_feature"]["on"], true);
+        assert_eq!(saved["schema_version"], CONFIG_SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn malformed_rule_is_dropped_instead_of_the_whole_config() {
+        let good = rule("Keep", "*.pdf", move_to(Path::new("/docs")));
+        let mut value = serde_json::to_value(AppConfig {
+            folders: vec![folder(Path::new("/data"), vec![good])],
+            ..Default::default()
+        })
+        .unwrap();
+        value["folders"][0]["rules"]
+            .as_array_mut()
+            .unwrap()
+            .push(serde_json::json!({ "id": "bad", "name": "Broken", "action": { "type": "Teleport" } }));
+        value["settings"] = serde_json::json!("not settings");
+
+        let config = migrate_config(value);
+        assert_eq!(config.folders.len(), 1);
+        let names: Vec<&str> = config.folders[0].rules.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["Keep"]);
+    }
+
+    #[test]
+    fn failed_write_leaves_previous_config_intact() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        let previous = serde_json::to_string(&AppConfig::default()).unwrap();
+        write_atomically(&path, |file| file.write_all(previous.as_bytes())).unwrap();
+
+        let result = write_atomically(&path, |file| {
+            file.write_all(b"{\"folders\": [")?;
+            Err(std::io::Error::other("simulated crash"))
+        });
+
+        assert!(result.is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), previous);
+        assert!(!path.with_extension("json.tmp").exists());
+    }
+}
@@ -1,8 +1,20 @@
-use rusqlite::{params, Result};
+use rusqlite::{params, Connection, Result};
 
-use super::models::ActivityLogEntry;
+use chrono::{Duration, NaiveDateTime};
+
+use super::models::{ActionCount, ActivityInsert, ActivityLogEntry, ActivitySummary, BucketSize, DailyActivity, RuleActivity, TimeBucket};
 use super::Database;
 
+/// Actions that moved a file (immediate, scheduled or manual), as an SQL list.
+const MOVE_ACTIONS: &str = "('move', 'moved', 'auto_move', 'manual_move_now')";
+
+/// Actions that deleted a file, as an SQL list.
+const DELETE_ACTIONS: &str = "('delete', 'auto_delete', 'manual_delete_now')";
+
+/// Rows that count as a processed file: the action went through, and it wasn't only
+/// scheduling or an undo.
+const PROCESSED: &str = "result IN ('success', 'renamed_collision') AND action NOT IN ('scheduled', 'undo')";
+
 impl Database {
     pub fn insert_activity(
         &self,
@@ -25,6 +37,43 @@ impl Database {
         Ok(())
     }
 
+    /// `insert_activity` for a move or delete, recording the file's size for the summary.
+    pub fn insert_activity_sized(&self, e: &ActivityInsert) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO activity_log (id, file_path, file_name, action, rule_name, folder_id, timestamp, result, details, size_bytes)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                e.id, e.file_path, e.file_name, e.action, e.rule_name, e.folder_id, e.timestamp, e.result, e.details,
+                e.size_bytes
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Insert many activity rows in one transaction (one fsync instead of one per row).
+    /// All or nothing: on error no row is kept.
+    pub fn insert_activity_batch(&self, entries: &[ActivityInsert]) -> Result<()> {
+        if entries.is_empty() {
+            return Ok(());
+        }
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        {
+            let mut stmt = tx.prepare_cached(
+                "INSERT INTO activity_log (id, file_path, file_name, action, rule_name, folder_id, timestamp, result, details, size_bytes)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            )?;
+            for e in entries {
+                stmt.execute(params![
+                    e.id, e.file_path, e.file_name, e.action, e.rule_name, e.folder_id, e.timestamp, e.result, e.details,
+                    e.size_bytes
+                ])?;
+            }
+        }
+        tx.commit()
+    }
+
     pub fn get_activity_log(
         &self,
         limit: u32,
@@ -81,6 +130,113 @@ impl Database {
         Ok(entries)
     }
 
+    /// Number of activity rows, optionally only those of one folder (the total behind
+    /// `get_activity_log`'s pages).
+    pub fn count_activity(&self, folder_id: Option<&str>) -> Result<u64> {
+        let conn = self.conn.lock().unwrap();
+        match folder_id {
+            Some(fid) => conn.query_row(
+                "SELECT COUNT(*) FROM activity_log WHERE folder_id = ?1",
+                params![fid],
+                |row| row.get(0),
+            ),
+            None => conn.query_row("SELECT COUNT(*) FROM activity_log", [], |row| row.get(0)),
+        }
+    }
+
+    /// Activity entries matching every word of `query` in the file name, path, rule name
+    /// or details. Ranked by relevance with the FTS5 index (words match as prefixes), or
+    /// newest first via LIKE when this SQLite build has no FTS5.
+    pub fn search_activity(&self, query: &str, limit: u32, offset: u32) -> Result<Vec<ActivityLogEntry>> {
+        let words: Vec<&str> = query.split_whitespace().collect();
+        if words.is_empty() {
+            return self.get_activity_log(limit, offset, None);
+        }
+        let conn = self.conn.lock().unwrap();
+        if has_fts(&conn) {
+            // Quote each word so punctuation (`report.pdf`, `C:\`) isn't read as FTS syntax
+            let match_expr = words
+                .iter()
+                .map(|w| format!("\"{}\"*", w.replace('"', "\"\"")))
+                .collect::<Vec<_>>()
+                .join(" ");
+            let mut stmt = conn.prepare(
+                "SELECT a.id, a.file_path, a.file_name, a.action, a.rule_name, a.folder_id, a.timestamp, a.result, a.details
+                 FROM activity_fts JOIN activity_log a ON a.rowid = activity_fts.rowid
+                 WHERE activity_fts MATCH ?1
+                 ORDER BY activity_fts.rank, a.timestamp DESC LIMIT ?2 OFFSET ?3",
+            )?;
+            let rows = stmt.query_map(params![match_expr, limit, offset], activity_from_row)?;
+            rows.collect()
+        } else {
+            search_activity_like(&conn, &words, limit, offset)
+        }
+    }
+
+    /// All activity rows older than `before`, oldest first (what `prune_old_logs` would delete).
+    pub fn get_logs_before(&self, before: &str) -> Result<Vec<ActivityLogEntry>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, file_path, file_name, action, rule_name, folder_id, timestamp, result, details
+             FROM activity_log WHERE timestamp < ?1 ORDER BY timestamp ASC",
+        )?;
+        let rows = stmt.query_map(params![before], |row| {
+            Ok(ActivityLogEntry {
+                id: row.get(0)?,
+                file_path: row.get(1)?,
+                file_name: row.get(2)?,
+                action: row.get(3)?,
+                rule_name: row.get(4)?,
+                folder_id: row.get(5)?,
+                timestamp: row.get(6)?,
+                result: row.get(7)?,
+                details: row.get(8)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// Visit every activity row, oldest first, optionally limited to one folder and to rows
+    /// at or after `since`. Rows are streamed to `visit` rather than collected, so exports
+    /// of a large log don't hold it all in memory. Returns the number of rows visited.
+    pub fn iter_activity_for_export(
+        &self,
+        folder_id: Option<&str>,
+        since: Option<&str>,
+        mut visit: impl FnMut(ActivityLogEntry) -> std::io::Result<()>,
+    ) -> std::result::Result<u32, String> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, file_path, file_name, action, rule_name, folder_id, timestamp, result, details
+                 FROM activity_log
+                 WHERE (?1 IS NULL OR folder_id = ?1) AND (?2 IS NULL OR timestamp >= ?2)
+                 ORDER BY timestamp ASC",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![folder_id, since], |row| {
+                Ok(ActivityLogEntry {
+                    id: row.get(0)?,
+                    file_path: row.get(1)?,
+                    file_name: row.get(2)?,
+                    action: row.get(3)?,
+                    rule_name: row.get(4)?,
+                    folder_id: row.get(5)?,
+                    timestamp: row.get(6)?,
+                    result: row.get(7)?,
+                    details: row.get(8)?,
+                })
+            })
+            .map_err(|e| e.to_string())?;
+        let mut count = 0;
+        for row in rows {
+            visit(row.map_err(|e| e.to_string())?).map_err(|e| e.to_string())?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
     pub fn prune_old_logs(&self, before: &str) -> Result<usize> {
         let conn = self.conn.lock().unwrap();
         conn.execute(
@@ -89,7 +245,113 @@ impl Database {
         )
     }
 
+    /// Moved/deleted/error counts per day or hour over the last `days` days up to `now`
+    /// (UTC), oldest first. Buckets without activity are included as zeros so the series
+    /// has no gaps.
+    pub fn get_activity_timeseries(
+        &self,
+        now: NaiveDateTime,
+        days: u32,
+        bucket: BucketSize,
+        folder_id: Option<&str>,
+    ) -> Result<Vec<TimeBucket>> {
+        let (step, format, count) = match bucket {
+            BucketSize::Daily => (Duration::days(1), "%Y-%m-%d 00:00:00", days.max(1)),
+            BucketSize::Hourly => (Duration::hours(1), "%Y-%m-%d %H:00:00", days.max(1) * 24),
+        };
+        // Truncate `now` to its bucket by formatting it with the bucket's own pattern
+        let last = NaiveDateTime::parse_from_str(&now.format(format).to_string(), "%Y-%m-%d %H:%M:%S").unwrap_or(now);
+        let start = last - step * (count as i32 - 1);
+
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT strftime(?1, timestamp) AS bucket,
+                    SUM(CASE WHEN result IN ('success', 'renamed_collision') AND action IN {} THEN 1 ELSE 0 END),
+                    SUM(CASE WHEN result IN ('success', 'renamed_collision') AND action IN {} THEN 1 ELSE 0 END),
+                    SUM(CASE WHEN result = 'error' THEN 1 ELSE 0 END)
+             FROM activity_log
+             WHERE timestamp >= ?2 AND (?3 IS NULL OR folder_id = ?3)
+             GROUP BY bucket",
+            MOVE_ACTIONS, DELETE_ACTIONS
+        ))?;
+        let start_str = start.format("%Y-%m-%d %H:%M:%S").to_string();
+        let mut counts = std::collections::HashMap::new();
+        let rows = stmt.query_map(params![format, start_str, folder_id], |row| {
+            Ok((row.get::<_, String>(0)?, (row.get::<_, u32>(1)?, row.get::<_, u32>(2)?, row.get::<_, u32>(3)?)))
+        })?;
+        for row in rows {
+            let (bucket_start, totals) = row?;
+            counts.insert(bucket_start, totals);
+        }
+
+        Ok((0..count)
+            .map(|i| {
+                let bucket_start = (start + step * i as i32).format("%Y-%m-%d %H:%M:%S").to_string();
+                let (moved, deleted, errors) = counts.get(&bucket_start).copied().unwrap_or_default();
+                TimeBucket { bucket_start, moved, deleted, errors }
+            })
+            .collect())
+    }
+
+    /// Dashboard totals for activity at or after `since`: processed files and bytes moved
+    /// per day, a breakdown by action, and the ten busiest rules.
+    pub fn get_activity_summary(&self, since: &str) -> Result<ActivitySummary> {
+        let conn = self.conn.lock().unwrap();
+        let moved_bytes = format!("COALESCE(SUM(CASE WHEN action IN {} THEN size_bytes END), 0)", MOVE_ACTIONS);
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT substr(timestamp, 1, 10) AS day, COUNT(*), {}
+             FROM activity_log WHERE timestamp >= ?1 AND {}
+             GROUP BY day ORDER BY day",
+            moved_bytes, PROCESSED
+        ))?;
+        let per_day = stmt
+            .query_map(params![since], |row| {
+                Ok(DailyActivity {
+                    day: row.get(0)?,
+                    files_processed: row.get(1)?,
+                    bytes_moved: row.get::<_, i64>(2)?.max(0) as u64,
+                })
+            })?
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT action, COUNT(*) AS n FROM activity_log
+             WHERE timestamp >= ?1 AND {}
+             GROUP BY action ORDER BY n DESC, action",
+            PROCESSED
+        ))?;
+        let by_action = stmt
+            .query_map(params![since], |row| Ok(ActionCount { action: row.get(0)?, count: row.get(1)? }))?
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT rule_name, COUNT(*) AS n, {} FROM activity_log
+             WHERE timestamp >= ?1 AND rule_name IS NOT NULL AND {}
+             GROUP BY rule_name ORDER BY n DESC, rule_name LIMIT 10",
+            moved_bytes, PROCESSED
+        ))?;
+        let top_rules = stmt
+            .query_map(params![since], |row| {
+                Ok(RuleActivity {
+                    rule_name: row.get(0)?,
+                    count: row.get(1)?,
+                    bytes_moved: row.get::<_, i64>(2)?.max(0) as u64,
+                })
+            })?
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(ActivitySummary {
+            files_processed: per_day.iter().map(|d| d.files_processed).sum(),
+            bytes_moved: per_day.iter().map(|d| d.bytes_moved).sum(),
+            per_day,
+            by_action,
+            top_rules,
+        })
+    }
+
     /// Get execution stats (last run + weekly count) for each rule in a folder.
+    /// Moves that were renamed on collision count as executions and are also tallied separately.
     pub fn get_rule_execution_stats(
         &self,
         folder_id: &str,
@@ -98,9 +360,10 @@ impl Database {
         let conn = self.conn.lock().unwrap();
         let mut stmt = conn.prepare(
             "SELECT rule_name, MAX(timestamp) as last_executed,
-                    SUM(CASE WHEN timestamp >= ?2 THEN 1 ELSE 0 END) as week_count
+                    SUM(CASE WHEN timestamp >= ?2 THEN 1 ELSE 0 END) as week_count,
+                    SUM(CASE WHEN timestamp >= ?2 AND result = 'renamed_collision' THEN 1 ELSE 0 END) as week_renamed
              FROM activity_log
-             WHERE folder_id = ?1 AND rule_name IS NOT NULL AND result = 'success'
+             WHERE folder_id = ?1 AND rule_name IS NOT NULL AND result IN ('success', 'renamed_collision')
              GROUP BY rule_name",
         )?;
         let rows = stmt.query_map(params![folder_id, since], |row| {
@@ -108,6 +371,7 @@ impl Database {
                 rule_name: row.get(0)?,
                 last_executed: row.get(1)?,
                 executions_this_week: row.get::<_, u32>(2).unwrap_or(0),
+                renamed_collisions_this_week: row.get::<_, u32>(3).unwrap_or(0),
             })
         })?;
         let mut entries = Vec::new();
@@ -117,3 +381,229 @@ impl Database {
         Ok(entries)
     }
 }
+
+/// Whether the activity full-text index exists (FTS5 was available when tables were created).
+pub(super) fn has_fts(conn: &Connection) -> bool {
+    conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'activity_fts'",
+        [],
+        |row| row.get::<_, i64>(0),
+    )
+    .unwrap_or(0)
+        > 0
+}
+
+fn activity_from_row(row: &rusqlite::Row) -> Result<ActivityLogEntry> {
+    Ok(ActivityLogEntry {
+        id: row.get(0)?,
+        file_path: row.get(1)?,
+        file_name: row.get(2)?,
+        action: row.get(3)?,
+        rule_name: row.get(4)?,
+        folder_id: row.get(5)?,
+        timestamp: row.get(6)?,
+        result: row.get(7)?,
+        details: row.get(8)?,
+    })
+}
+
+/// `search_activity` without FTS5: every word must appear in one of the searched columns.
+fn search_activity_like(conn: &Connection, words: &[&str], limit: u32, offset: u32) -> Result<Vec<ActivityLogEntry>> {
+    let clauses = (0..words.len())
+        .map(|i| {
+            format!(
+                "(file_name LIKE ?{0} OR file_path LIKE ?{0} OR rule_name LIKE ?{0} OR details LIKE ?{0})",
+                i + 3
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" AND ");
+    let sql = format!(
+        "SELECT id, file_path, file_name, action, rule_name, folder_id, timestamp, result, details
+         FROM activity_log WHERE {} ORDER BY timestamp DESC LIMIT ?1 OFFSET ?2",
+        clauses
+    );
+    let mut values: Vec<rusqlite::types::Value> = vec![(limit as i64).into(), (offset as i64).into()];
+    values.extend(words.iter().map(|w| format!("%{}%", w).into()));
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map(rusqlite::params_from_iter(values), activity_from_row)?;
+    rows.collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Database;
+
+    fn log(db: &Database, id: &str, action: &str, result: &str, timestamp: &str) {
+        db.insert_activity(id, "/a.pdf", "a.pdf", action, Some("PDFs"), Some("folder-1"), timestamp, result, None)
+            .unwrap();
+    }
+
+    fn at(timestamp: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(timestamp, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    #[test]
+    fn activity_count_applies_folder_filter() {
+        let db = Database::open_in_memory().unwrap();
+        log(&db, "a1", "move", "success", "2024-01-01 00:00:00");
+        log(&db, "a2", "move", "success", "2024-01-01 00:01:00");
+        db.insert_activity("b1", "/b.pdf", "b.pdf", "move", None, Some("folder-2"), "2024-01-01 00:02:00", "success", None)
+            .unwrap();
+
+        assert_eq!(db.count_activity(None).unwrap(), 3);
+        assert_eq!(db.count_activity(Some("folder-1")).unwrap(), 2);
+        assert_eq!(db.count_activity(Some("missing")).unwrap(), 0);
+    }
+
+    #[test]
+    fn batch_insert_writes_10k_rows_in_one_transaction() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::open_with_recovery(&dir.path().join("data.db")).unwrap();
+        let entries: Vec<ActivityInsert> = (0..10_000)
+            .map(|i| ActivityInsert {
+                id: format!("a{}", i),
+                file_path: format!("/inbox/f{}.pdf", i),
+                file_name: format!("f{}.pdf", i),
+                action: "moved".to_string(),
+                rule_name: Some("PDFs".to_string()),
+                folder_id: Some("folder-1".to_string()),
+                timestamp: "2026-03-01 09:00:00".to_string(),
+                result: "success".to_string(),
+                details: None,
+                size_bytes: Some(1),
+            })
+            .collect();
+
+        let started = std::time::Instant::now();
+        db.insert_activity_batch(&entries).unwrap();
+        // One commit for all rows: well under a second on any disk, where one per row takes many
+        assert!(started.elapsed() < std::time::Duration::from_secs(10), "took {:?}", started.elapsed());
+        let count: i64 = db.conn.lock().unwrap().query_row("SELECT COUNT(*) FROM activity_log", [], |r| r.get(0)).unwrap();
+        assert_eq!(count, 10_000);
+
+        // All or nothing: a duplicate id rolls back the whole batch
+        let fresh = ActivityInsert { id: "fresh".to_string(), ..entries[0].clone() };
+        assert!(db.insert_activity_batch(&[fresh, entries[0].clone()]).is_err());
+        let count: i64 = db.conn.lock().unwrap().query_row("SELECT COUNT(*) FROM activity_log", [], |r| r.get(0)).unwrap();
+        assert_eq!(count, 10_000);
+    }
+
+    #[test]
+    fn daily_series_buckets_by_day_and_fills_gaps() {
+        let db = Database::open_in_memory().unwrap();
+        log(&db, "1", "auto_move", "success", "2026-03-01 09:00:00"); // before the window
+        log(&db, "2", "moved", "success", "2026-03-02 08:00:00");
+        log(&db, "3", "move", "renamed_collision", "2026-03-02 23:59:59");
+        log(&db, "4", "auto_delete", "success", "2026-03-02 12:00:00");
+        log(&db, "5", "auto_move", "error", "2026-03-04 10:00:00");
+        log(&db, "6", "scheduled", "success", "2026-03-04 10:00:00");
+        // Left in place: neither moved nor an error
+        log(&db, "7", "auto_move", "skipped_conflict", "2026-03-04 11:00:00");
+        log(&db, "8", "moved", "skipped_low_space", "2026-03-04 11:00:00");
+
+        let series = db.get_activity_timeseries(at("2026-03-04 15:30:00"), 3, BucketSize::Daily, None).unwrap();
+        let summary: Vec<(&str, u32, u32, u32)> =
+            series.iter().map(|b| (b.bucket_start.as_str(), b.moved, b.deleted, b.errors)).collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("2026-03-02 00:00:00", 2, 1, 0),
+                ("2026-03-03 00:00:00", 0, 0, 0),
+                ("2026-03-04 00:00:00", 0, 0, 1),
+            ]
+        );
+        let other_folder = db
+            .get_activity_timeseries(at("2026-03-04 15:30:00"), 3, BucketSize::Daily, Some("folder-2"))
+            .unwrap();
+        assert!(other_folder.iter().all(|b| b.moved + b.deleted + b.errors == 0));
+    }
+
+    #[test]
+    fn hourly_series_covers_every_hour_of_the_window() {
+        let db = Database::open_in_memory().unwrap();
+        log(&db, "1", "moved", "success", "2026-03-04 14:10:00");
+        log(&db, "2", "moved", "success", "2026-03-04 14:50:00");
+
+        let series = db.get_activity_timeseries(at("2026-03-04 15:30:00"), 1, BucketSize::Hourly, None).unwrap();
+        assert_eq!(series.len(), 24);
+        assert_eq!(series[0].bucket_start, "2026-03-03 16:00:00");
+        assert_eq!(series[23].bucket_start, "2026-03-04 15:00:00");
+        assert_eq!(series[22].moved, 2);
+        assert_eq!(series.iter().map(|b| b.moved).sum::<u32>(), 2);
+    }
+
+    #[test]
+    fn search_finds_words_across_columns_and_follows_deletes() {
+        let db = Database::open_in_memory().unwrap();
+        db.insert_activity("1", "/home/me/Downloads/report.pdf", "report.pdf", "moved", Some("PDFs"), None, "2026-03-01 09:00:00", "success", Some("File moved to /docs"))
+            .unwrap();
+        db.insert_activity("2", "/home/me/Downloads/invoice-acme.pdf", "invoice-acme.pdf", "moved", Some("Invoices"), None, "2026-03-02 09:00:00", "success", None)
+            .unwrap();
+        db.insert_activity("3", "/home/me/Pictures/cat.jpg", "cat.jpg", "auto_delete", Some("Old photos"), None, "2025-01-01 09:00:00", "success", None)
+            .unwrap();
+
+        let ids = |query: &str| -> Vec<String> {
+            db.search_activity(query, 50, 0).unwrap().into_iter().map(|e| e.id).collect()
+        };
+        assert_eq!(ids("report.pdf"), vec!["1"]);
+        assert_eq!(ids("invoic"), vec!["2"], "words match as prefixes");
+        assert_eq!(ids("downloads pdfs"), vec!["1"], "every word must match");
+        assert_eq!(ids("photos"), vec!["3"]);
+        assert_eq!(ids("nothing-like-this").len(), 0);
+
+        db.prune_old_logs("2026-01-01 00:00:00").unwrap();
+        assert!(ids("photos").is_empty(), "pruned rows leave the index");
+
+        let conn = db.conn.lock().unwrap();
+        let like: Vec<String> = search_activity_like(&conn, &["downloads", "INVOICE"], 50, 0)
+            .unwrap()
+            .into_iter()
+            .map(|e| e.id)
+            .collect();
+        assert_eq!(like, vec!["2"]);
+    }
+
+    #[test]
+    fn summary_groups_by_day_action_and_rule() {
+        let db = Database::open_in_memory().unwrap();
+        let sized = |id: &str, action: &str, rule: &str, ts: &str, result: &str, size: Option<i64>| {
+            db.insert_activity_sized(&ActivityInsert {
+                id: id.to_string(),
+                file_path: "/a.pdf".to_string(),
+                file_name: "a.pdf".to_string(),
+                action: action.to_string(),
+                rule_name: Some(rule.to_string()),
+                folder_id: Some("folder-1".to_string()),
+                timestamp: ts.to_string(),
+                result: result.to_string(),
+                details: None,
+                size_bytes: size,
+            })
+            .unwrap();
+        };
+        sized("0", "moved", "PDFs", "2026-02-20 09:00:00", "success", Some(999)); // before `since`
+        sized("1", "moved", "PDFs", "2026-03-01 09:00:00", "success", Some(100));
+        sized("2", "auto_move", "PDFs", "2026-03-01 10:00:00", "renamed_collision", Some(50));
+        sized("3", "auto_delete", "Old files", "2026-03-02 10:00:00", "success", Some(7000));
+        sized("4", "moved", "Images", "2026-03-02 11:00:00", "success", None);
+        sized("5", "moved", "Images", "2026-03-02 12:00:00", "error", Some(300));
+        log(&db, "6", "scheduled", "success", "2026-03-02 12:00:00");
+
+        let summary = db.get_activity_summary("2026-03-01 00:00:00").unwrap();
+        assert_eq!(summary.files_processed, 4);
+        assert_eq!(summary.bytes_moved, 150, "deletes, errors and unknown sizes aren't counted");
+        assert_eq!(
+            summary.per_day,
+            vec![
+                DailyActivity { day: "2026-03-01".to_string(), files_processed: 2, bytes_moved: 150 },
+                DailyActivity { day: "2026-03-02".to_string(), files_processed: 2, bytes_moved: 0 },
+            ]
+        );
+        let actions: Vec<(&str, u32)> = summary.by_action.iter().map(|a| (a.action.as_str(), a.count)).collect();
+        assert_eq!(actions, vec![("moved", 2), ("auto_delete", 1), ("auto_move", 1)]);
+        assert_eq!(summary.top_rules[0], RuleActivity { rule_name: "PDFs".to_string(), count: 2, bytes_moved: 150 });
+        assert_eq!(summary.top_rules.len(), 3);
+    }
+}
@@ -0,0 +1,74 @@
+use std::path::Path;
+
+use rusqlite::{params, OptionalExtension, Result};
+
+use super::Database;
+
+impl Database {
+    /// Content hash recorded for `file_path`, if it was hashed at this size and mtime.
+    pub fn get_file_hash(&self, file_path: &str, size_bytes: u64, modified: &str) -> Result<Option<String>> {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row(
+            "SELECT hash FROM file_hashes WHERE file_path = ?1 AND size_bytes = ?2 AND modified = ?3",
+            params![file_path, size_bytes as i64, modified],
+            |row| row.get(0),
+        )
+        .optional()
+    }
+
+    /// Remember a file's content hash; a later size or mtime change makes it stale.
+    pub fn put_file_hash(&self, file_path: &str, size_bytes: u64, modified: &str, hash: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO file_hashes (file_path, size_bytes, modified, hash) VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(file_path) DO UPDATE SET
+               size_bytes = excluded.size_bytes,
+               modified = excluded.modified,
+               hash = excluded.hash",
+            params![file_path, size_bytes as i64, modified, hash],
+        )?;
+        Ok(())
+    }
+
+    /// Forget the hashes of files that no longer exist (deleted or moved away).
+    /// Returns the number of rows removed.
+    pub fn prune_missing_file_hashes(&self) -> Result<usize> {
+        let mut conn = self.conn.lock().unwrap();
+        let paths: Vec<String> = {
+            let mut stmt = conn.prepare("SELECT file_path FROM file_hashes")?;
+            let rows = stmt.query_map([], |row| row.get(0))?;
+            rows.collect::<Result<_>>()?
+        };
+        let missing: Vec<&String> = paths.iter().filter(|p| !Path::new(p).exists()).collect();
+        if missing.is_empty() {
+            return Ok(0);
+        }
+        let tx = conn.transaction()?;
+        for path in &missing {
+            tx.execute("DELETE FROM file_hashes WHERE file_path = ?1", params![path])?;
+        }
+        tx.commit()?;
+        Ok(missing.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hashes_of_missing_files_are_pruned() {
+        let tmp = tempfile::tempdir().unwrap();
+        let kept = tmp.path().join("kept.bin");
+        std::fs::write(&kept, "x").unwrap();
+        let kept = kept.to_string_lossy().to_string();
+        let gone = tmp.path().join("gone.bin").to_string_lossy().to_string();
+        let db = Database::open_in_memory().unwrap();
+        db.put_file_hash(&kept, 1, "t", "aaa").unwrap();
+        db.put_file_hash(&gone, 1, "t", "bbb").unwrap();
+
+        assert_eq!(db.prune_missing_file_hashes().unwrap(), 1);
+        assert_eq!(db.get_file_hash(&kept, 1, "t").unwrap(), Some("aaa".to_string()));
+        assert_eq!(db.get_file_hash(&gone, 1, "t").unwrap(), None);
+    }
+}
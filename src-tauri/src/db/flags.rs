@@ -0,0 +1,21 @@
+use rusqlite::{params, OptionalExtension, Result};
+
+use super::Database;
+
+impl Database {
+    /// Whether the named one-off task (see `set_flag`) has already run on this database.
+    pub fn has_flag(&self, name: &str) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        let found: Option<String> = conn
+            .query_row("SELECT name FROM app_flags WHERE name = ?1", params![name], |row| row.get(0))
+            .optional()?;
+        Ok(found.is_some())
+    }
+
+    /// Record that a one-off task has run, so it isn't repeated.
+    pub fn set_flag(&self, name: &str, now: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("INSERT OR IGNORE INTO app_flags (name, set_at) VALUES (?1, ?2)", params![name, now])?;
+        Ok(())
+    }
+}
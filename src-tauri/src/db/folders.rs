@@ -0,0 +1,188 @@
+use std::collections::HashMap;
+
+use rusqlite::{params, Result};
+
+use super::models::FolderSummary;
+use super::Database;
+
+/// Tables whose rows belong to one watched folder and are meaningless without it.
+/// The activity log and undo history are kept: they're history, pruned by retention.
+const FOLDER_TABLES: [&str; 5] = ["scheduled_deletions", "rule_metadata", "file_index", "review_queue", "handled_files"];
+
+impl Database {
+    /// Per-folder scheduled-entry counts and latest activity timestamps, keyed by folder id,
+    /// computed with one grouped query per table. Folders with no rows are absent.
+    pub fn get_folder_summaries(&self) -> Result<HashMap<String, FolderSummary>> {
+        let conn = self.conn.lock().unwrap();
+        let mut summaries: HashMap<String, FolderSummary> = HashMap::new();
+
+        let mut stmt = conn.prepare(
+            "SELECT folder_id, COUNT(*) FROM scheduled_deletions GROUP BY folder_id",
+        )?;
+        let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, u32>(1)?)))?;
+        for row in rows {
+            let (folder_id, count) = row?;
+            summaries.entry(folder_id).or_default().scheduled_count = count;
+        }
+
+        let mut stmt = conn.prepare(
+            "SELECT folder_id, MAX(timestamp) FROM activity_log
+             WHERE folder_id IS NOT NULL GROUP BY folder_id",
+        )?;
+        let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?)))?;
+        for row in rows {
+            let (folder_id, last) = row?;
+            summaries.entry(folder_id).or_default().last_activity_ts = last;
+        }
+
+        Ok(summaries)
+    }
+
+    /// Re-point all DB rows of `source_folder_id` to `target_folder_id` in one transaction
+    /// (used when merging two watched folders). `rule_id_map` maps each moved rule's old
+    /// id to its new id so rule metadata follows the rule.
+    /// Scheduled entries that would collide with one the target already has for the same
+    /// file+rule are dropped.
+    pub fn reassign_folder_data(
+        &self,
+        source_folder_id: &str,
+        target_folder_id: &str,
+        rule_id_map: &[(String, String)],
+    ) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        tx.execute(
+            "UPDATE OR IGNORE scheduled_deletions SET folder_id = ?2 WHERE folder_id = ?1",
+            params![source_folder_id, target_folder_id],
+        )?;
+        tx.execute(
+            "DELETE FROM scheduled_deletions WHERE folder_id = ?1",
+            params![source_folder_id],
+        )?;
+        tx.execute(
+            "UPDATE activity_log SET folder_id = ?2 WHERE folder_id = ?1",
+            params![source_folder_id, target_folder_id],
+        )?;
+        tx.execute(
+            "UPDATE OR IGNORE file_index SET folder_id = ?2 WHERE folder_id = ?1",
+            params![source_folder_id, target_folder_id],
+        )?;
+        for (old_id, new_id) in rule_id_map {
+            tx.execute(
+                "UPDATE rule_metadata SET rule_id = ?3, folder_id = ?4 WHERE rule_id = ?1 AND folder_id = ?2",
+                params![old_id, source_folder_id, new_id, target_folder_id],
+            )?;
+        }
+        tx.execute(
+            "DELETE FROM rule_metadata WHERE folder_id = ?1",
+            params![source_folder_id],
+        )?;
+        tx.commit()
+    }
+
+    /// Delete every row of `folder_id` from the folder-keyed tables, in one transaction.
+    /// Returns the number of rows removed.
+    pub fn delete_folder_data(&self, folder_id: &str) -> Result<usize> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let mut removed = 0;
+        for table in FOLDER_TABLES {
+            removed += tx.execute(&format!("DELETE FROM {table} WHERE folder_id = ?1"), params![folder_id])?;
+        }
+        tx.commit()?;
+        Ok(removed)
+    }
+
+    /// Delete rows of the folder-keyed tables whose folder isn't in `known_folder_ids`.
+    /// Returns the number of rows removed.
+    pub fn delete_orphaned_folder_data(&self, known_folder_ids: &[&str]) -> Result<usize> {
+        let placeholders = vec!["?"; known_folder_ids.len()].join(", ");
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let mut removed = 0;
+        for table in FOLDER_TABLES {
+            removed += tx.execute(
+                &format!("DELETE FROM {table} WHERE folder_id NOT IN ({placeholders})"),
+                rusqlite::params_from_iter(known_folder_ids),
+            )?;
+        }
+        tx.commit()?;
+        Ok(removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn count(db: &Database, sql: &str, folder_id: &str) -> i64 {
+        let conn = db.conn.lock().unwrap();
+        conn.query_row(sql, params![folder_id], |row| row.get(0)).unwrap()
+    }
+
+    #[test]
+    fn folder_summaries_count_and_latest_activity() {
+        let db = Database::open_in_memory().unwrap();
+        db.insert_activity("a1", "/f/a", "a", "moved", None, Some("f1"), "2026-01-01 00:00:00", "success", None).unwrap();
+        db.insert_activity("a2", "/f/b", "b", "moved", None, Some("f1"), "2026-01-03 00:00:00", "success", None).unwrap();
+        db.upsert_scheduled_deletion("s1", "/g/c", "f2", "Logs", "c", None, None, "2026-01-01 00:00:00", "2026-01-02 00:00:00", "delete", None, false, 0).unwrap();
+
+        let summaries = db.get_folder_summaries().unwrap();
+        assert_eq!(summaries["f1"].scheduled_count, 0);
+        assert_eq!(summaries["f1"].last_activity_ts.as_deref(), Some("2026-01-03 00:00:00"));
+        assert_eq!(summaries["f2"].scheduled_count, 1);
+        assert!(summaries["f2"].last_activity_ts.is_none());
+    }
+
+    #[test]
+    fn reassign_folder_data_moves_references() {
+        let db = Database::open_in_memory().unwrap();
+        db.insert_activity("a1", "/src/a.pdf", "a.pdf", "moved", Some("PDFs"), Some("src"), "2026-01-01 00:00:00", "success", None).unwrap();
+        db.upsert_scheduled_deletion("s1", "/src/b.log", "src", "Logs", "b.log", Some("log"), None, "2026-01-01 00:00:00", "2026-01-02 00:00:00", "delete", None, false, 0).unwrap();
+        db.insert_rule_metadata("old-rule", "src", "2026-01-01T00:00:00Z").unwrap();
+
+        db.reassign_folder_data("src", "dst", &[("old-rule".to_string(), "new-rule".to_string())]).unwrap();
+
+        assert_eq!(count(&db, "SELECT COUNT(*) FROM activity_log WHERE folder_id = ?1", "src"), 0);
+        assert_eq!(count(&db, "SELECT COUNT(*) FROM activity_log WHERE folder_id = ?1", "dst"), 1);
+        assert_eq!(count(&db, "SELECT COUNT(*) FROM scheduled_deletions WHERE folder_id = ?1", "dst"), 1);
+        let meta = db.get_rule_metadata("dst").unwrap();
+        assert_eq!(meta.len(), 1);
+        assert_eq!(meta[0].rule_id, "new-rule");
+        assert!(db.get_rule_metadata("src").unwrap().is_empty());
+    }
+
+    fn add_folder_rows(db: &Database, folder_id: &str) {
+        let path = format!("/{folder_id}/a.log");
+        db.upsert_scheduled_deletion(&format!("{folder_id}-s"), &path, folder_id, "Logs", "a.log", Some("log"), None, "2026-01-01 00:00:00", "2026-01-02 00:00:00", "delete", None, false, 0).unwrap();
+        db.insert_rule_metadata(&format!("{folder_id}-rule"), folder_id, "2026-01-01T00:00:00Z").unwrap();
+        db.upsert_file(&format!("{folder_id}-f"), &path, folder_id, "a.log", Some("log"), None, "2026-01-01 00:00:00", None, None, None).unwrap();
+        db.enqueue_review(&format!("{folder_id}-r"), &path, "a.log", folder_id, "rule", "Logs", "", "2026-01-01 00:00:00").unwrap();
+        db.record_handled_file(&path, "rule", folder_id, "run_command", None, true, "2026-01-01 00:00:00").unwrap();
+        db.insert_activity(&format!("{folder_id}-a"), &path, "a.log", "moved", None, Some(folder_id), "2026-01-01 00:00:00", "success", None).unwrap();
+    }
+
+    fn folder_row_counts(db: &Database, folder_id: &str) -> Vec<i64> {
+        FOLDER_TABLES
+            .iter()
+            .map(|table| count(db, &format!("SELECT COUNT(*) FROM {table} WHERE folder_id = ?1"), folder_id))
+            .collect()
+    }
+
+    #[test]
+    fn deleting_folder_data_clears_every_folder_table() {
+        let db = Database::open_in_memory().unwrap();
+        add_folder_rows(&db, "gone");
+        add_folder_rows(&db, "kept");
+
+        assert_eq!(db.delete_folder_data("gone").unwrap(), FOLDER_TABLES.len());
+        assert_eq!(folder_row_counts(&db, "gone"), [0, 0, 0, 0, 0]);
+        assert_eq!(folder_row_counts(&db, "kept"), [1, 1, 1, 1, 1]);
+        assert_eq!(count(&db, "SELECT COUNT(*) FROM activity_log WHERE folder_id = ?1", "gone"), 1, "history is kept");
+
+        add_folder_rows(&db, "orphan");
+        assert_eq!(db.delete_orphaned_folder_data(&["kept"]).unwrap(), FOLDER_TABLES.len());
+        assert_eq!(folder_row_counts(&db, "orphan"), [0, 0, 0, 0, 0]);
+        assert_eq!(folder_row_counts(&db, "kept"), [1, 1, 1, 1, 1]);
+    }
+}
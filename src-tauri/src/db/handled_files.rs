@@ -0,0 +1,54 @@
+use rusqlite::{params, OptionalExtension, Result};
+
+use super::models::HandledFile;
+use super::Database;
+
+impl Database {
+    /// How a run-once action (`action` of rule `rule_id`) last went on this file, or None
+    /// if it never ran on it.
+    pub fn get_handled_file(&self, file_path: &str, rule_id: &str, action: &str) -> Result<Option<HandledFile>> {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row(
+            "SELECT succeeded, attempt_count, last_attempt_at, file_stamp FROM handled_files
+             WHERE file_path = ?1 AND rule_id = ?2 AND action = ?3",
+            params![file_path, rule_id, action],
+            |row| {
+                Ok(HandledFile {
+                    succeeded: row.get(0)?,
+                    attempt_count: row.get(1)?,
+                    last_attempt_at: row.get(2)?,
+                    file_stamp: row.get(3)?,
+                })
+            },
+        )
+        .optional()
+    }
+
+    /// Record one run of a run-once action on a file. Failures count up `attempt_count`
+    /// (for retry backoff); a success marks the file handled for good. A different
+    /// `file_stamp` than last time is a new file, so its attempts count from one again.
+    #[allow(clippy::too_many_arguments)]
+    pub fn record_handled_file(
+        &self,
+        file_path: &str,
+        rule_id: &str,
+        folder_id: &str,
+        action: &str,
+        file_stamp: Option<&str>,
+        succeeded: bool,
+        now: &str,
+    ) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO handled_files (file_path, rule_id, folder_id, action, succeeded, attempt_count, last_attempt_at, file_stamp)
+             VALUES (?1, ?2, ?3, ?4, ?5, 1, ?6, ?7)
+             ON CONFLICT(file_path, rule_id, action) DO UPDATE SET
+               succeeded = excluded.succeeded,
+               attempt_count = CASE WHEN file_stamp IS excluded.file_stamp THEN attempt_count + 1 ELSE 1 END,
+               last_attempt_at = excluded.last_attempt_at,
+               file_stamp = excluded.file_stamp",
+            params![file_path, rule_id, folder_id, action, succeeded, now, file_stamp],
+        )?;
+        Ok(())
+    }
+}
@@ -0,0 +1,164 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use rusqlite::{params, ErrorCode, Result};
+
+use super::Database;
+
+impl Database {
+    /// Run `PRAGMA integrity_check`. Ok(false) means SQLite found damage; each problem
+    /// it reports is logged.
+    pub fn integrity_check(&self) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("PRAGMA integrity_check")?;
+        let problems: Vec<String> = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<Result<_>>()?;
+        if problems.len() == 1 && problems[0] == "ok" {
+            return Ok(true);
+        }
+        for problem in &problems {
+            log::error!("Database integrity check: {}", problem);
+        }
+        Ok(false)
+    }
+
+    /// Write a consistent copy of the database to `path`, replacing any previous copy.
+    pub fn backup_to(&self, path: &Path) -> std::result::Result<(), String> {
+        let partial = path.with_extension("partial");
+        let _ = fs::remove_file(&partial);
+        let conn = self.conn.lock().unwrap();
+        conn.execute("VACUUM INTO ?1", params![partial.to_string_lossy()])
+            .map_err(|e| format!("Failed to back up the database: {}", e))?;
+        fs::rename(&partial, path).map_err(|e| e.to_string())
+    }
+
+    /// Open the database at `db_path`, checking its integrity first. A healthy database
+    /// refreshes the backup next to it; a damaged one (failed integrity check, or SQLite
+    /// reporting it corrupt or not a database) is set aside as `*.corrupt` and replaced by
+    /// that backup, when there is one. Other errors, like a lock held by another instance
+    /// or missing permissions, are returned with the file left alone.
+    pub fn open_with_recovery(db_path: &Path) -> Result<Self> {
+        let backup = backup_path(db_path);
+        match Self::open_checked(db_path) {
+            Ok(db) => {
+                log::info!("Database integrity check passed");
+                if let Err(e) = db.backup_to(&backup) {
+                    log::warn!("{}", e);
+                }
+                Ok(db)
+            }
+            Err(OpenFailure::Other(e)) => Err(e),
+            Err(OpenFailure::Corrupt(reason)) if backup.exists() => {
+                log::error!("Database {} is damaged ({}); restoring from backup", db_path.display(), reason);
+                let corrupt = db_path.with_extension("db.corrupt");
+                if let Err(e) = fs::rename(db_path, &corrupt) {
+                    // Copying over it would destroy the only copy of the damaged data
+                    log::error!("Failed to set aside the damaged database, not restoring the backup: {}", e);
+                    return Self::open_path(db_path);
+                }
+                // The damaged file's WAL must not be replayed onto the restored backup
+                for suffix in ["-wal", "-shm"] {
+                    let sidecar = sidecar_path(db_path, suffix);
+                    if sidecar.exists() {
+                        let _ = fs::rename(&sidecar, sidecar_path(&corrupt, suffix));
+                    }
+                }
+                if let Err(e) = fs::copy(&backup, db_path) {
+                    log::error!("Failed to restore the database backup: {}", e);
+                }
+                Self::open_path(db_path)
+            }
+            Err(OpenFailure::Corrupt(reason)) => {
+                log::error!("Database {} is damaged ({}) and there is no backup", db_path.display(), reason);
+                Self::open_path(db_path)
+            }
+        }
+    }
+
+    fn open_checked(db_path: &Path) -> std::result::Result<Self, OpenFailure> {
+        let db = Self::open_path(db_path).map_err(OpenFailure::from)?;
+        match db.integrity_check() {
+            Ok(true) => Ok(db),
+            Ok(false) => Err(OpenFailure::Corrupt("integrity check failed".to_string())),
+            Err(e) => Err(OpenFailure::from(e)),
+        }
+    }
+}
+
+/// Why `open_checked` rejected a database.
+enum OpenFailure {
+    /// The file is damaged and may be replaced by the backup
+    Corrupt(String),
+    /// Anything else (busy, locked, permissions, …): the file must be left alone
+    Other(rusqlite::Error),
+}
+
+impl From<rusqlite::Error> for OpenFailure {
+    fn from(e: rusqlite::Error) -> Self {
+        match e.sqlite_error_code() {
+            Some(ErrorCode::DatabaseCorrupt | ErrorCode::NotADatabase) => OpenFailure::Corrupt(e.to_string()),
+            _ => OpenFailure::Other(e),
+        }
+    }
+}
+
+/// SQLite's file next to `db_path` with `suffix` appended (`data.db` → `data.db-wal`).
+pub fn sidecar_path(db_path: &Path, suffix: &str) -> PathBuf {
+    let mut name = db_path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// Where the last known-good copy of `db_path` is kept (`data.db` → `data.db.bak`).
+pub fn backup_path(db_path: &Path) -> PathBuf {
+    db_path.with_extension("db.bak")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn healthy_database_passes_and_is_backed_up() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("data.db");
+
+        let db = Database::open_with_recovery(&db_path).unwrap();
+        assert!(db.integrity_check().unwrap());
+        assert!(backup_path(&db_path).exists());
+        assert!(Database::open_in_memory().unwrap().integrity_check().unwrap());
+    }
+
+    #[test]
+    fn unreadable_database_is_restored_from_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("data.db");
+        {
+            let db = Database::open_with_recovery(&db_path).unwrap();
+            db.insert_activity("a1", "/a.pdf", "a.pdf", "moved", None, None, "2026-01-01 00:00:00", "success", None)
+                .unwrap();
+            db.backup_to(&backup_path(&db_path)).unwrap();
+        }
+        fs::write(&db_path, vec![0xAB; 8192]).unwrap();
+
+        let db = Database::open_with_recovery(&db_path).unwrap();
+        assert!(db.integrity_check().unwrap());
+        assert_eq!(db.get_activity_log(10, 0, None).unwrap().len(), 1);
+        assert!(db_path.with_extension("db.corrupt").exists());
+    }
+
+    #[test]
+    fn database_that_cant_be_opened_is_left_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("data.db");
+        Database::open_with_recovery(&db_path).unwrap();
+        fs::remove_file(&db_path).unwrap();
+        // Not corruption: SQLite can't open a directory at all
+        fs::create_dir(&db_path).unwrap();
+
+        assert!(Database::open_with_recovery(&db_path).is_err());
+        assert!(db_path.is_dir());
+        assert!(!db_path.with_extension("db.corrupt").exists());
+    }
+}
@@ -18,7 +18,6 @@ impl Database {
         Ok(())
     }
 
-    #[allow(dead_code)]
     pub fn update_rule_triggered(
         &self,
         rule_id: &str,
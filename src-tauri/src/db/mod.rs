@@ -1,18 +1,52 @@
 use rusqlite::{Connection, Result};
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 
 use crate::config::app_data_dir;
 
 pub struct Database {
     pub conn: Mutex<Connection>,
+    /// Where deletions are staged (see [`crate::staging::staging_dir`]).
+    pub staging_dir: PathBuf,
 }
 
 impl Database {
     pub fn new() -> Result<Self> {
-        let db_path = app_data_dir().join("data.db");
+        Self::open_with_recovery(&app_data_dir().join("data.db"))
+    }
+
+    /// In-memory database with the full schema, for tests. Deletions stage into the
+    /// temp dir, never the user's app data.
+    #[cfg(test)]
+    pub fn open_in_memory() -> Result<Self> {
+        let staging_dir = std::env::temp_dir().join("folder-organizer-test-staging");
+        Self::from_connection(Connection::open_in_memory()?, staging_dir)
+    }
+
+    /// The database file at `db_path`, staging deletions next to it.
+    fn open_path(db_path: &Path) -> Result<Self> {
+        let data_dir = db_path.parent().unwrap_or(Path::new("."));
+        Self::from_connection(Self::open_file(db_path)?, crate::staging::staging_dir(data_dir))
+    }
+
+    /// Open the database file in WAL mode: readers don't wait for a writer's fsync, and
+    /// `synchronous=NORMAL` only syncs at checkpoints (safe against corruption in WAL
+    /// mode; a crash can lose at most the last few commits). Waits up to 5 s on a lock.
+    fn open_file(db_path: &Path) -> Result<Connection> {
         let conn = Connection::open(db_path)?;
+        let mode: String = conn.pragma_update_and_check(None, "journal_mode", "WAL", |row| row.get(0))?;
+        if !mode.eq_ignore_ascii_case("wal") {
+            log::warn!("SQLite refused WAL mode, staying in {} mode", mode);
+        }
+        conn.pragma_update(None, "synchronous", "NORMAL")?;
+        conn.busy_timeout(std::time::Duration::from_secs(5))?;
+        Ok(conn)
+    }
+
+    fn from_connection(conn: Connection, staging_dir: PathBuf) -> Result<Self> {
         let db = Self {
             conn: Mutex::new(conn),
+            staging_dir,
         };
         db.init_tables()?;
         Ok(db)
@@ -31,7 +65,8 @@ impl Database {
                 folder_id   TEXT,
                 timestamp   TEXT NOT NULL,
                 result      TEXT NOT NULL,
-                details     TEXT
+                details     TEXT,
+                size_bytes  INTEGER
             );
 
             CREATE TABLE IF NOT EXISTS file_index (
@@ -54,7 +89,8 @@ impl Database {
                 action          TEXT NOT NULL,
                 timestamp       TEXT NOT NULL,
                 expires_at      TEXT NOT NULL,
-                restored        INTEGER NOT NULL DEFAULT 0
+                restored        INTEGER NOT NULL DEFAULT 0,
+                batch_id        TEXT
             );
 
             CREATE INDEX IF NOT EXISTS idx_activity_timestamp ON activity_log(timestamp);
@@ -84,9 +120,52 @@ impl Database {
                 action_type     TEXT NOT NULL DEFAULT 'delete',
                 move_destination TEXT,
                 keep_source     INTEGER NOT NULL DEFAULT 0,
-                rule_priority   INTEGER NOT NULL DEFAULT 0
+                rule_priority   INTEGER NOT NULL DEFAULT 0,
+                attempt_count   INTEGER NOT NULL DEFAULT 0,
+                last_attempt_at TEXT,
+                status          TEXT NOT NULL DEFAULT 'pending'
             );
 
+            CREATE TABLE IF NOT EXISTS review_queue (
+                id              TEXT PRIMARY KEY,
+                file_path       TEXT NOT NULL,
+                file_name       TEXT NOT NULL,
+                folder_id       TEXT NOT NULL,
+                rule_id         TEXT NOT NULL,
+                rule_name       TEXT NOT NULL,
+                reason          TEXT NOT NULL DEFAULT '',
+                flagged_at      TEXT NOT NULL,
+                status          TEXT NOT NULL DEFAULT 'pending',
+                resolved_at     TEXT
+            );
+
+            CREATE TABLE IF NOT EXISTS file_hashes (
+                file_path       TEXT PRIMARY KEY,
+                size_bytes      INTEGER NOT NULL,
+                modified        TEXT NOT NULL,
+                hash            TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS app_flags (
+                name            TEXT PRIMARY KEY,
+                set_at          TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS handled_files (
+                file_path       TEXT NOT NULL,
+                rule_id         TEXT NOT NULL,
+                folder_id       TEXT NOT NULL,
+                action          TEXT NOT NULL,
+                succeeded       INTEGER NOT NULL DEFAULT 0,
+                attempt_count   INTEGER NOT NULL DEFAULT 0,
+                last_attempt_at TEXT NOT NULL,
+                file_stamp      TEXT,
+                PRIMARY KEY (file_path, rule_id, action)
+            );
+
+            CREATE UNIQUE INDEX IF NOT EXISTS idx_review_file_rule ON review_queue(file_path, rule_id);
+            CREATE INDEX IF NOT EXISTS idx_review_status ON review_queue(status);
+
             CREATE UNIQUE INDEX IF NOT EXISTS idx_sched_del_file_rule ON scheduled_deletions(file_path, rule_name);
             CREATE INDEX IF NOT EXISTS idx_sched_del_after ON scheduled_deletions(delete_after);
             CREATE INDEX IF NOT EXISTS idx_sched_del_folder ON scheduled_deletions(folder_id);
@@ -153,6 +232,105 @@ impl Database {
             ALTER TABLE scheduled_deletions ADD COLUMN rule_priority INTEGER NOT NULL DEFAULT 0;
         ");
 
+        // Migration: add retry tracking to scheduled_deletions for existing databases
+        let has_attempt_count: bool = conn
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('scheduled_deletions') WHERE name = 'attempt_count'",
+                [],
+                |row| row.get::<_, i64>(0),
+            )
+            .unwrap_or(0)
+            > 0;
+        if !has_attempt_count {
+            conn.execute_batch("
+                ALTER TABLE scheduled_deletions ADD COLUMN attempt_count INTEGER NOT NULL DEFAULT 0;
+                ALTER TABLE scheduled_deletions ADD COLUMN last_attempt_at TEXT;
+            ")?;
+        }
+
+        // Migration: add approval status to scheduled_deletions for existing databases
+        let has_sched_status: bool = conn
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('scheduled_deletions') WHERE name = 'status'",
+                [],
+                |row| row.get::<_, i64>(0),
+            )
+            .unwrap_or(0)
+            > 0;
+        if !has_sched_status {
+            conn.execute_batch("ALTER TABLE scheduled_deletions ADD COLUMN status TEXT NOT NULL DEFAULT 'pending';")?;
+        }
+
+        // Migration: add batch_id to undo_history for existing databases
+        let has_batch_id: bool = conn
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('undo_history') WHERE name = 'batch_id'",
+                [],
+                |row| row.get::<_, i64>(0),
+            )
+            .unwrap_or(0)
+            > 0;
+        if !has_batch_id {
+            conn.execute_batch("ALTER TABLE undo_history ADD COLUMN batch_id TEXT;")?;
+        }
+        conn.execute_batch("CREATE INDEX IF NOT EXISTS idx_undo_batch ON undo_history(batch_id);")?;
+
+        // Migration: add size_bytes to activity_log for existing databases
+        let has_activity_size: bool = conn
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('activity_log') WHERE name = 'size_bytes'",
+                [],
+                |row| row.get::<_, i64>(0),
+            )
+            .unwrap_or(0)
+            > 0;
+        if !has_activity_size {
+            conn.execute_batch("ALTER TABLE activity_log ADD COLUMN size_bytes INTEGER;")?;
+        }
+
+        // Migration: add file_stamp to handled_files for existing databases
+        let has_file_stamp: bool = conn
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('handled_files') WHERE name = 'file_stamp'",
+                [],
+                |row| row.get::<_, i64>(0),
+            )
+            .unwrap_or(0)
+            > 0;
+        if !has_file_stamp {
+            conn.execute_batch("ALTER TABLE handled_files ADD COLUMN file_stamp TEXT;")?;
+        }
+
+        // Full-text index over the activity log, kept in sync by triggers. Optional: a SQLite
+        // build without FTS5 just falls back to LIKE search (see `search_activity`).
+        let had_fts = activity::has_fts(&conn);
+        let fts = conn.execute_batch("
+            CREATE VIRTUAL TABLE IF NOT EXISTS activity_fts USING fts5(
+                file_name, file_path, rule_name, details,
+                content='activity_log', content_rowid='rowid'
+            );
+            CREATE TRIGGER IF NOT EXISTS activity_fts_insert AFTER INSERT ON activity_log BEGIN
+                INSERT INTO activity_fts(rowid, file_name, file_path, rule_name, details)
+                VALUES (new.rowid, new.file_name, new.file_path, new.rule_name, new.details);
+            END;
+            CREATE TRIGGER IF NOT EXISTS activity_fts_delete AFTER DELETE ON activity_log BEGIN
+                INSERT INTO activity_fts(activity_fts, rowid, file_name, file_path, rule_name, details)
+                VALUES ('delete', old.rowid, old.file_name, old.file_path, old.rule_name, old.details);
+            END;
+            CREATE TRIGGER IF NOT EXISTS activity_fts_update AFTER UPDATE ON activity_log BEGIN
+                INSERT INTO activity_fts(activity_fts, rowid, file_name, file_path, rule_name, details)
+                VALUES ('delete', old.rowid, old.file_name, old.file_path, old.rule_name, old.details);
+                INSERT INTO activity_fts(rowid, file_name, file_path, rule_name, details)
+                VALUES (new.rowid, new.file_name, new.file_path, new.rule_name, new.details);
+            END;
+        ");
+        match fts {
+            // Index rows logged before the table existed
+            Ok(()) if !had_fts => conn.execute_batch("INSERT INTO activity_fts(activity_fts) VALUES ('rebuild');")?,
+            Ok(()) => {}
+            Err(e) => log::warn!("Full-text search unavailable, activity search will use LIKE: {}", e),
+        }
+
         Ok(())
     }
 }
@@ -160,9 +338,15 @@ impl Database {
 // ── Sub-modules ─────────────────────────────────────────────
 
 mod activity;
+mod file_hashes;
 mod file_index;
+mod flags;
+mod folders;
+mod handled_files;
+mod integrity;
 mod metadata;
 mod models;
+mod review_queue;
 mod scheduled_deletions;
 mod storage;
 mod undo;
@@ -170,6 +354,110 @@ mod undo;
 // ── Re-exports ──────────────────────────────────────────────
 
 pub use models::{
-    ActivityLogEntry, DbStats, FileIndexEntry, RuleExecutionStats, RuleMetadata,
-    ScheduledDeletion, TableQueryResult, UndoEntry,
+    ActivityInsert, ActivityLogEntry, ActivityLogPage, ActivitySummary, BucketSize, DbStats, FileIndexEntry, FolderSummary, ReviewItem,
+    RuleExecutionStats, RuleMetadata, ScheduledDeletion, ScheduledDeletionPage, TableQueryResult, TimeBucket, UndoBatch, UndoEntry,
 };
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn columns(db: &Database, table: &str) -> Vec<String> {
+        let conn = db.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!("SELECT name FROM pragma_table_info('{}')", table)).unwrap();
+        let names = stmt.query_map([], |row| row.get(0)).unwrap();
+        names.map(|n| n.unwrap()).collect()
+    }
+
+    #[test]
+    fn new_database_has_scheduled_deletions_table() {
+        let dir = tempfile::tempdir().unwrap();
+        // Same path `Database::new()` takes, minus the real app data dir
+        let db = Database::open_with_recovery(&dir.path().join("data.db")).unwrap();
+        let journal_mode: String = db.conn.lock().unwrap().query_row("PRAGMA journal_mode", [], |row| row.get(0)).unwrap();
+        assert_eq!(journal_mode, "wal");
+        let cols = columns(&db, "scheduled_deletions");
+        for expected in ["file_path", "delete_after", "rule_priority", "attempt_count", "status"] {
+            assert!(cols.iter().any(|c| c == expected), "missing column {}", expected);
+        }
+        assert!(db.get_scheduled_deletions().unwrap().is_empty());
+    }
+
+    #[test]
+    fn old_scheduled_deletions_table_is_migrated() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("data.db");
+        {
+            let conn = Connection::open(&db_path).unwrap();
+            conn.execute_batch(
+                "CREATE TABLE scheduled_deletions (
+                    id TEXT PRIMARY KEY, file_path TEXT NOT NULL, folder_id TEXT NOT NULL,
+                    rule_name TEXT NOT NULL, file_name TEXT NOT NULL, extension TEXT,
+                    size_bytes INTEGER, scheduled_at TEXT NOT NULL, delete_after TEXT NOT NULL
+                 );
+                 INSERT INTO scheduled_deletions VALUES
+                    ('d1', '/a.log', 'folder-1', 'Logs', 'a.log', 'log', 1, '2024-01-01 00:00:00', '2024-01-02 00:00:00');",
+            )
+            .unwrap();
+        }
+
+        let db = Database::open_with_recovery(&db_path).unwrap();
+        let entries = db.get_scheduled_deletions().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].action_type, "delete");
+        assert_eq!(entries[0].attempt_count, 0);
+        assert_eq!(entries[0].status, "pending");
+    }
+
+    #[test]
+    fn scheduled_deletions_page_by_folder() {
+        let db = Database::open_in_memory().unwrap();
+        for i in 0..5 {
+            let folder = if i % 2 == 0 { "folder-1" } else { "folder-2" };
+            db.upsert_scheduled_deletion(
+                &format!("d{i}"), &format!("/f{i}.log"), folder, "Logs", &format!("f{i}.log"), Some("log"), None,
+                "2024-01-01 00:00:00", &format!("2024-01-0{} 00:00:00", i + 1), "delete", None, false, 0,
+            )
+            .unwrap();
+        }
+
+        let page = db.get_scheduled_deletions_paged(2, 2, None).unwrap();
+        assert_eq!(page.total, 5);
+        assert_eq!(page.rows.iter().map(|r| r.id.as_str()).collect::<Vec<_>>(), ["d2", "d3"]);
+
+        let page = db.get_scheduled_deletions_paged(10, 1, Some("folder-1")).unwrap();
+        assert_eq!(page.total, 3);
+        assert_eq!(page.rows.iter().map(|r| r.id.as_str()).collect::<Vec<_>>(), ["d2", "d4"]);
+
+        let conn = db.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare(
+                "EXPLAIN QUERY PLAN SELECT id FROM scheduled_deletions WHERE folder_id = ?1
+                 ORDER BY delete_after ASC, rule_priority ASC LIMIT 10",
+            )
+            .unwrap();
+        let plan: Vec<String> = stmt.query_map(["folder-1"], |row| row.get(3)).unwrap().map(|r| r.unwrap()).collect();
+        assert!(plan.iter().any(|p| p.contains("idx_sched_del_folder")), "{:?}", plan);
+    }
+
+    #[test]
+    fn next_due_deletion_skips_given_up_entries_and_waits_out_retries() {
+        let db = Database::open_in_memory().unwrap();
+        for (id, due) in [("given-up", "2024-01-01 00:00:00"), ("retrying", "2024-01-02 00:00:00"), ("fresh", "2024-01-03 00:00:00")] {
+            db.upsert_scheduled_deletion(
+                id, &format!("/{id}.log"), "folder-1", id, &format!("{id}.log"), Some("log"), None,
+                "2024-01-01 00:00:00", due, "delete", None, false, 0,
+            )
+            .unwrap();
+        }
+        for _ in 0..3 {
+            db.record_deletion_attempt("given-up", "2024-01-01 00:00:00").unwrap();
+        }
+        db.record_deletion_attempt("retrying", "2024-01-02 12:00:00").unwrap();
+        let hours = |attempts: u32| chrono::Duration::hours(attempts as i64);
+
+        assert_eq!(db.get_next_due_deletion(3, hours).unwrap().as_deref(), Some("2024-01-02 13:00:00"));
+        assert_eq!(db.get_next_due_deletion(3, |_| chrono::Duration::days(2)).unwrap().as_deref(), Some("2024-01-03 00:00:00"));
+        assert_eq!(db.get_next_due_deletion(4, hours).unwrap().as_deref(), Some("2024-01-01 03:00:00"));
+    }
+}
@@ -11,6 +11,39 @@ pub struct ActivityLogEntry {
     pub details: Option<String>,
 }
 
+/// One page of the activity log plus the total across all pages.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ActivityLogPage {
+    pub entries: Vec<ActivityLogEntry>,
+    pub total: u64,
+}
+
+/// One activity row for `Database::insert_activity_sized` and `insert_activity_batch`.
+#[derive(Debug, Clone)]
+pub struct ActivityInsert {
+    pub id: String,
+    pub file_path: String,
+    pub file_name: String,
+    pub action: String,
+    pub rule_name: Option<String>,
+    pub folder_id: Option<String>,
+    pub timestamp: String,
+    pub result: String,
+    pub details: Option<String>,
+    pub size_bytes: Option<i64>,
+}
+
+/// Last run of a run-once action (RunCommand, Notify) on one file, from `handled_files`.
+#[derive(Debug, Clone)]
+pub struct HandledFile {
+    pub succeeded: bool,
+    pub attempt_count: u32,
+    pub last_attempt_at: String,
+    /// Size and mtime of the file when it last ran, so a new file at the same path
+    /// counts as unhandled. None for rows recorded before stamps were kept.
+    pub file_stamp: Option<String>,
+}
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct FileIndexEntry {
     pub id: String,
@@ -30,10 +63,83 @@ pub struct UndoEntry {
     pub id: String,
     pub original_path: String,
     pub current_path: Option<String>,
+    /// e.g. `auto_move`, `auto_copy`, `rename` (original_path = old name, current_path = new name)
     pub action: String,
     pub timestamp: String,
     pub expires_at: String,
     pub restored: bool,
+    /// Shared by every entry created in the same scan or scheduler run (None for single events)
+    pub batch_id: Option<String>,
+}
+
+/// Undo entries created together in one scan or scheduler run.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct UndoBatch {
+    /// The run's batch id; an entry recorded outside a batch forms its own group keyed by its id
+    pub batch_id: String,
+    /// Non-restored entry ids in the batch, newest first (pass to `undo_batch`)
+    pub undo_ids: Vec<String>,
+    pub count: u32,
+    /// Earliest entry timestamp in the batch
+    pub timestamp: String,
+    /// Entry count per undo action (`auto_move`, `auto_delete`, ...)
+    pub actions: std::collections::BTreeMap<String, u32>,
+}
+
+/// Dashboard totals over the last N days (see `get_activity_summary`).
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct ActivitySummary {
+    /// Successful actions of any kind (scheduling and undo don't count)
+    pub files_processed: u32,
+    /// Total size of files moved, where the size was recorded
+    pub bytes_moved: u64,
+    /// Per day with activity, oldest first
+    pub per_day: Vec<DailyActivity>,
+    /// Successful actions per action type, most frequent first
+    pub by_action: Vec<ActionCount>,
+    /// Rules with the most successful actions, most first
+    pub top_rules: Vec<RuleActivity>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct DailyActivity {
+    /// `YYYY-MM-DD` (UTC)
+    pub day: String,
+    pub files_processed: u32,
+    pub bytes_moved: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct ActionCount {
+    pub action: String,
+    pub count: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct RuleActivity {
+    pub rule_name: String,
+    pub count: u32,
+    pub bytes_moved: u64,
+}
+
+/// Width of one time-series bucket.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+pub enum BucketSize {
+    Daily,
+    Hourly,
+}
+
+/// Activity counts for one time-series bucket (all zero for a quiet period).
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct TimeBucket {
+    /// Start of the bucket, `YYYY-MM-DD HH:00:00` (UTC)
+    pub bucket_start: String,
+    /// Successful moves (scheduled, immediate and manual)
+    pub moved: u32,
+    /// Successful deletions
+    pub deleted: u32,
+    /// Entries of any action that failed
+    pub errors: u32,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -49,6 +155,9 @@ pub struct RuleExecutionStats {
     pub rule_name: String,
     pub last_executed: Option<String>,
     pub executions_this_week: u32,
+    /// Executions this week whose move had to rename on a destination collision
+    #[serde(default)]
+    pub renamed_collisions_this_week: u32,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -75,12 +184,33 @@ pub struct ScheduledDeletion {
     /// Rule index in the folder's rule list (lower = higher priority)
     #[serde(default)]
     pub rule_priority: u32,
+    /// Failed execution attempts so far (locked file, permission error, ...)
+    #[serde(default)]
+    pub attempt_count: u32,
+    /// When the last failed attempt happened
+    #[serde(default)]
+    pub last_attempt_at: Option<String>,
+    /// "pending", or "awaiting_approval" for due deletions held back by
+    /// `require_deletion_approval`
+    #[serde(default = "default_deletion_status")]
+    pub status: String,
 }
 
 fn default_action_type() -> String {
     "delete".to_string()
 }
 
+fn default_deletion_status() -> String {
+    "pending".to_string()
+}
+
+/// One page of scheduled actions plus the total across all pages.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ScheduledDeletionPage {
+    pub total: u64,
+    pub rows: Vec<ScheduledDeletion>,
+}
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct TableStats {
     pub table_name: String,
@@ -100,3 +230,26 @@ pub struct DbStats {
     pub trash_size_bytes: u64,
     pub tables: Vec<TableStats>,
 }
+
+/// DB-side aggregates for one watched folder (see `Database::get_folder_summaries`).
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct FolderSummary {
+    pub scheduled_count: u32,
+    pub last_activity_ts: Option<String>,
+}
+
+/// A file a Review rule flagged for the user to decide on.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ReviewItem {
+    pub id: String,
+    pub file_path: String,
+    pub file_name: String,
+    pub folder_id: String,
+    pub rule_id: String,
+    pub rule_name: String,
+    pub reason: String,
+    pub flagged_at: String,
+    /// "pending", "approved", or "dismissed"
+    pub status: String,
+    pub resolved_at: Option<String>,
+}
@@ -0,0 +1,79 @@
+use rusqlite::{params, OptionalExtension, Result, Row};
+
+use super::models::ReviewItem;
+use super::Database;
+
+const REVIEW_COLUMNS: &str =
+    "id, file_path, file_name, folder_id, rule_id, rule_name, reason, flagged_at, status, resolved_at";
+
+fn review_from_row(row: &Row) -> Result<ReviewItem> {
+    Ok(ReviewItem {
+        id: row.get(0)?,
+        file_path: row.get(1)?,
+        file_name: row.get(2)?,
+        folder_id: row.get(3)?,
+        rule_id: row.get(4)?,
+        rule_name: row.get(5)?,
+        reason: row.get(6)?,
+        flagged_at: row.get(7)?,
+        status: row.get(8)?,
+        resolved_at: row.get(9)?,
+    })
+}
+
+impl Database {
+    /// Flag a file for review. Returns false if this rule already flagged the file —
+    /// including items the user resolved, so dismissed files don't come back every scan.
+    #[allow(clippy::too_many_arguments)]
+    pub fn enqueue_review(
+        &self,
+        id: &str,
+        file_path: &str,
+        file_name: &str,
+        folder_id: &str,
+        rule_id: &str,
+        rule_name: &str,
+        reason: &str,
+        flagged_at: &str,
+    ) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        let inserted = conn.execute(
+            "INSERT OR IGNORE INTO review_queue
+                (id, file_path, file_name, folder_id, rule_id, rule_name, reason, flagged_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![id, file_path, file_name, folder_id, rule_id, rule_name, reason, flagged_at],
+        )?;
+        Ok(inserted > 0)
+    }
+
+    /// Items still waiting on a decision, oldest first.
+    pub fn get_pending_reviews(&self) -> Result<Vec<ReviewItem>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM review_queue WHERE status = 'pending' ORDER BY flagged_at ASC",
+            REVIEW_COLUMNS
+        ))?;
+        let rows = stmt.query_map([], review_from_row)?;
+        rows.collect()
+    }
+
+    pub fn get_review_item(&self, id: &str) -> Result<Option<ReviewItem>> {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row(
+            &format!("SELECT {} FROM review_queue WHERE id = ?1", REVIEW_COLUMNS),
+            params![id],
+            review_from_row,
+        )
+        .optional()
+    }
+
+    /// Record the user's decision on a pending item.
+    pub fn resolve_review(&self, id: &str, status: &str, resolved_at: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE review_queue SET status = ?1, resolved_at = ?2 WHERE id = ?3 AND status = 'pending'",
+            params![status, resolved_at, id],
+        )?;
+        Ok(())
+    }
+}
@@ -1,8 +1,38 @@
+use chrono::{Duration, NaiveDateTime};
 use rusqlite::{params, Result};
 
-use super::models::ScheduledDeletion;
+use super::models::{ScheduledDeletion, ScheduledDeletionPage};
 use super::Database;
 
+/// Format of the `delete_after` and `last_attempt_at` columns.
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Columns read by [`scheduled_from_row`], in order.
+const SCHEDULED_COLUMNS: &str = "id, file_path, folder_id, rule_name, file_name, extension, size_bytes, scheduled_at, delete_after,
+    COALESCE(action_type, 'delete'), move_destination, COALESCE(keep_source, 0), COALESCE(rule_priority, 0),
+    COALESCE(attempt_count, 0), last_attempt_at, COALESCE(status, 'pending')";
+
+fn scheduled_from_row(row: &rusqlite::Row) -> Result<ScheduledDeletion> {
+    Ok(ScheduledDeletion {
+        id: row.get(0)?,
+        file_path: row.get(1)?,
+        folder_id: row.get(2)?,
+        rule_name: row.get(3)?,
+        file_name: row.get(4)?,
+        extension: row.get(5)?,
+        size_bytes: row.get(6)?,
+        scheduled_at: row.get(7)?,
+        delete_after: row.get(8)?,
+        action_type: row.get(9)?,
+        move_destination: row.get(10)?,
+        keep_source: row.get::<_, i32>(11).unwrap_or(0) != 0,
+        rule_priority: row.get::<_, u32>(12).unwrap_or(0),
+        attempt_count: row.get::<_, u32>(13).unwrap_or(0),
+        last_attempt_at: row.get(14)?,
+        status: row.get(15)?,
+    })
+}
+
 impl Database {
     /// Insert or update a scheduled action keyed on (file_path, rule_name).
     /// Multiple rules can independently schedule actions on the same file.
@@ -45,6 +75,75 @@ impl Database {
         Ok(!already_exists)
     }
 
+    /// Record a failed execution attempt. Returns the new attempt count.
+    pub fn record_deletion_attempt(&self, id: &str, now: &str) -> Result<u32> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE scheduled_deletions SET attempt_count = attempt_count + 1, last_attempt_at = ?2 WHERE id = ?1",
+            params![id, now],
+        )?;
+        conn.query_row(
+            "SELECT attempt_count FROM scheduled_deletions WHERE id = ?1",
+            params![id],
+            |row| row.get(0),
+        )
+    }
+
+    /// Hold a due deletion back until the user approves it.
+    /// Returns `true` if the entry wasn't already awaiting approval.
+    pub fn mark_deletion_awaiting_approval(&self, id: &str) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        let changed = conn.execute(
+            "UPDATE scheduled_deletions SET status = 'awaiting_approval' WHERE id = ?1 AND status != 'awaiting_approval'",
+            params![id],
+        )?;
+        Ok(changed > 0)
+    }
+
+    /// IDs of all deletions waiting for the user's approval, oldest due first.
+    pub fn get_awaiting_approval_ids(&self) -> Result<Vec<String>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id FROM scheduled_deletions WHERE status = 'awaiting_approval' ORDER BY delete_after ASC, rule_priority ASC",
+        )?;
+        let rows = stmt.query_map([], |row| row.get(0))?;
+        rows.collect()
+    }
+
+    /// Due time of the earliest pending scheduled deletion, if any. Entries that failed
+    /// `max_attempts` times are left out; failed ones count from the end of their retry
+    /// `backoff` (given the attempt count) when that is later than their due time.
+    pub fn get_next_due_deletion(
+        &self,
+        max_attempts: u32,
+        backoff: impl Fn(u32) -> Duration,
+    ) -> Result<Option<String>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT delete_after, COALESCE(attempt_count, 0), last_attempt_at FROM scheduled_deletions
+             WHERE action_type = 'delete' AND status = 'pending' AND COALESCE(attempt_count, 0) < ?1",
+        )?;
+        let rows = stmt.query_map(params![max_attempts], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, u32>(1)?, row.get::<_, Option<String>>(2)?))
+        })?;
+        let mut next: Option<String> = None;
+        for row in rows {
+            let (delete_after, attempts, last_attempt_at) = row?;
+            let retry_at = last_attempt_at
+                .filter(|_| attempts > 0)
+                .and_then(|at| NaiveDateTime::parse_from_str(&at, TIMESTAMP_FORMAT).ok())
+                .map(|at| (at + backoff(attempts)).format(TIMESTAMP_FORMAT).to_string());
+            let due = match retry_at {
+                Some(retry_at) if retry_at > delete_after => retry_at,
+                _ => delete_after,
+            };
+            if next.as_ref().is_none_or(|n| due < *n) {
+                next = Some(due);
+            }
+        }
+        Ok(next)
+    }
+
     /// Check whether a file is already scheduled.
     #[allow(dead_code)]
     pub fn is_file_scheduled(&self, file_path: &str) -> bool {
@@ -62,28 +161,10 @@ impl Database {
     /// Get all scheduled actions (ordered by delete_after ascending, then rule priority).
     pub fn get_scheduled_deletions(&self) -> Result<Vec<ScheduledDeletion>> {
         let conn = self.conn.lock().unwrap();
-        let mut stmt = conn.prepare(
-            "SELECT id, file_path, folder_id, rule_name, file_name, extension, size_bytes, scheduled_at, delete_after,
-                    COALESCE(action_type, 'delete'), move_destination, COALESCE(keep_source, 0), COALESCE(rule_priority, 0)
-             FROM scheduled_deletions ORDER BY delete_after ASC, rule_priority ASC",
-        )?;
-        let rows = stmt.query_map([], |row| {
-            Ok(ScheduledDeletion {
-                id: row.get(0)?,
-                file_path: row.get(1)?,
-                folder_id: row.get(2)?,
-                rule_name: row.get(3)?,
-                file_name: row.get(4)?,
-                extension: row.get(5)?,
-                size_bytes: row.get(6)?,
-                scheduled_at: row.get(7)?,
-                delete_after: row.get(8)?,
-                action_type: row.get(9)?,
-                move_destination: row.get(10)?,
-                keep_source: row.get::<_, i32>(11).unwrap_or(0) != 0,
-                rule_priority: row.get::<_, u32>(12).unwrap_or(0),
-            })
-        })?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {SCHEDULED_COLUMNS} FROM scheduled_deletions ORDER BY delete_after ASC, rule_priority ASC"
+        ))?;
+        let rows = stmt.query_map([], scheduled_from_row)?;
         let mut entries = Vec::new();
         for row in rows {
             entries.push(row?);
@@ -91,32 +172,59 @@ impl Database {
         Ok(entries)
     }
 
+    /// Number of scheduled actions, optionally only those of one folder.
+    pub fn count_scheduled_deletions(&self, folder_id: Option<&str>) -> Result<u64> {
+        let conn = self.conn.lock().unwrap();
+        match folder_id {
+            Some(fid) => conn.query_row(
+                "SELECT COUNT(*) FROM scheduled_deletions WHERE folder_id = ?1",
+                params![fid],
+                |row| row.get(0),
+            ),
+            None => conn.query_row("SELECT COUNT(*) FROM scheduled_deletions", [], |row| row.get(0)),
+        }
+    }
+
+    /// One page of [`get_scheduled_deletions`](Self::get_scheduled_deletions), in the same order,
+    /// optionally only for one folder.
+    pub fn get_scheduled_deletions_paged(
+        &self,
+        limit: u32,
+        offset: u32,
+        folder_id: Option<&str>,
+    ) -> Result<ScheduledDeletionPage> {
+        let total = self.count_scheduled_deletions(folder_id)?;
+        let conn = self.conn.lock().unwrap();
+        // Separate statements rather than `?1 IS NULL OR folder_id = ?1`, which can't use idx_sched_del_folder.
+        let rows = match folder_id {
+            Some(fid) => {
+                let mut stmt = conn.prepare(&format!(
+                    "SELECT {SCHEDULED_COLUMNS} FROM scheduled_deletions WHERE folder_id = ?1
+                     ORDER BY delete_after ASC, rule_priority ASC LIMIT ?2 OFFSET ?3"
+                ))?;
+                let rows = stmt.query_map(params![fid, limit, offset], scheduled_from_row)?;
+                rows.collect::<Result<Vec<_>>>()?
+            }
+            None => {
+                let mut stmt = conn.prepare(&format!(
+                    "SELECT {SCHEDULED_COLUMNS} FROM scheduled_deletions
+                     ORDER BY delete_after ASC, rule_priority ASC LIMIT ?1 OFFSET ?2"
+                ))?;
+                let rows = stmt.query_map(params![limit, offset], scheduled_from_row)?;
+                rows.collect::<Result<Vec<_>>>()?
+            }
+        };
+        Ok(ScheduledDeletionPage { total, rows })
+    }
+
     /// Get scheduled actions whose execute time has passed.
     /// Ordered by delete_after ASC, then rule_priority ASC (top-of-list rule wins ties).
     pub fn get_due_deletions(&self, now: &str) -> Result<Vec<ScheduledDeletion>> {
         let conn = self.conn.lock().unwrap();
-        let mut stmt = conn.prepare(
-            "SELECT id, file_path, folder_id, rule_name, file_name, extension, size_bytes, scheduled_at, delete_after,
-                    COALESCE(action_type, 'delete'), move_destination, COALESCE(keep_source, 0), COALESCE(rule_priority, 0)
-             FROM scheduled_deletions WHERE delete_after <= ?1 ORDER BY delete_after ASC, rule_priority ASC",
-        )?;
-        let rows = stmt.query_map(params![now], |row| {
-            Ok(ScheduledDeletion {
-                id: row.get(0)?,
-                file_path: row.get(1)?,
-                folder_id: row.get(2)?,
-                rule_name: row.get(3)?,
-                file_name: row.get(4)?,
-                extension: row.get(5)?,
-                size_bytes: row.get(6)?,
-                scheduled_at: row.get(7)?,
-                delete_after: row.get(8)?,
-                action_type: row.get(9)?,
-                move_destination: row.get(10)?,
-                keep_source: row.get::<_, i32>(11).unwrap_or(0) != 0,
-                rule_priority: row.get::<_, u32>(12).unwrap_or(0),
-            })
-        })?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {SCHEDULED_COLUMNS} FROM scheduled_deletions WHERE delete_after <= ?1 ORDER BY delete_after ASC, rule_priority ASC"
+        ))?;
+        let rows = stmt.query_map(params![now], scheduled_from_row)?;
         let mut entries = Vec::new();
         for row in rows {
             entries.push(row?);
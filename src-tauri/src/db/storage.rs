@@ -5,21 +5,24 @@ use super::models::{TableStats, TableQueryResult};
 use super::Database;
 
 impl Database {
-    /// Get the on-disk size of the database file in bytes.
+    /// Get the on-disk size of the database in bytes, including its write-ahead log.
     pub fn get_db_file_size(&self) -> u64 {
         let db_path = app_data_dir().join("data.db");
-        std::fs::metadata(db_path).map(|m| m.len()).unwrap_or(0)
+        [db_path.clone(), super::integrity::sidecar_path(&db_path, "-wal")]
+            .iter()
+            .map(|p| std::fs::metadata(p).map(|m| m.len()).unwrap_or(0))
+            .sum()
     }
 
-    /// Get the size of the trash_staging directory in bytes (legacy, returns 0).
+    /// Get the size of the trash_staging directory in bytes.
     pub fn get_trash_staging_size(&self) -> u64 {
-        0
+        crate::staging::staged_size(&self.staging_dir)
     }
 
     /// Get row counts for all tables.
     pub fn get_table_stats(&self) -> Result<Vec<TableStats>> {
         let conn = self.conn.lock().unwrap();
-        let tables = ["activity_log", "file_index", "undo_history", "rule_metadata", "scheduled_deletions"];
+        let tables = ["activity_log", "file_index", "undo_history", "rule_metadata", "scheduled_deletions", "review_queue", "file_hashes"];
         let mut stats = Vec::new();
         for table in &tables {
             let count: i64 = conn.query_row(
@@ -48,7 +51,7 @@ impl Database {
         filters: Option<&std::collections::HashMap<String, Vec<String>>>,
     ) -> Result<TableQueryResult> {
         // Whitelist tables to prevent SQL injection
-        let allowed_tables = ["activity_log", "file_index", "undo_history", "rule_metadata", "scheduled_deletions"];
+        let allowed_tables = ["activity_log", "file_index", "undo_history", "rule_metadata", "scheduled_deletions", "review_queue", "file_hashes"];
         if !allowed_tables.contains(&table) {
             return Err(rusqlite::Error::InvalidParameterName(format!(
                 "Table '{}' not allowed",
@@ -172,7 +175,7 @@ impl Database {
     /// Get distinct values for a column in a table (for filter dropdowns).
     /// Returns up to 200 distinct values.
     pub fn get_column_values(&self, table: &str, column: &str) -> Result<Vec<String>> {
-        let allowed_tables = ["activity_log", "file_index", "undo_history", "rule_metadata", "scheduled_deletions"];
+        let allowed_tables = ["activity_log", "file_index", "undo_history", "rule_metadata", "scheduled_deletions", "review_queue", "file_hashes"];
         if !allowed_tables.contains(&table) {
             return Err(rusqlite::Error::InvalidParameterName(format!(
                 "Table '{}' not allowed", table
@@ -203,7 +206,7 @@ impl Database {
 
     /// Clear all rows from a specific table.
     pub fn clear_table(&self, table: &str) -> Result<u64> {
-        let allowed_tables = ["activity_log", "file_index", "undo_history", "rule_metadata", "scheduled_deletions"];
+        let allowed_tables = ["activity_log", "file_index", "undo_history", "rule_metadata", "scheduled_deletions", "review_queue", "file_hashes"];
         if !allowed_tables.contains(&table) {
             return Err(rusqlite::Error::InvalidParameterName(format!(
                 "Table '{}' not allowed",
@@ -1,9 +1,12 @@
+use std::collections::HashMap;
+
 use rusqlite::{params, Result};
 
-use super::models::UndoEntry;
+use super::models::{UndoBatch, UndoEntry};
 use super::Database;
 
 impl Database {
+    #[allow(clippy::too_many_arguments)]
     pub fn insert_undo(
         &self,
         id: &str,
@@ -12,12 +15,13 @@ impl Database {
         action: &str,
         timestamp: &str,
         expires_at: &str,
+        batch_id: Option<&str>,
     ) -> Result<()> {
         let conn = self.conn.lock().unwrap();
         conn.execute(
-            "INSERT INTO undo_history (id, original_path, current_path, action, timestamp, expires_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-            params![id, original_path, current_path, action, timestamp, expires_at],
+            "INSERT INTO undo_history (id, original_path, current_path, action, timestamp, expires_at, batch_id)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![id, original_path, current_path, action, timestamp, expires_at, batch_id],
         )?;
         Ok(())
     }
@@ -25,7 +29,7 @@ impl Database {
     pub fn get_undo_entries(&self) -> Result<Vec<UndoEntry>> {
         let conn = self.conn.lock().unwrap();
         let mut stmt = conn.prepare(
-            "SELECT id, original_path, current_path, action, timestamp, expires_at, restored
+            "SELECT id, original_path, current_path, action, timestamp, expires_at, restored, batch_id
              FROM undo_history WHERE restored = 0 ORDER BY timestamp DESC",
         )?;
         let rows = stmt.query_map([], |row| {
@@ -37,6 +41,7 @@ impl Database {
                 timestamp: row.get(4)?,
                 expires_at: row.get(5)?,
                 restored: row.get(6)?,
+                batch_id: row.get(7)?,
             })
         })?;
         let mut entries = Vec::new();
@@ -46,6 +51,68 @@ impl Database {
         Ok(entries)
     }
 
+    /// Non-restored entries recorded after `since` (same `YYYY-MM-DD HH:MM:SS` format), newest first.
+    pub fn get_undo_entries_since(&self, since: &str) -> Result<Vec<UndoEntry>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, original_path, current_path, action, timestamp, expires_at, restored, batch_id
+             FROM undo_history WHERE restored = 0 AND timestamp > ?1 ORDER BY timestamp DESC",
+        )?;
+        let rows = stmt.query_map(params![since], |row| {
+            Ok(UndoEntry {
+                id: row.get(0)?,
+                original_path: row.get(1)?,
+                current_path: row.get(2)?,
+                action: row.get(3)?,
+                timestamp: row.get(4)?,
+                expires_at: row.get(5)?,
+                restored: row.get(6)?,
+                batch_id: row.get(7)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// Non-restored entries grouped by the run that created them, newest batch first.
+    pub fn get_undo_batches(&self) -> Result<Vec<UndoBatch>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, action, timestamp, COALESCE(batch_id, id)
+             FROM undo_history WHERE restored = 0 ORDER BY timestamp DESC",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+            ))
+        })?;
+
+        let mut batches: Vec<UndoBatch> = Vec::new();
+        let mut index: HashMap<String, usize> = HashMap::new();
+        for row in rows {
+            let (id, action, timestamp, batch_id) = row?;
+            let i = *index.entry(batch_id.clone()).or_insert_with(|| {
+                batches.push(UndoBatch {
+                    batch_id,
+                    undo_ids: Vec::new(),
+                    count: 0,
+                    timestamp: timestamp.clone(),
+                    actions: Default::default(),
+                });
+                batches.len() - 1
+            });
+            let batch = &mut batches[i];
+            batch.undo_ids.push(id);
+            batch.count += 1;
+            // Rows arrive newest first, so the last one seen is the earliest
+            batch.timestamp = timestamp;
+            *batch.actions.entry(action).or_default() += 1;
+        }
+        Ok(batches)
+    }
+
     pub fn mark_restored(&self, id: &str) -> Result<()> {
         let conn = self.conn.lock().unwrap();
         conn.execute(
@@ -63,3 +130,32 @@ impl Database {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn undo_entries_group_by_batch() {
+        let db = Database::open_in_memory().unwrap();
+        let expires = "2026-01-08 00:00:00";
+        db.insert_undo("a", "/a", Some("/x/a"), "auto_move", "2026-01-01 10:00:00", expires, Some("scan-1")).unwrap();
+        db.insert_undo("b", "/b", None, "auto_delete", "2026-01-01 10:00:01", expires, Some("scan-1")).unwrap();
+        db.insert_undo("c", "/c", Some("/x/c"), "auto_move", "2026-01-01 10:00:02", expires, Some("scan-1")).unwrap();
+        db.insert_undo("d", "/d", Some("/x/d"), "auto_move", "2026-01-02 09:00:00", expires, None).unwrap();
+        db.mark_restored("c").unwrap();
+
+        let batches = db.get_undo_batches().unwrap();
+        assert_eq!(batches.len(), 2);
+        // Unbatched entry is its own group, keyed by its id
+        assert_eq!(batches[0].batch_id, "d");
+        assert_eq!(batches[0].count, 1);
+
+        let scan = &batches[1];
+        assert_eq!(scan.batch_id, "scan-1");
+        assert_eq!(scan.undo_ids, vec!["b", "a"]);
+        assert_eq!(scan.timestamp, "2026-01-01 10:00:00");
+        assert_eq!(scan.actions.get("auto_move"), Some(&1));
+        assert_eq!(scan.actions.get("auto_delete"), Some(&1));
+    }
+}
@@ -0,0 +1,339 @@
+//! Lightweight document metadata readers for `meta:` conditions.
+//!
+//! PDFs yield the uncompressed Info dictionary (`title`, `author`, `subject`,
+//! `keywords`, `creator`, `producer`); JPEGs yield EXIF `make`, `model` and
+//! `datetaken` (`YYYY-MM-DD HH:MM:SS`). Anything unreadable yields no fields.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// How much of each end of a PDF is searched for the Info dictionary.
+const PDF_SCAN_BYTES: u64 = 64 * 1024;
+
+/// How much of a JPEG is read looking for the EXIF segment.
+const JPEG_SCAN_BYTES: u64 = 128 * 1024;
+
+const PDF_INFO_KEYS: &[&str] = &["Title", "Author", "Subject", "Keywords", "Creator", "Producer"];
+
+const EXIF_MAKE: u16 = 0x010F;
+const EXIF_MODEL: u16 = 0x0110;
+const EXIF_DATE_TIME: u16 = 0x0132;
+const EXIF_IFD_POINTER: u16 = 0x8769;
+const EXIF_DATE_TIME_ORIGINAL: u16 = 0x9003;
+
+/// Metadata fields of the file at `path`, keyed by lowercase field name.
+pub fn read_fields(path: &Path) -> HashMap<String, String> {
+    let Ok(mut file) = File::open(path) else {
+        return HashMap::new();
+    };
+    let mut magic = [0u8; 4];
+    if file.read_exact(&mut magic).is_err() {
+        return HashMap::new();
+    }
+    match magic {
+        [b'%', b'P', b'D', b'F'] => pdf_fields(&mut file).unwrap_or_default(),
+        [0xFF, 0xD8, ..] => jpeg_fields(&mut file).unwrap_or_default(),
+        _ => HashMap::new(),
+    }
+}
+
+// ── PDF ─────────────────────────────────────────────────────
+
+/// The Info dictionary usually sits in the trailer at the end, but linearized files
+/// put it up front, so both ends are searched (the last occurrence wins, as with
+/// incremental updates).
+fn pdf_fields(file: &mut File) -> Option<HashMap<String, String>> {
+    let len = file.metadata().ok()?.len();
+    let mut bytes = Vec::new();
+    file.seek(SeekFrom::Start(0)).ok()?;
+    file.by_ref().take(PDF_SCAN_BYTES).read_to_end(&mut bytes).ok()?;
+    if len > PDF_SCAN_BYTES {
+        let tail_start = len.saturating_sub(PDF_SCAN_BYTES).max(PDF_SCAN_BYTES);
+        file.seek(SeekFrom::Start(tail_start)).ok()?;
+        file.take(PDF_SCAN_BYTES).read_to_end(&mut bytes).ok()?;
+    }
+
+    let mut fields = HashMap::new();
+    for key in PDF_INFO_KEYS {
+        let needle = format!("/{}", key);
+        let value = find_all(&bytes, needle.as_bytes())
+            .filter_map(|at| pdf_string_after(&bytes[at + needle.len()..]))
+            .last();
+        if let Some(value) = value {
+            fields.insert(key.to_lowercase(), value);
+        }
+    }
+    Some(fields)
+}
+
+fn find_all<'a>(haystack: &'a [u8], needle: &'a [u8]) -> impl Iterator<Item = usize> + 'a {
+    haystack.windows(needle.len()).enumerate().filter(move |(_, w)| *w == needle).map(|(i, _)| i)
+}
+
+/// Decode the literal `(...)` or hex `<...>` string right after a dictionary key.
+fn pdf_string_after(bytes: &[u8]) -> Option<String> {
+    let start = bytes.iter().position(|b| !b.is_ascii_whitespace())?;
+    let raw = match bytes[start] {
+        b'(' => pdf_literal(&bytes[start + 1..])?,
+        b'<' if bytes.get(start + 1) != Some(&b'<') => {
+            let end = bytes[start + 1..].iter().position(|&b| b == b'>')?;
+            pdf_hex(&bytes[start + 1..start + 1 + end])
+        }
+        _ => return None,
+    };
+    let text = decode_pdf_text(&raw);
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+/// Body of a literal string (after the opening paren): balanced parens and backslash escapes.
+fn pdf_literal(bytes: &[u8]) -> Option<Vec<u8>> {
+    let mut out = Vec::new();
+    let mut depth = 0;
+    let mut iter = bytes.iter().copied();
+    while let Some(b) = iter.next() {
+        match b {
+            b'\\' => match iter.next()? {
+                b'n' => out.push(b'\n'),
+                b'r' => out.push(b'\r'),
+                b't' => out.push(b'\t'),
+                b'\r' | b'\n' => {}
+                escaped => out.push(escaped),
+            },
+            b'(' => {
+                depth += 1;
+                out.push(b);
+            }
+            b')' if depth == 0 => return Some(out),
+            b')' => {
+                depth -= 1;
+                out.push(b);
+            }
+            _ => out.push(b),
+        }
+    }
+    None
+}
+
+fn pdf_hex(bytes: &[u8]) -> Vec<u8> {
+    let digits: Vec<u8> = bytes
+        .iter()
+        .filter_map(|&b| (b as char).to_digit(16).map(|d| d as u8))
+        .collect();
+    digits.chunks(2).map(|pair| pair[0] << 4 | pair.get(1).copied().unwrap_or(0)).collect()
+}
+
+/// PDF text strings are UTF-16BE with a BOM, or a Latin-1-like encoding otherwise.
+fn decode_pdf_text(raw: &[u8]) -> String {
+    match raw {
+        [0xFE, 0xFF, rest @ ..] => {
+            let units: Vec<u16> = rest.chunks_exact(2).map(|c| u16::from_be_bytes([c[0], c[1]])).collect();
+            String::from_utf16_lossy(&units)
+        }
+        _ => raw.iter().map(|&b| b as char).collect(),
+    }
+}
+
+// ── JPEG / EXIF ─────────────────────────────────────────────
+
+/// Walk the JPEG segments up to the start of scan, looking for an EXIF APP1 segment.
+fn jpeg_fields(file: &mut File) -> Option<HashMap<String, String>> {
+    let mut bytes = Vec::new();
+    file.seek(SeekFrom::Start(0)).ok()?;
+    file.take(JPEG_SCAN_BYTES).read_to_end(&mut bytes).ok()?;
+
+    let mut pos = 2;
+    while pos + 4 <= bytes.len() && bytes[pos] == 0xFF {
+        let marker = bytes[pos + 1];
+        let len = u16::from_be_bytes([bytes[pos + 2], bytes[pos + 3]]) as usize;
+        let segment = bytes.get(pos + 4..pos + 2 + len)?;
+        if marker == 0xE1 {
+            if let Some(tiff) = segment.strip_prefix(b"Exif\0\0") {
+                return Some(exif_fields(tiff));
+            }
+        }
+        // Start of scan: image data follows, no more metadata
+        if marker == 0xDA {
+            break;
+        }
+        pos += 2 + len;
+    }
+    None
+}
+
+/// Fields from a TIFF-structured EXIF block.
+fn exif_fields(tiff: &[u8]) -> HashMap<String, String> {
+    let mut fields = HashMap::new();
+    let Some(reader) = TiffReader::new(tiff) else {
+        return fields;
+    };
+    let Some(ifd0) = reader.u32_at(4).map(|o| reader.entries(o as usize)) else {
+        return fields;
+    };
+    let mut date_taken = None;
+    let mut date_modified = None;
+    for &(tag, entry) in &ifd0 {
+        match tag {
+            EXIF_MAKE => insert_ascii(&mut fields, "make", reader.ascii(entry)),
+            EXIF_MODEL => insert_ascii(&mut fields, "model", reader.ascii(entry)),
+            EXIF_DATE_TIME => date_modified = reader.ascii(entry),
+            EXIF_IFD_POINTER => {
+                let sub_ifd = reader.u32_at(entry + 8).map(|o| reader.entries(o as usize)).unwrap_or_default();
+                date_taken = sub_ifd
+                    .iter()
+                    .find(|(tag, _)| *tag == EXIF_DATE_TIME_ORIGINAL)
+                    .and_then(|&(_, e)| reader.ascii(e));
+            }
+            _ => {}
+        }
+    }
+    // EXIF dates are `YYYY:MM:DD HH:MM:SS`; dashes make them compare like typed dates
+    if let Some(date) = date_taken.or(date_modified) {
+        let normalized = match date.split_once(' ') {
+            Some((day, time)) => format!("{} {}", day.replace(':', "-"), time),
+            None => date.replace(':', "-"),
+        };
+        fields.insert("datetaken".to_string(), normalized);
+    }
+    fields
+}
+
+fn insert_ascii(fields: &mut HashMap<String, String>, key: &str, value: Option<String>) {
+    if let Some(value) = value {
+        fields.insert(key.to_string(), value);
+    }
+}
+
+struct TiffReader<'a> {
+    data: &'a [u8],
+    little_endian: bool,
+}
+
+impl<'a> TiffReader<'a> {
+    fn new(data: &'a [u8]) -> Option<Self> {
+        let little_endian = match data.get(..2)? {
+            b"II" => true,
+            b"MM" => false,
+            _ => return None,
+        };
+        Some(Self { data, little_endian })
+    }
+
+    fn u16_at(&self, offset: usize) -> Option<u16> {
+        let b: [u8; 2] = self.data.get(offset..offset + 2)?.try_into().ok()?;
+        Some(if self.little_endian { u16::from_le_bytes(b) } else { u16::from_be_bytes(b) })
+    }
+
+    fn u32_at(&self, offset: usize) -> Option<u32> {
+        let b: [u8; 4] = self.data.get(offset..offset + 4)?.try_into().ok()?;
+        Some(if self.little_endian { u32::from_le_bytes(b) } else { u32::from_be_bytes(b) })
+    }
+
+    /// `(tag, entry offset)` for every entry of the IFD at `offset`.
+    fn entries(&self, offset: usize) -> Vec<(u16, usize)> {
+        let count = self.u16_at(offset).unwrap_or(0) as usize;
+        (0..count)
+            .map(|i| offset + 2 + i * 12)
+            .filter_map(|entry| self.u16_at(entry).map(|tag| (tag, entry)))
+            .collect()
+    }
+
+    /// An ASCII (type 2) value, trimmed of its NUL terminator and padding.
+    fn ascii(&self, entry: usize) -> Option<String> {
+        if self.u16_at(entry + 2)? != 2 {
+            return None;
+        }
+        let count = self.u32_at(entry + 4)? as usize;
+        let start = if count <= 4 { entry + 8 } else { self.u32_at(entry + 8)? as usize };
+        let raw = self.data.get(start..start + count)?;
+        let text = String::from_utf8_lossy(raw);
+        let text = text.trim_end_matches('\0').trim();
+        (!text.is_empty()).then(|| text.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    /// A one-page PDF whose Info dictionary carries `title` and an escaped author.
+    fn pdf_with_title(title: &str) -> Vec<u8> {
+        format!(
+            "%PDF-1.4\n1 0 obj\n<< /Type /Catalog /Pages 2 0 R >>\nendobj\n\
+             2 0 obj\n<< /Type /Pages /Kids [] /Count 0 >>\nendobj\n\
+             3 0 obj\n<< /Title ({}) /Author (ACME \\(Legal\\)) >>\nendobj\n\
+             trailer\n<< /Root 1 0 R /Info 3 0 R >>\n%%EOF\n",
+            title
+        )
+        .into_bytes()
+    }
+
+    /// A minimal big-endian JPEG header with Make in IFD0 and DateTimeOriginal in the EXIF IFD.
+    fn jpeg_with_date_taken(date: &str) -> Vec<u8> {
+        let date = format!("{}\0", date);
+        let make = b"Acme\0";
+        // TIFF header (8) + IFD0 with 2 entries (2 + 24 + 4) + EXIF IFD with 1 entry (2 + 12 + 4)
+        let ifd0 = 8u32;
+        let exif_ifd = ifd0 + 30;
+        let date_at = exif_ifd + 18;
+        let mut tiff = b"MM\0\x2a".to_vec();
+        tiff.extend(ifd0.to_be_bytes());
+        tiff.extend(2u16.to_be_bytes());
+        tiff.extend(EXIF_MAKE.to_be_bytes());
+        tiff.extend(2u16.to_be_bytes());
+        tiff.extend((make.len() as u32).to_be_bytes());
+        tiff.extend((date_at + date.len() as u32).to_be_bytes());
+        tiff.extend(EXIF_IFD_POINTER.to_be_bytes());
+        tiff.extend(4u16.to_be_bytes());
+        tiff.extend(1u32.to_be_bytes());
+        tiff.extend(exif_ifd.to_be_bytes());
+        tiff.extend(0u32.to_be_bytes());
+        tiff.extend(1u16.to_be_bytes());
+        tiff.extend(EXIF_DATE_TIME_ORIGINAL.to_be_bytes());
+        tiff.extend(2u16.to_be_bytes());
+        tiff.extend((date.len() as u32).to_be_bytes());
+        tiff.extend(date_at.to_be_bytes());
+        tiff.extend(0u32.to_be_bytes());
+        tiff.extend(date.as_bytes());
+        tiff.extend(make);
+
+        let mut app1 = b"Exif\0\0".to_vec();
+        app1.extend(tiff);
+        let mut jpeg = vec![0xFF, 0xD8, 0xFF, 0xE1];
+        jpeg.extend(((app1.len() + 2) as u16).to_be_bytes());
+        jpeg.extend(app1);
+        jpeg.extend([0xFF, 0xDA, 0x00, 0x02, 0xFF, 0xD9]);
+        jpeg
+    }
+
+    #[test]
+    fn reads_pdf_info_dictionary() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("report.pdf");
+        fs::write(&path, pdf_with_title("Quarterly Report")).unwrap();
+
+        let fields = read_fields(&path);
+        assert_eq!(fields.get("title").map(String::as_str), Some("Quarterly Report"));
+        assert_eq!(fields.get("author").map(String::as_str), Some("ACME (Legal)"));
+        assert_eq!(decode_pdf_text(&pdf_hex(b"FEFF00480069")), "Hi");
+    }
+
+    #[test]
+    fn reads_exif_date_taken_and_ignores_other_files() {
+        let tmp = tempfile::tempdir().unwrap();
+        let photo = tmp.path().join("IMG_0001.jpg");
+        fs::write(&photo, jpeg_with_date_taken("2023:06:01 14:30:00")).unwrap();
+
+        let fields = read_fields(&photo);
+        assert_eq!(fields.get("datetaken").map(String::as_str), Some("2023-06-01 14:30:00"));
+        assert_eq!(fields.get("make").map(String::as_str), Some("Acme"));
+
+        let text = tmp.path().join("notes.txt");
+        fs::write(&text, "%PD").unwrap();
+        assert!(read_fields(&text).is_empty());
+        assert!(read_fields(&tmp.path().join("missing.jpg")).is_empty());
+    }
+}
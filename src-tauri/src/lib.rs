@@ -1,11 +1,20 @@
+mod archive;
+mod backoff;
+mod cli;
 mod commands;
 mod condition;
 mod config;
 #[cfg(windows)]
 mod context_menu;
 mod db;
+mod doc_meta;
+mod log_archive;
+mod portable;
 mod rules;
 mod scheduler;
+mod staging;
+#[cfg(test)]
+mod test_support;
 mod watcher;
 
 use std::sync::{Arc, Mutex};
@@ -15,7 +24,15 @@ use tauri::{Emitter, Manager};
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    env_logger::init();
+    // Logs go to stderr so headless `--json` output on stdout stays parseable
+    env_logger::Builder::from_default_env()
+        .target(env_logger::Target::Stderr)
+        .init();
+
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(code) = cli::run_headless(&args) {
+        std::process::exit(code);
+    }
 
     let app_config = config::load_config();
     let database =
@@ -38,6 +55,8 @@ pub fn run() {
         db: db_arc.clone(),
         watcher: Arc::new(Mutex::new(file_watcher)),
         scan_running: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+        scan_interval_override: Arc::new(Mutex::new(None)),
+        scheduler_clock: Arc::new(Mutex::new(scheduler::SchedulerClock::default())),
     };
 
     let tray_config = config_arc.clone();
@@ -45,10 +64,13 @@ pub fn run() {
     let single_instance_config = config_arc.clone();
     let scheduler_config = config_arc.clone();
     let scheduler_db = db_arc.clone();
+    let scheduler_override = state.scan_interval_override.clone();
+    let scheduler_clock = state.scheduler_clock.clone();
 
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_notification::init())
         .plugin(tauri_plugin_updater::Builder::new().build())
         .plugin(tauri_plugin_autostart::init(
             tauri_plugin_autostart::MacosLauncher::LaunchAgent,
@@ -70,29 +92,13 @@ pub fn run() {
                     let app_handle = app.clone();
 
                     std::thread::spawn(move || {
-                        let path = std::path::PathBuf::from(&folder_path);
-                        if path.exists() {
-                            let mut config = cfg.lock().unwrap();
-                            let already_exists = config.folders.iter().any(|f| f.path == path);
-                            let folder_id = if already_exists {
-                                config.folders.iter().find(|f| f.path == path).unwrap().id.clone()
-                            } else {
-                                let folder = config::WatchedFolder {
-                                    id: uuid::Uuid::new_v4().to_string(),
-                                    path,
-                                    enabled: true,
-                                    rules: Vec::new(),
-                                    whitelist: Vec::new(),
-                                    watch_subdirectories: false,
-                                };
-                                let id = folder.id.clone();
-                                config.folders.push(folder);
-                                let _ = config::save_config(&config);
-                                id
-                            };
-                            drop(config);
-                            std::thread::sleep(std::time::Duration::from_millis(500));
-                            let _ = app_handle.emit("navigate-to-folder", &folder_id);
+                        let added = commands::watch_folder_from_cli(&mut cfg.lock().unwrap(), &folder_path);
+                        match added {
+                            Ok(folder_id) => {
+                                std::thread::sleep(std::time::Duration::from_millis(500));
+                                let _ = app_handle.emit("navigate-to-folder", &folder_id);
+                            }
+                            Err(e) => log::warn!("Not watching {}: {}", folder_path, e),
                         }
                     });
                 }
@@ -104,113 +110,227 @@ pub fn run() {
             commands::save_config_cmd,
             commands::export_config,
             commands::import_config,
+            commands::merge_config,
+            commands::export_config_portable,
+            commands::import_config_portable,
             commands::get_config_path,
             commands::get_watched_folders,
+            commands::get_folders_overview,
             commands::add_watched_folder,
+            commands::add_watched_folders,
+            commands::check_folder_overlap,
             commands::remove_watched_folder,
+            commands::get_archived_folders,
+            commands::restore_watched_folder,
+            commands::purge_archived_folder,
             commands::toggle_watched_folder,
             commands::toggle_watch_subdirectories,
+            commands::set_auto_watch_new_subdirs,
+            commands::merge_folders,
+            commands::reconcile_folders,
+            commands::pause_folder,
+            commands::resume_folder,
+            commands::get_folder_pause_status,
+            commands::pause_automation,
+            commands::resume_automation,
             commands::get_folder_whitelist,
             commands::set_folder_whitelist,
+            commands::set_folder_default_action,
             commands::get_rules,
             commands::add_rule,
             commands::update_rule,
+            commands::validate_move_rule,
+            commands::find_shadowed_rules,
+            commands::get_rules_by_tag,
+            commands::set_rule_tags,
             commands::delete_rule,
             commands::get_rule_metadata,
             commands::reorder_rules,
+            commands::move_rule,
             commands::copy_rules_to_folder,
+            commands::export_folder_rules,
+            commands::import_folder_rules,
+            commands::find_duplicate_rules,
+            commands::dedupe_rules,
+            commands::get_rule_presets,
+            commands::save_folder_as_preset,
+            commands::delete_rule_preset,
             commands::move_rule_to_folder,
             commands::get_activity_log,
+            commands::get_activity_log_page,
+            commands::search_activity_log,
+            commands::export_activity_log_csv,
+            commands::list_log_archives,
+            commands::read_log_archive,
             commands::get_pending_actions,
             commands::get_undo_entries,
             commands::undo_action,
+            commands::undo_batch,
+            commands::undo_all_since,
+            commands::get_undo_batches,
+            commands::get_review_queue,
+            commands::resolve_review_item,
             commands::get_scheduled_deletions,
+            commands::get_scheduled_deletions_paged,
             commands::cancel_scheduled_deletion,
             commands::run_deletions,
             commands::delete_scheduled_now,
+            commands::approve_deletion,
+            commands::approve_all_due,
+            commands::get_next_deletion_run,
+            commands::archive_scheduled_deletions,
             commands::get_rule_execution_stats,
+            commands::get_activity_timeseries,
+            commands::get_activity_summary,
             commands::scan_now,
             commands::scan_folder,
+            commands::scan_folder_now,
+            commands::scan_dry_run,
+            commands::preview_rule_on_folder,
+            commands::profile_folder_scan,
+            commands::get_folder_cleanliness,
             commands::ensure_dir,
+            commands::probe_destination,
             commands::open_in_explorer,
+            commands::reveal_in_explorer,
             commands::restart_watcher,
             commands::stop_watcher,
             commands::get_watcher_status,
+            commands::get_watcher_details,
+            commands::get_rule_backoffs,
+            commands::set_temporary_scan_interval,
             commands::parse_condition_text,
             commands::condition_to_text,
+            commands::condition_to_ast_string,
+            commands::tokenize_condition,
+            commands::run_condition_golden_tests,
             commands::validate_condition_text,
             commands::test_condition,
+            commands::test_rule,
             commands::get_db_stats,
             commands::query_db_table,
             commands::get_column_values,
             commands::clear_db_table,
             commands::enforce_storage_limit,
             commands::get_db_path,
+            commands::check_db_integrity,
         ])
         .setup(move |app| {
+            // ── Desktop notifications for Notify rules (fired from the watcher thread) ──
+            {
+                use tauri_plugin_notification::NotificationExt;
+                let app_handle = app.handle().clone();
+                watcher::set_notifier(move |title, body| {
+                    if let Err(e) = app_handle.notification().builder().title(title).body(body).show() {
+                        log::warn!("Failed to show notification: {}", e);
+                    }
+                });
+            }
+
+            // ── Raw watcher events for debugging (only sent while debug_watcher_events is on) ──
+            {
+                let app_handle = app.handle().clone();
+                watcher::set_raw_event_sink(move |event| {
+                    let _ = app_handle.emit("raw-fs-event", event);
+                });
+            }
+
+            // ── Folder list changes made by the watcher (auto-watched subfolders) ──
+            {
+                let app_handle = app.handle().clone();
+                watcher::set_folders_changed_sink(move || {
+                    let _ = app_handle.emit("folders-changed", ());
+                });
+            }
+
             // ── Start periodic scheduler (maintenance + process due actions + daily scan) ──
             {
                 let scheduler_config = scheduler_config.clone();
                 let scheduler_db = scheduler_db.clone();
+                let scheduler_override = scheduler_override.clone();
+                let scheduler_clock = scheduler_clock.clone();
                 let app_handle = app.handle().clone();
                 std::thread::spawn(move || {
-                    let mut last_full_scan_day: Option<u32> = None;
                     loop {
-                        let interval = {
-                            let cfg = scheduler_config.lock().unwrap();
-                            // Enforce minimum 1 minute interval
-                            cfg.settings.scan_interval_minutes.max(1)
-                        };
-
-                        // Track wall-clock time to detect system sleep/standby.
-                        // If thread::sleep(5 min) actually takes >> 5 min, the system
-                        // was likely asleep and we should process immediately.
-                        let sleep_duration = std::time::Duration::from_secs((interval as u64) * 60);
+                        // Wake every minute so due actions and the daily scan aren't held
+                        // back by a long scan interval. Track wall-clock time to detect
+                        // system sleep/standby: if the short sleep actually took much
+                        // longer, the system was likely asleep and we catch up immediately.
                         let before_sleep = std::time::Instant::now();
-                        std::thread::sleep(sleep_duration);
+                        std::thread::sleep(scheduler::SCHEDULER_TICK);
                         let actual_elapsed = before_sleep.elapsed();
 
-                        let system_was_sleeping = actual_elapsed > sleep_duration + std::time::Duration::from_secs(60);
+                        let system_was_sleeping =
+                            actual_elapsed > scheduler::SCHEDULER_TICK + std::time::Duration::from_secs(60);
                         if system_was_sleeping {
                             log::info!(
                                 "System appears to have been sleeping (slept {}s instead of {}s), processing immediately",
-                                actual_elapsed.as_secs(), sleep_duration.as_secs()
+                                actual_elapsed.as_secs(), scheduler::SCHEDULER_TICK.as_secs()
                             );
                         }
 
+                        // Configured interval (min 1 minute), unless a temporary override is active
+                        let (interval, override_active) = {
+                            let cfg = scheduler_config.lock().unwrap();
+                            scheduler::effective_scan_interval(
+                                cfg.settings.scan_interval_minutes,
+                                &scheduler_override,
+                                std::time::Instant::now(),
+                            )
+                        };
+                        let now = chrono::Local::now();
+                        let today = now.format("%j").to_string().parse::<u32>().unwrap_or(0);
+                        let plan = {
+                            let mut clock = scheduler_clock.lock().unwrap();
+                            clock.mark_tick(chrono::Utc::now());
+                            clock.tick(std::time::Instant::now(), today, interval, override_active, system_was_sleeping)
+                        };
+
                         // Run maintenance (log pruning, undo cleanup, storage enforcement)
-                        {
+                        // on the scan interval
+                        if plan.run_cycle {
                             let cfg = scheduler_config.lock().unwrap();
                             scheduler::run_scheduled_cleanup(&cfg, &scheduler_db);
                         }
 
-                        // Process due scheduled actions (deletions & moves) on EVERY cycle.
-                        // Each entry's `due_at` timestamp gates when it actually executes,
-                        // so running this frequently is safe and ensures timely processing.
-                        {
-                            let cfg = scheduler_config.lock().unwrap().clone();
-                            let processed = scheduler::process_due_deletions_with_config(&scheduler_db, Some(&cfg));
-                            if processed > 0 {
-                                log::info!("Processed {} due scheduled actions", processed);
-                                let _ = app_handle.emit("dashboard-data-changed", ());
-                            }
+                        // Globally paused: skip processing. The missed daily scan runs
+                        // on the first tick after the pause ends.
+                        if scheduler_config.lock().unwrap().settings.is_paused_at(chrono::Utc::now()) {
+                            continue;
+                        }
+
+                        // Process due scheduled actions (deletions & moves) on every tick;
+                        // with a `deletion_cron`, deletions only on the ticks its fire times
+                        // allow. Each entry's `due_at` timestamp gates when it actually
+                        // executes, so running this frequently is safe and ensures timely processing.
+                        let cfg = scheduler_config.lock().unwrap().clone();
+                        let deletions_due = scheduler_clock
+                            .lock()
+                            .unwrap()
+                            .deletions_due(cfg.settings.deletion_cron.as_deref(), chrono::Utc::now());
+                        let run = scheduler::process_due_deletions_with_config(&scheduler_db, Some(&cfg), deletions_due);
+                        if run.processed > 0 {
+                            log::info!("Processed {} due scheduled actions", run.processed);
+                            let _ = app_handle.emit("dashboard-data-changed", ());
+                        }
+                        if run.awaiting_approval > 0 {
+                            let _ = app_handle.emit("deletions-awaiting-approval", run.awaiting_approval);
+                            let _ = app_handle.emit("dashboard-data-changed", ());
                         }
 
                         // Daily full scan at midnight — catches anything the watcher missed
                         // (e.g. files added during sleep, network drives reconnecting, etc.)
-                        // Also triggers immediately after system wake from sleep.
-                        let now = chrono::Local::now();
-                        let today = now.format("%j").to_string().parse::<u32>().unwrap_or(0);
-                        let should_daily_scan = last_full_scan_day != Some(today) || system_was_sleeping;
-                        if should_daily_scan {
-                            log::info!("Running daily full scan (day {})", today);
+                        // Also triggers immediately after system wake from sleep, and on every
+                        // cycle while a temporary (catch-up) interval override is active.
+                        if plan.run_daily_scan {
+                            log::info!("Running full scan (day {})", today);
                             let cfg = scheduler_config.lock().unwrap().clone();
                             let scanned = scheduler::scan_existing_files(&cfg, &scheduler_db);
                             if scanned > 0 {
                                 log::info!("Daily scan: {} files matched rules", scanned);
                             }
                             let _ = app_handle.emit("dashboard-data-changed", ());
-                            last_full_scan_day = Some(today);
+                            scheduler_clock.lock().unwrap().full_scan_done(today);
                         }
                     }
                 });
@@ -323,31 +443,14 @@ pub fn run() {
 
                     // Add the folder (if not already watched) and emit event after a short delay
                     std::thread::spawn(move || {
-                        let path = std::path::PathBuf::from(&folder_path);
-                        if path.exists() {
-                            let mut config = cfg.lock().unwrap();
-                            let already_exists = config.folders.iter().any(|f| f.path == path);
-                            let folder_id = if already_exists {
-                                config.folders.iter().find(|f| f.path == path).unwrap().id.clone()
-                            } else {
-                                let folder = config::WatchedFolder {
-                                    id: uuid::Uuid::new_v4().to_string(),
-                                    path,
-                                    enabled: true,
-                                    rules: Vec::new(),
-                                    whitelist: Vec::new(),
-                                    watch_subdirectories: false,
-                                };
-                                let id = folder.id.clone();
-                                config.folders.push(folder);
-                                let _ = config::save_config(&config);
-                                id
-                            };
-                            drop(config);
-
-                            // Wait for frontend to be ready, then emit navigation event
-                            std::thread::sleep(std::time::Duration::from_millis(1500));
-                            let _ = app_handle.emit("navigate-to-folder", &folder_id);
+                        let added = commands::watch_folder_from_cli(&mut cfg.lock().unwrap(), &folder_path);
+                        match added {
+                            Ok(folder_id) => {
+                                // Wait for frontend to be ready, then emit navigation event
+                                std::thread::sleep(std::time::Duration::from_millis(1500));
+                                let _ = app_handle.emit("navigate-to-folder", &folder_id);
+                            }
+                            Err(e) => log::warn!("Not watching {}: {}", folder_path, e),
                         }
                     });
                 }
@@ -0,0 +1,165 @@
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use flate2::read::MultiGzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+
+use crate::config::app_data_dir;
+use crate::db::{ActivityLogEntry, Database};
+
+const ARCHIVE_SUFFIX: &str = ".ndjson.gz";
+
+/// One archive file as shown in the UI.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct LogArchiveInfo {
+    /// File name, e.g. `2026-03.ndjson.gz`
+    pub name: String,
+    pub size_bytes: u64,
+}
+
+/// Where pruned activity rows are kept: one `YYYY-MM.ndjson.gz` per month.
+pub fn archive_dir() -> PathBuf {
+    app_data_dir().join("log_archives")
+}
+
+/// Archive then delete activity rows older than `before`. Rows are only deleted
+/// once they've been written, so a failed archive leaves the DB untouched.
+/// Returns the number of rows pruned.
+pub fn archive_and_prune_logs(db: &Database, dir: &Path, before: &str) -> Result<usize, String> {
+    let entries = db.get_logs_before(before).map_err(|e| e.to_string())?;
+    if entries.is_empty() {
+        return Ok(0);
+    }
+    append_to_archives(dir, &entries)?;
+    db.prune_old_logs(before).map_err(|e| e.to_string())
+}
+
+/// Append entries to their month's archive (by timestamp prefix `YYYY-MM`), one JSON
+/// object per line. Each call writes a new gzip member, so existing data is never rewritten.
+fn append_to_archives(dir: &Path, entries: &[ActivityLogEntry]) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create log archive folder: {}", e))?;
+
+    let mut by_month: BTreeMap<&str, Vec<&ActivityLogEntry>> = BTreeMap::new();
+    for entry in entries {
+        let month = entry.timestamp.get(..7).unwrap_or("unknown");
+        by_month.entry(month).or_default().push(entry);
+    }
+
+    for (month, rows) in by_month {
+        let path = dir.join(format!("{}{}", month, ARCHIVE_SUFFIX));
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+        let mut encoder = GzEncoder::new(file, Compression::default());
+        for row in rows {
+            let line = serde_json::to_string(row).map_err(|e| e.to_string())?;
+            writeln!(encoder, "{}", line).map_err(|e| e.to_string())?;
+        }
+        encoder
+            .finish()
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    }
+    Ok(())
+}
+
+/// Archive files in `dir`, newest month first.
+pub fn list_archives(dir: &Path) -> Result<Vec<LogArchiveInfo>, String> {
+    let read = match fs::read_dir(dir) {
+        Ok(read) => read,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.to_string()),
+    };
+    let mut archives: Vec<LogArchiveInfo> = read
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let name = e.file_name().to_string_lossy().to_string();
+            if !name.ends_with(ARCHIVE_SUFFIX) {
+                return None;
+            }
+            let size_bytes = e.metadata().map(|m| m.len()).unwrap_or(0);
+            Some(LogArchiveInfo { name, size_bytes })
+        })
+        .collect();
+    archives.sort_by(|a, b| b.name.cmp(&a.name));
+    Ok(archives)
+}
+
+/// Read every entry from one archive. `name` must be a bare archive file name.
+pub fn read_archive(dir: &Path, name: &str) -> Result<Vec<ActivityLogEntry>, String> {
+    if !name.ends_with(ARCHIVE_SUFFIX) || name.contains(['/', '\\']) || name.contains("..") {
+        return Err(format!("Not a log archive: {}", name));
+    }
+    let path = dir.join(name);
+    let file = fs::File::open(&path).map_err(|e| format!("Failed to open {}: {}", name, e))?;
+    let reader = BufReader::new(MultiGzDecoder::new(file));
+    let mut entries = Vec::new();
+    for line in reader.lines() {
+        let line = line.map_err(|e| format!("Failed to read {}: {}", name, e))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        entries.push(serde_json::from_str(&line).map_err(|e| e.to_string())?);
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log(db: &Database, id: &str, timestamp: &str) {
+        db.insert_activity(id, "/data/a.pdf", "a.pdf", "move", Some("PDFs"), None, timestamp, "success", None)
+            .unwrap();
+    }
+
+    #[test]
+    fn pruning_writes_rows_to_monthly_gzip() {
+        let tmp = tempfile::tempdir().unwrap();
+        let db = Database::open_in_memory().unwrap();
+        log(&db, "old-1", "2026-01-05 10:00:00");
+        log(&db, "old-2", "2026-02-10 10:00:00");
+        log(&db, "new", "2026-06-01 10:00:00");
+
+        let pruned = archive_and_prune_logs(&db, tmp.path(), "2026-03-01 00:00:00").unwrap();
+        assert_eq!(pruned, 2);
+
+        let names: Vec<String> = list_archives(tmp.path()).unwrap().into_iter().map(|a| a.name).collect();
+        assert_eq!(names, vec!["2026-02.ndjson.gz", "2026-01.ndjson.gz"]);
+        let jan = read_archive(tmp.path(), "2026-01.ndjson.gz").unwrap();
+        assert_eq!(jan.len(), 1);
+        assert_eq!(jan[0].id, "old-1");
+
+        let remaining = db.get_activity_log(10, 0, None).unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].id, "new");
+    }
+
+    #[test]
+    fn later_prunes_append_to_existing_archive() {
+        let tmp = tempfile::tempdir().unwrap();
+        let db = Database::open_in_memory().unwrap();
+        log(&db, "a", "2026-01-05 10:00:00");
+        archive_and_prune_logs(&db, tmp.path(), "2026-01-10 00:00:00").unwrap();
+        log(&db, "b", "2026-01-20 10:00:00");
+        archive_and_prune_logs(&db, tmp.path(), "2026-02-01 00:00:00").unwrap();
+
+        let ids: Vec<String> = read_archive(tmp.path(), "2026-01.ndjson.gz")
+            .unwrap()
+            .into_iter()
+            .map(|e| e.id)
+            .collect();
+        assert_eq!(ids, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn read_archive_rejects_paths() {
+        let tmp = tempfile::tempdir().unwrap();
+        assert!(read_archive(tmp.path(), "../config.json").is_err());
+        assert!(read_archive(tmp.path(), "../x.ndjson.gz").is_err());
+    }
+}
@@ -0,0 +1,140 @@
+use std::path::{Path, PathBuf};
+
+use crate::config::{Action, AppConfig, WatchedFolder};
+
+/// Stands for the base directory in a portable config, e.g. `{BASE}/Photos`.
+pub const BASE_PLACEHOLDER: &str = "{BASE}";
+
+/// Rewrite paths under `base` as `{BASE}/…` (always with `/`, so the file works on any OS).
+/// Returns how many paths were templated; paths outside `base` are left as they are.
+pub fn make_portable(config: &mut AppConfig, base: &Path) -> u32 {
+    let mut templated = 0;
+    for_each_path(config, &mut |path| {
+        if let Ok(rest) = path.strip_prefix(base) {
+            let mut portable = BASE_PLACEHOLDER.to_string();
+            for part in rest.components() {
+                portable.push('/');
+                portable.push_str(&part.as_os_str().to_string_lossy());
+            }
+            *path = PathBuf::from(portable);
+            templated += 1;
+        }
+    });
+    templated
+}
+
+/// Resolve `{BASE}/…` paths of a portable config against this machine's `base`.
+pub fn resolve_portable(config: &mut AppConfig, base: &Path) {
+    for_each_path(config, &mut |path| {
+        let Some(rest) = portable_rest(path) else {
+            return;
+        };
+        let resolved = rest.split('/').filter(|part| !part.is_empty()).fold(base.to_path_buf(), |p, part| p.join(part));
+        *path = resolved;
+    });
+}
+
+/// Whether any path in the config is still a `{BASE}/…` template, i.e. it came from a
+/// portable export and hasn't been resolved against a base directory.
+pub fn has_unresolved_paths(config: &AppConfig) -> bool {
+    let mut found = false;
+    for_each_path(&mut config.clone(), &mut |path| found |= portable_rest(path).is_some());
+    found
+}
+
+/// The part of a `{BASE}/…` path after the placeholder, or None for any other path.
+fn portable_rest(path: &Path) -> Option<&str> {
+    let rest = path.to_str()?.strip_prefix(BASE_PLACEHOLDER)?;
+    (rest.is_empty() || rest.starts_with('/')).then_some(rest)
+}
+
+/// Visit every machine-specific path in the config: watched (and archived) folder paths,
+/// `settings.default_sort_root`, and the destinations of rules and default actions in
+/// folders and presets — `Move` (including quota `destinations`), `MoveByCategory`,
+/// `Archive`, `Link`, and a `Review` action's `on_approve`.
+fn for_each_path(config: &mut AppConfig, visit: &mut impl FnMut(&mut PathBuf)) {
+    visit(&mut config.settings.default_sort_root);
+    for folder in config.folders.iter_mut().chain(config.archived_folders.iter_mut()) {
+        folder_paths(folder, visit);
+    }
+    for rule in config.presets.iter_mut().flat_map(|p| p.rules.iter_mut()) {
+        action_paths(&mut rule.action, visit);
+    }
+}
+
+fn folder_paths(folder: &mut WatchedFolder, visit: &mut impl FnMut(&mut PathBuf)) {
+    visit(&mut folder.path);
+    for rule in &mut folder.rules {
+        action_paths(&mut rule.action, visit);
+    }
+    if let Some(action) = &mut folder.default_action {
+        action_paths(action, visit);
+    }
+}
+
+fn action_paths(action: &mut Action, visit: &mut impl FnMut(&mut PathBuf)) {
+    match action {
+        Action::Move { destination, destinations, .. } => {
+            visit(destination);
+            for quota in destinations {
+                visit(&mut quota.path);
+            }
+        }
+        Action::MoveByCategory { root } => visit(root),
+        Action::Archive { destination, .. } | Action::Link { destination, .. } => visit(destination),
+        Action::Review { on_approve: Some(inner), .. } => action_paths(inner, visit),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{folder, move_to, rule};
+
+    #[test]
+    fn paths_under_the_base_round_trip_to_another_machine() {
+        let base = Path::new("/home/me/sorted");
+        let mut config = AppConfig {
+            folders: vec![folder(
+                &base.join("inbox"),
+                vec![
+                    rule("Photos", "*.jpg", move_to(&base.join("Photos").join("{year}"))),
+                    rule("Elsewhere", "*.iso", move_to(Path::new("/mnt/isos"))),
+                ],
+            )],
+            ..Default::default()
+        };
+        config.settings.default_sort_root = base.to_path_buf();
+
+        assert_eq!(make_portable(&mut config, base), 3);
+        assert_eq!(config.settings.default_sort_root, PathBuf::from("{BASE}"));
+        assert_eq!(config.folders[0].path, PathBuf::from("{BASE}/inbox"));
+        let Action::Move { destination, .. } = &config.folders[0].rules[0].action else { panic!() };
+        assert_eq!(destination, &PathBuf::from("{BASE}/Photos/{year}"));
+
+        let new_base = Path::new("/Users/you/Sorted");
+        resolve_portable(&mut config, new_base);
+        assert_eq!(config.settings.default_sort_root, new_base);
+        assert_eq!(config.folders[0].path, new_base.join("inbox"));
+        let Action::Move { destination, .. } = &config.folders[0].rules[0].action else { panic!() };
+        assert_eq!(destination, &new_base.join("Photos").join("{year}"));
+        let Action::Move { destination, .. } = &config.folders[0].rules[1].action else { panic!() };
+        assert_eq!(destination, Path::new("/mnt/isos"), "paths outside the base are untouched");
+        assert!(!has_unresolved_paths(&config));
+    }
+
+    #[test]
+    fn templated_paths_are_detected_until_resolved() {
+        let mut config = AppConfig {
+            folders: vec![folder(Path::new("/inbox"), vec![rule("Photos", "*.jpg", move_to(Path::new("{BASE}/Photos")))])],
+            ..Default::default()
+        };
+        assert!(has_unresolved_paths(&config));
+        resolve_portable(&mut config, Path::new("/home/me"));
+        assert!(!has_unresolved_paths(&config));
+
+        config.folders[0].path = PathBuf::from("{BASE}Elsewhere");
+        assert!(!has_unresolved_paths(&config), "only the placeholder as a whole component counts");
+    }
+}
@@ -1,11 +1,18 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant, SystemTime};
 
-use chrono::Utc;
+use chrono::{DateTime, Local, Utc};
+use filetime::FileTime;
+use sha2::{Digest, Sha256};
+use regex::Regex;
 use uuid::Uuid;
 
-use crate::condition;
-use crate::config::{Action, Rule, WatchedFolder};
+use crate::condition::{self, EvalOptions, FileContext, SizeRank};
+use crate::config::{has_ignored_extension, Action, AppSettings, ConflictPolicy, DeletionMode, QuotaDestination, Rule, WatchedFolder};
 use crate::db::Database;
 
 /// Translate a raw `std::io::Error` into a short, user-friendly reason.
@@ -71,8 +78,49 @@ pub struct RuleActionResult {
     pub file_name: String,
     pub action: String,
     pub rule_name: String,
+    /// Id of the rule that acted; empty when only the rule name is known
+    pub rule_id: String,
     pub success: bool,
     pub details: Option<String>,
+    /// The destination already had a file with this name, so a ` (n)` suffix was appended.
+    pub renamed_collision: bool,
+    /// The destination drive had less free space than the rule's `min_dest_free_bytes`,
+    /// so the file was left in place.
+    pub skipped_low_space: bool,
+    /// The destination already had this name and the rule's conflict policy kept the
+    /// existing file, so the source was left in place.
+    pub skipped_conflict: bool,
+    /// Where the file (or its copy) ended up, when the move/copy succeeded
+    pub dest_path: Option<PathBuf>,
+}
+
+impl RuleActionResult {
+    /// Size of the file at its destination, for the activity log. None when nothing was
+    /// moved or copied (or it's no longer there).
+    pub fn size_bytes(&self) -> Option<i64> {
+        let meta = fs::metadata(self.dest_path.as_ref()?).ok()?;
+        meta.is_file().then_some(meta.len() as i64)
+    }
+
+    /// The rule really acted on the file (not an error or a deliberate skip).
+    pub fn fired(&self) -> bool {
+        self.success && !self.skipped_low_space && !self.skipped_conflict && !self.rule_id.is_empty()
+    }
+
+    /// Value for the activity log `result` column.
+    pub fn result_label(&self) -> &'static str {
+        if self.skipped_low_space {
+            "skipped_low_space"
+        } else if self.skipped_conflict {
+            "skipped_conflict"
+        } else if !self.success {
+            "error"
+        } else if self.renamed_collision {
+            "renamed_collision"
+        } else {
+            "success"
+        }
+    }
 }
 
 /// Result of evaluating a file against folder rules.
@@ -94,11 +142,10 @@ pub enum EvalOutcome {
 
 /// Check whether a file should be skipped by whitelist patterns.
 ///
-/// Matching is done against:
-/// - the bare filename (legacy behavior)
-/// - the normalized relative path, when provided
-/// - the relative path prefixed with `/` so patterns like `*/working*`
-///   also match top-level paths like `working/file.txt`
+/// Patterns without a `/` match the bare filename only (`*.tmp`). Patterns with a `/`
+/// match the normalized path relative to the folder root instead (`node_modules/*`),
+/// and also that path prefixed with `/` so patterns like `*/working*` match
+/// top-level paths like `working/file.txt`.
 pub fn is_whitelisted_with_relative_path(
     file_name: &str,
     relative_path: Option<&str>,
@@ -110,13 +157,13 @@ pub fn is_whitelisted_with_relative_path(
         .map(|p| p.trim_start_matches("./").trim_start_matches('/').to_lowercase());
 
     for pattern in whitelist {
-        let pattern_lower = pattern.to_lowercase();
-
-        if glob_match(&pattern_lower, &name_lower) {
-            return true;
-        }
+        let pattern_lower = pattern.replace('\\', "/").to_lowercase();
 
-        if let Some(rel) = relative_lower.as_deref() {
+        if !pattern_lower.contains('/') {
+            if glob_match(&pattern_lower, &name_lower) {
+                return true;
+            }
+        } else if let Some(rel) = relative_lower.as_deref() {
             if glob_match(&pattern_lower, rel) {
                 return true;
             }
@@ -170,6 +217,60 @@ fn glob_match_impl(pattern: &[char], text: &[char], pi: usize, ti: usize) -> boo
     pi == pattern.len() && ti == text.len()
 }
 
+/// Every folder `category_for_extension` can return.
+pub const CATEGORY_FOLDERS: [&str; 7] = ["Images", "Videos", "Audio", "Documents", "Archives", "Code", "Other"];
+
+/// Category folder a MoveByCategory action sorts a file with extension `ext` into.
+/// Case-insensitive; unknown or missing extensions go to `Other`.
+pub fn category_for_extension(ext: &str) -> &'static str {
+    match ext.to_lowercase().as_str() {
+        "jpg" | "jpeg" | "png" | "gif" | "bmp" | "webp" | "svg" | "tif" | "tiff" | "heic" | "ico" | "raw" => "Images",
+        "mp4" | "mkv" | "mov" | "avi" | "wmv" | "webm" | "m4v" | "flv" => "Videos",
+        "mp3" | "wav" | "flac" | "aac" | "ogg" | "m4a" | "wma" | "opus" => "Audio",
+        "pdf" | "doc" | "docx" | "xls" | "xlsx" | "ppt" | "pptx" | "odt" | "ods" | "odp" | "txt" | "rtf" | "md"
+        | "csv" | "epub" => "Documents",
+        "zip" | "rar" | "7z" | "tar" | "gz" | "bz2" | "xz" | "iso" => "Archives",
+        "rs" | "py" | "js" | "ts" | "tsx" | "jsx" | "java" | "c" | "cpp" | "h" | "cs" | "go" | "rb" | "php"
+        | "html" | "css" | "json" | "xml" | "yaml" | "yml" | "toml" | "sh" | "ps1" | "sql" => "Code",
+        _ => "Other",
+    }
+}
+
+/// `root/<Category>` for a MoveByCategory action on `file_path`.
+pub fn category_destination(root: &Path, file_path: &Path) -> PathBuf {
+    let ext = file_path.extension().map(|e| e.to_string_lossy()).unwrap_or_default();
+    root.join(category_for_extension(&ext))
+}
+
+/// Expand placeholders in a Move destination for a specific file:
+/// - `{year}`, `{month}`, `{day}` — the file's modified date (local time, zero-padded)
+/// - `{ext}` — the lowercase extension without the dot (`no_ext` if the file has none)
+///
+/// e.g. `D:\sorted\{year}\{month}` → `D:\sorted\2024\06`.
+/// Falls back to the current date if the file's modified time can't be read.
+pub fn expand_destination(template: &Path, file_path: &Path) -> PathBuf {
+    let text = template.to_string_lossy();
+    if !text.contains('{') {
+        return template.to_path_buf();
+    }
+
+    let modified: DateTime<Local> = fs::metadata(file_path)
+        .and_then(|m| m.modified())
+        .map(DateTime::from)
+        .unwrap_or_else(|_| Local::now());
+    let ext = file_path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_else(|| "no_ext".to_string());
+
+    PathBuf::from(
+        text.replace("{year}", &modified.format("%Y").to_string())
+            .replace("{month}", &modified.format("%m").to_string())
+            .replace("{day}", &modified.format("%d").to_string())
+            .replace("{ext}", &ext),
+    )
+}
+
 /// Check if a file is inside a given directory (the Move destination).
 /// Used to auto-whitelist files already at the destination.
 fn is_file_in_dir(file_path: &Path, dir: &Path) -> bool {
@@ -181,6 +282,387 @@ fn is_file_in_dir(file_path: &Path, dir: &Path) -> bool {
     }
 }
 
+/// Whether a file's last-modified time is at least `days` days ago.
+/// `days == 0` always passes; unreadable metadata never does.
+fn is_older_than_days(file_path: &Path, days: u32) -> bool {
+    if days == 0 {
+        return true;
+    }
+    fs::metadata(file_path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok())
+        .map(|age| age.as_secs() >= days as u64 * 24 * 60 * 60)
+        .unwrap_or(false)
+}
+
+/// State shared by all files evaluated in one pass over a folder (a scan).
+/// Single-file callers (the watcher) use `ScanPass::for_file`.
+#[derive(Debug, Default)]
+pub struct ScanPass {
+    /// Size rank of each file among its siblings (only computed if a rule needs it)
+    size_ranks: HashMap<PathBuf, SizeRank>,
+    /// Immediate actions taken per rule id in this pass, for `Rule::max_per_run`
+    actions_per_rule: HashMap<String, u32>,
+    /// Per-rule condition timings by rule id, when profiling is on
+    profile: Option<HashMap<String, RuleProfile>>,
+    /// Undo entries for immediate actions are tagged with this run id
+    batch_id: Option<String>,
+    /// `AppSettings::global_ignore_extensions`; matching files are skipped before any rule
+    ignored_extensions: Vec<String>,
+    /// `AppSettings::allow_run_command`; RunCommand rules fail instead of running while false
+    allow_run_command: bool,
+    /// `AppSettings::deletion_mode`, for originals removed by Archive rules
+    deletion_mode: DeletionMode,
+    /// Local time rules' active windows are checked against; None = the real clock
+    local_now: Option<chrono::NaiveDateTime>,
+    /// Files under each Duplicate reference dir by size, looked up once per pass
+    reference_sizes: HashMap<PathBuf, Arc<HashMap<u64, Vec<PathBuf>>>>,
+    /// Notify results raised in this pass, kept apart from each file's outcome so a
+    /// later Move can't drop them; callers drain them with `take_notifications`
+    notifications: Vec<RuleActionResult>,
+    /// Bytes in each quota'd Move destination, for `QuotaDestination::max_bytes`
+    quota_usage: QuotaUsage,
+}
+
+/// How expensive one rule's condition was over a scan.
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct RuleProfile {
+    pub rule_name: String,
+    pub files_tested: u32,
+    pub total_micros: u64,
+    pub matches: u32,
+}
+
+impl ScanPass {
+    /// Start a scan over `files`, pre-ranking each directory once if any rule needs it.
+    pub fn new(folder: &WatchedFolder, files: &[PathBuf]) -> Self {
+        let size_ranks = if folder_uses_size_rank(folder) {
+            rank_by_size(files)
+        } else {
+            HashMap::new()
+        };
+        Self { size_ranks, ..Self::default() }
+    }
+
+    /// A pass for a single file, ranking it against its directory on demand.
+    pub fn for_file(folder: &WatchedFolder, file_path: &Path) -> Self {
+        let mut pass = Self::default();
+        if folder_uses_size_rank(folder) {
+            if let Some(rank) = file_path.parent().and_then(|dir| size_rank_in_dir(dir, file_path)) {
+                pass.size_ranks.insert(file_path.to_path_buf(), rank);
+            }
+        }
+        pass
+    }
+
+    /// Skip files with any of these extensions (the global partial-download ignore list).
+    pub fn with_ignored_extensions(mut self, extensions: &[String]) -> Self {
+        self.ignored_extensions = extensions.to_vec();
+        self
+    }
+
+    /// Check rules' active windows against this local time instead of the clock.
+    #[cfg(test)]
+    pub fn with_local_now(mut self, now: chrono::NaiveDateTime) -> Self {
+        self.local_now = Some(now);
+        self
+    }
+
+    fn local_now(&self) -> chrono::NaiveDateTime {
+        self.local_now.unwrap_or_else(|| Local::now().naive_local())
+    }
+
+    /// Notifications raised since the last call, to log and show alongside the outcome.
+    pub fn take_notifications(&mut self) -> Vec<RuleActionResult> {
+        std::mem::take(&mut self.notifications)
+    }
+
+    /// Let RunCommand rules start their programs (`AppSettings::allow_run_command`).
+    pub fn with_run_command_allowed(mut self, allowed: bool) -> Self {
+        self.allow_run_command = allowed;
+        self
+    }
+
+    /// Remove archived originals the way `AppSettings::deletion_mode` says.
+    pub fn with_deletion_mode(mut self, mode: DeletionMode) -> Self {
+        self.deletion_mode = mode;
+        self
+    }
+
+    /// Tag undo entries created during this pass with `batch_id`, so a whole scan can be undone together.
+    pub fn with_batch_id(mut self, batch_id: &str) -> Self {
+        self.batch_id = Some(batch_id.to_string());
+        self
+    }
+
+    /// Time every rule's condition evaluation during this pass.
+    pub fn with_profiling(mut self) -> Self {
+        self.profile = Some(HashMap::new());
+        self
+    }
+
+    /// Collected timings in the folder's rule order (rules never tested report zeros).
+    pub fn rule_profiles(&self, folder: &WatchedFolder) -> Vec<RuleProfile> {
+        folder
+            .rules
+            .iter()
+            .map(|rule| {
+                self.profile
+                    .as_ref()
+                    .and_then(|p| p.get(&rule.id).cloned())
+                    .unwrap_or_else(|| RuleProfile { rule_name: rule.name.clone(), ..Default::default() })
+            })
+            .collect()
+    }
+
+    fn record_evaluation(&mut self, rule: &Rule, elapsed: Duration, matched: bool) {
+        if let Some(profile) = self.profile.as_mut() {
+            let entry = profile.entry(rule.id.clone()).or_insert_with(|| RuleProfile {
+                rule_name: rule.name.clone(),
+                ..Default::default()
+            });
+            entry.files_tested += 1;
+            entry.total_micros += elapsed.as_micros() as u64;
+            if matched {
+                entry.matches += 1;
+            }
+        }
+    }
+
+    /// The rule can't act on another file this pass: it hit its per-run cap, or it is
+    /// backed off after repeated failures. Either way the file waits for a later scan.
+    fn must_defer(&self, rule: &Rule) -> bool {
+        if crate::backoff::is_backed_off(rule) {
+            return true;
+        }
+        match rule.max_per_run {
+            Some(cap) => self.actions_per_rule.get(&rule.id).copied().unwrap_or(0) >= cap,
+            None => false,
+        }
+    }
+
+    fn record_action(&mut self, rule: &Rule) {
+        *self.actions_per_rule.entry(rule.id.clone()).or_insert(0) += 1;
+    }
+
+    /// Whether a file with the same content as `file_path` exists under `reference_dir`.
+    /// Only same-size files are hashed; with `db`, hashes are cached by path, size and mtime.
+    /// Copies inside `folder_root` are evaluated by the same rule, so only an earlier one
+    /// (see [`is_earlier_copy`]) counts: of identical copies in the folder, the first is kept.
+    fn is_duplicate_in(
+        &mut self,
+        file_path: &Path,
+        reference_dir: &Path,
+        folder_root: &Path,
+        db: Option<&Database>,
+    ) -> bool {
+        let size = match fs::metadata(file_path) {
+            Ok(meta) if meta.is_file() => meta.len(),
+            _ => return false,
+        };
+        let by_size = self
+            .reference_sizes
+            .entry(reference_dir.to_path_buf())
+            .or_insert_with(|| reference_files_by_size(reference_dir));
+        let candidates: Vec<&PathBuf> = match by_size.get(&size) {
+            Some(same_size) => same_size
+                .iter()
+                .filter(|p| p.as_path() != file_path)
+                .filter(|p| !p.starts_with(folder_root) || is_earlier_copy(p, file_path))
+                .collect(),
+            None => return false,
+        };
+        if candidates.is_empty() {
+            return false;
+        }
+        let Some(hash) = cached_file_hash(file_path, db) else {
+            return false;
+        };
+        candidates.into_iter().any(|p| cached_file_hash(p, db).as_ref() == Some(&hash))
+    }
+}
+
+/// Bytes in each quota'd Move destination over one run: measured on disk the first time
+/// the destination is considered, then counted up as files move in. Emptying a
+/// destination frees its quota for the next run.
+#[derive(Debug, Default)]
+pub struct QuotaUsage {
+    bytes: HashMap<PathBuf, u64>,
+}
+
+impl QuotaUsage {
+    /// Bytes in `destination` (as configured; templated ones are measured from their
+    /// static prefix).
+    fn used(&mut self, destination: &Path) -> u64 {
+        *self.bytes.entry(destination.to_path_buf()).or_insert_with(|| {
+            let root = crate::config::static_destination(destination);
+            crate::scheduler::collect_files(&root, true)
+                .iter()
+                .filter_map(|f| fs::metadata(f).ok())
+                .map(|m| m.len())
+                .sum()
+        })
+    }
+
+    /// Count a quota'd Move's bytes against the destination [`claim_destination`] picked,
+    /// once the move succeeded.
+    pub(crate) fn count(&mut self, rule: &Rule, result: &RuleActionResult) {
+        let Action::Move { ref destinations, .. } = rule.action else {
+            return;
+        };
+        if let (Some(claimed), Some(size)) = (destinations.first(), result.size_bytes()) {
+            *self.bytes.entry(claimed.path.clone()).or_default() += size as u64;
+        }
+    }
+
+    /// Like [`QuotaUsage::count`], for a move that didn't go through `execute_action`.
+    pub(crate) fn count_bytes(&mut self, rule: &Rule, bytes: u64) {
+        if let Action::Move { ref destinations, .. } = rule.action {
+            if let Some(claimed) = destinations.first() {
+                *self.bytes.entry(claimed.path.clone()).or_default() += bytes;
+            }
+        }
+    }
+}
+
+/// `rule` with its Move destination resolved for this file: the first quota'd destination
+/// that can take it, given `usage`. It comes back as `destination` and as the only entry
+/// left in `destinations`, so the move is counted against its quota once it succeeds
+/// (see [`QuotaUsage::count`]). Rules without `destinations` come back unchanged; None if
+/// every destination is full.
+pub(crate) fn claim_destination(rule: &Rule, file_path: &Path, usage: &mut QuotaUsage) -> Option<Rule> {
+    let Action::Move { ref destinations, .. } = rule.action else {
+        return Some(rule.clone());
+    };
+    if destinations.is_empty() {
+        return Some(rule.clone());
+    }
+    let size = fs::metadata(file_path).map(|m| m.len()).unwrap_or(0);
+    let moved: HashMap<PathBuf, u64> = destinations.iter().map(|d| (d.path.clone(), usage.used(&d.path))).collect();
+    let Some(chosen) = pick_quota_destination(destinations, file_path, size, &moved, get_disk_space) else {
+        log::info!("Deferring {}: every destination of rule '{}' is over quota or full", file_path.display(), rule.name);
+        return None;
+    };
+
+    let mut resolved = rule.clone();
+    if let Action::Move { ref mut destination, ref mut destinations, .. } = resolved.action {
+        destinations.retain(|d| d.path == chosen);
+        *destination = chosen;
+    }
+    Some(resolved)
+}
+
+/// Every file under a Duplicate reference dir (recursively) grouped by size, plus the
+/// mtime of every directory in it when listed. Adding, removing or renaming a file changes
+/// its directory's mtime, so the listing is current while none of them changed.
+#[derive(Debug)]
+struct ReferenceIndex {
+    by_size: Arc<HashMap<u64, Vec<PathBuf>>>,
+    dir_mtimes: Vec<(PathBuf, Option<SystemTime>)>,
+}
+
+impl ReferenceIndex {
+    fn build(dir: &Path) -> Self {
+        let mut by_size: HashMap<u64, Vec<PathBuf>> = HashMap::new();
+        let mut dir_mtimes = vec![(dir.to_path_buf(), dir_mtime(dir))];
+        for path in crate::scheduler::collect_files(dir, true) {
+            let Ok(meta) = fs::metadata(&path) else {
+                continue;
+            };
+            if meta.is_file() {
+                by_size.entry(meta.len()).or_default().push(path);
+            } else if meta.is_dir() {
+                dir_mtimes.push((path, meta.modified().ok()));
+            }
+        }
+        Self { by_size: Arc::new(by_size), dir_mtimes }
+    }
+
+    fn is_current(&self) -> bool {
+        self.dir_mtimes.iter().all(|(dir, mtime)| dir_mtime(dir) == *mtime)
+    }
+}
+
+fn dir_mtime(dir: &Path) -> Option<SystemTime> {
+    fs::metadata(dir).and_then(|m| m.modified()).ok()
+}
+
+/// `dir`'s files by size, from a listing kept across passes (so each watcher event
+/// doesn't walk the reference tree again) until a directory in it changes.
+fn reference_files_by_size(dir: &Path) -> Arc<HashMap<u64, Vec<PathBuf>>> {
+    static INDEXES: OnceLock<Mutex<HashMap<PathBuf, ReferenceIndex>>> = OnceLock::new();
+    let mut indexes = INDEXES.get_or_init(Default::default).lock().unwrap();
+    if let Some(index) = indexes.get(dir).filter(|index| index.is_current()) {
+        return index.by_size.clone();
+    }
+    let index = ReferenceIndex::build(dir);
+    let by_size = index.by_size.clone();
+    indexes.insert(dir.to_path_buf(), index);
+    by_size
+}
+
+/// Whether `candidate` comes before `file` among copies of the same content: older
+/// mtime first, then path order, so exactly one copy is never a duplicate.
+fn is_earlier_copy(candidate: &Path, file: &Path) -> bool {
+    let modified = |p: &Path| fs::metadata(p).and_then(|m| m.modified()).ok();
+    (modified(candidate), candidate) < (modified(file), file)
+}
+
+/// SHA-256 of a file's content (lowercase hex), from `db`'s cache while the file's size
+/// and mtime are unchanged. None if the file can't be read.
+fn cached_file_hash(path: &Path, db: Option<&Database>) -> Option<String> {
+    let meta = fs::metadata(path).ok()?;
+    let modified = DateTime::<Utc>::from(meta.modified().ok()?).to_rfc3339();
+    let key = path.to_string_lossy();
+    if let Some(hash) = db.and_then(|db| db.get_file_hash(&key, meta.len(), &modified).ok().flatten()) {
+        return Some(hash);
+    }
+    let hash = match hash_file(path) {
+        Ok(hash) => hash,
+        Err(e) => {
+            log::warn!("Could not hash {}: {}", path.display(), friendly_io_error(&e));
+            return None;
+        }
+    };
+    if let Some(db) = db {
+        let _ = db.put_file_hash(&key, meta.len(), &modified, &hash);
+    }
+    Some(hash)
+}
+
+fn hash_file(path: &Path) -> std::io::Result<String> {
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut fs::File::open(path)?, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// The first destination whose bytes `moved` so far plus `size` stay within its quota and
+/// whose drive (per `available`) has room for `size`. Unknown free space counts as room.
+fn pick_quota_destination(
+    destinations: &[QuotaDestination],
+    file_path: &Path,
+    size: u64,
+    moved: &HashMap<PathBuf, u64>,
+    available: impl Fn(&Path) -> std::io::Result<u64>,
+) -> Option<PathBuf> {
+    destinations
+        .iter()
+        .find(|d| {
+            let already = moved.get(&d.path).copied().unwrap_or(0);
+            if d.max_bytes.is_some_and(|max| already + size > max) {
+                return false;
+            }
+            // The destination may not exist yet; its drive is that of the nearest existing parent
+            let expanded = expand_destination(&d.path, file_path);
+            let Some(existing) = expanded.ancestors().find(|p| p.exists()) else {
+                return true;
+            };
+            available(existing).map_or(true, |free| free >= size)
+        })
+        .map(|d| d.path.clone())
+}
+
 /// Evaluate a single file against a folder's rules (in priority order).
 /// Returns full outcome including scheduled deletions.
 ///
@@ -192,111 +674,328 @@ fn is_file_in_dir(file_path: &Path, dir: &Path) -> bool {
 ///   scheduled — the one that fires earliest (shortest delay). On equal delay, the
 ///   rule higher in the list (lower index) wins.
 /// - Immediate cut-mode Move (delay=0) executes immediately and stops evaluation.
-pub fn evaluate_file_full(
+/// - If no explicit rule matched, the folder's `default_action` (if any) is evaluated
+///   last, like a catch-all rule, once the file is older than `default_after_days`.
+/// - A rule with `max_per_run` that has already taken that many immediate actions in
+///   the current pass defers the file untouched to the next scan.
+///
+/// Size ranks and per-rule action counts are shared across `pass`: a scan, or
+/// `ScanPass::for_file` for a single file (the watcher).
+pub fn evaluate_file_in_pass(
     file_path: &Path,
     folder: &WatchedFolder,
     db: &Database,
+    pass: &mut ScanPass,
 ) -> EvalOutcome {
-    let file_name = file_path
-        .file_name()
-        .unwrap_or_default()
-        .to_string_lossy()
-        .to_string();
+    match plan_file_with_hashes(file_path, folder, pass, Some(db)) {
+        Some(plan) => execute_plan(file_path, folder, db, &plan, pass),
+        None => EvalOutcome::NoMatch,
+    }
+}
 
-    let relative_path = file_path
-        .strip_prefix(&folder.path)
-        .unwrap_or(file_path)
-        .to_string_lossy()
-        .replace('\\', "/");
+/// A rule chosen to act on a file later (or, for copies, alongside the destructive winner).
+struct PlannedRule {
+    rule: Rule,
+    rule_index: usize,
+    delay_minutes: u32,
+    /// Resolved Move destination (placeholders expanded); None for deletes
+    destination: Option<PathBuf>,
+}
+
+/// What evaluation decided to do with one file, before anything touches disk or the DB.
+/// `evaluate_file_in_pass` executes it; a dry run only reports it.
+pub struct FilePlan {
+    file_name: String,
+    file_ctx: FileContext,
+    /// Immediate actions in rule order: copies, then at most one cut-mode move (always last)
+    immediate: Vec<Rule>,
+    /// Evaluation stopped early — an immediate move consumed the file, or a rule hit its
+    /// per-run cap (or error backoff) or matched outside its active window and the file
+    /// is deferred. Nothing is scheduled or cleaned up.
+    stopped: bool,
+    /// Winning delayed destructive rule: earliest fire time, tie-break by list order
+    destructive: Option<PlannedRule>,
+    /// Delayed copies that fire before the destructive winner
+    scheduled_copies: Vec<PlannedRule>,
+    /// Delayed copies suppressed by the destructive winner (their stale entries get removed)
+    dominated_copies: Vec<Rule>,
+}
+
+/// Decide which rules act on a file and how. Returns None if the global ignore list or the
+/// folder whitelist skips it.
+/// Only reads the filesystem; the per-run caps in `pass` are counted as if the plan runs.
+pub fn plan_file(
+    file_path: &Path,
+    folder: &WatchedFolder,
+    pass: &mut ScanPass,
+) -> Option<FilePlan> {
+    plan_file_with_hashes(file_path, folder, pass, None)
+}
+
+/// Whether `rule`'s condition matches the file and neither the global ignore list nor the
+/// folder or rule whitelist skips it. The checks a scan makes before acting, without the
+/// action-specific skips (file already in the destination, per-run caps).
+pub fn rule_matches_file(file_path: &Path, folder: &WatchedFolder, rule: &Rule, pass: &mut ScanPass) -> bool {
+    MatchSubject::new(file_path, folder, pass).is_some_and(|mut subject| subject.matches(rule, pass, None))
+}
+
+/// A file as the rules of its folder see it: its name, its path relative to the folder
+/// and the metadata conditions read.
+struct MatchSubject<'a> {
+    file_path: &'a Path,
+    folder: &'a WatchedFolder,
+    file_name: String,
+    relative_path: String,
+    ctx: FileContext,
+}
 
-    // Check folder-level whitelist first
-    if is_whitelisted_with_relative_path(&file_name, Some(&relative_path), &folder.whitelist) {
-        return EvalOutcome::NoMatch;
+impl<'a> MatchSubject<'a> {
+    /// None when the global ignore list or the folder whitelist skips the file.
+    fn new(file_path: &'a Path, folder: &'a WatchedFolder, pass: &ScanPass) -> Option<Self> {
+        let file_name = file_path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        let relative_path = file_path
+            .strip_prefix(&folder.path)
+            .unwrap_or(file_path)
+            .to_string_lossy()
+            .replace('\\', "/");
+        // Partial downloads and temp files are never touched, whatever the rules say
+        if has_ignored_extension(&file_name, &pass.ignored_extensions)
+            || is_whitelisted_with_relative_path(&file_name, Some(&relative_path), &folder.whitelist)
+        {
+            return None;
+        }
+        let mut ctx = FileContext::from_path(file_path);
+        ctx.size_rank = pass.size_ranks.get(file_path).copied();
+        Some(Self { file_path, folder, file_name, relative_path, ctx })
     }
 
-    // Track the first outcome to return
-    let mut first_outcome: Option<EvalOutcome> = None;
+    fn is_whitelisted_by(&self, rule: &Rule) -> bool {
+        is_whitelisted_with_relative_path(&self.file_name, Some(&self.relative_path), &rule.whitelist)
+    }
+
+    /// Whether `rule`'s condition matches, unless its whitelist skips the file. Duplicate
+    /// conditions' reference dirs are checked once per file; with `db`, their hashes are cached.
+    fn matches(&mut self, rule: &Rule, pass: &mut ScanPass, db: Option<&Database>) -> bool {
+        if self.is_whitelisted_by(rule) {
+            return false;
+        }
+        for dir in condition::duplicate_reference_dirs(&rule.condition) {
+            if let Entry::Vacant(slot) = self.ctx.duplicate_in.entry(dir) {
+                let duplicate = pass.is_duplicate_in(self.file_path, slot.key(), &self.folder.path, db);
+                slot.insert(duplicate);
+            }
+        }
+        let opts = EvalOptions { case_sensitive: rule.case_sensitive };
+        let subject = if rule.match_subdirectories { &self.relative_path } else { &self.file_name };
+        condition::evaluate_with_options(&rule.condition, subject, &self.ctx, opts)
+    }
+}
+
+/// [`plan_file`], caching the content hashes of `Duplicate` conditions in `db` and
+/// reading quota'd destinations' usage from it.
+fn plan_file_with_hashes(
+    file_path: &Path,
+    folder: &WatchedFolder,
+    pass: &mut ScanPass,
+    db: Option<&Database>,
+) -> Option<FilePlan> {
+    let mut subject = MatchSubject::new(file_path, folder, pass)?;
+
+    let mut immediate: Vec<Rule> = Vec::new();
+    let mut stopped = false;
 
     // Among destructive rules, find the winner: earliest fire time, tie-break by list order.
-    // We collect the winner during the loop, then schedule it after.
     // Copies are also collected and only scheduled if they fire before the destructive winner.
-    struct DestructiveCandidate {
-        rule_index: usize,
-        delay_minutes: u32,
-    }
-    struct CopyCandidate {
-        rule_index: usize,
-        delay_minutes: u32,
-        dest_str: String,
-    }
-    let mut best_destructive: Option<DestructiveCandidate> = None;
-    let mut copy_candidates: Vec<CopyCandidate> = Vec::new();
+    let mut best_destructive: Option<PlannedRule> = None;
+    let mut copy_candidates: Vec<PlannedRule> = Vec::new();
 
-    for (rule_index, rule) in folder.rules.iter().enumerate() {
+    // The folder's default action runs as a lowest-priority catch-all, only when
+    // no explicit rule matched and the file is old enough.
+    let default_rule = folder.default_rule();
+    let mut any_rule_matched = false;
+    let default_candidate = default_rule
+        .as_ref()
+        .filter(|_| is_older_than_days(file_path, folder.default_after_days))
+        .map(|r| (folder.rules.len(), r));
+
+    let local_now = pass.local_now();
+    let explicit_rules = folder.rules_by_priority().into_iter().enumerate();
+    for (rule_index, rule) in explicit_rules.chain(default_candidate) {
+        let is_default = rule_index == folder.rules.len();
+        if is_default && any_rule_matched {
+            break;
+        }
         if !rule.is_enabled() {
             continue;
         }
 
         // Check rule-level whitelist
-        if is_whitelisted_with_relative_path(&file_name, Some(&relative_path), &rule.whitelist) {
+        if subject.is_whitelisted_by(rule) {
             continue;
         }
 
         // Auto-whitelist: if this is a Move rule, skip files already in the destination
-        if let Action::Move { ref destination, .. } = rule.action {
-            if is_file_in_dir(file_path, destination) {
+        if let Action::Move { ref destination, ref destinations, .. } = rule.action {
+            let mut all = std::iter::once(destination).chain(destinations.iter().map(|d| &d.path));
+            let resolved = |dest: &Path| {
+                resolve_destination(rule, dest, false, &folder.path, file_path)
+                    .unwrap_or_else(|_| expand_destination(dest, file_path))
+            };
+            if all.any(|dest| is_file_in_dir(file_path, &resolved(dest))) {
+                continue;
+            }
+        }
+        if let Action::MoveByCategory { ref root } = rule.action {
+            if is_file_in_dir(file_path, &category_destination(root, file_path)) {
                 continue;
             }
         }
 
-        let matched = if rule.match_subdirectories {
-            condition::evaluate(&rule.condition, &relative_path)
-        } else {
-            condition::evaluate(&rule.condition, &file_name)
-        };
+        let started = Instant::now();
+        let matched = subject.matches(rule, pass, db);
+        pass.record_evaluation(rule, started.elapsed(), matched);
 
         if !matched {
             continue;
         }
+        any_rule_matched = true;
+
+        // Outside its active window the rule defers the file to a later scan, like a
+        // per-run cap: later rules don't take it, and what it scheduled stays scheduled
+        if !rule.is_active_at(local_now) {
+            stopped = true;
+            break;
+        }
 
         // Condition matched — decide what to do based on action type
         match &rule.action {
-            Action::Move { delay_minutes, keep_source, destination } if *keep_source => {
+            Action::Move { delay_minutes, keep_source, destination, destinations, preserve_structure, .. }
+                if *keep_source =>
+            {
                 // Copy mode: non-destructive, collect for later (schedule only if it fires before destructive winner)
-                let dest_file = destination.join(&file_name);
-                if dest_file.exists() {
+                let target = |dest: &Path| resolve_destination(rule, dest, *preserve_structure, &folder.path, file_path);
+                let mut all = std::iter::once(destination).chain(destinations.iter().map(|d| &d.path));
+                if all.any(|dest| target(dest).is_ok_and(|dir| dir.join(&subject.file_name).exists())) {
                     continue; // Already copied
                 }
+                let destination = match target(destination) {
+                    Ok(destination) => destination,
+                    Err(e) => {
+                        log::warn!("Skipping {}: {}", file_path.display(), e);
+                        continue;
+                    }
+                };
 
                 if *delay_minutes > 0 {
-                    copy_candidates.push(CopyCandidate {
+                    copy_candidates.push(PlannedRule {
+                        rule: rule.clone(),
                         rule_index,
                         delay_minutes: *delay_minutes,
-                        dest_str: destination.to_string_lossy().to_string(),
+                        destination: Some(destination),
                     });
                 } else {
-                    // Immediate copy — always execute
-                    let result = execute_action(file_path, &file_name, rule, folder, db);
-                    let outcome = EvalOutcome::Action(result);
-                    if first_outcome.is_none() {
-                        first_outcome = Some(outcome);
+                    // Immediate copy — runs unless the rule hit its per-run cap
+                    if pass.must_defer(rule) {
+                        stopped = true;
+                        break;
                     }
+                    let Some(resolved) = claim_destination(rule, file_path, &mut pass.quota_usage) else {
+                        continue;
+                    };
+                    pass.record_action(rule);
+                    immediate.push(resolved);
                 }
                 continue;
             }
             Action::Move { delay_minutes: 0, .. } => {
-                // Immediate cut-mode move — execute now, file is consumed, stop evaluation
-                return EvalOutcome::Action(execute_action(file_path, &file_name, rule, folder, db));
+                // Immediate cut-mode move — the file is consumed, stop evaluation.
+                // If the rule already hit its per-run cap or every quota'd destination
+                // is full, defer the file to the next scan.
+                stopped = true;
+                if !pass.must_defer(rule) {
+                    if let Some(resolved) = claim_destination(rule, file_path, &mut pass.quota_usage) {
+                        pass.record_action(rule);
+                        immediate.push(resolved);
+                    }
+                }
+                break;
             }
-            Action::Move { delay_minutes, .. } => {
+            Action::Move { delay_minutes, destination, preserve_structure, .. } => {
                 // Scheduled cut-mode move — destructive candidate
                 let dominated = match &best_destructive {
                     Some(best) => *delay_minutes >= best.delay_minutes,
                     None => false,
                 };
                 if !dominated {
-                    best_destructive = Some(DestructiveCandidate { rule_index, delay_minutes: *delay_minutes });
+                    match resolve_destination(rule, destination, *preserve_structure, &folder.path, file_path) {
+                        Ok(destination) => {
+                            best_destructive = Some(PlannedRule {
+                                rule: rule.clone(),
+                                rule_index,
+                                delay_minutes: *delay_minutes,
+                                destination: Some(destination),
+                            });
+                        }
+                        Err(e) => log::warn!("Skipping {}: {}", file_path.display(), e),
+                    }
+                }
+            }
+            Action::MoveByCategory { .. } => {
+                // Always immediate — the file is consumed, like an immediate cut-mode move
+                stopped = true;
+                if !pass.must_defer(rule) {
+                    pass.record_action(rule);
+                    immediate.push(rule.clone());
+                }
+                break;
+            }
+            Action::Archive { .. } => {
+                // Zipped then recycled — the file is consumed, like an immediate move
+                stopped = true;
+                if !pass.must_defer(rule) {
+                    pass.record_action(rule);
+                    immediate.push(rule.clone());
                 }
+                break;
+            }
+            Action::Link { destination, .. } => {
+                // Non-destructive, like an immediate copy — skip files already linked there
+                if existing_link(&expand_destination(destination, file_path), file_path).is_some() {
+                    continue;
+                }
+                if pass.must_defer(rule) {
+                    stopped = true;
+                    break;
+                }
+                pass.record_action(rule);
+                immediate.push(rule.clone());
+            }
+            Action::Notify { .. } => {
+                // Non-destructive, like an immediate copy — evaluation continues
+                if pass.must_defer(rule) {
+                    stopped = true;
+                    break;
+                }
+                pass.record_action(rule);
+                immediate.push(rule.clone());
+            }
+            Action::RunCommand { .. } => {
+                // The program takes over the file — no later rule acts on it
+                stopped = true;
+                if !pass.must_defer(rule) {
+                    pass.record_action(rule);
+                    immediate.push(rule.clone());
+                }
+                break;
+            }
+            Action::Review { .. } => {
+                // Flag for the user and leave the file alone — no later rule acts on it
+                stopped = true;
+                if !pass.must_defer(rule) {
+                    pass.record_action(rule);
+                    immediate.push(rule.clone());
+                }
+                break;
             }
             Action::Delete { delay_minutes, .. } => {
                 // Scheduled delete — destructive candidate
@@ -305,25 +1004,140 @@ pub fn evaluate_file_full(
                     None => false,
                 };
                 if !dominated {
-                    best_destructive = Some(DestructiveCandidate { rule_index, delay_minutes: *delay_minutes });
+                    best_destructive = Some(PlannedRule {
+                        rule: rule.clone(),
+                        rule_index,
+                        delay_minutes: *delay_minutes,
+                        destination: None,
+                    });
                 }
             }
         }
     }
 
+    // A copy is dominated (suppressed) if:
+    //   - its delay > destructive delay (fires after destruction), OR
+    //   - its delay == destructive delay AND it's listed after the destructive rule
+    //     (scheduler uses rule_priority = list index, so higher index executes later)
+    // If no destructive winner, all copies are valid.
+    let mut scheduled_copies = Vec::new();
+    let mut dominated_copies = Vec::new();
+    for copy in copy_candidates {
+        let dominated = match &best_destructive {
+            Some(w) => {
+                copy.delay_minutes > w.delay_minutes
+                    || (copy.delay_minutes == w.delay_minutes && copy.rule_index > w.rule_index)
+            }
+            None => false,
+        };
+        if dominated {
+            dominated_copies.push(copy.rule);
+        } else {
+            scheduled_copies.push(copy);
+        }
+    }
+
+    Some(FilePlan {
+        file_name: subject.file_name,
+        file_ctx: subject.ctx,
+        immediate,
+        stopped,
+        destructive: best_destructive,
+        scheduled_copies,
+        dominated_copies,
+    })
+}
+
+/// Carry out a plan: run immediate actions, schedule the destructive winner and the
+/// surviving copies, and clean up stale scheduled entries.
+fn execute_plan(
+    file_path: &Path,
+    folder: &WatchedFolder,
+    db: &Database,
+    plan: &FilePlan,
+    pass: &mut ScanPass,
+) -> EvalOutcome {
+    let batch_id = pass.batch_id.as_deref();
+    let notifications = &mut pass.notifications;
+    let file_name = &plan.file_name;
+    let file_ctx = &plan.file_ctx;
+
+    // Track the first outcome to return
+    let mut first_outcome: Option<EvalOutcome> = None;
+
+    for rule in &plan.immediate {
+        // Review, Archive and RunCommand always come last (they stop evaluation)
+        if let Action::Review { ref reason, .. } = rule.action {
+            // An already-queued file is a no-op
+            return match flag_for_review(file_path, file_name, rule, folder, db, reason) {
+                Some(result) => EvalOutcome::Action(RuleActionResult { rule_id: rule.id.clone(), ..result }),
+                None => first_outcome.unwrap_or(EvalOutcome::NoMatch),
+            };
+        }
+        if let Action::Archive { .. } = rule.action {
+            let mut result = execute_archive(file_path, file_name, rule, db, batch_id, pass.deletion_mode);
+            result.rule_id = rule.id.clone();
+            note_outcome(rule, &result);
+            result.details = with_time_note(result.details, rule, file_ctx);
+            return EvalOutcome::Action(result);
+        }
+        if let Action::RunCommand { ref program, ref args } = rule.action {
+            // Re-scans leave files the program already handled alone, and retry failures with backoff
+            if !run_once_due(db, file_path, rule, "run_command") {
+                return first_outcome.unwrap_or(EvalOutcome::NoMatch);
+            }
+            let mut result = execute_run_command(file_path, file_name, program, args, &rule.name, pass.allow_run_command);
+            result.rule_id = rule.id.clone();
+            record_run_once(db, file_path, rule, folder, "run_command", result.success);
+            result.details = with_time_note(result.details, rule, file_ctx);
+            return EvalOutcome::Action(result);
+        }
+        if let Action::Notify { ref message } = rule.action {
+            // Raised whatever later rules do with the file; re-scans don't notify about it again
+            if run_once_due(db, file_path, rule, "notified") {
+                let details = message.replace("{name}", file_name);
+                let result = plain_result(file_path, file_name, "notified", &rule.name, true, Some(details));
+                record_run_once(db, file_path, rule, folder, "notified", true);
+                notifications.push(RuleActionResult { rule_id: rule.id.clone(), ..result });
+            }
+            continue;
+        }
+        let mut result = execute_action(file_path, file_name, rule, folder, db);
+        result.rule_id = rule.id.clone();
+        note_outcome(rule, &result);
+        result.details = with_time_note(result.details, rule, file_ctx);
+        if let Some(ref dest) = result.dest_path {
+            crate::watcher::note_own_write(dest);
+            record_move_undo(db, file_path, dest, undo_action_for(&rule.action), batch_id);
+        }
+        if result.fired() {
+            pass.quota_usage.count(rule, &result);
+        }
+        let consumes_file = matches!(rule.action, Action::Move { keep_source: false, .. } | Action::MoveByCategory { .. });
+        if consumes_file {
+            return EvalOutcome::Action(result);
+        }
+        if first_outcome.is_none() {
+            first_outcome = Some(EvalOutcome::Action(result));
+        }
+    }
+    if plan.stopped {
+        return first_outcome.unwrap_or(EvalOutcome::NoMatch);
+    }
+
     // Schedule the winning destructive rule (if any) and remove stale losers
-    if let Some(ref winner) = best_destructive {
-        let rule = &folder.rules[winner.rule_index];
+    if let Some(ref winner) = plan.destructive {
+        let rule = &winner.rule;
         let file_path_str = file_path.to_string_lossy().to_string();
 
         // Remove any previously-scheduled destructive entries from losing rules
         let _ = db.remove_losers_for_file(&file_path_str, &rule.name);
 
-        match &rule.action {
-            Action::Move { delay_minutes, destination, .. } => {
+        match &winner.destination {
+            Some(destination) => {
                 let dest_str = destination.to_string_lossy().to_string();
                 let newly_inserted = schedule_action(
-                    file_path, &file_name, rule, folder, db, *delay_minutes, "move", Some(&dest_str), false, winner.rule_index as u32,
+                    file_path, file_name, rule, folder, db, winner.delay_minutes, "move", Some(&dest_str), false, winner.rule_index as u32,
                 );
                 let outcome = EvalOutcome::Scheduled {
                     file_path: file_path_str,
@@ -331,15 +1145,15 @@ pub fn evaluate_file_full(
                     rule_name: rule.name.clone(),
                     newly_inserted,
                     action_type: "scheduled_move".to_string(),
-                    details: Some(format!("→ {}", dest_str)),
+                    details: with_time_note(Some(format!("→ {}", dest_str)), rule, file_ctx),
                 };
                 if first_outcome.is_none() {
                     first_outcome = Some(outcome);
                 }
             }
-            Action::Delete { delay_minutes, .. } => {
+            None => {
                 let newly_inserted = schedule_action(
-                    file_path, &file_name, rule, folder, db, *delay_minutes, "delete", None, false, winner.rule_index as u32,
+                    file_path, file_name, rule, folder, db, winner.delay_minutes, "delete", None, false, winner.rule_index as u32,
                 );
                 let outcome = EvalOutcome::Scheduled {
                     file_path: file_path_str,
@@ -347,7 +1161,7 @@ pub fn evaluate_file_full(
                     rule_name: rule.name.clone(),
                     newly_inserted,
                     action_type: "scheduled_delete".to_string(),
-                    details: None,
+                    details: with_time_note(None, rule, file_ctx),
                 };
                 if first_outcome.is_none() {
                     first_outcome = Some(outcome);
@@ -361,28 +1175,21 @@ pub fn evaluate_file_full(
         let _ = db.remove_losers_for_file(&file_path_str, "");
     }
 
+    // Copies that would fire at or after the destructive action — remove any stale entry
+    for rule in &plan.dominated_copies {
+        let _ = db.remove_scheduled_deletions_by_rule(&folder.id, &rule.name);
+    }
+
     // Schedule copies that fire before the destructive winner.
-    // A copy is dominated (suppressed) if:
-    //   - its delay > destructive delay (fires after destruction), OR
-    //   - its delay == destructive delay AND it's listed after the destructive rule
-    //     (scheduler uses rule_priority = list index, so higher index executes later)
-    // If no destructive winner, all copies are valid.
-    for copy in &copy_candidates {
-        let dominated = match &best_destructive {
-            Some(w) => {
-                copy.delay_minutes > w.delay_minutes
-                    || (copy.delay_minutes == w.delay_minutes && copy.rule_index > w.rule_index)
-            }
-            None => false,
-        };
-        if dominated {
-            // This copy would fire at or after the destructive action — skip it and remove any stale entry
-            let _ = db.remove_scheduled_deletions_by_rule(&folder.id, &folder.rules[copy.rule_index].name);
-            continue;
-        }
-        let rule = &folder.rules[copy.rule_index];
+    for copy in &plan.scheduled_copies {
+        let rule = &copy.rule;
+        let dest_str = copy
+            .destination
+            .as_ref()
+            .map(|d| d.to_string_lossy().to_string())
+            .unwrap_or_default();
         let newly_inserted = schedule_action(
-            file_path, &file_name, rule, folder, db, copy.delay_minutes, "move", Some(&copy.dest_str), true, copy.rule_index as u32,
+            file_path, file_name, rule, folder, db, copy.delay_minutes, "move", Some(&dest_str), true, copy.rule_index as u32,
         );
         let outcome = EvalOutcome::Scheduled {
             file_path: file_path.to_string_lossy().to_string(),
@@ -390,7 +1197,7 @@ pub fn evaluate_file_full(
             rule_name: rule.name.clone(),
             newly_inserted,
             action_type: "scheduled_move".to_string(),
-            details: Some(format!("→ {}", copy.dest_str)),
+            details: with_time_note(Some(format!("→ {}", dest_str)), rule, file_ctx),
         };
         if first_outcome.is_none() {
             first_outcome = Some(outcome);
@@ -400,6 +1207,216 @@ pub fn evaluate_file_full(
     first_outcome.unwrap_or(EvalOutcome::NoMatch)
 }
 
+/// One action a dry run says would happen to a file.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct PlannedAction {
+    pub file_path: String,
+    pub file_name: String,
+    pub rule_name: String,
+    /// "move", "copy", "delete", "archive", or "review"
+    pub action: String,
+    /// Resolved destination folder for moves/copies (placeholders expanded)
+    pub destination: Option<String>,
+    /// None = runs immediately; otherwise when it would run (`YYYY-MM-DD HH:MM:SS`, UTC)
+    pub scheduled_for: Option<String>,
+}
+
+impl FilePlan {
+    /// Some rule acts on the file now or later (or would, but it is deferred by a per-run
+    /// cap or an active window).
+    pub fn is_handled(&self) -> bool {
+        self.stopped
+            || !self.immediate.is_empty()
+            || self.destructive.is_some()
+            || !self.scheduled_copies.is_empty()
+    }
+
+    /// Describe the plan as a list of actions, without running any of them.
+    pub fn planned_actions(&self, file_path: &Path) -> Vec<PlannedAction> {
+        let now = Utc::now();
+        let planned = |rule: &Rule, destination: Option<&PathBuf>, delay_minutes: u32| {
+            let action = match &rule.action {
+                Action::Move { keep_source: true, .. } => "copy",
+                Action::Move { .. } | Action::MoveByCategory { .. } => "move",
+                Action::Delete { .. } => "delete",
+                Action::Archive { .. } => "archive",
+                Action::Link { .. } => "link",
+                Action::Notify { .. } => "notify",
+                Action::RunCommand { .. } => "run_command",
+                Action::Review { .. } => "review",
+            };
+            let scheduled_for = (delay_minutes > 0).then(|| {
+                (now + chrono::Duration::minutes(delay_minutes as i64))
+                    .format("%Y-%m-%d %H:%M:%S")
+                    .to_string()
+            });
+            PlannedAction {
+                file_path: file_path.to_string_lossy().to_string(),
+                file_name: self.file_name.clone(),
+                rule_name: rule.name.clone(),
+                action: action.to_string(),
+                destination: destination.map(|d| d.to_string_lossy().to_string()),
+                scheduled_for,
+            }
+        };
+
+        let mut actions: Vec<PlannedAction> = self
+            .immediate
+            .iter()
+            .map(|rule| {
+                let destination = match &rule.action {
+                    Action::Move { destination, .. }
+                    | Action::Archive { destination, .. }
+                    | Action::Link { destination, .. } => {
+                        Some(expand_destination(destination, file_path))
+                    }
+                    Action::MoveByCategory { root } => Some(category_destination(root, file_path)),
+                    Action::Delete { .. }
+                    | Action::Notify { .. }
+                    | Action::RunCommand { .. }
+                    | Action::Review { .. } => None,
+                };
+                planned(rule, destination.as_ref(), 0)
+            })
+            .collect();
+        if !self.stopped {
+            let scheduled = self.destructive.iter().chain(self.scheduled_copies.iter());
+            actions.extend(scheduled.map(|p| planned(&p.rule, p.destination.as_ref(), p.delay_minutes)));
+        }
+        actions
+    }
+}
+
+/// A rule that can never act because a rule evaluated before it always wins.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ShadowWarning {
+    pub shadowed_rule_id: String,
+    pub by_rule_id: String,
+    /// Human-readable explanation, e.g. "…moves every file immediately"
+    pub reason: String,
+}
+
+/// How an earlier rule that matches every file keeps later rules from acting,
+/// mirroring the decisions in `plan_file_with_hashes`.
+enum Shadow {
+    /// Consumes the file immediately: evaluation stops, nothing below runs.
+    Everything,
+    /// Scheduled destructive action: later destructive rules and copies with an
+    /// equal or longer delay never win against it.
+    DelayedFrom(u32),
+}
+
+/// Static check over a folder's rules (no filesystem access): which rules are shadowed
+/// by an earlier, unconditional one. Only enabled rules with no whitelist or active
+/// window can shadow; non-destructive actions (copies, links, notify) never do. The
+/// folder's default action counts as shadowed by any always-matching rule.
+pub fn shadowed_rules(folder: &WatchedFolder) -> Vec<ShadowWarning> {
+    let ordered: Vec<&Rule> = folder.rules_by_priority().into_iter().filter(|r| r.is_enabled()).collect();
+    let mut warnings = Vec::new();
+    for (i, by) in ordered.iter().enumerate() {
+        let unconditional =
+            condition::always_matches(&by.condition) && by.whitelist.is_empty() && by.active_window.is_none();
+        if !unconditional {
+            continue;
+        }
+        let shadow = match &by.action {
+            Action::Move { keep_source: true, .. } | Action::Link { .. } | Action::Notify { .. } => None,
+            Action::Move { delay_minutes: 0, .. }
+            | Action::MoveByCategory { .. }
+            | Action::Archive { .. }
+            | Action::RunCommand { .. }
+            | Action::Review { .. } => Some(Shadow::Everything),
+            Action::Move { delay_minutes, .. } | Action::Delete { delay_minutes, .. } => {
+                Some(Shadow::DelayedFrom(*delay_minutes))
+            }
+        };
+        if let Some(shadow) = shadow {
+            for later in &ordered[i + 1..] {
+                let reason = match (&shadow, &later.action) {
+                    (Shadow::Everything, _) => format!("\"{}\" matches every file and acts on it first", by.name),
+                    (
+                        Shadow::DelayedFrom(by_delay),
+                        Action::Move { delay_minutes, .. } | Action::Delete { delay_minutes, .. },
+                    ) if delay_minutes >= by_delay && (*delay_minutes > 0 || matches!(later.action, Action::Delete { .. })) => {
+                        format!("\"{}\" matches every file and is scheduled to act on it first", by.name)
+                    }
+                    _ => continue,
+                };
+                if !warnings.iter().any(|w: &ShadowWarning| w.shadowed_rule_id == later.id) {
+                    warnings.push(ShadowWarning {
+                        shadowed_rule_id: later.id.clone(),
+                        by_rule_id: by.id.clone(),
+                        reason,
+                    });
+                }
+            }
+        }
+    }
+    // Any always-matching rule means the default action (only for unmatched files) never runs
+    let catch_all = ordered
+        .iter()
+        .find(|r| condition::always_matches(&r.condition) && r.whitelist.is_empty() && r.active_window.is_none());
+    if let (Some(default_rule), Some(by)) = (folder.default_rule(), catch_all) {
+        warnings.push(ShadowWarning {
+            shadowed_rule_id: default_rule.id,
+            by_rule_id: by.id.clone(),
+            reason: format!("\"{}\" matches every file, so no file is left for the default action", by.name),
+        });
+    }
+    warnings
+}
+
+/// Whether any of the folder's rules needs size rankings.
+pub fn folder_uses_size_rank(folder: &WatchedFolder) -> bool {
+    folder.rules.iter().any(|r| condition::uses_size_rank(&r.condition))
+}
+
+/// Rank files by size within each parent directory. Ties are broken by path so the
+/// ranking is stable between scans.
+pub fn rank_by_size(files: &[PathBuf]) -> HashMap<PathBuf, SizeRank> {
+    let mut by_dir: HashMap<&Path, Vec<(u64, &PathBuf)>> = HashMap::new();
+    for file in files {
+        let size = fs::metadata(file).map(|m| m.len()).unwrap_or(0);
+        let dir = file.parent().unwrap_or(Path::new(""));
+        by_dir.entry(dir).or_default().push((size, file));
+    }
+
+    let mut ranks = HashMap::new();
+    for (_, mut entries) in by_dir {
+        entries.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(b.1)));
+        let count = entries.len() as u32;
+        for (i, (_, file)) in entries.into_iter().enumerate() {
+            let largest = i as u32 + 1;
+            ranks.insert(file.clone(), SizeRank { largest, smallest: count - largest + 1 });
+        }
+    }
+    ranks
+}
+
+/// Size rank of one file among the files directly inside `dir`.
+fn size_rank_in_dir(dir: &Path, file_path: &Path) -> Option<SizeRank> {
+    let siblings: Vec<PathBuf> = fs::read_dir(dir)
+        .ok()?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.is_file())
+        .collect();
+    rank_by_size(&siblings).remove(file_path)
+}
+
+/// Append a note to `details` when the rule's condition read the creation time but the
+/// platform didn't provide one (so the modified time was used instead).
+fn with_time_note(details: Option<String>, rule: &Rule, ctx: &FileContext) -> Option<String> {
+    if !ctx.created_is_fallback || !condition::uses_created_time(&rule.condition) {
+        return details;
+    }
+    let note = "created time unavailable, used modified time";
+    Some(match details {
+        Some(d) => format!("{} ({})", d, note),
+        None => note.to_string(),
+    })
+}
+
 /// Schedule a file for a future action (delete or move) by inserting into the scheduled_deletions table.
 /// Uses upsert so re-scans don't create duplicates.
 /// Returns true if a new entry was inserted, false if already scheduled.
@@ -461,173 +1478,958 @@ fn execute_action(
     file_path: &Path,
     file_name: &str,
     rule: &Rule,
-    _folder: &WatchedFolder,
+    folder: &WatchedFolder,
     _db: &Database,
 ) -> RuleActionResult {
     match &rule.action {
-        Action::Move { destination, keep_source, .. } => {
-            execute_move(file_path, destination, file_name, &rule.name, *keep_source)
+        Action::Move { destination, keep_source, on_conflict, preserve_structure, .. } => {
+            match resolve_destination(rule, destination, *preserve_structure, &folder.path, file_path) {
+                Ok(destination) => execute_move(
+                    file_path,
+                    &destination,
+                    file_name,
+                    &rule.name,
+                    *keep_source,
+                    *on_conflict,
+                    rule.min_dest_free_bytes,
+                ),
+                Err(e) => plain_result(file_path, file_name, "move", &rule.name, false, Some(e)),
+            }
         }
+        Action::MoveByCategory { root } => execute_move(
+            file_path,
+            &category_destination(root, file_path),
+            file_name,
+            &rule.name,
+            false,
+            ConflictPolicy::Rename,
+            rule.min_dest_free_bytes,
+        ),
+        Action::Link { destination, hard } => execute_link(file_path, destination, file_name, &rule.name, *hard),
         Action::Delete { .. } => {
             // This branch should not be reached — Delete is handled by schedule_deletion
             unreachable!("Delete actions are handled by schedule_deletion, not execute_action")
         }
+        Action::Archive { .. } => {
+            unreachable!("Archive actions are handled by execute_archive, not execute_action")
+        }
+        Action::Notify { .. } => {
+            unreachable!("Notify actions are handled in execute_plan, not execute_action")
+        }
+        Action::RunCommand { .. } => {
+            unreachable!("RunCommand actions are handled by execute_run_command, not execute_action")
+        }
+        Action::Review { .. } => {
+            unreachable!("Review actions are handled by flag_for_review, not execute_action")
+        }
+    }
+}
+
+/// Feed an executed action into the rule's error backoff. Skips (low space, name
+/// conflict) are deliberate and count neither way.
+fn note_outcome(rule: &Rule, result: &RuleActionResult) {
+    if !result.skipped_low_space && !result.skipped_conflict {
+        crate::backoff::record_outcome(rule, result.success, result.details.as_deref());
+    }
+}
+
+/// A result with no move-specific flags set.
+fn plain_result(file_path: &Path, file_name: &str, action: &str, rule_name: &str, success: bool, details: Option<String>) -> RuleActionResult {
+    RuleActionResult {
+        file_path: file_path.to_string_lossy().to_string(),
+        file_name: file_name.to_string(),
+        action: action.to_string(),
+        rule_name: rule_name.to_string(),
+        rule_id: String::new(),
+        success,
+        details,
+        renamed_collision: false,
+        skipped_low_space: false,
+        skipped_conflict: false,
+        dest_path: None,
     }
 }
 
-fn execute_move(
+/// Add the file to the review queue. None if this rule already flagged it
+/// (pending or resolved), so re-scans stay quiet.
+fn flag_for_review(
     file_path: &Path,
-    destination: &Path,
     file_name: &str,
+    rule: &Rule,
+    folder: &WatchedFolder,
+    db: &Database,
+    reason: &str,
+) -> Option<RuleActionResult> {
+    let now = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    match db.enqueue_review(
+        &Uuid::new_v4().to_string(),
+        &file_path.to_string_lossy(),
+        file_name,
+        &folder.id,
+        rule.id(),
+        &rule.name,
+        reason,
+        &now,
+    ) {
+        Ok(false) => None,
+        Ok(true) => {
+            let details = if reason.is_empty() {
+                "Flagged for review".to_string()
+            } else {
+                format!("Flagged for review: {}", reason)
+            };
+            Some(plain_result(file_path, file_name, "review", &rule.name, true, Some(details)))
+        }
+        Err(e) => {
+            log::error!("Failed to flag {} for review: {}", file_name, e);
+            Some(plain_result(file_path, file_name, "review", &rule.name, false, Some(format!("Failed to flag for review: {}", e))))
+        }
+    }
+}
+
+/// Move a file into `destination` right away, outside rule evaluation. Renames on
+/// collision and records an undo entry tagged with `batch_id`.
+pub fn move_file_now(
+    file_path: &Path,
+    destination: &Path,
     rule_name: &str,
-    keep_source: bool,
+    db: &Database,
+    batch_id: Option<&str>,
 ) -> RuleActionResult {
-    if let Err(e) = fs::create_dir_all(destination) {
-        return RuleActionResult {
-            file_path: file_path.to_string_lossy().to_string(),
-            file_name: file_name.to_string(),
-            action: "move".to_string(),
-            rule_name: rule_name.to_string(),
-            success: false,
-            details: Some(format!("Failed to create destination: {}", friendly_io_error(&e))),
-        };
+    let file_name = file_path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    let result = execute_move(file_path, destination, &file_name, rule_name, false, ConflictPolicy::Rename, None);
+    if let Some(ref dest) = result.dest_path {
+        crate::watcher::note_own_write(dest);
+        record_move_undo(db, file_path, dest, "auto_move", batch_id);
     }
+    result
+}
 
-    let dest_file = destination.join(file_name);
-    let final_dest = if dest_file.exists() {
-        let stem = file_path.file_stem().unwrap_or_default().to_string_lossy();
-        let ext = if file_path.is_file() {
-            file_path
-                .extension()
-                .map(|e| format!(".{}", e.to_string_lossy()))
-                .unwrap_or_default()
-        } else {
-            String::new()
-        };
-        let mut counter = 1;
-        loop {
-            let candidate = destination.join(format!("{} ({}){}", stem, counter, ext));
-            if !candidate.exists() {
-                break candidate;
+/// Run the action a user approved for a reviewed file, immediately (delays are ignored).
+pub fn execute_approved_action(
+    file_path: &Path,
+    folder_root: &Path,
+    rule: &Rule,
+    action: &Action,
+    db: &Database,
+    settings: &AppSettings,
+) -> RuleActionResult {
+    let file_name = file_path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    let mut result = match action {
+        Action::Move { destination, keep_source, on_conflict, preserve_structure, .. } => {
+            let result = match resolve_destination(rule, destination, *preserve_structure, folder_root, file_path) {
+                Ok(destination) => execute_move(
+                    file_path,
+                    &destination,
+                    &file_name,
+                    &rule.name,
+                    *keep_source,
+                    *on_conflict,
+                    rule.min_dest_free_bytes,
+                ),
+                Err(e) => plain_result(file_path, &file_name, "move", &rule.name, false, Some(e)),
+            };
+            if let Some(ref dest) = result.dest_path {
+                crate::watcher::note_own_write(dest);
+                record_move_undo(db, file_path, dest, undo_action_for(action), None);
             }
-            counter += 1;
+            result
         }
-    } else {
-        dest_file
+        Action::MoveByCategory { root } => {
+            let result = execute_move(
+                file_path,
+                &category_destination(root, file_path),
+                &file_name,
+                &rule.name,
+                false,
+                ConflictPolicy::Rename,
+                rule.min_dest_free_bytes,
+            );
+            if let Some(ref dest) = result.dest_path {
+                crate::watcher::note_own_write(dest);
+                record_move_undo(db, file_path, dest, "auto_move", None);
+            }
+            result
+        }
+        Action::Link { destination, hard } => {
+            let result = execute_link(file_path, destination, &file_name, &rule.name, *hard);
+            if let Some(ref dest) = result.dest_path {
+                crate::watcher::note_own_write(dest);
+                record_move_undo(db, file_path, dest, "auto_link", None);
+            }
+            result
+        }
+        Action::Delete { .. } => {
+            let now = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+            match crate::scheduler::safe_delete(file_path, db, &now, "review_delete", None, settings.deletion_mode) {
+                Ok(()) => plain_result(file_path, &file_name, "delete", &rule.name, true, None),
+                Err(e) => plain_result(file_path, &file_name, "delete", &rule.name, false, Some(e)),
+            }
+        }
+        Action::Archive { .. } => execute_archive(file_path, &file_name, rule, db, None, settings.deletion_mode),
+        Action::Notify { message } => {
+            plain_result(file_path, &file_name, "notified", &rule.name, true, Some(message.replace("{name}", &file_name)))
+        }
+        Action::RunCommand { program, args } => {
+            execute_run_command(file_path, &file_name, program, args, &rule.name, settings.allow_run_command)
+        }
+        Action::Review { .. } => plain_result(
+            file_path,
+            &file_name,
+            "review",
+            &rule.name,
+            false,
+            Some("An approved action can't be another review".to_string()),
+        ),
     };
+    result.rule_id = rule.id.clone();
+    result
+}
 
-    let action_label = if keep_source { "copied" } else { "moved" };
-    let action_verb = if keep_source { "Copied" } else { "Moved" };
+/// Zip the file into the Archive `rule`'s `archive_name` under its `destination`, then
+/// remove the original like a deletion (`deletion_mode`, with its own undo entry).
+/// Undo of the archive itself extracts the file back out of the zip (the entry is kept).
+fn execute_archive(
+    file_path: &Path,
+    file_name: &str,
+    rule: &Rule,
+    db: &Database,
+    batch_id: Option<&str>,
+    deletion_mode: DeletionMode,
+) -> RuleActionResult {
+    let Action::Archive { destination, archive_name } = &rule.action else {
+        unreachable!("execute_archive is only called for Archive rules")
+    };
+    let rule_name = &rule.name;
+    let fail = |details: String| plain_result(file_path, file_name, "archive", rule_name, false, Some(details));
+    if file_path.is_dir() {
+        return fail("Folders can't be archived".to_string());
+    }
+    let destination = expand_destination(destination, file_path);
+    if let Err(e) = fs::create_dir_all(&destination) {
+        return fail(format!("Failed to create destination: {}", friendly_io_error(&e)));
+    }
+    let mut zip_name = expand_destination(Path::new(archive_name), file_path)
+        .to_string_lossy()
+        .to_string();
+    if !zip_name.to_lowercase().ends_with(".zip") {
+        zip_name.push_str(".zip");
+    }
+    let zip_path = destination.join(zip_name);
 
-    // Copy mode: always copy, never remove source
-    if keep_source {
-        let copy_result = if file_path.is_dir() {
-            copy_dir_recursive(file_path, &final_dest).map(|_| ())
-        } else {
-            fs::copy(file_path, &final_dest).map(|_| ())
-        };
-        return match copy_result {
-            Ok(_) => RuleActionResult {
-                file_path: file_path.to_string_lossy().to_string(),
-                file_name: file_name.to_string(),
-                action: action_label.to_string(),
-                rule_name: rule_name.to_string(),
-                success: true,
-                details: Some(format!("{} to {}", action_verb, final_dest.display())),
-            },
-            Err(e) => RuleActionResult {
-                file_path: file_path.to_string_lossy().to_string(),
-                file_name: file_name.to_string(),
-                action: "copy".to_string(),
-                rule_name: rule_name.to_string(),
-                success: false,
-                details: Some(format!("Copy failed: {}", friendly_io_error(&e))),
-            },
-        };
+    crate::watcher::note_own_write(&zip_path);
+    crate::watcher::note_own_write(&crate::archive::partial_path(&zip_path));
+    let entry_name = match crate::archive::append_to_zip(&zip_path, file_path) {
+        Ok(name) => name,
+        Err(e) => return fail(format!("Failed to add to {}: {}", zip_path.display(), friendly_io_error(&e))),
+    };
+
+    let now = Utc::now();
+    let now_str = now.format("%Y-%m-%d %H:%M:%S").to_string();
+    if let Err(e) = crate::scheduler::safe_delete(file_path, db, &now_str, "archive_source", batch_id, deletion_mode) {
+        // The zip has a copy, but the original is still here — report it rather than pretend
+        return fail(format!("Added to {} but the original couldn't be removed: {}", zip_path.display(), e));
     }
 
-    // Cut mode: try rename first (atomic), fallback to copy + delete
-    match fs::rename(file_path, &final_dest) {
-        Ok(_) => RuleActionResult {
-            file_path: file_path.to_string_lossy().to_string(),
-            file_name: file_name.to_string(),
-            action: action_label.to_string(),
-            rule_name: rule_name.to_string(),
-            success: true,
-            details: Some(format!("{} to {}", action_verb, final_dest.display())),
-        },
+    let _ = db.insert_undo(
+        &Uuid::new_v4().to_string(),
+        &file_path.to_string_lossy(),
+        Some(&crate::archive::undo_location(&zip_path, &entry_name)),
+        "auto_archive",
+        &now_str,
+        &(now + chrono::Duration::days(7)).format("%Y-%m-%d %H:%M:%S").to_string(),
+        batch_id,
+    );
+    let details = if entry_name == file_name {
+        format!("Archived to {}", zip_path.display())
+    } else {
+        format!("Archived to {} as {}", zip_path.display(), entry_name)
+    };
+    plain_result(file_path, file_name, "archive", rule_name, true, Some(details))
+}
+
+/// Whether a run-once action (RunCommand, Notify) of `rule` should act on this file now:
+/// never again once it succeeded, and after a failure only when the same backoff as for
+/// failed scheduled actions has passed. The record lives in `handled_files`, so pruning
+/// the activity log doesn't make it run again. A file whose size or mtime differs from
+/// the recorded one (a new download under the same name) counts as never handled.
+fn run_once_due(db: &Database, file_path: &Path, rule: &Rule, action: &str) -> bool {
+    let handled = match db.get_handled_file(&file_path.to_string_lossy(), &rule.id, action) {
+        Ok(handled) => handled,
         Err(e) => {
-            if file_path.is_dir() {
-                // Directory cross-device move: recursive copy then remove
-                match copy_dir_recursive(file_path, &final_dest) {
-                    Ok(_) => {
-                        if let Err(rm_err) = fs::remove_dir_all(file_path) {
-                            log::warn!("Copied dir to {} but failed to remove source: {}", final_dest.display(), rm_err);
-                        }
-                        RuleActionResult {
-                            file_path: file_path.to_string_lossy().to_string(),
-                            file_name: file_name.to_string(),
-                            action: action_label.to_string(),
-                            rule_name: rule_name.to_string(),
-                            success: true,
-                            details: Some(format!("{} to {}", action_verb, final_dest.display())),
-                        }
-                    }
-                    Err(copy_err) => RuleActionResult {
-                        file_path: file_path.to_string_lossy().to_string(),
-                        file_name: file_name.to_string(),
-                        action: "move".to_string(),
-                        rule_name: rule_name.to_string(),
-                        success: false,
-                        details: Some(format!(
-                            "Move failed: {}, dir copy failed: {}",
-                            friendly_io_error(&e), friendly_io_error(&copy_err)
-                        )),
-                    },
-                }
-            } else {
-                match fs::copy(file_path, &final_dest) {
-                    Ok(_) => {
-                        if let Err(rm_err) = fs::remove_file(file_path) {
-                            log::warn!("Copied file to {} but failed to remove source: {}", final_dest.display(), rm_err);
-                        }
-                        RuleActionResult {
-                            file_path: file_path.to_string_lossy().to_string(),
-                            file_name: file_name.to_string(),
-                            action: action_label.to_string(),
-                            rule_name: rule_name.to_string(),
-                            success: true,
-                            details: Some(format!("{} to {}", action_verb, final_dest.display())),
-                        }
-                    }
-                    Err(copy_err) => RuleActionResult {
-                        file_path: file_path.to_string_lossy().to_string(),
-                        file_name: file_name.to_string(),
-                        action: "move".to_string(),
-                        rule_name: rule_name.to_string(),
-                        success: false,
-                        details: Some(format!(
-                            "Move failed: {}, copy failed: {}",
-                            friendly_io_error(&e), friendly_io_error(&copy_err)
-                        )),
-                    },
-                }
-            }
+            log::warn!("Skipping {} for {}: {}", rule.name, file_path.display(), e);
+            return false;
         }
+    };
+    match handled {
+        None => true,
+        Some(handled) if handled.file_stamp.is_some() && handled.file_stamp != file_stamp(file_path) => true,
+        Some(handled) if handled.succeeded => false,
+        Some(handled) => chrono::NaiveDateTime::parse_from_str(&handled.last_attempt_at, "%Y-%m-%d %H:%M:%S")
+            .map(|last| last.and_utc() + crate::scheduler::retry_backoff(handled.attempt_count) <= Utc::now())
+            .unwrap_or(true),
     }
 }
 
-/// Recursively copy a directory and all its contents to a new location.
-pub fn copy_dir_recursive(src: &Path, dst: &Path) -> std::io::Result<()> {
-    fs::create_dir_all(dst)?;
-    for entry in fs::read_dir(src)? {
-        let entry = entry?;
-        let src_path = entry.path();
-        let dst_path = dst.join(entry.file_name());
-        if src_path.is_dir() {
-            copy_dir_recursive(&src_path, &dst_path)?;
-        } else {
-            fs::copy(&src_path, &dst_path)?;
+/// Remember that a run-once action ran on this file, for `run_once_due`. The stamp is
+/// taken after the run, so a program that rewrites the file doesn't trigger itself again.
+fn record_run_once(db: &Database, file_path: &Path, rule: &Rule, folder: &WatchedFolder, action: &str, succeeded: bool) {
+    let now = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    let stamp = file_stamp(file_path);
+    if let Err(e) = db.record_handled_file(&file_path.to_string_lossy(), &rule.id, &folder.id, action, stamp.as_deref(), succeeded, &now) {
+        log::warn!("Could not record {} on {}: {}", rule.name, file_path.display(), e);
+    }
+}
+
+/// A file's size and mtime as `size:rfc3339`, telling apart different files that
+/// appeared at the same path. None if it can't be read.
+fn file_stamp(path: &Path) -> Option<String> {
+    let meta = fs::metadata(path).ok()?;
+    let modified = DateTime::<Utc>::from(meta.modified().ok()?).to_rfc3339();
+    Some(format!("{}:{}", meta.len(), modified))
+}
+
+/// Bytes of stdout/stderr kept in a RunCommand result's details.
+const RUN_COMMAND_OUTPUT_LIMIT: usize = 500;
+
+/// How long a RunCommand program may run before it is killed and the run counts as failed.
+const RUN_COMMAND_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// Run a RunCommand rule's program on the file and wait for it to exit, up to
+/// `RUN_COMMAND_TIMEOUT`. Success means the program exited with status 0.
+fn execute_run_command(
+    file_path: &Path,
+    file_name: &str,
+    program: &str,
+    args: &[String],
+    rule_name: &str,
+    allowed: bool,
+) -> RuleActionResult {
+    let result = |success: bool, details: String| plain_result(file_path, file_name, "run_command", rule_name, success, Some(details));
+    if !allowed {
+        return result(false, "Running commands is turned off in settings".to_string());
+    }
+
+    let path = file_path.to_string_lossy();
+    let dir = file_path.parent().map(|d| d.to_string_lossy()).unwrap_or_default();
+    let fill = |template: &str| {
+        template
+            .replace("{path}", &path)
+            .replace("{name}", file_name)
+            .replace("{dir}", &dir)
+    };
+
+    let mut command = std::process::Command::new(fill(program));
+    command.args(args.iter().map(|a| fill(a))).stdin(std::process::Stdio::null());
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        // CREATE_NO_WINDOW — don't flash a console for each file
+        command.creation_flags(0x0800_0000);
+    }
+
+    let output = match output_with_timeout(&mut command, RUN_COMMAND_TIMEOUT) {
+        Ok(Some(output)) => output,
+        Ok(None) => return result(false, format!("Killed after running for {} s", RUN_COMMAND_TIMEOUT.as_secs())),
+        Err(e) => return result(false, format!("Failed to start {}: {}", program, friendly_io_error(&e))),
+    };
+    let mut details = match output.status.code() {
+        Some(code) => format!("Exited with code {}", code),
+        None => "Terminated by a signal".to_string(),
+    };
+    for (label, bytes) in [("stdout", &output.stdout), ("stderr", &output.stderr)] {
+        let text = truncated_output(bytes);
+        if !text.is_empty() {
+            details.push_str(&format!("; {}: {}", label, text));
+        }
+    }
+    result(output.status.success(), details)
+}
+
+/// How long to wait for a finished program's output once it has exited. A background
+/// process it started may keep the pipes open indefinitely.
+const RUN_COMMAND_OUTPUT_GRACE: Duration = Duration::from_secs(2);
+
+/// Like `Command::output`, but kills the program if it hasn't exited within `timeout`
+/// and returns None. Pipes are drained on their own threads so a chatty program can't
+/// block on a full pipe while we wait; they're never waited on for long, since a
+/// grandchild that inherited them can hold them open after the program exits.
+fn output_with_timeout(command: &mut std::process::Command, timeout: Duration) -> std::io::Result<Option<std::process::Output>> {
+    use std::io::Read;
+    use std::process::Stdio;
+    use std::sync::mpsc;
+
+    fn drain<R: Read + Send + 'static>(pipe: Option<R>) -> mpsc::Receiver<Vec<u8>> {
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let mut bytes = Vec::new();
+            if let Some(mut pipe) = pipe {
+                let _ = pipe.read_to_end(&mut bytes);
+            }
+            let _ = tx.send(bytes);
+        });
+        rx
+    }
+
+    let mut child = command.stdout(Stdio::piped()).stderr(Stdio::piped()).spawn()?;
+    let stdout = drain(child.stdout.take());
+    let stderr = drain(child.stderr.take());
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            // Grandchildren may still hold the pipes open; leave the readers to finish on their own
+            return Ok(None);
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    };
+    let collect = |rx: mpsc::Receiver<Vec<u8>>| rx.recv_timeout(RUN_COMMAND_OUTPUT_GRACE).unwrap_or_default();
+    Ok(Some(std::process::Output { status, stdout: collect(stdout), stderr: collect(stderr) }))
+}
+
+/// The first `RUN_COMMAND_OUTPUT_LIMIT` bytes of a program's output, trimmed, with
+/// "…" when cut short.
+fn truncated_output(bytes: &[u8]) -> String {
+    let text = String::from_utf8_lossy(&bytes[..bytes.len().min(RUN_COMMAND_OUTPUT_LIMIT)]);
+    let text = text.trim();
+    if bytes.len() > RUN_COMMAND_OUTPUT_LIMIT {
+        format!("{}…", text)
+    } else {
+        text.to_string()
+    }
+}
+
+/// `name (1).ext`, `name (2).ext`, … in `destination`, for renaming around a collision.
+fn collision_candidates<'a>(destination: &'a Path, file_path: &Path) -> impl Iterator<Item = PathBuf> + 'a {
+    let stem = file_path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+    let ext = if file_path.is_file() {
+        file_path
+            .extension()
+            .map(|e| format!(".{}", e.to_string_lossy()))
+            .unwrap_or_default()
+    } else {
+        String::new()
+    };
+    (1..).map(move |counter| destination.join(format!("{} ({}){}", stem, counter, ext)))
+}
+
+/// A link in `destination` that already points at `file_path`: the plain name or a
+/// collision-renamed one.
+fn existing_link(destination: &Path, file_path: &Path) -> Option<PathBuf> {
+    let file_name = file_path.file_name()?;
+    std::iter::once(destination.join(file_name))
+        .chain(collision_candidates(destination, file_path))
+        .take_while(|candidate| candidate.symlink_metadata().is_ok())
+        .find(|candidate| links_to(candidate, file_path))
+}
+
+/// Whether `link` is a symlink to `target`, or a hard link to the same file.
+fn links_to(link: &Path, target: &Path) -> bool {
+    let Ok(meta) = link.symlink_metadata() else {
+        return false;
+    };
+    if meta.file_type().is_symlink() {
+        return fs::read_link(link).is_ok_and(|to| to == target);
+    }
+    let Ok(target_meta) = fs::metadata(target) else {
+        return false;
+    };
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        meta.dev() == target_meta.dev() && meta.ino() == target_meta.ino()
+    }
+    #[cfg(not(unix))]
+    {
+        // Hard links share their metadata; std has no stable file ID on Windows
+        meta.is_file() && meta.len() == target_meta.len() && meta.modified().ok() == target_meta.modified().ok()
+    }
+}
+
+/// Create a symbolic (or hard) link to the file in `destination`, renaming around
+/// any name collision. The original is never touched.
+fn execute_link(
+    file_path: &Path,
+    destination: &Path,
+    file_name: &str,
+    rule_name: &str,
+    hard: bool,
+) -> RuleActionResult {
+    let fail = |details: String| plain_result(file_path, file_name, "link", rule_name, false, Some(details));
+    if hard && file_path.is_dir() {
+        return fail("Folders can't be hard-linked".to_string());
+    }
+    let destination = expand_destination(destination, file_path);
+    if let Err(e) = fs::create_dir_all(&destination) {
+        return fail(format!("Failed to create destination: {}", friendly_io_error(&e)));
+    }
+
+    let dest_file = destination.join(file_name);
+    let renamed_collision = dest_file.symlink_metadata().is_ok();
+    let link_path = if renamed_collision {
+        collision_candidates(&destination, file_path)
+            .find(|candidate| candidate.symlink_metadata().is_err())
+            .expect("unbounded counter always finds a free name")
+    } else {
+        dest_file
+    };
+
+    let created = if hard {
+        fs::hard_link(file_path, &link_path)
+    } else {
+        create_symlink(file_path, &link_path)
+    };
+    if let Err(e) = created {
+        return fail(format!("Failed to create link: {}", friendly_link_error(&e)));
+    }
+
+    let kind = if hard { "Hard-linked" } else { "Linked" };
+    let collision_note = if renamed_collision { " (renamed to avoid a name collision)" } else { "" };
+    RuleActionResult {
+        renamed_collision,
+        dest_path: Some(link_path.clone()),
+        ..plain_result(
+            file_path,
+            file_name,
+            "linked",
+            rule_name,
+            true,
+            Some(format!("{} at {}{}", kind, link_path.display(), collision_note)),
+        )
+    }
+}
+
+#[cfg(unix)]
+fn create_symlink(target: &Path, link: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(target, link)
+}
+
+#[cfg(windows)]
+fn create_symlink(target: &Path, link: &Path) -> std::io::Result<()> {
+    if target.is_dir() {
+        std::os::windows::fs::symlink_dir(target, link)
+    } else {
+        std::os::windows::fs::symlink_file(target, link)
+    }
+}
+
+/// Like `friendly_io_error`, explaining the Windows symlink privilege requirement.
+fn friendly_link_error(e: &std::io::Error) -> String {
+    // ERROR_PRIVILEGE_NOT_HELD
+    if cfg!(windows) && e.raw_os_error() == Some(1314) {
+        return "Symbolic links need Developer Mode or administrator rights on Windows — \
+                enable one, or use a hard link"
+            .to_string();
+    }
+    friendly_io_error(e)
+}
+
+/// Where a Move rule sends `file_path`: `destination` with its date/extension placeholders
+/// (`expand_destination`) and `{1}`, `{2}`, … regex capture groups filled in, plus the
+/// file's subfolders below `folder_root` when `preserve_structure` is set.
+/// Errors when the destination references a capture group that didn't match, or one
+/// whose value isn't a plain folder name.
+pub(crate) fn resolve_destination(
+    rule: &Rule,
+    destination: &Path,
+    preserve_structure: bool,
+    folder_root: &Path,
+    file_path: &Path,
+) -> Result<PathBuf, String> {
+    let destination = expand_captures(&expand_destination(destination, file_path), rule, file_path)?;
+    if !preserve_structure {
+        return Ok(destination);
+    }
+    match file_path.parent().and_then(|dir| dir.strip_prefix(folder_root).ok()) {
+        Some(relative_dir) => Ok(destination.join(relative_dir)),
+        None => Ok(destination),
+    }
+}
+
+/// Replace `{1}`, `{2}`, … in `destination` with the capture groups of the rule's regex
+/// condition matched against the file name.
+fn expand_captures(destination: &Path, rule: &Rule, file_path: &Path) -> Result<PathBuf, String> {
+    let text = destination.to_string_lossy();
+    if !text.contains('{') {
+        return Ok(destination.to_path_buf());
+    }
+    let placeholder = Regex::new(r"\{(\d+)\}").unwrap();
+    if !placeholder.is_match(&text) {
+        return Ok(destination.to_path_buf());
+    }
+    let file_name = file_path.file_name().unwrap_or_default().to_string_lossy();
+    let captures = condition::regex_captures(&rule.condition, &file_name).ok_or_else(|| {
+        format!(
+            "Destination {} uses capture groups, but rule '{}' has no regex condition matching '{}'",
+            text, rule.name, file_name
+        )
+    })?;
+
+    let mut missing = None;
+    let mut unsafe_value = None;
+    let expanded = placeholder.replace_all(&text, |caps: &regex::Captures| {
+        let index: usize = caps[1].parse().unwrap_or(usize::MAX);
+        match captures.get(index).cloned().flatten() {
+            Some(value) if is_path_segment(&value) => value,
+            Some(value) => {
+                unsafe_value.get_or_insert((index, value));
+                String::new()
+            }
+            None => {
+                missing.get_or_insert(index);
+                String::new()
+            }
+        }
+    });
+    if let Some(index) = missing {
+        return Err(format!(
+            "Destination {} uses {{{}}}, but group {} of rule '{}' didn't match '{}'",
+            text, index, index, rule.name, file_name
+        ));
+    }
+    match unsafe_value {
+        Some((index, value)) => Err(format!(
+            "Destination {} uses {{{}}}, but group {} of rule '{}' captured '{}' from '{}', which isn't a single folder name",
+            text, index, index, rule.name, value, file_name
+        )),
+        None => Ok(PathBuf::from(expanded.into_owned())),
+    }
+}
+
+/// True if a capture can stand as one folder name: not empty, `.` or `..`, and free of
+/// path separators, so it can't lead outside the destination.
+fn is_path_segment(value: &str) -> bool {
+    !matches!(value, "" | "." | "..") && !value.contains(['/', '\\'])
+}
+
+pub fn execute_move(
+    file_path: &Path,
+    destination: &Path,
+    file_name: &str,
+    rule_name: &str,
+    keep_source: bool,
+    on_conflict: ConflictPolicy,
+    min_dest_free_bytes: Option<u64>,
+) -> RuleActionResult {
+    let destination = &expand_destination(destination, file_path);
+    if let Err(e) = fs::create_dir_all(destination) {
+        return RuleActionResult {
+            file_path: file_path.to_string_lossy().to_string(),
+            file_name: file_name.to_string(),
+            action: "move".to_string(),
+            rule_name: rule_name.to_string(),
+            rule_id: String::new(),
+            success: false,
+            renamed_collision: false,
+            skipped_low_space: false,
+            skipped_conflict: false,
+            dest_path: None,
+            details: Some(format!("Failed to create destination: {}", friendly_io_error(&e))),
+        };
+    }
+
+    if let Some(min_free) = min_dest_free_bytes {
+        if let Err(reason) = check_free_space(destination, min_free, get_disk_space) {
+            log::warn!("Skipping {}: {}", file_path.display(), reason);
+            return RuleActionResult {
+                file_path: file_path.to_string_lossy().to_string(),
+                file_name: file_name.to_string(),
+                action: "move".to_string(),
+                rule_name: rule_name.to_string(),
+                rule_id: String::new(),
+                success: false,
+                renamed_collision: false,
+                skipped_low_space: true,
+                skipped_conflict: false,
+                dest_path: None,
+                details: Some(reason),
+            };
+        }
+    }
+
+    let dest_file = destination.join(file_name);
+    let mut renamed_collision = false;
+    let mut collision_note = "";
+    let final_dest = if !dest_file.exists() {
+        dest_file
+    } else {
+        let skip_reason = match on_conflict {
+            ConflictPolicy::Skip => Some("a file with this name already exists there"),
+            // Replacing a folder would delete everything in it, so never do that
+            ConflictPolicy::Overwrite | ConflictPolicy::KeepNewer if dest_file.is_dir() => {
+                Some("a folder with this name is in the way")
+            }
+            ConflictPolicy::KeepNewer if !is_newer_than(file_path, &dest_file) => {
+                Some("the existing file there is newer")
+            }
+            _ => None,
+        };
+        if let Some(reason) = skip_reason {
+            return RuleActionResult {
+                file_path: file_path.to_string_lossy().to_string(),
+                file_name: file_name.to_string(),
+                action: "move".to_string(),
+                rule_name: rule_name.to_string(),
+                rule_id: String::new(),
+                success: false,
+                renamed_collision: false,
+                skipped_low_space: false,
+                skipped_conflict: true,
+                dest_path: None,
+                details: Some(format!("Skipped: {} ({})", reason, destination.display())),
+            };
+        }
+        if on_conflict != ConflictPolicy::Rename {
+            collision_note = if on_conflict == ConflictPolicy::KeepNewer {
+                " (replaced an older file with the same name)"
+            } else {
+                " (overwrote an existing file with the same name)"
+            };
+            dest_file
+        } else {
+            renamed_collision = true;
+            collision_note = " (renamed to avoid a name collision)";
+            collision_candidates(destination, file_path)
+                .find(|candidate| !candidate.exists())
+                .expect("unbounded counter always finds a free name")
+        }
+    };
+
+    let action_label = if keep_source { "copied" } else { "moved" };
+    let action_verb = if keep_source { "Copied" } else { "Moved" };
+
+    // Copy mode: always copy, never remove source
+    if keep_source {
+        let copy_result = if file_path.is_dir() {
+            copy_dir_recursive(file_path, &final_dest).map(|_| ())
+        } else {
+            copy_file_preserving_times(file_path, &final_dest).map(|_| ())
+        };
+        return match copy_result {
+            Ok(_) => RuleActionResult {
+                file_path: file_path.to_string_lossy().to_string(),
+                file_name: file_name.to_string(),
+                action: action_label.to_string(),
+                rule_name: rule_name.to_string(),
+                rule_id: String::new(),
+                success: true,
+                renamed_collision,
+                skipped_low_space: false,
+                skipped_conflict: false,
+                dest_path: Some(final_dest.clone()),
+                details: Some(format!("{} to {}{}", action_verb, final_dest.display(), collision_note)),
+            },
+            Err(e) => RuleActionResult {
+                file_path: file_path.to_string_lossy().to_string(),
+                file_name: file_name.to_string(),
+                action: "copy".to_string(),
+                rule_name: rule_name.to_string(),
+                rule_id: String::new(),
+                success: false,
+                renamed_collision: false,
+                skipped_low_space: false,
+                skipped_conflict: false,
+                dest_path: None,
+                details: Some(format!("Copy failed: {}", friendly_io_error(&e))),
+            },
+        };
+    }
+
+    // Cut mode: try rename first (atomic), fallback to copy + delete
+    match fs::rename(file_path, &final_dest) {
+        Ok(_) => RuleActionResult {
+            file_path: file_path.to_string_lossy().to_string(),
+            file_name: file_name.to_string(),
+            action: action_label.to_string(),
+            rule_name: rule_name.to_string(),
+            rule_id: String::new(),
+            success: true,
+            renamed_collision,
+            skipped_low_space: false,
+            skipped_conflict: false,
+            dest_path: Some(final_dest.clone()),
+            details: Some(format!("{} to {}{}", action_verb, final_dest.display(), collision_note)),
+        },
+        Err(e) => {
+            if file_path.is_dir() {
+                // Directory cross-device move: recursive copy then remove
+                match copy_dir_recursive(file_path, &final_dest) {
+                    Ok(_) => {
+                        if let Err(rm_err) = fs::remove_dir_all(file_path) {
+                            log::warn!("Copied dir to {} but failed to remove source: {}", final_dest.display(), rm_err);
+                        }
+                        RuleActionResult {
+                            file_path: file_path.to_string_lossy().to_string(),
+                            file_name: file_name.to_string(),
+                            action: action_label.to_string(),
+                            rule_name: rule_name.to_string(),
+                            rule_id: String::new(),
+                            success: true,
+                            renamed_collision,
+                            skipped_low_space: false,
+                            skipped_conflict: false,
+                            dest_path: Some(final_dest.clone()),
+                            details: Some(format!("{} to {}{}", action_verb, final_dest.display(), collision_note)),
+                        }
+                    }
+                    Err(copy_err) => RuleActionResult {
+                        file_path: file_path.to_string_lossy().to_string(),
+                        file_name: file_name.to_string(),
+                        action: "move".to_string(),
+                        rule_name: rule_name.to_string(),
+                        rule_id: String::new(),
+                        success: false,
+                        renamed_collision: false,
+                        skipped_low_space: false,
+                        skipped_conflict: false,
+                        dest_path: None,
+                        details: Some(format!(
+                            "Move failed: {}, dir copy failed: {}",
+                            friendly_io_error(&e), friendly_io_error(&copy_err)
+                        )),
+                    },
+                }
+            } else {
+                match copy_file_preserving_times(file_path, &final_dest) {
+                    Ok(_) => {
+                        if let Err(rm_err) = fs::remove_file(file_path) {
+                            log::warn!("Copied file to {} but failed to remove source: {}", final_dest.display(), rm_err);
+                        }
+                        RuleActionResult {
+                            file_path: file_path.to_string_lossy().to_string(),
+                            file_name: file_name.to_string(),
+                            action: action_label.to_string(),
+                            rule_name: rule_name.to_string(),
+                            rule_id: String::new(),
+                            success: true,
+                            renamed_collision,
+                            skipped_low_space: false,
+                            skipped_conflict: false,
+                            dest_path: Some(final_dest.clone()),
+                            details: Some(format!("{} to {}{}", action_verb, final_dest.display(), collision_note)),
+                        }
+                    }
+                    Err(copy_err) => RuleActionResult {
+                        file_path: file_path.to_string_lossy().to_string(),
+                        file_name: file_name.to_string(),
+                        action: "move".to_string(),
+                        rule_name: rule_name.to_string(),
+                        rule_id: String::new(),
+                        success: false,
+                        renamed_collision: false,
+                        skipped_low_space: false,
+                        skipped_conflict: false,
+                        dest_path: None,
+                        details: Some(format!(
+                            "Move failed: {}, copy failed: {}",
+                            friendly_io_error(&e), friendly_io_error(&copy_err)
+                        )),
+                    },
+                }
+            }
+        }
+    }
+}
+
+/// Free bytes available to this process on the drive holding `path`.
+pub fn get_disk_space(path: &Path) -> std::io::Result<u64> {
+    fs4::available_space(path)
+}
+
+/// Ok if the drive holding `destination` has at least `min_free` bytes available,
+/// per the `available` provider. If free space can't be determined, the move is
+/// allowed (with a warning) rather than blocked.
+pub fn check_free_space(
+    destination: &Path,
+    min_free: u64,
+    available: impl Fn(&Path) -> std::io::Result<u64>,
+) -> Result<(), String> {
+    match available(destination) {
+        Ok(free) if free < min_free => Err(format!(
+            "Destination has {} MB free, rule requires {} MB",
+            free / 1_048_576,
+            min_free / 1_048_576
+        )),
+        Ok(_) => Ok(()),
+        Err(e) => {
+            log::warn!("Could not read free space for {}: {}", destination.display(), e);
+            Ok(())
+        }
+    }
+}
+
+/// Undo action recorded for a file an immediate action put at a new path.
+fn undo_action_for(action: &Action) -> &'static str {
+    match action {
+        Action::Move { keep_source: true, .. } => "auto_copy",
+        Action::Link { .. } => "auto_link",
+        _ => "auto_move",
+    }
+}
+
+/// Record an undo entry for an immediate move/copy (same 7-day window as scheduled actions).
+pub fn record_move_undo(db: &Database, original: &Path, dest: &Path, undo_action: &str, batch_id: Option<&str>) {
+    let now = Utc::now();
+    let expires = now + chrono::Duration::days(7);
+    let _ = db.insert_undo(
+        &Uuid::new_v4().to_string(),
+        &original.to_string_lossy(),
+        Some(&dest.to_string_lossy()),
+        undo_action,
+        &now.format("%Y-%m-%d %H:%M:%S").to_string(),
+        &expires.format("%Y-%m-%d %H:%M:%S").to_string(),
+        batch_id,
+    );
+}
+
+/// Whether `a` was modified more recently than `b`. Unreadable times count as not newer.
+fn is_newer_than(a: &Path, b: &Path) -> bool {
+    let mtime = |p: &Path| fs::metadata(p).and_then(|m| m.modified()).ok();
+    match (mtime(a), mtime(b)) {
+        (Some(a), Some(b)) => a > b,
+        _ => false,
+    }
+}
+
+/// Copy a file and carry over its modified/accessed times.
+/// `fs::rename` keeps timestamps, but `fs::copy` doesn't reliably do so on every
+/// platform — without this, archived files would all look freshly modified.
+pub fn copy_file_preserving_times(src: &Path, dst: &Path) -> std::io::Result<u64> {
+    let bytes = fs::copy(src, dst)?;
+    if let Ok(meta) = fs::metadata(src) {
+        let mtime = FileTime::from_last_modification_time(&meta);
+        let atime = FileTime::from_last_access_time(&meta);
+        if let Err(e) = filetime::set_file_times(dst, atime, mtime) {
+            log::warn!("Copied {} but failed to preserve timestamps: {}", dst.display(), e);
+        }
+    }
+    Ok(bytes)
+}
+
+/// Recursively copy a directory and all its contents to a new location.
+pub fn copy_dir_recursive(src: &Path, dst: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(dst)?;
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        let src_path = entry.path();
+        let dst_path = dst.join(entry.file_name());
+        if src_path.is_dir() {
+            copy_dir_recursive(&src_path, &dst_path)?;
+        } else {
+            copy_file_preserving_times(&src_path, &dst_path)?;
         }
     }
     Ok(())
@@ -636,6 +2438,974 @@ pub fn copy_dir_recursive(src: &Path, dst: &Path) -> std::io::Result<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::TimeZone;
+    use crate::config::DEFAULT_ACTION_RULE_NAME;
+    use crate::test_support::{evaluate_file_full, folder, move_to, rule, watched_dir};
+
+    #[test]
+    fn catch_all_rules_shadow_the_rules_below() {
+        let out = Path::new("/out");
+        let delete = |delay_minutes| Action::Delete { after_days: 0, delay_minutes };
+        let mut copy_all = rule("Copy all", "*", move_to(out));
+        if let Action::Move { keep_source, .. } = &mut copy_all.action {
+            *keep_source = true;
+        }
+        let mut f = folder(Path::new("/inbox"), vec![
+            copy_all,
+            rule("Purge", "*", delete(60)),
+            rule("PDFs", "*.pdf", move_to(out)),
+            rule("Old logs", "*.log", delete(120)),
+            rule("Quick", "*.tmp", delete(30)),
+        ]);
+        f.default_action = Some(delete(10));
+
+        let pairs: Vec<(String, String)> = shadowed_rules(&f)
+            .into_iter()
+            .map(|w| (w.shadowed_rule_id, w.by_rule_id))
+            .collect();
+        // The copy shadows nothing; the immediate move and the faster delete still win
+        assert_eq!(pairs, vec![
+            ("Old logs-id".to_string(), "Purge-id".to_string()),
+            ("default-folder-1".to_string(), "Copy all-id".to_string()),
+        ]);
+
+        f.rules.insert(0, rule("Sort all", "*", move_to(out)));
+        assert_eq!(shadowed_rules(&f).len(), 6, "an immediate catch-all move shadows everything");
+    }
+
+    #[test]
+    fn globally_ignored_extensions_skip_every_rule() {
+        let (tmp, watched) = watched_dir();
+        let file = watched.join("video.mp4.CRDOWNLOAD");
+        fs::write(&file, "x").unwrap();
+        let f = folder(&watched, vec![rule("All", "*", move_to(&tmp.path().join("out")))]);
+
+        let mut pass = ScanPass::new(&f, std::slice::from_ref(&file))
+            .with_ignored_extensions(&["crdownload".to_string()]);
+        assert!(plan_file(&file, &f, &mut pass).is_none());
+        assert!(plan_file(&file, &f, &mut ScanPass::new(&f, std::slice::from_ref(&file))).is_some());
+    }
+
+    #[test]
+    fn review_rule_enqueues_without_touching_the_file() {
+        let (tmp, watched) = watched_dir();
+        let file = watched.join("maybe-invoice.pdf");
+        fs::write(&file, "x").unwrap();
+        let review = Action::Review { reason: "unsure".to_string(), on_approve: None };
+        let f = folder(&watched, vec![
+            rule("Check", "maybe-*", review),
+            rule("PDFs", "*.pdf", move_to(&tmp.path().join("pdfs"))),
+        ]);
+        let db = Database::open_in_memory().unwrap();
+
+        match evaluate_file_full(&file, &f, &db) {
+            EvalOutcome::Action(result) => {
+                assert_eq!(result.action, "review");
+                assert_eq!(result.details.as_deref(), Some("Flagged for review: unsure"));
+            }
+            _ => panic!("expected the file to be flagged"),
+        }
+        assert!(file.exists(), "review must not move the file");
+        assert!(!tmp.path().join("pdfs").exists(), "later rules don't run");
+        let queue = db.get_pending_reviews().unwrap();
+        assert_eq!(queue.len(), 1);
+        assert_eq!(queue[0].rule_name, "Check");
+
+        // Re-scans don't flag it again
+        assert!(matches!(evaluate_file_full(&file, &f, &db), EvalOutcome::NoMatch));
+        assert_eq!(db.get_pending_reviews().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn run_command_is_refused_unless_allowed() {
+        let (_tmp, watched) = watched_dir();
+        let file = watched.join("clip.mov");
+        fs::write(&file, "x").unwrap();
+        let run = Action::RunCommand { program: "ffmpeg".to_string(), args: vec!["-i".to_string(), "{path}".to_string()] };
+        let f = folder(&watched, vec![rule("Transcode", "*.mov", run)]);
+        let db = Database::open_in_memory().unwrap();
+
+        match evaluate_file_full(&file, &f, &db) {
+            EvalOutcome::Action(result) => {
+                assert!(!result.success);
+                assert_eq!(result.details.as_deref(), Some("Running commands is turned off in settings"));
+            }
+            _ => panic!("expected a refused run_command result"),
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn run_command_substitutes_placeholders_and_runs_once() {
+        let (_tmp, watched) = watched_dir();
+        let file = watched.join("clip.mov");
+        fs::write(&file, "x").unwrap();
+        let run = Action::RunCommand {
+            program: "sh".to_string(),
+            args: vec!["-c".to_string(), "echo \"$0 in $1\"; exit 3".to_string(), "{name}".to_string(), "{dir}".to_string()],
+        };
+        let f = folder(&watched, vec![rule("Transcode", "*.mov", run)]);
+        let db = Database::open_in_memory().unwrap();
+        let evaluate = || {
+            let mut pass = ScanPass::for_file(&f, &file).with_run_command_allowed(true);
+            evaluate_file_in_pass(&file, &f, &db, &mut pass)
+        };
+
+        let EvalOutcome::Action(result) = evaluate() else { panic!("expected the command to run") };
+        assert!(!result.success, "non-zero exit is a failure");
+        assert_eq!(
+            result.details,
+            Some(format!("Exited with code 3; stdout: clip.mov in {}", watched.display()))
+        );
+
+        // A failure is retried, but only once its backoff has passed
+        assert!(matches!(evaluate(), EvalOutcome::NoMatch));
+        let path = file.to_string_lossy();
+        let stamp = file_stamp(&file);
+        db.record_handled_file(&path, "Transcode-id", "folder-1", "run_command", stamp.as_deref(), false, "2000-01-01 00:00:00").unwrap();
+        assert!(matches!(evaluate(), EvalOutcome::Action(_)));
+
+        // Once it has succeeded for a file, later scans skip it
+        let now = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        db.record_handled_file(&path, "Transcode-id", "folder-1", "run_command", stamp.as_deref(), true, &now).unwrap();
+        assert!(matches!(evaluate(), EvalOutcome::NoMatch));
+
+        // ...but a new file downloaded under the same name is run on again
+        fs::write(&file, "a different clip").unwrap();
+        assert!(matches!(evaluate(), EvalOutcome::Action(_)));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn hung_programs_are_killed_after_the_timeout() {
+        let started = Instant::now();
+        let mut command = std::process::Command::new("sleep");
+        command.arg("30");
+        assert!(output_with_timeout(&mut command, Duration::from_millis(200)).unwrap().is_none());
+        assert!(started.elapsed() < Duration::from_secs(10));
+
+        let mut command = std::process::Command::new("echo");
+        command.arg("done");
+        let output = output_with_timeout(&mut command, Duration::from_secs(10)).unwrap().unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "done\n");
+
+        // A background process left holding the pipes doesn't keep us waiting
+        let started = Instant::now();
+        let mut command = std::process::Command::new("sh");
+        command.args(["-c", "echo started; sleep 30 &"]);
+        let output = output_with_timeout(&mut command, Duration::from_secs(10)).unwrap().unwrap();
+        assert!(output.status.success());
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+
+    #[test]
+    fn notify_logs_without_touching_the_file_and_lets_later_rules_run() {
+        let (_tmp, watched) = watched_dir();
+        let file = watched.join("setup.exe");
+        fs::write(&file, "x").unwrap();
+        let notify = Action::Notify { message: "{name} downloaded".to_string() };
+        let delete = Action::Delete { after_days: 0, delay_minutes: 60 };
+        let f = folder(&watched, vec![rule("Installers", "*.exe", notify), rule("Cleanup", "*", delete)]);
+        let db = Database::open_in_memory().unwrap();
+        let mut pass = ScanPass::for_file(&f, &file);
+
+        assert!(matches!(evaluate_file_in_pass(&file, &f, &db, &mut pass), EvalOutcome::Scheduled { .. }));
+        let notices = pass.take_notifications();
+        assert_eq!(notices.len(), 1);
+        assert_eq!(notices[0].action, "notified");
+        assert!(notices[0].success);
+        assert_eq!(notices[0].details.as_deref(), Some("setup.exe downloaded"));
+        assert!(file.exists());
+        assert!(db.is_file_scheduled(&file.to_string_lossy()), "later rules still run");
+
+        // Re-scans don't notify again
+        assert!(matches!(evaluate_file_in_pass(&file, &f, &db, &mut pass), EvalOutcome::Scheduled { .. }));
+        assert!(pass.take_notifications().is_empty());
+    }
+
+    #[test]
+    fn notify_before_a_move_still_notifies() {
+        let (tmp, watched) = watched_dir();
+        let file = watched.join("setup.exe");
+        fs::write(&file, "x").unwrap();
+        let notify = Action::Notify { message: "{name} sorted".to_string() };
+        let f = folder(&watched, vec![
+            rule("Installers", "*.exe", notify),
+            rule("Move", "*.exe", move_to(&tmp.path().join("installers"))),
+        ]);
+        let db = Database::open_in_memory().unwrap();
+        let mut pass = ScanPass::for_file(&f, &file);
+
+        let EvalOutcome::Action(result) = evaluate_file_in_pass(&file, &f, &db, &mut pass) else { panic!("expected a move") };
+        assert_eq!(result.rule_name, "Move");
+        let notices = pass.take_notifications();
+        assert_eq!(notices.len(), 1);
+        assert_eq!(notices[0].details.as_deref(), Some("setup.exe sorted"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn link_rule_renames_around_collisions_and_links_once() {
+        let (tmp, watched) = watched_dir();
+        let file = watched.join("contract.pdf");
+        fs::write(&file, "x").unwrap();
+        let by_client = tmp.path().join("clients");
+        fs::create_dir_all(&by_client).unwrap();
+        fs::write(by_client.join("contract.pdf"), "someone else's").unwrap();
+        let link = Action::Link { destination: by_client.clone(), hard: false };
+        let f = folder(&watched, vec![rule("Shortcuts", "*.pdf", link)]);
+        let db = Database::open_in_memory().unwrap();
+
+        let EvalOutcome::Action(result) = evaluate_file_full(&file, &f, &db) else { panic!("expected a link") };
+        let link_path = by_client.join("contract (1).pdf");
+        assert_eq!(result.action, "linked");
+        assert!(result.renamed_collision);
+        assert_eq!(
+            result.details,
+            Some(format!("Linked at {} (renamed to avoid a name collision)", link_path.display()))
+        );
+        assert_eq!(fs::read_link(&link_path).unwrap(), file);
+        assert!(file.exists(), "the original stays put");
+        assert_eq!(db.get_undo_entries().unwrap()[0].action, "auto_link");
+
+        // Already linked: later scans leave it alone
+        assert!(matches!(evaluate_file_full(&file, &f, &db), EvalOutcome::NoMatch));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn hard_link_shares_the_file() {
+        let (tmp, watched) = watched_dir();
+        let file = watched.join("photo.jpg");
+        fs::write(&file, "pixels").unwrap();
+        let dest = tmp.path().join("album");
+        let f = folder(&watched, vec![rule("Album", "*.jpg", Action::Link { destination: dest.clone(), hard: true })]);
+        let db = Database::open_in_memory().unwrap();
+
+        let EvalOutcome::Action(result) = evaluate_file_full(&file, &f, &db) else { panic!("expected a link") };
+        assert_eq!(result.details, Some(format!("Hard-linked at {}", dest.join("photo.jpg").display())));
+        assert!(links_to(&dest.join("photo.jpg"), &file));
+        assert!(matches!(evaluate_file_full(&file, &f, &db), EvalOutcome::NoMatch));
+    }
+
+    #[test]
+    fn rules_outside_their_active_window_wait() {
+        use crate::config::TimeWindow;
+        use chrono::{NaiveDate, Weekday};
+        let (tmp, watched) = watched_dir();
+        let file = watched.join("song.mp3");
+        fs::write(&file, "x").unwrap();
+        let mut nightly = rule("Nightly", "*.mp3", move_to(&tmp.path().join("music")));
+        nightly.active_window = Some(TimeWindow { start_hour: 22, end_hour: 6, days: vec![Weekday::Fri] });
+        let f = folder(&watched, vec![nightly]);
+        // 2026-03-06 is a Friday
+        let at = |day: u32, hour: u32| NaiveDate::from_ymd_opt(2026, 3, day).unwrap().and_hms_opt(hour, 30, 0).unwrap();
+        let plan_at = |now| plan_file(&file, &f, &mut ScanPass::for_file(&f, &file).with_local_now(now)).unwrap();
+        let plans_at = |now| !plan_at(now).planned_actions(&file).is_empty();
+
+        assert!(!plans_at(at(6, 14)), "Friday afternoon is outside the window");
+        assert!(plan_at(at(6, 14)).is_handled(), "the file is deferred, not left to later rules");
+        assert!(plans_at(at(6, 23)));
+        assert!(plans_at(at(7, 2)), "Friday's window runs into Saturday morning");
+        assert!(!plans_at(at(7, 23)), "Saturday's own window isn't enabled");
+        assert!(!plans_at(at(6, 2)), "Friday morning belongs to Thursday's window");
+    }
+
+    #[test]
+    fn schedules_survive_scans_outside_the_rule_window() {
+        use crate::config::TimeWindow;
+        use chrono::NaiveDate;
+        let (_tmp, watched) = watched_dir();
+        let file = watched.join("old.log");
+        fs::write(&file, "x").unwrap();
+        let mut office = rule("Old logs", "*.log", Action::Delete { after_days: 0, delay_minutes: 60 });
+        office.active_window = Some(TimeWindow { start_hour: 9, end_hour: 17, days: Vec::new() });
+        let f = folder(&watched, vec![office]);
+        let db = Database::open_in_memory().unwrap();
+        let at = |hour: u32| NaiveDate::from_ymd_opt(2026, 3, 9).unwrap().and_hms_opt(hour, 0, 0).unwrap();
+        let evaluate_at = |hour| evaluate_file_in_pass(&file, &f, &db, &mut ScanPass::for_file(&f, &file).with_local_now(at(hour)));
+
+        assert!(matches!(evaluate_at(10), EvalOutcome::Scheduled { .. }));
+        assert!(matches!(evaluate_at(20), EvalOutcome::NoMatch));
+        assert_eq!(db.get_scheduled_deletions().unwrap().len(), 1, "the evening scan keeps the schedule");
+    }
+
+    #[test]
+    fn time_window_edges() {
+        use crate::config::TimeWindow;
+        use chrono::NaiveDate;
+        let at = |hour: u32| NaiveDate::from_ymd_opt(2026, 3, 9).unwrap().and_hms_opt(hour, 0, 0).unwrap();
+        let office = TimeWindow { start_hour: 9, end_hour: 17, days: Vec::new() };
+        assert!(office.contains(at(9)));
+        assert!(!office.contains(at(17)), "end hour is exclusive");
+        let all_day = TimeWindow { start_hour: 0, end_hour: 0, days: Vec::new() };
+        assert!(all_day.contains(at(3)));
+    }
+
+    #[test]
+    fn truncated_output_marks_cut_off_text() {
+        assert_eq!(truncated_output(b"  done\n"), "done");
+        let long = vec![b'a'; RUN_COMMAND_OUTPUT_LIMIT + 1];
+        assert_eq!(truncated_output(&long), format!("{}…", "a".repeat(RUN_COMMAND_OUTPUT_LIMIT)));
+    }
+
+    #[test]
+    fn archive_rule_zips_into_templated_name_and_removes_the_original() {
+        let (tmp, watched) = watched_dir();
+        let archive = tmp.path().join("archive");
+        let file = watched.join("app.log");
+        fs::write(&file, "log line").unwrap();
+        let mtime = FileTime::from_unix_time(Local.with_ymd_and_hms(2026, 3, 15, 12, 0, 0).unwrap().timestamp(), 0);
+        filetime::set_file_mtime(&file, mtime).unwrap();
+
+        let zip_logs = Action::Archive { destination: archive.clone(), archive_name: "logs-{year}-{month}".to_string() };
+        let f = folder(&watched, vec![rule("Old logs", "*.log", zip_logs)]);
+        let db = Database::open_in_memory().unwrap();
+
+        let zip_path = archive.join("logs-2026-03.zip");
+        match evaluate_file_full(&file, &f, &db) {
+            EvalOutcome::Action(result) => {
+                assert!(result.success, "{:?}", result.details);
+                assert_eq!(result.details, Some(format!("Archived to {}", zip_path.display())));
+            }
+            _ => panic!("expected the file to be archived"),
+        }
+        assert!(!file.exists());
+        let undo = db.get_undo_entries().unwrap();
+        let archived = undo.iter().find(|u| u.action == "auto_archive").unwrap();
+        assert_eq!(archived.current_path, Some(crate::archive::undo_location(&zip_path, "app.log")));
+        // The original went the deletion_mode's way (staged here) and can be restored too
+        let removed = undo.iter().find(|u| u.action == "archive_source").unwrap();
+        let staged = PathBuf::from(removed.current_path.as_ref().unwrap());
+        assert_eq!(fs::read_to_string(&staged).unwrap(), "log line");
+        fs::remove_dir_all(staged.parent().unwrap()).unwrap();
+
+        crate::archive::extract_entry(&zip_path, "app.log", &file).unwrap();
+        assert_eq!(fs::read_to_string(&file).unwrap(), "log line");
+    }
+
+    #[test]
+    fn default_action_applies_when_no_rule_matches() {
+        let (tmp, watched) = watched_dir();
+        let pdfs = tmp.path().join("pdfs");
+        let archive = tmp.path().join("archive");
+        let file = watched.join("notes.txt");
+        fs::write(&file, "x").unwrap();
+
+        let mut f = folder(&watched, vec![rule("PDFs", "*.pdf", move_to(&pdfs))]);
+        f.default_action = Some(move_to(&archive));
+        let db = Database::open_in_memory().unwrap();
+
+        match evaluate_file_full(&file, &f, &db) {
+            EvalOutcome::Action(result) => {
+                assert!(result.success);
+                assert_eq!(result.rule_name, DEFAULT_ACTION_RULE_NAME);
+            }
+            _ => panic!("expected the default action to run"),
+        }
+        assert!(archive.join("notes.txt").exists());
+        assert!(!file.exists());
+    }
+
+    #[test]
+    fn default_action_skipped_when_rule_matches() {
+        let (tmp, watched) = watched_dir();
+        let pdfs = tmp.path().join("pdfs");
+        let archive = tmp.path().join("archive");
+        let file = watched.join("report.pdf");
+        fs::write(&file, "x").unwrap();
+
+        let mut f = folder(&watched, vec![rule("PDFs", "*.pdf", move_to(&pdfs))]);
+        f.default_action = Some(move_to(&archive));
+        let db = Database::open_in_memory().unwrap();
+
+        match evaluate_file_full(&file, &f, &db) {
+            EvalOutcome::Action(result) => assert_eq!(result.rule_name, "PDFs"),
+            _ => panic!("expected the matching rule to run"),
+        }
+        assert!(pdfs.join("report.pdf").exists());
+        assert!(!archive.exists());
+    }
+
+    #[test]
+    fn default_action_waits_until_file_is_old_enough() {
+        let (tmp, watched) = watched_dir();
+        let archive = tmp.path().join("archive");
+        let file = watched.join("fresh.txt");
+        fs::write(&file, "x").unwrap();
+
+        let mut f = folder(&watched, Vec::new());
+        f.default_action = Some(move_to(&archive));
+        f.default_after_days = 60;
+        let db = Database::open_in_memory().unwrap();
+
+        assert!(matches!(evaluate_file_full(&file, &f, &db), EvalOutcome::NoMatch));
+        assert!(file.exists());
+    }
+
+    #[test]
+    fn age_condition_reads_file_modified_time() {
+        let (tmp, watched) = watched_dir();
+        let shots = tmp.path().join("shots");
+        let stale = watched.join("old.png");
+        let fresh = watched.join("new.png");
+        fs::write(&stale, "x").unwrap();
+        fs::write(&fresh, "x").unwrap();
+        let forty_days_ago = FileTime::from_unix_time(Utc::now().timestamp() - 40 * 86_400, 0);
+        filetime::set_file_mtime(&stale, forty_days_ago).unwrap();
+
+        let f = folder(&watched, vec![rule("Stale shots", "*.png AND modified>30d", move_to(&shots))]);
+        let db = Database::open_in_memory().unwrap();
+
+        assert!(matches!(evaluate_file_full(&fresh, &f, &db), EvalOutcome::NoMatch));
+        assert!(matches!(evaluate_file_full(&stale, &f, &db), EvalOutcome::Action(_)));
+        assert!(shots.join("old.png").exists());
+        assert!(fresh.exists());
+    }
+
+    #[test]
+    fn rank_by_size_ranks_within_each_directory() {
+        let (_tmp, watched) = watched_dir();
+        let sub = watched.join("sub");
+        fs::create_dir_all(&sub).unwrap();
+        let files: Vec<PathBuf> = [("a", 1), ("b", 3), ("c", 2)]
+            .iter()
+            .map(|(name, size)| {
+                let path = watched.join(name);
+                fs::write(&path, vec![0u8; *size]).unwrap();
+                path
+            })
+            .chain(std::iter::once(sub.join("d")))
+            .collect();
+        fs::write(&files[3], "x").unwrap();
+
+        let ranks = rank_by_size(&files);
+        assert_eq!(ranks[&files[1]], SizeRank { largest: 1, smallest: 3 });
+        assert_eq!(ranks[&files[2]], SizeRank { largest: 2, smallest: 2 });
+        assert_eq!(ranks[&files[0]], SizeRank { largest: 3, smallest: 1 });
+        // Alone in its own directory
+        assert_eq!(ranks[&files[3]], SizeRank { largest: 1, smallest: 1 });
+        assert_eq!(size_rank_in_dir(&watched, &files[1]), Some(SizeRank { largest: 1, smallest: 3 }));
+    }
+
+    #[test]
+    fn duplicate_condition_matches_identical_content_only() {
+        let (tmp, watched) = watched_dir();
+        let archive = tmp.path().join("archive");
+        fs::create_dir_all(archive.join("2023")).unwrap();
+        fs::write(archive.join("2023/IMG_1.jpg"), "same bytes").unwrap();
+        fs::write(archive.join("other.jpg"), "diff bytes").unwrap();
+
+        let copy = watched.join("IMG_1 (1).jpg");
+        let same_size = watched.join("IMG_2.jpg");
+        let smaller = watched.join("IMG_3.jpg");
+        fs::write(&copy, "same bytes").unwrap();
+        fs::write(&same_size, "new  bytes").unwrap();
+        fs::write(&smaller, "tiny").unwrap();
+
+        let dupes = tmp.path().join("dupes");
+        let cond = format!("duplicate:{}", archive.display());
+        let f = folder(&watched, vec![rule("Dupes", &cond, move_to(&dupes))]);
+        let db = Database::open_in_memory().unwrap();
+        let files = vec![copy.clone(), same_size.clone(), smaller.clone()];
+        let mut pass = ScanPass::new(&f, &files);
+        for file in &files {
+            evaluate_file_in_pass(file, &f, &db, &mut pass);
+        }
+
+        assert!(dupes.join("IMG_1 (1).jpg").exists());
+        assert!(same_size.exists());
+        assert!(smaller.exists());
+        // Same-size files were hashed and cached; the smaller one never was
+        let key = archive.join("2023/IMG_1.jpg");
+        let modified = DateTime::<Utc>::from(fs::metadata(&key).unwrap().modified().unwrap()).to_rfc3339();
+        assert!(db.get_file_hash(&key.to_string_lossy(), 10, &modified).unwrap().is_some());
+        let modified = DateTime::<Utc>::from(fs::metadata(&smaller).unwrap().modified().unwrap()).to_rfc3339();
+        assert!(db.get_file_hash(&smaller.to_string_lossy(), 4, &modified).unwrap().is_none());
+    }
+
+    #[test]
+    fn duplicates_within_the_watched_folder_keep_the_first_copy() {
+        let (tmp, watched) = watched_dir();
+        let older = watched.join("b.txt");
+        let newer = watched.join("a.txt");
+        let tied = watched.join("c.txt");
+        for file in [&older, &newer, &tied] {
+            fs::write(file, "same bytes").unwrap();
+        }
+        filetime::set_file_mtime(&older, FileTime::from_unix_time(1_600_000_000, 0)).unwrap();
+        filetime::set_file_mtime(&newer, FileTime::from_unix_time(1_700_000_000, 0)).unwrap();
+        filetime::set_file_mtime(&tied, FileTime::from_unix_time(1_700_000_000, 0)).unwrap();
+
+        let dupes = tmp.path().join("dupes");
+        let cond = format!("duplicate:{}", watched.display());
+        let f = folder(&watched, vec![rule("Dupes", &cond, move_to(&dupes))]);
+        let db = Database::open_in_memory().unwrap();
+        let files = vec![newer.clone(), older.clone(), tied.clone()];
+        let mut pass = ScanPass::new(&f, &files);
+        for file in &files {
+            evaluate_file_in_pass(file, &f, &db, &mut pass);
+        }
+
+        assert!(older.exists(), "the oldest copy is the original");
+        assert!(dupes.join("a.txt").exists());
+        assert!(dupes.join("c.txt").exists());
+    }
+
+    #[test]
+    fn reference_index_is_reused_until_a_directory_in_it_changes() {
+        let tmp = tempfile::tempdir().unwrap();
+        let archive = tmp.path().join("archive");
+        fs::create_dir_all(archive.join("2023")).unwrap();
+        fs::write(archive.join("2023/a.jpg"), "aaa").unwrap();
+        // Old mtimes, so the changes below are seen even on coarse-grained filesystems
+        let old = FileTime::from_unix_time(1_600_000_000, 0);
+        filetime::set_file_mtime(&archive, old).unwrap();
+        filetime::set_file_mtime(archive.join("2023"), old).unwrap();
+
+        let first = reference_files_by_size(&archive);
+        assert!(Arc::ptr_eq(&first, &reference_files_by_size(&archive)), "unchanged tree isn't walked again");
+
+        fs::write(archive.join("2023/b.jpg"), "bbbb").unwrap();
+        let relisted = reference_files_by_size(&archive);
+        assert!(!Arc::ptr_eq(&first, &relisted));
+        assert_eq!(relisted[&4], vec![archive.join("2023/b.jpg")]);
+    }
+
+    #[test]
+    fn category_for_extension_maps_known_types() {
+        assert_eq!(category_for_extension("JPG"), "Images");
+        assert_eq!(category_for_extension("mkv"), "Videos");
+        assert_eq!(category_for_extension("flac"), "Audio");
+        assert_eq!(category_for_extension("docx"), "Documents");
+        assert_eq!(category_for_extension("7z"), "Archives");
+        assert_eq!(category_for_extension("rs"), "Code");
+        assert_eq!(category_for_extension("xyz"), "Other");
+        assert_eq!(category_for_extension(""), "Other");
+    }
+
+    #[test]
+    fn move_by_category_sorts_into_category_folders() {
+        let (tmp, watched) = watched_dir();
+        let root = tmp.path().join("sorted");
+        let f = folder(&watched, vec![rule("Sort", "*", Action::MoveByCategory { root: root.clone() })]);
+        let db = Database::open_in_memory().unwrap();
+
+        for name in ["photo.png", "notes.pdf", "mystery.qqq", "README"] {
+            let file = watched.join(name);
+            fs::write(&file, "x").unwrap();
+            assert!(matches!(evaluate_file_full(&file, &f, &db), EvalOutcome::Action(ref r) if r.success));
+        }
+        assert!(root.join("Images/photo.png").exists());
+        assert!(root.join("Documents/notes.pdf").exists());
+        assert!(root.join("Other/mystery.qqq").exists());
+        assert!(root.join("Other/README").exists());
+    }
+
+    #[test]
+    fn expand_destination_uses_file_mtime_and_extension() {
+        let (tmp, watched) = watched_dir();
+        let file = watched.join("Report.PDF");
+        fs::write(&file, "x").unwrap();
+        let mtime = Local.with_ymd_and_hms(2024, 6, 5, 12, 0, 0).unwrap();
+        filetime::set_file_mtime(&file, FileTime::from_unix_time(mtime.timestamp(), 0)).unwrap();
+
+        let template = tmp.path().join("sorted").join("{year}").join("{month}-{day}").join("{ext}");
+        assert_eq!(
+            expand_destination(&template, &file),
+            tmp.path().join("sorted").join("2024").join("06-05").join("pdf")
+        );
+
+        let plain = tmp.path().join("plain");
+        assert_eq!(expand_destination(&plain, &file), plain);
+        assert!(expand_destination(Path::new("/x/{ext}"), Path::new("/y/README")).ends_with("no_ext"));
+    }
+
+    #[test]
+    fn templated_move_creates_dated_subfolders() {
+        let (tmp, watched) = watched_dir();
+        let file = watched.join("photo.jpg");
+        fs::write(&file, "x").unwrap();
+        let mtime = Local.with_ymd_and_hms(2023, 12, 31, 12, 0, 0).unwrap();
+        filetime::set_file_mtime(&file, FileTime::from_unix_time(mtime.timestamp(), 0)).unwrap();
+
+        let template = tmp.path().join("sorted").join("{year}").join("{month}");
+        let f = folder(&watched, vec![rule("Photos", "*.jpg", move_to(&template))]);
+        let db = Database::open_in_memory().unwrap();
+
+        assert!(matches!(evaluate_file_full(&file, &f, &db), EvalOutcome::Action(_)));
+        assert!(tmp.path().join("sorted").join("2023").join("12").join("photo.jpg").exists());
+    }
+
+    #[test]
+    fn higher_priority_rule_wins_regardless_of_list_order() {
+        let (tmp, watched) = watched_dir();
+        let first = tmp.path().join("first");
+        let second = tmp.path().join("second");
+        let file = watched.join("a.pdf");
+        fs::write(&file, "x").unwrap();
+
+        let mut preferred = rule("Preferred", "*.pdf", move_to(&second));
+        preferred.priority = 10;
+        let f = folder(&watched, vec![rule("Listed first", "*.pdf", move_to(&first)), preferred]);
+        let db = Database::open_in_memory().unwrap();
+
+        match evaluate_file_full(&file, &f, &db) {
+            EvalOutcome::Action(result) => assert_eq!(result.rule_name, "Preferred"),
+            _ => panic!("expected an immediate move"),
+        }
+        assert!(second.join("a.pdf").exists());
+        assert!(!first.exists());
+    }
+
+    #[test]
+    fn free_space_check_uses_threshold() {
+        let dest = Path::new("/archive");
+        let gb = 1024 * 1024 * 1024;
+        assert!(check_free_space(dest, 10 * gb, |_| Ok(5 * gb)).is_err());
+        assert!(check_free_space(dest, 10 * gb, |_| Ok(20 * gb)).is_ok());
+        // Unknown free space doesn't block the move
+        assert!(check_free_space(dest, 10 * gb, |_| Err(std::io::Error::other("unsupported"))).is_ok());
+    }
+
+    #[test]
+    fn move_skipped_when_destination_lacks_space() {
+        let (tmp, watched) = watched_dir();
+        let archive = tmp.path().join("archive");
+        let file = watched.join("big.iso");
+        fs::write(&file, "x").unwrap();
+
+        let mut r = rule("Archive", "*.iso", move_to(&archive));
+        r.min_dest_free_bytes = Some(u64::MAX);
+        let f = folder(&watched, vec![r]);
+        let db = Database::open_in_memory().unwrap();
+
+        match evaluate_file_full(&file, &f, &db) {
+            EvalOutcome::Action(result) => {
+                assert!(result.skipped_low_space);
+                assert_eq!(result.result_label(), "skipped_low_space");
+            }
+            _ => panic!("expected a skipped action"),
+        }
+        assert!(file.exists());
+    }
+
+    #[test]
+    fn quota_destinations_spill_to_the_next_when_full() {
+        let (tmp, watched) = watched_dir();
+        let drive_a = tmp.path().join("a");
+        let drive_b = tmp.path().join("b");
+        let mut action = move_to(&drive_a);
+        if let Action::Move { ref mut destinations, .. } = action {
+            *destinations = vec![
+                QuotaDestination { path: drive_a.clone(), max_bytes: Some(250) },
+                QuotaDestination { path: drive_b.clone(), max_bytes: None },
+            ];
+        }
+        let f = folder(&watched, vec![rule("Media", "*.mkv", action)]);
+        let db = Database::open_in_memory().unwrap();
+
+        let files: Vec<PathBuf> = (1..=3).map(|i| watched.join(format!("ep{}.mkv", i))).collect();
+        for file in &files {
+            fs::write(file, [0u8; 100]).unwrap();
+        }
+        let mut pass = ScanPass::new(&f, &files);
+        for file in &files {
+            assert!(matches!(evaluate_file_in_pass(file, &f, &db, &mut pass), EvalOutcome::Action(_)));
+        }
+
+        // 100 + 100 fit A's 250-byte quota, the third would not
+        assert!(drive_a.join("ep1.mkv").exists());
+        assert!(drive_a.join("ep2.mkv").exists());
+        assert!(drive_b.join("ep3.mkv").exists());
+    }
+
+    #[test]
+    fn quota_usage_is_measured_on_disk_and_skips_failed_moves() {
+        let (tmp, watched) = watched_dir();
+        let drive_a = tmp.path().join("a");
+        let drive_b = tmp.path().join("b");
+        let mut action = move_to(&drive_a);
+        if let Action::Move { ref mut destinations, .. } = action {
+            *destinations = vec![
+                QuotaDestination { path: drive_a.clone(), max_bytes: Some(150) },
+                QuotaDestination { path: drive_b.clone(), max_bytes: None },
+            ];
+        }
+        let f = folder(&watched, vec![rule("Media", "*.mkv", action)]);
+        let db = Database::open_in_memory().unwrap();
+        let file = watched.join("ep1.mkv");
+
+        // A file where drive A's folder should be: the move fails and isn't counted, so
+        // once A is usable the next file in the same pass still fits its quota
+        fs::write(&drive_a, "not a folder").unwrap();
+        fs::write(&file, [0u8; 100]).unwrap();
+        let mut pass = ScanPass::for_file(&f, &file);
+        evaluate_file_in_pass(&file, &f, &db, &mut pass);
+        assert!(file.exists());
+        fs::remove_file(&drive_a).unwrap();
+        evaluate_file_in_pass(&file, &f, &db, &mut pass);
+        assert!(drive_a.join("ep1.mkv").exists());
+
+        // A later run measures what A already holds
+        fs::write(&file, [0u8; 100]).unwrap();
+        evaluate_file_full(&file, &f, &db);
+        assert!(drive_b.join("ep1.mkv").exists());
+
+        // ...so emptying A frees its quota again
+        fs::remove_file(drive_a.join("ep1.mkv")).unwrap();
+        fs::write(&file, [0u8; 100]).unwrap();
+        evaluate_file_full(&file, &f, &db);
+        assert!(drive_a.join("ep1.mkv").exists());
+    }
+
+    #[test]
+    fn quota_destination_needs_free_space_for_the_file() {
+        let (tmp, watched) = watched_dir();
+        let (a, b) = (tmp.path().join("a"), tmp.path().join("b"));
+        fs::create_dir_all(&a).unwrap();
+        fs::create_dir_all(&b).unwrap();
+        let destinations = vec![
+            QuotaDestination { path: a.clone(), max_bytes: None },
+            QuotaDestination { path: b.clone(), max_bytes: Some(1000) },
+        ];
+        let file = watched.join("movie.mkv");
+        let moved = HashMap::new();
+        let a_full = |p: &Path| Ok(if p == a { 10 } else { u64::MAX });
+        assert_eq!(pick_quota_destination(&destinations, &file, 500, &moved, a_full), Some(b.clone()));
+        assert_eq!(pick_quota_destination(&destinations, &file, 5, &moved, a_full), Some(a.clone()));
+
+        let moved = HashMap::from([(b.clone(), 600)]);
+        assert_eq!(pick_quota_destination(&destinations, &file, 500, &moved, a_full), None);
+    }
+
+    #[test]
+    fn colliding_move_is_flagged_as_renamed() {
+        let (tmp, watched) = watched_dir();
+        let pdfs = tmp.path().join("pdfs");
+        fs::create_dir_all(&pdfs).unwrap();
+        fs::write(pdfs.join("report.pdf"), "existing").unwrap();
+        let file = watched.join("report.pdf");
+        fs::write(&file, "new").unwrap();
+
+        let f = folder(&watched, vec![rule("PDFs", "*.pdf", move_to(&pdfs))]);
+        let db = Database::open_in_memory().unwrap();
+
+        match evaluate_file_full(&file, &f, &db) {
+            EvalOutcome::Action(result) => {
+                assert!(result.success);
+                assert!(result.renamed_collision);
+                assert_eq!(result.result_label(), "renamed_collision");
+            }
+            _ => panic!("expected an immediate move"),
+        }
+        assert!(pdfs.join("report (1).pdf").exists());
+    }
+
+    /// Move `report.pdf` onto an existing `pdfs/report.pdf` with the given policy and
+    /// source/existing mtimes; returns the result and the destination dir.
+    fn move_onto_existing(policy: ConflictPolicy, source_mtime: i64, existing_mtime: i64) -> (RuleActionResult, PathBuf, tempfile::TempDir) {
+        let (tmp, watched) = watched_dir();
+        let pdfs = tmp.path().join("pdfs");
+        fs::create_dir_all(&pdfs).unwrap();
+        let existing = pdfs.join("report.pdf");
+        fs::write(&existing, "existing").unwrap();
+        filetime::set_file_mtime(&existing, FileTime::from_unix_time(existing_mtime, 0)).unwrap();
+        let file = watched.join("report.pdf");
+        fs::write(&file, "new").unwrap();
+        filetime::set_file_mtime(&file, FileTime::from_unix_time(source_mtime, 0)).unwrap();
+
+        let action = Action::Move {
+            destination: pdfs.clone(),
+            delay_minutes: 0,
+            keep_source: false,
+            on_conflict: policy,
+            destinations: Vec::new(),
+            preserve_structure: false,
+        };
+        let f = folder(&watched, vec![rule("PDFs", "*.pdf", action)]);
+        let db = Database::open_in_memory().unwrap();
+        match evaluate_file_full(&file, &f, &db) {
+            EvalOutcome::Action(result) => (result, pdfs, tmp),
+            _ => panic!("expected an immediate move"),
+        }
+    }
+
+    #[test]
+    fn conflict_skip_leaves_source() {
+        let (result, pdfs, tmp) = move_onto_existing(ConflictPolicy::Skip, 2_000_000_000, 1_600_000_000);
+        assert_eq!(result.result_label(), "skipped_conflict");
+        assert!(tmp.path().join("watched/report.pdf").exists());
+        assert_eq!(fs::read_to_string(pdfs.join("report.pdf")).unwrap(), "existing");
+    }
+
+    #[test]
+    fn conflict_overwrite_replaces_existing() {
+        let (result, pdfs, _tmp) = move_onto_existing(ConflictPolicy::Overwrite, 1_600_000_000, 2_000_000_000);
+        assert_eq!(result.result_label(), "success");
+        assert!(result.details.unwrap().contains("overwrote"));
+        assert_eq!(fs::read_to_string(pdfs.join("report.pdf")).unwrap(), "new");
+        assert!(!pdfs.join("report (1).pdf").exists());
+    }
+
+    #[test]
+    fn conflict_overwrite_never_replaces_a_folder() {
+        let (tmp, watched) = watched_dir();
+        let pdfs = tmp.path().join("pdfs");
+        fs::create_dir_all(pdfs.join("report.pdf")).unwrap();
+        fs::write(pdfs.join("report.pdf/keep.txt"), "keep").unwrap();
+        let file = watched.join("report.pdf");
+        fs::write(&file, "new").unwrap();
+
+        let action = Action::Move {
+            destination: pdfs.clone(),
+            delay_minutes: 0,
+            keep_source: false,
+            on_conflict: ConflictPolicy::Overwrite,
+            destinations: Vec::new(),
+            preserve_structure: false,
+        };
+        let f = folder(&watched, vec![rule("PDFs", "*.pdf", action)]);
+        let db = Database::open_in_memory().unwrap();
+        match evaluate_file_full(&file, &f, &db) {
+            EvalOutcome::Action(result) => assert_eq!(result.result_label(), "skipped_conflict"),
+            _ => panic!("expected a skipped move"),
+        }
+        assert!(file.exists());
+        assert!(pdfs.join("report.pdf/keep.txt").exists());
+    }
+
+    #[test]
+    fn conflict_keep_newer_compares_mtimes() {
+        let (result, pdfs, _tmp) = move_onto_existing(ConflictPolicy::KeepNewer, 2_000_000_000, 1_600_000_000);
+        assert_eq!(result.result_label(), "success");
+        assert!(result.details.unwrap().contains("replaced an older file"));
+        assert_eq!(fs::read_to_string(pdfs.join("report.pdf")).unwrap(), "new");
+
+        let (result, pdfs, _tmp) = move_onto_existing(ConflictPolicy::KeepNewer, 1_600_000_000, 2_000_000_000);
+        assert_eq!(result.result_label(), "skipped_conflict");
+        assert!(result.details.unwrap().contains("newer"));
+        assert_eq!(fs::read_to_string(pdfs.join("report.pdf")).unwrap(), "existing");
+    }
+
+    #[test]
+    fn copy_preserves_modified_time() {
+        let (tmp, watched) = watched_dir();
+        let copies = tmp.path().join("copies");
+        let file = watched.join("photo.jpg");
+        fs::write(&file, "x").unwrap();
+        let old_mtime = FileTime::from_unix_time(1_600_000_000, 0);
+        filetime::set_file_mtime(&file, old_mtime).unwrap();
+
+        let copy_rule = Action::Move {
+            destination: copies.clone(),
+            delay_minutes: 0,
+            keep_source: true,
+            on_conflict: ConflictPolicy::Rename,
+            destinations: Vec::new(),
+            preserve_structure: false,
+        };
+        let f = folder(&watched, vec![rule("Copy", "*.jpg", copy_rule)]);
+        let db = Database::open_in_memory().unwrap();
+        assert!(matches!(evaluate_file_full(&file, &f, &db), EvalOutcome::Action(_)));
+
+        let copied = fs::metadata(copies.join("photo.jpg")).unwrap();
+        assert_eq!(FileTime::from_last_modification_time(&copied), old_mtime);
+        assert!(file.exists());
+    }
+
+    fn preserving_move(destination: &Path) -> Action {
+        let mut action = move_to(destination);
+        if let Action::Move { ref mut preserve_structure, .. } = action {
+            *preserve_structure = true;
+        }
+        action
+    }
+
+    #[test]
+    fn preserve_structure_recreates_subfolders() {
+        let (tmp, watched) = watched_dir();
+        let sorted = tmp.path().join("sorted");
+        let nested = watched.join("a/b");
+        fs::create_dir_all(&nested).unwrap();
+        let file = nested.join("c.pdf");
+        fs::write(&file, "x").unwrap();
+        let top = watched.join("top.pdf");
+        fs::write(&top, "y").unwrap();
+        let f = folder(&watched, vec![rule("PDFs", "*.pdf", preserving_move(&sorted))]);
+        let db = Database::open_in_memory().unwrap();
+
+        assert!(matches!(evaluate_file_full(&file, &f, &db), EvalOutcome::Action(_)));
+        assert!(matches!(evaluate_file_full(&top, &f, &db), EvalOutcome::Action(_)));
+        assert_eq!(fs::read_to_string(sorted.join("a/b/c.pdf")).unwrap(), "x");
+        assert_eq!(fs::read_to_string(sorted.join("top.pdf")).unwrap(), "y");
+    }
+
+    #[test]
+    fn preserve_structure_only_collides_within_the_same_subfolder() {
+        let (tmp, watched) = watched_dir();
+        let sorted = tmp.path().join("sorted");
+        for dir in ["x", "y"] {
+            fs::create_dir_all(watched.join(dir)).unwrap();
+            fs::write(watched.join(dir).join("report.pdf"), dir).unwrap();
+        }
+        fs::create_dir_all(sorted.join("x")).unwrap();
+        fs::write(sorted.join("x/report.pdf"), "existing").unwrap();
+        let f = folder(&watched, vec![rule("PDFs", "*.pdf", preserving_move(&sorted))]);
+        let db = Database::open_in_memory().unwrap();
+
+        for dir in ["x", "y"] {
+            evaluate_file_full(&watched.join(dir).join("report.pdf"), &f, &db);
+        }
+        assert_eq!(fs::read_to_string(sorted.join("x/report.pdf")).unwrap(), "existing");
+        assert_eq!(fs::read_to_string(sorted.join("x/report (1).pdf")).unwrap(), "x");
+        assert_eq!(fs::read_to_string(sorted.join("y/report.pdf")).unwrap(), "y");
+        assert!(!sorted.join("report.pdf").exists());
+    }
+
+    #[test]
+    fn regex_capture_groups_route_into_destination() {
+        let (tmp, watched) = watched_dir();
+        let sorted = tmp.path().join("sorted");
+        for name in ["ClientA_invoice.pdf", "ClientB_invoice.pdf", "draft.pdf"] {
+            fs::write(watched.join(name), name).unwrap();
+        }
+        let rules = vec![
+            rule("Invoices", r"/^(Client\w+?)_invoice\.pdf$/", move_to(&sorted.join("{1}"))),
+            rule("Drafts", r"/^(draft)(_v\d+)?\.pdf$/", move_to(&sorted.join("{2}"))),
+        ];
+        let f = folder(&watched, rules);
+        let db = Database::open_in_memory().unwrap();
+
+        evaluate_file_full(&watched.join("ClientA_invoice.pdf"), &f, &db);
+        evaluate_file_full(&watched.join("ClientB_invoice.pdf"), &f, &db);
+        assert!(sorted.join("ClientA/ClientA_invoice.pdf").exists());
+        assert!(sorted.join("ClientB/ClientB_invoice.pdf").exists());
+
+        match evaluate_file_full(&watched.join("draft.pdf"), &f, &db) {
+            EvalOutcome::Action(result) => {
+                assert!(!result.success);
+                assert!(result.details.unwrap().contains("{2}"));
+            }
+            _ => panic!("expected a failed move"),
+        }
+        assert!(watched.join("draft.pdf").exists());
+    }
+
+    #[test]
+    fn captures_that_leave_the_destination_are_rejected() {
+        let r = rule("Split", r"/^(.*?)_(.*)\.pdf$/", move_to(Path::new("/sorted/{1}")));
+        let dest = Path::new("/sorted/{1}");
+
+        assert_eq!(
+            expand_captures(dest, &r, Path::new("/in/Client_a.pdf")).unwrap(),
+            PathBuf::from("/sorted/Client")
+        );
+        for name in ["_a.pdf", "._a.pdf", ".._a.pdf", "a\\b_c.pdf"] {
+            let err = expand_captures(dest, &r, &Path::new("/in").join(name)).unwrap_err();
+            assert!(err.contains("single folder name"), "{name}: {err}");
+        }
+    }
 
     #[test]
     fn whitelist_matches_relative_path() {
@@ -662,4 +3432,17 @@ mod tests {
         let whitelist = vec!["*.tmp".to_string()];
         assert!(is_whitelisted_with_relative_path("cache.tmp", None, &whitelist));
     }
+
+    #[test]
+    fn whitelist_path_and_filename_patterns_coexist() {
+        let whitelist = vec!["node_modules/*".to_string(), "*.tmp".to_string()];
+        let skipped = |name: &str, rel: &str| is_whitelisted_with_relative_path(name, Some(rel), &whitelist);
+
+        assert!(skipped("index.js", "node_modules/left-pad/index.js"));
+        assert!(skipped("cache.tmp", "src/cache.tmp"));
+        assert!(!skipped("index.js", "src/index.js"));
+        // Slash-less patterns never match folder names
+        let by_name = vec!["tmp*".to_string()];
+        assert!(!is_whitelisted_with_relative_path("a.txt", Some("tmpdir/a.txt"), &by_name));
+    }
 }
@@ -1,17 +1,234 @@
 use std::fs;
-use std::collections::HashSet;
-use std::path::Path;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
 use chrono::Utc;
 use uuid::Uuid;
 
-use crate::config::AppConfig;
-use crate::db::Database;
-use crate::rules::{is_whitelisted_with_relative_path, friendly_io_error, friendly_trash_error};
+use crate::config::{is_managed_destination, normalize_folder_path, AppConfig, AppSettings, ConflictPolicy, DeletionMode, Rule, WatchedFolder};
+use crate::db::{ActivityInsert, Database};
+use crate::log_archive;
+use crate::rules::{is_whitelisted_with_relative_path, friendly_io_error, friendly_trash_error, PlannedAction, RuleProfile};
+use crate::staging;
+
+/// A temporary replacement for `scan_interval_minutes`, used for a rapid catch-up
+/// (e.g. right after enabling a folder). Reverts automatically at `expires_at`.
+#[derive(Debug, Clone, Copy)]
+pub struct ScanIntervalOverride {
+    pub interval_minutes: u32,
+    pub expires_at: Instant,
+}
+
+impl ScanIntervalOverride {
+    pub fn new(interval_minutes: u32, for_minutes: u32, now: Instant) -> Self {
+        Self {
+            interval_minutes,
+            expires_at: now + Duration::from_secs(for_minutes as u64 * 60),
+        }
+    }
+}
+
+/// Resolve the interval the scheduler loop should use right now.
+/// An active override wins over the configured interval; an expired one is cleared.
+/// Returns `(interval_minutes, override_active)`, with the interval clamped to at least 1 minute.
+pub fn effective_scan_interval(
+    configured_minutes: u32,
+    override_slot: &Mutex<Option<ScanIntervalOverride>>,
+    now: Instant,
+) -> (u32, bool) {
+    let mut slot = override_slot.lock().unwrap();
+    match *slot {
+        Some(o) if now < o.expires_at => (o.interval_minutes.max(1), true),
+        Some(_) => {
+            log::info!("Temporary scan interval expired, reverting to {} min", configured_minutes);
+            *slot = None;
+            (configured_minutes.max(1), false)
+        }
+        None => (configured_minutes.max(1), false),
+    }
+}
+
+/// How often the scheduler thread wakes up. Due scheduled actions are checked on every
+/// tick, so they run within a minute of their `due_at` whatever the scan interval is.
+pub const SCHEDULER_TICK: Duration = Duration::from_secs(60);
+
+/// What the scheduler thread should do on one tick, besides processing due actions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TickPlan {
+    /// Maintenance (and catch-up scans while an override is active) on the scan interval.
+    pub run_cycle: bool,
+    /// The once-a-day full scan.
+    pub run_daily_scan: bool,
+}
+
+/// Parse `deletion_cron`. Accepts standard 5-field expressions (`min hour dom month dow`,
+/// weekdays numbered Sun=0…Sat=6, with 7 also Sunday) as well as the `cron` crate's own form
+/// with a leading seconds field, passed through as is (weekdays numbered Sun=1…Sat=7).
+pub fn parse_deletion_cron(expr: &str) -> Result<cron::Schedule, String> {
+    let expr = expr.trim();
+    let fields: Vec<&str> = expr.split_whitespace().collect();
+    let full = match fields[..] {
+        [minute, hour, dom, month, dow] => format!("0 {} {} {} {} {}", minute, hour, dom, month, crate_weekdays(dow)),
+        _ => expr.to_string(),
+    };
+    cron::Schedule::from_str(&full).map_err(|e| format!("Invalid deletion schedule \"{}\": {}", expr, e))
+}
+
+/// Renumber a standard day-of-week field (Sun=0 or 7) for the `cron` crate (Sun=1).
+/// Names, `*` and steps are kept; a range ending on 7 (`5-7`) becomes `6-7,1`.
+fn crate_weekdays(field: &str) -> String {
+    let day = |n: &str| n.parse::<u32>().ok().filter(|n| *n <= 7).map(|n| n % 7 + 1);
+    field
+        .split(',')
+        .map(|item| {
+            let (base, step) = match item.split_once('/') {
+                Some((base, step)) => (base, Some(step)),
+                None => (item, None),
+            };
+            let base = match base.split_once('-') {
+                Some((from, to)) => match (day(from), day(to)) {
+                    (Some(from), Some(1)) if from > 1 && step.is_none() => format!("{}-7,1", from),
+                    (Some(from), Some(to)) => format!("{}-{}", from, to),
+                    _ => base.to_string(),
+                },
+                None => day(base).map_or_else(|| base.to_string(), |d| d.to_string()),
+            };
+            match step {
+                Some(step) => format!("{}/{}", base, step),
+                None => base,
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// First fire time of `schedule` at or after `at`, evaluated in local time.
+fn next_cron_fire(schedule: &cron::Schedule, at: chrono::DateTime<Utc>) -> Option<chrono::DateTime<Utc>> {
+    let from = (at - chrono::Duration::seconds(1)).with_timezone(&chrono::Local);
+    schedule.after(&from).next().map(|fire| fire.to_utc())
+}
+
+/// The `deletion_cron` the clock is following, and when it next fires.
+#[derive(Debug)]
+struct DeletionCron {
+    expr: String,
+    /// `None` if the expression doesn't parse: actions then run on every tick.
+    schedule: Option<cron::Schedule>,
+    next_fire: Option<chrono::DateTime<Utc>>,
+}
+
+/// Decides, tick by tick, when the interval-based work and the daily scan are due.
+#[derive(Debug, Default)]
+pub struct SchedulerClock {
+    last_cycle: Option<Instant>,
+    last_full_scan_day: Option<u32>,
+    last_tick_at: Option<chrono::DateTime<Utc>>,
+    deletion_cron: Option<DeletionCron>,
+}
+
+impl SchedulerClock {
+    /// `today` is the local day of the year; `woke_from_sleep` forces both the cycle and
+    /// a full scan. The first tick always runs the cycle.
+    pub fn tick(
+        &mut self,
+        now: Instant,
+        today: u32,
+        interval_minutes: u32,
+        override_active: bool,
+        woke_from_sleep: bool,
+    ) -> TickPlan {
+        let interval = Duration::from_secs(interval_minutes.max(1) as u64 * 60);
+        let run_cycle = woke_from_sleep || self.last_cycle.is_none_or(|last| now.duration_since(last) >= interval);
+        if run_cycle {
+            self.last_cycle = Some(now);
+        }
+        let run_daily_scan = self.last_full_scan_day != Some(today) || woke_from_sleep || (run_cycle && override_active);
+        TickPlan { run_cycle, run_daily_scan }
+    }
+
+    /// Record that the full scan for `today` ran, so it isn't repeated until tomorrow.
+    pub fn full_scan_done(&mut self, today: u32) {
+        self.last_full_scan_day = Some(today);
+    }
+
+    /// Record the wall-clock time of a tick, for `next_deletion_run`.
+    pub fn mark_tick(&mut self, at: chrono::DateTime<Utc>) {
+        self.last_tick_at = Some(at);
+    }
+
+    /// Whether due scheduled deletions should be processed on this tick. Without a
+    /// `deletion_cron` they run on every tick; with one, on the first tick at or after
+    /// each fire time (a fire missed while paused or asleep runs on the next tick).
+    /// Scheduled moves and copies don't wait for it.
+    pub fn deletions_due(&mut self, cron: Option<&str>, now: chrono::DateTime<Utc>) -> bool {
+        let Some(expr) = cron.map(str::trim).filter(|e| !e.is_empty()) else {
+            self.deletion_cron = None;
+            return true;
+        };
+        if self.deletion_cron.as_ref().is_none_or(|c| c.expr != expr) {
+            let schedule = parse_deletion_cron(expr)
+                .inspect_err(|e| log::warn!("{}; running due actions on every tick", e))
+                .ok();
+            let next_fire = schedule.as_ref().and_then(|s| next_cron_fire(s, now));
+            self.deletion_cron = Some(DeletionCron { expr: expr.to_string(), schedule, next_fire });
+        }
+        let Some(cron) = self.deletion_cron.as_mut() else {
+            return true;
+        };
+        let Some(schedule) = &cron.schedule else {
+            return true;
+        };
+        match cron.next_fire {
+            Some(fire) if now >= fire => {
+                cron.next_fire = next_cron_fire(schedule, now + chrono::Duration::seconds(1));
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// When the scheduler will next process a deletion due at `due`: the first tick at
+    /// or after it (and after `now`), and with a `deletion_cron`, at or after the cron
+    /// fire that lets it run. Before the first tick, ticks are counted from `now`.
+    /// `None` if the cron expression never fires again.
+    pub fn next_deletion_run(
+        &self,
+        due: chrono::DateTime<Utc>,
+        cron: Option<&str>,
+        now: chrono::DateTime<Utc>,
+    ) -> Option<chrono::DateTime<Utc>> {
+        let tick = chrono::Duration::from_std(SCHEDULER_TICK).unwrap_or(chrono::Duration::minutes(1));
+        let anchor = self.last_tick_at.unwrap_or(now);
+        let mut target = due.max(now);
+        let schedule = cron
+            .map(str::trim)
+            .filter(|e| !e.is_empty())
+            .and_then(|e| parse_deletion_cron(e).ok());
+        if let Some(schedule) = schedule {
+            // A fire that has passed but not been acted on yet runs on the next tick
+            let pending_fire = self
+                .deletion_cron
+                .as_ref()
+                .and_then(|c| c.next_fire)
+                .is_some_and(|fire| fire <= now);
+            if !(pending_fire && due <= now) {
+                target = next_cron_fire(&schedule, target)?;
+            }
+        }
+        let ticks = ((target - anchor).num_seconds() + tick.num_seconds() - 1) / tick.num_seconds();
+        Some(anchor + tick * ticks.max(1) as i32)
+    }
+}
+
+/// `app_flags` entry set once the orphaned folder data sweep has run.
+const ORPHAN_SWEEP_FLAG: &str = "orphaned_folder_data_swept";
 
 /// Run the periodic maintenance tasks (log pruning, undo cleanup, storage enforcement).
 /// This runs on the scan_interval_minutes schedule. It does NOT run deletions —
-/// deletions are handled by `process_due_deletions` on a daily schedule.
+/// those are handled by `process_due_deletions_with_config` on every scheduler tick.
 pub fn run_scheduled_cleanup(
     config: &AppConfig,
     db: &Database,
@@ -26,7 +243,13 @@ pub fn run_scheduled_cleanup(
     let retention_days = config.settings.log_retention_days;
     let cutoff = now - chrono::Duration::days(retention_days as i64);
     let cutoff_str = cutoff.format("%Y-%m-%d %H:%M:%S").to_string();
-    let _ = db.prune_old_logs(&cutoff_str);
+    if config.settings.archive_logs_before_pruning {
+        if let Err(e) = log_archive::archive_and_prune_logs(db, &log_archive::archive_dir(), &cutoff_str) {
+            log::warn!("Log archiving failed, keeping old entries: {}", e);
+        }
+    } else {
+        let _ = db.prune_old_logs(&cutoff_str);
+    }
 
     // 3. Enforce storage size limit
     let max_mb = config.settings.max_storage_mb;
@@ -40,7 +263,14 @@ pub fn run_scheduled_cleanup(
         }
     }
 
-    // 4. Clean up scheduled_deletions for files that no longer exist
+    // 4. Permanently remove staged deletions past their retention
+    let retention = Duration::from_secs(config.settings.staging_retention_days as u64 * 86_400);
+    let purged = staging::purge_older_than(&db.staging_dir, retention, std::time::SystemTime::now());
+    if purged > 0 {
+        log::info!("Purged {} staged deletions older than {} days", purged, config.settings.staging_retention_days);
+    }
+
+    // 5. Clean up scheduled_deletions for files that no longer exist
     if let Ok(all_scheduled) = db.get_scheduled_deletions() {
         for entry in all_scheduled {
             if !Path::new(&entry.file_path).exists() {
@@ -49,31 +279,164 @@ pub fn run_scheduled_cleanup(
         }
     }
 
+    // 6. Forget content hashes (Duplicate conditions) of files that are gone
+    match db.prune_missing_file_hashes() {
+        Ok(removed) if removed > 0 => log::info!("Pruned {} hashes of missing files", removed),
+        Ok(_) => {}
+        Err(e) => log::warn!("File hash cleanup failed: {}", e),
+    }
+
+    // 7. Once per database, drop rows of folders that are neither watched nor archived,
+    // left behind before purging a folder deleted its data. An empty config may be a
+    // failed load, so it never wipes everything.
+    let known: Vec<&str> = config
+        .folders
+        .iter()
+        .chain(&config.archived_folders)
+        .map(|f| f.id.as_str())
+        .collect();
+    if !known.is_empty() && !db.has_flag(ORPHAN_SWEEP_FLAG).unwrap_or(true) {
+        match db.delete_orphaned_folder_data(&known) {
+            Ok(removed) => {
+                if removed > 0 {
+                    log::info!("Removed {} DB rows of folders no longer in config", removed);
+                }
+                let _ = db.set_flag(ORPHAN_SWEEP_FLAG, &now_str);
+            }
+            Err(e) => log::warn!("Orphaned folder data cleanup failed: {}", e),
+        }
+    }
+
     log::info!("Scheduled cleanup completed at {}", now_str);
 }
 
+/// Pauses after every `batch_size` items so a large run doesn't saturate the disk
+/// or recycle bin. `batch_size == 0` never pauses.
+pub struct BatchThrottle<S: FnMut(Duration)> {
+    batch_size: u32,
+    pause: Duration,
+    started: u32,
+    sleep: S,
+}
+
+impl<S: FnMut(Duration)> BatchThrottle<S> {
+    pub fn new(batch_size: u32, pause: Duration, sleep: S) -> Self {
+        Self { batch_size, pause, started: 0, sleep }
+    }
+
+    /// Call before each item; sleeps when the previous batch is full (never before the first item).
+    fn before_item(&mut self) {
+        if self.batch_size > 0 && self.started > 0 && self.started.is_multiple_of(self.batch_size) {
+            (self.sleep)(self.pause);
+        }
+        self.started += 1;
+    }
+}
+
+/// Wait before the first retry of a failed scheduled action; doubles per failed attempt.
+const RETRY_BACKOFF_BASE: chrono::Duration = chrono::Duration::minutes(5);
+/// Longest wait between retries.
+const RETRY_BACKOFF_MAX: chrono::Duration = chrono::Duration::hours(12);
+
+/// How long to wait after the `attempts`-th failure before trying again. Also paces
+/// retries of failed run-once actions (`rules::run_once_due`).
+pub fn retry_backoff(attempts: u32) -> chrono::Duration {
+    let factor = 1i32 << attempts.saturating_sub(1).min(16);
+    (RETRY_BACKOFF_BASE * factor).min(RETRY_BACKOFF_MAX)
+}
+
+/// Whether a due entry may be attempted now: never-failed entries always can, failed
+/// ones once their backoff window has passed, given-up ones never.
+fn retry_due(entry: &crate::db::ScheduledDeletion, max_attempts: u32, now: chrono::DateTime<Utc>) -> bool {
+    if entry.attempt_count == 0 {
+        return true;
+    }
+    if entry.attempt_count >= max_attempts {
+        return false;
+    }
+    let last = entry
+        .last_attempt_at
+        .as_deref()
+        .and_then(|at| chrono::NaiveDateTime::parse_from_str(at, "%Y-%m-%d %H:%M:%S").ok());
+    match last {
+        Some(last) => last.and_utc() + retry_backoff(entry.attempt_count) <= now,
+        None => true,
+    }
+}
+
+/// Outcome of one pass over the due scheduled actions.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DueRun {
+    /// Actions that ran successfully
+    pub processed: u32,
+    /// Deletions newly held back for approval (`require_deletion_approval`)
+    pub awaiting_approval: u32,
+}
+
 /// Process due scheduled actions with optional config validation.
-/// Handles both scheduled deletions and scheduled moves.
+/// Handles both scheduled deletions and scheduled moves, pausing between batches
+/// per `deletion_batch_size` / `deletion_batch_pause_ms`. With
+/// `require_deletion_approval` set, due deletions are marked `awaiting_approval`
+/// instead and only run through `process_selected_deletions_now`. `run_deletions` false
+/// (a `deletion_cron` that isn't firing) leaves due deletions for later but still runs
+/// due moves and copies.
 pub fn process_due_deletions_with_config(
     db: &Database,
     config: Option<&AppConfig>,
-) -> u32 {
+    run_deletions: bool,
+) -> DueRun {
+    let throttle = match config {
+        Some(cfg) => BatchThrottle::new(
+            cfg.settings.deletion_batch_size,
+            Duration::from_millis(cfg.settings.deletion_batch_pause_ms as u64),
+            std::thread::sleep as fn(Duration),
+        ),
+        None => BatchThrottle::new(0, Duration::ZERO, std::thread::sleep as fn(Duration)),
+    };
+    process_due_with_throttle(db, config, throttle, run_deletions)
+}
+
+fn process_due_with_throttle<S: FnMut(Duration)>(
+    db: &Database,
+    config: Option<&AppConfig>,
+    mut throttle: BatchThrottle<S>,
+    run_deletions: bool,
+) -> DueRun {
     let now = Utc::now();
+    let default_settings = AppSettings::default();
+    let settings = config.map_or(&default_settings, |cfg| &cfg.settings);
+    let deletion_mode = settings.deletion_mode;
+    let max_attempts = settings.max_deletion_attempts.max(1);
+    if config.is_some_and(|cfg| cfg.settings.is_paused_at(now)) {
+        log::debug!("Automation is paused, leaving due scheduled actions for later");
+        return DueRun::default();
+    }
     let now_str = now.format("%Y-%m-%d %H:%M:%S").to_string();
+    let batch_id = Uuid::new_v4().to_string();
     let mut count = 0u32;
+    let mut awaiting_approval = 0u32;
     // Track file paths already consumed by a destructive action in this batch
     let mut consumed_paths: HashSet<String> = HashSet::new();
+    // Quota'd destinations are measured once per run
+    let mut quota_usage = crate::rules::QuotaUsage::default();
 
     match db.get_due_deletions(&now_str) {
         Ok(due) => {
-            for entry in due {
+            for mut entry in due {
                 // Skip if this file was already consumed by an earlier destructive action
                 if consumed_paths.contains(&entry.file_path) {
                     continue;
                 }
+                if entry.action_type == "delete" && !run_deletions {
+                    continue;
+                }
 
                 if let Some(cfg) = config {
                     let folder = cfg.folders.iter().find(|f| f.id == entry.folder_id);
+                    // Paused folder: leave the entry due so it runs once the pause ends
+                    if folder.is_some_and(|f| f.is_paused_at(now)) {
+                        continue;
+                    }
                     let should_run = match folder {
                         Some(f) if f.enabled => {
                             let relative_path = Path::new(&entry.file_path)
@@ -89,7 +452,8 @@ pub fn process_due_deletions_with_config(
                             ) {
                                 false
                             } else {
-                                f.rules.iter().any(|r| {
+                                let default_rule = f.default_rule();
+                                f.rules.iter().chain(default_rule.iter()).any(|r| {
                                     r.is_enabled()
                                         && r.name == entry.rule_name
                                         // Check rule-level whitelist
@@ -113,9 +477,14 @@ pub fn process_due_deletions_with_config(
                         let _ = db.cancel_scheduled_deletion(&entry.id);
                         continue;
                     }
+                    // Outside the rule's active window: leave it due for the next window
+                    let local_now = chrono::Local::now().naive_local();
+                    if scheduled_rule(cfg, &entry).is_some_and(|r| !r.is_active_at(local_now)) {
+                        continue;
+                    }
                 }
 
-                let path = Path::new(&entry.file_path);
+                let path = &PathBuf::from(&entry.file_path);
                 if !path.exists() {
                     // File is gone — remove ALL scheduled entries for this path
                     let _ = db.remove_scheduled_deletion_by_path(&entry.file_path);
@@ -123,22 +492,40 @@ pub fn process_due_deletions_with_config(
                     continue;
                 }
 
+                if settings.require_deletion_approval && entry.action_type == "delete" {
+                    if db.mark_deletion_awaiting_approval(&entry.id).unwrap_or(false) {
+                        awaiting_approval += 1;
+                    }
+                    continue;
+                }
+
+                // Locked or otherwise failing file: wait out the backoff before retrying
+                if !retry_due(&entry, max_attempts, now) {
+                    continue;
+                }
+
+                throttle.before_item();
                 let is_move = entry.action_type == "move";
+                let rule = config.and_then(|cfg| scheduled_rule(cfg, &entry));
+                let folder_root = config.and_then(|cfg| cfg.folders.iter().find(|f| f.id == entry.folder_id)).map(|f| f.path.clone());
                 let result = if is_move {
-                    execute_scheduled_move(path, &entry, db, &now_str)
+                    execute_scheduled_move(path, &mut entry, rule.as_ref(), folder_root.as_deref(), db, Some(&batch_id), &mut quota_usage)
                 } else {
-                    safe_delete(path, db, &now_str, "auto_delete")
+                    safe_delete(path, db, &now_str, "auto_delete", Some(&batch_id), deletion_mode).map(|_| "success")
                 };
                 let success = result.is_ok();
+                let result_label = *result.as_ref().unwrap_or(&"error");
+                let skipped = result_label == "skipped_conflict";
 
                 let action_label = if is_move {
                     if entry.keep_source { "auto_copy" } else { "auto_move" }
                 } else {
                     "auto_delete"
                 };
-                let detail = if is_move {
+                let mut detail = if is_move {
                     let verb = if entry.keep_source { "copied" } else { "moved" };
                     match &result {
+                        Ok(_) if skipped => scheduled_conflict_detail(&entry),
                         Ok(_) => format!("File {} to {}", verb, entry.move_destination.as_deref().unwrap_or("?")),
                         Err(err) => format!("Failed to {} file: {}", if entry.keep_source { "copy" } else { "move" }, err),
                     }
@@ -149,19 +536,56 @@ pub fn process_due_deletions_with_config(
                     }
                 };
 
-                let _ = db.insert_activity(
-                    &Uuid::new_v4().to_string(),
-                    &entry.file_path,
-                    &entry.file_name,
-                    action_label,
-                    Some(&entry.rule_name),
-                    Some(&entry.folder_id),
-                    &now_str,
-                    if success { "success" } else { "error" },
-                    Some(&detail),
-                );
-                if success {
+                // Failures are logged once when first seen and once when giving up;
+                // the retries in between only go to the log file.
+                let mut log_activity = true;
+                let mut gave_up = false;
+                if !success {
+                    match db.record_deletion_attempt(&entry.id, &now_str) {
+                        Ok(attempts) if attempts >= max_attempts => {
+                            gave_up = true;
+                            detail = format!("Giving up after {} attempts. {}", attempts, detail);
+                        }
+                        Ok(attempts) => {
+                            log_activity = attempts == 1;
+                            log::warn!(
+                                "Scheduled {} of {} failed (attempt {}/{}), retrying in {} min",
+                                entry.action_type,
+                                entry.file_path,
+                                attempts,
+                                max_attempts,
+                                retry_backoff(attempts).num_minutes()
+                            );
+                        }
+                        // Couldn't count the attempt: keep the entry and retry next tick
+                        // rather than treating a database hiccup as retries exhausted
+                        Err(e) => {
+                            log_activity = false;
+                            log::warn!(
+                                "Scheduled {} of {} failed ({}); couldn't record the attempt: {}",
+                                entry.action_type,
+                                entry.file_path,
+                                detail,
+                                e
+                            );
+                        }
+                    }
+                }
+
+                if log_activity {
+                    let _ = db.insert_activity_sized(&entry_activity(&entry, action_label, &now_str, result_label, detail));
+                }
+                if gave_up && !settings.keep_failed_deletions {
+                    let _ = db.cancel_scheduled_deletion(&entry.id);
+                }
+                if skipped {
+                    // The rule's conflict policy leaves the file where it is
+                    let _ = db.cancel_scheduled_deletion(&entry.id);
+                } else if success {
                     count += 1;
+                    if let Some(rule) = &rule {
+                        let _ = db.update_rule_triggered(&rule.id, &entry.folder_id, &now_str);
+                    }
                     if is_move && entry.keep_source {
                         // Copy mode: only remove this specific entry — other rules' entries survive
                         let _ = db.cancel_scheduled_deletion(&entry.id);
@@ -181,151 +605,110 @@ pub fn process_due_deletions_with_config(
     if count > 0 {
         log::info!("Processed {} due scheduled actions", count);
     }
-    count
+    if awaiting_approval > 0 {
+        log::info!("{} due deletions are awaiting approval", awaiting_approval);
+    }
+    DueRun { processed: count, awaiting_approval }
 }
 
-/// Execute a scheduled move action.
-/// Returns Ok on success, Err with a human-readable message on failure.
+/// The rule a scheduled entry was created by (looked up by folder and rule name, the
+/// folder's default action included), for the options the entry doesn't store and for
+/// stamping its `last_triggered_at`.
+fn scheduled_rule(config: &AppConfig, entry: &crate::db::ScheduledDeletion) -> Option<Rule> {
+    let folder = config.folders.iter().find(|f| f.id == entry.folder_id)?;
+    folder
+        .rules
+        .iter()
+        .cloned()
+        .chain(folder.default_rule())
+        .find(|r| r.name == entry.rule_name)
+}
+
+/// Execute a scheduled move action the way an immediate one runs: the rule's conflict
+/// policy and `min_dest_free_bytes` apply when `rule` is known (Rename and no minimum
+/// otherwise). Returns Ok with the activity result label — `success`, `renamed_collision`,
+/// or `skipped_conflict` when the policy left the file where it is — or Err with a
+/// human-readable message. A rule with quota'd `destinations` picks its destination
+/// now, against `usage`, rather than when the move was scheduled; `entry` is updated
+/// with where the file goes.
 fn execute_scheduled_move(
     file_path: &Path,
-    entry: &crate::db::ScheduledDeletion,
+    entry: &mut crate::db::ScheduledDeletion,
+    rule: Option<&Rule>,
+    folder_root: Option<&Path>,
     db: &Database,
-    now_str: &str,
-) -> Result<(), String> {
-    let destination_str = match &entry.move_destination {
-        Some(d) => d.clone(),
-        None => {
-            log::error!("Scheduled move for {} has no destination", entry.file_path);
-            return Err("No destination configured".to_string());
-        }
-    };
-    let destination = Path::new(&destination_str);
-    if let Err(e) = fs::create_dir_all(destination) {
-        log::error!("Failed to create destination {}: {}", destination.display(), e);
-        return Err(format!("Failed to create destination: {}", friendly_io_error(&e)));
-    }
-
-    let file_name = file_path.file_name().unwrap_or_default();
-    let dest_file = destination.join(file_name);
-    let final_dest = if dest_file.exists() {
-        let stem = file_path.file_stem().unwrap_or_default().to_string_lossy();
-        let ext_str = if file_path.is_file() {
-            file_path.extension().map(|e| format!(".{}", e.to_string_lossy())).unwrap_or_default()
-        } else {
-            String::new()
-        };
-        let mut counter = 1;
-        loop {
-            let candidate = destination.join(format!("{} ({}){}", stem, counter, ext_str));
-            if !candidate.exists() {
-                break candidate;
+    batch_id: Option<&str>,
+    usage: &mut crate::rules::QuotaUsage,
+) -> Result<&'static str, String> {
+    let mut claimed = None;
+    if let (Some(rule), Some(folder_root)) = (rule, folder_root) {
+        if let crate::config::Action::Move { destinations, preserve_structure, .. } = &rule.action {
+            if !destinations.is_empty() {
+                let Some(quota_rule) = crate::rules::claim_destination(rule, file_path, usage) else {
+                    return Err("Every destination is over its quota or out of space".to_string());
+                };
+                if let crate::config::Action::Move { destination, .. } = &quota_rule.action {
+                    let resolved = crate::rules::resolve_destination(&quota_rule, destination, *preserve_structure, folder_root, file_path)?;
+                    entry.move_destination = Some(resolved.to_string_lossy().to_string());
+                }
+                claimed = Some(quota_rule);
             }
-            counter += 1;
         }
-    } else {
-        dest_file
+    }
+    let size = fs::metadata(file_path).map(|m| m.len()).unwrap_or(0);
+    let Some(destination) = &entry.move_destination else {
+        log::error!("Scheduled move for {} has no destination", entry.file_path);
+        return Err("No destination configured".to_string());
     };
-
-    let keep_source = entry.keep_source;
-    let undo_action = if keep_source { "auto_copy" } else { "auto_move" };
-
-    // Copy mode: always copy, never remove source
-    if keep_source {
-        let copy_result = if file_path.is_dir() {
-            crate::rules::copy_dir_recursive(file_path, &final_dest).map(|_| ())
-        } else {
-            fs::copy(file_path, &final_dest).map(|_| ())
-        };
-        return match copy_result {
-            Ok(_) => {
-                let expires = Utc::now() + chrono::Duration::days(7);
-                let _ = db.insert_undo(
-                    &Uuid::new_v4().to_string(),
-                    &file_path.to_string_lossy(),
-                    Some(&final_dest.to_string_lossy()),
-                    undo_action,
-                    now_str,
-                    &expires.format("%Y-%m-%d %H:%M:%S").to_string(),
-                );
-                Ok(())
-            }
-            Err(e) => {
-                log::error!("Failed to copy {}: {}", file_path.display(), e);
-                Err(format!("Copy failed: {}", friendly_io_error(&e)))
-            }
-        };
+    let on_conflict = match rule.map(|r| &r.action) {
+        Some(crate::config::Action::Move { on_conflict, .. }) => *on_conflict,
+        _ => ConflictPolicy::Rename,
+    };
+    let file_name = file_path.file_name().unwrap_or_default().to_string_lossy();
+    let result = crate::rules::execute_move(
+        file_path,
+        Path::new(destination),
+        &file_name,
+        &entry.rule_name,
+        entry.keep_source,
+        on_conflict,
+        rule.and_then(|r| r.min_dest_free_bytes),
+    );
+    if result.skipped_conflict {
+        return Ok("skipped_conflict");
     }
-
-    // Cut mode: try rename first, fallback to copy + delete
-    match fs::rename(file_path, &final_dest) {
-        Ok(_) => {
-            let expires = Utc::now() + chrono::Duration::days(7);
-            let _ = db.insert_undo(
-                &Uuid::new_v4().to_string(),
-                &file_path.to_string_lossy(),
-                Some(&final_dest.to_string_lossy()),
-                undo_action,
-                now_str,
-                &expires.format("%Y-%m-%d %H:%M:%S").to_string(),
-            );
-            Ok(())
-        }
-        Err(_) => {
-            if file_path.is_dir() {
-                match crate::rules::copy_dir_recursive(file_path, &final_dest) {
-                    Ok(_) => {
-                        if let Err(rm_err) = fs::remove_dir_all(file_path) {
-                            log::warn!("Copied dir to {} but failed to remove source: {}", final_dest.display(), rm_err);
-                        }
-                        let expires = Utc::now() + chrono::Duration::days(7);
-                        let _ = db.insert_undo(
-                            &Uuid::new_v4().to_string(),
-                            &file_path.to_string_lossy(),
-                            Some(&final_dest.to_string_lossy()),
-                            undo_action,
-                            now_str,
-                            &expires.format("%Y-%m-%d %H:%M:%S").to_string(),
-                        );
-                        Ok(())
-                    }
-                    Err(e) => {
-                        log::error!("Failed to move dir {}: {}", file_path.display(), e);
-                        Err(format!("Move failed: {}", friendly_io_error(&e)))
-                    }
-                }
-            } else {
-                // Cross-device: try copy + delete
-                match fs::copy(file_path, &final_dest) {
-                    Ok(_) => {
-                        if let Err(rm_err) = fs::remove_file(file_path) {
-                            log::warn!("Copied file to {} but failed to remove source: {}", final_dest.display(), rm_err);
-                        }
-                        let expires = Utc::now() + chrono::Duration::days(7);
-                        let _ = db.insert_undo(
-                            &Uuid::new_v4().to_string(),
-                            &file_path.to_string_lossy(),
-                            Some(&final_dest.to_string_lossy()),
-                            undo_action,
-                            now_str,
-                            &expires.format("%Y-%m-%d %H:%M:%S").to_string(),
-                        );
-                        Ok(())
-                    }
-                    Err(e) => {
-                        log::error!("Failed to move {}: {}", file_path.display(), e);
-                        Err(format!("Move failed: {}", friendly_io_error(&e)))
-                    }
-                }
+    match &result.dest_path {
+        Some(dest) if result.success => {
+            crate::watcher::note_own_write(dest);
+            let undo_action = if entry.keep_source { "auto_copy" } else { "auto_move" };
+            crate::rules::record_move_undo(db, file_path, dest, undo_action, batch_id);
+            if let Some(claimed) = &claimed {
+                usage.count_bytes(claimed, size);
             }
+            Ok(result.result_label())
+        }
+        _ => {
+            let reason = result.details.unwrap_or_else(|| "Move failed".to_string());
+            log::error!("Scheduled move of {} failed: {}", file_path.display(), reason);
+            Err(reason)
         }
     }
 }
 
+/// Activity details for a scheduled move the rule's conflict policy skipped.
+fn scheduled_conflict_detail(entry: &crate::db::ScheduledDeletion) -> String {
+    format!(
+        "Skipped: a file with this name already exists in {}",
+        entry.move_destination.as_deref().unwrap_or("?")
+    )
+}
+
 /// Immediately process selected scheduled actions by IDs (ignores due date).
 /// Handles both deletions and moves. Returns the number of files successfully processed.
 pub fn process_selected_deletions_now(
     db: &Database,
     deletion_ids: &[String],
+    config: &AppConfig,
 ) -> u32 {
     if deletion_ids.is_empty() {
         return 0;
@@ -334,21 +717,28 @@ pub fn process_selected_deletions_now(
     let selected: HashSet<&str> = deletion_ids.iter().map(String::as_str).collect();
     let now = Utc::now();
     let now_str = now.format("%Y-%m-%d %H:%M:%S").to_string();
+    let batch_id = Uuid::new_v4().to_string();
     let mut count = 0u32;
+    let mut quota_usage = crate::rules::QuotaUsage::default();
 
     match db.get_scheduled_deletions() {
         Ok(all) => {
-            for entry in all.into_iter().filter(|e| selected.contains(e.id.as_str())) {
-                let path = Path::new(&entry.file_path);
+            for mut entry in all.into_iter().filter(|e| selected.contains(e.id.as_str())) {
+                let path = &PathBuf::from(&entry.file_path);
                 let is_move = entry.action_type == "move";
 
                 if path.exists() {
+                    let rule = scheduled_rule(config, &entry);
+                    let folder_root = config.folders.iter().find(|f| f.id == entry.folder_id).map(|f| f.path.clone());
                     let result = if is_move {
-                        execute_scheduled_move(path, &entry, db, &now_str)
+                        execute_scheduled_move(path, &mut entry, rule.as_ref(), folder_root.as_deref(), db, Some(&batch_id), &mut quota_usage)
                     } else {
-                        safe_delete(path, db, &now_str, "manual_delete_now")
+                        safe_delete(path, db, &now_str, "manual_delete_now", Some(&batch_id), config.settings.deletion_mode)
+                            .map(|_| "success")
                     };
                     let success = result.is_ok();
+                    let result_label = *result.as_ref().unwrap_or(&"error");
+                    let skipped = result_label == "skipped_conflict";
 
                     let action_label = if is_move {
                         if entry.keep_source { "manual_copy_now" } else { "manual_move_now" }
@@ -358,6 +748,7 @@ pub fn process_selected_deletions_now(
                     let detail = if is_move {
                         let verb = if entry.keep_source { "copied" } else { "moved" };
                         match &result {
+                            Ok(_) if skipped => scheduled_conflict_detail(&entry),
                             Ok(_) => format!("File {} to {}", verb, entry.move_destination.as_deref().unwrap_or("?")),
                             Err(err) => format!("Failed to {} file: {}", if entry.keep_source { "copy" } else { "move" }, err),
                         }
@@ -368,20 +759,15 @@ pub fn process_selected_deletions_now(
                         }
                     };
 
-                    let _ = db.insert_activity(
-                        &Uuid::new_v4().to_string(),
-                        &entry.file_path,
-                        &entry.file_name,
-                        action_label,
-                        Some(&entry.rule_name),
-                        Some(&entry.folder_id),
-                        &now_str,
-                        if success { "success" } else { "error" },
-                        Some(&detail),
-                    );
+                    let _ = db.insert_activity_sized(&entry_activity(&entry, action_label, &now_str, result_label, detail));
 
-                    if success {
+                    if skipped {
+                        let _ = db.cancel_scheduled_deletion(&entry.id);
+                    } else if success {
                         count += 1;
+                        if let Some(rule) = &rule {
+                            let _ = db.update_rule_triggered(&rule.id, &entry.folder_id, &now_str);
+                        }
                         let _ = db.remove_scheduled_deletion_by_path(&entry.file_path);
                     }
                 } else {
@@ -411,6 +797,168 @@ pub fn process_selected_deletions_now(
     count
 }
 
+/// Move every file `rule_name` has queued in `folder_id` into `destination` instead of
+/// letting its scheduled action run, removing each from the queue. Collisions are
+/// renamed and undo entries share one batch. Files already gone are just de-scheduled.
+/// Returns the number of files moved.
+pub fn archive_scheduled_deletions(
+    db: &Database,
+    folder_id: &str,
+    rule_name: &str,
+    destination: &Path,
+) -> u32 {
+    let entries = match db.get_scheduled_deletions() {
+        Ok(all) => all,
+        Err(e) => {
+            log::error!("Failed to query scheduled deletions: {}", e);
+            return 0;
+        }
+    };
+    let now_str = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    let batch_id = Uuid::new_v4().to_string();
+    let mut count = 0u32;
+
+    for entry in entries
+        .into_iter()
+        .filter(|e| e.folder_id == folder_id && e.rule_name == rule_name)
+    {
+        let path = Path::new(&entry.file_path);
+        if !path.exists() {
+            let _ = db.cancel_scheduled_deletion(&entry.id);
+            continue;
+        }
+        let result = crate::rules::move_file_now(path, destination, rule_name, db, Some(&batch_id));
+        let detail = match (&result.dest_path, &result.details) {
+            (Some(dest), _) => format!("Archived to {} instead of its scheduled action", dest.display()),
+            (None, Some(err)) => err.clone(),
+            (None, None) => "Archive failed".to_string(),
+        };
+        let _ = db.insert_activity_sized(&entry_activity(&entry, "archived", &now_str, result.result_label(), detail));
+        if result.success {
+            count += 1;
+            let _ = db.cancel_scheduled_deletion(&entry.id);
+        }
+    }
+
+    if count > 0 {
+        log::info!("Archived {} queued files for rule '{}' to {}", count, rule_name, destination.display());
+    }
+    count
+}
+
+/// What a full scan did, per folder and per action. Serialized as-is by the
+/// headless `--scan-now --json` CLI.
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct ScanReport {
+    /// Files matched by any rule across all folders
+    pub total_processed: u32,
+    pub folders: Vec<FolderScanReport>,
+    /// Matched files per action ("move", "delete", "scheduled", …) across all folders
+    pub actions: BTreeMap<String, u32>,
+    pub errors: Vec<ScanError>,
+}
+
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct FolderScanReport {
+    pub folder_id: String,
+    pub path: String,
+    pub processed: u32,
+    pub actions: BTreeMap<String, u32>,
+}
+
+/// A file the scan couldn't handle: a failed action or a panic while evaluating it.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ScanError {
+    pub file_path: String,
+    pub message: String,
+}
+
+impl ScanReport {
+    fn record(&mut self, folder: usize, action: &str) {
+        self.total_processed += 1;
+        *self.actions.entry(action.to_string()).or_default() += 1;
+        let folder = &mut self.folders[folder];
+        folder.processed += 1;
+        *folder.actions.entry(action.to_string()).or_default() += 1;
+    }
+}
+
+/// Activity rows a scan buffers before writing them in one transaction.
+const ACTIVITY_FLUSH_CHUNK: usize = 500;
+
+/// Write the buffered scan activity rows and clear the buffer.
+fn flush_activity(db: &Database, pending: &mut Vec<ActivityInsert>) {
+    if let Err(e) = db.insert_activity_batch(pending) {
+        log::error!("Failed to log {} scan activity entries: {}", pending.len(), e);
+    }
+    pending.clear();
+}
+
+/// Activity row for a rule that acted on a file.
+pub(crate) fn action_activity(action_result: &crate::rules::RuleActionResult, folder_id: &str, now_str: &str) -> ActivityInsert {
+    ActivityInsert {
+        id: Uuid::new_v4().to_string(),
+        file_path: action_result.file_path.clone(),
+        file_name: action_result.file_name.clone(),
+        action: action_result.action.clone(),
+        rule_name: Some(action_result.rule_name.clone()),
+        folder_id: Some(folder_id.to_string()),
+        timestamp: now_str.to_string(),
+        result: action_result.result_label().to_string(),
+        details: action_result.details.clone(),
+        size_bytes: action_result.size_bytes(),
+    }
+}
+
+/// Activity row for running (or failing to run) a scheduled entry's action.
+fn entry_activity(entry: &crate::db::ScheduledDeletion, action: &str, now_str: &str, result: &str, detail: String) -> ActivityInsert {
+    ActivityInsert {
+        id: Uuid::new_v4().to_string(),
+        file_path: entry.file_path.clone(),
+        file_name: entry.file_name.clone(),
+        action: action.to_string(),
+        rule_name: Some(entry.rule_name.clone()),
+        folder_id: Some(entry.folder_id.clone()),
+        timestamp: now_str.to_string(),
+        result: result.to_string(),
+        details: Some(detail),
+        size_bytes: entry.size_bytes,
+    }
+}
+
+/// Activity row for a file a scan newly scheduled for a delayed move or deletion.
+fn scheduled_activity(
+    file_path: String,
+    file_name: String,
+    rule_name: String,
+    action_type: &str,
+    details: Option<String>,
+    folder_id: &str,
+    now_str: &str,
+) -> ActivityInsert {
+    let base = if action_type.contains("move") {
+        "File scheduled for move"
+    } else {
+        "File scheduled for deletion"
+    };
+    let detail = match details {
+        Some(ref d) => format!("{} {}", base, d),
+        None => base.to_string(),
+    };
+    ActivityInsert {
+        id: Uuid::new_v4().to_string(),
+        file_path,
+        file_name,
+        action: "scheduled".to_string(),
+        rule_name: Some(rule_name),
+        folder_id: Some(folder_id.to_string()),
+        timestamp: now_str.to_string(),
+        result: "success".to_string(),
+        details: Some(detail),
+        size_bytes: None,
+    }
+}
+
 /// Scan all enabled folders for existing files and evaluate rules.
 /// This handles files that were added while the app was not running.
 /// Scheduled actions (delete/move with delay) log a "scheduled" activity entry.
@@ -420,73 +968,100 @@ pub fn scan_existing_files(
     config: &AppConfig,
     db: &Database,
 ) -> u32 {
-    let now_str = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
-    let mut total_processed = 0u32;
+    scan_existing_files_with_report(config, db).total_processed
+}
+
+/// [`scan_existing_files`], returning the full per-folder/per-action report.
+pub fn scan_existing_files_with_report(
+    config: &AppConfig,
+    db: &Database,
+) -> ScanReport {
+    let now = Utc::now();
+    let now_str = now.format("%Y-%m-%d %H:%M:%S").to_string();
+    let batch_id = Uuid::new_v4().to_string();
+    let mut report = ScanReport::default();
+    if config.settings.is_paused_at(now) {
+        log::info!("Automation is paused, skipping scan");
+        return report;
+    }
 
     for folder in &config.folders {
-        if !folder.enabled || !folder.path.exists() {
+        if !folder.enabled || !folder.path.exists() || folder.is_paused_at(now) {
             continue;
         }
+        let folder_index = report.folders.len();
+        report.folders.push(FolderScanReport {
+            folder_id: folder.id.clone(),
+            path: folder.path.to_string_lossy().to_string(),
+            ..Default::default()
+        });
 
         let needs_recursive = folder.watch_subdirectories
             || folder.rules.iter().any(|r| r.match_subdirectories);
 
         let files = collect_files(&folder.path, needs_recursive);
+        let mut pass = crate::rules::ScanPass::new(folder, &files)
+            .with_ignored_extensions(&config.settings.global_ignore_extensions)
+            .with_run_command_allowed(config.settings.allow_run_command)
+            .with_deletion_mode(config.settings.deletion_mode)
+            .with_batch_id(&batch_id);
+        let mut pending = Vec::new();
 
         for path in files {
+            if is_managed_destination(&path, &folder.path, config) {
+                continue;
+            }
+            if pending.len() >= ACTIVITY_FLUSH_CHUNK {
+                flush_activity(db, &mut pending);
+            }
             // Catch panics per-file to prevent one bad file from crashing the entire scan
             let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-                crate::rules::evaluate_file_full(&path, folder, db)
+                crate::rules::evaluate_file_in_pass(&path, folder, db, &mut pass)
             }));
 
             match result {
                 Ok(crate::rules::EvalOutcome::Action(action_result)) => {
-                    let _ = db.insert_activity(
-                        &Uuid::new_v4().to_string(),
-                        &action_result.file_path,
-                        &action_result.file_name,
-                        &action_result.action,
-                        Some(&action_result.rule_name),
-                        Some(&folder.id),
-                        &now_str,
-                        if action_result.success { "success" } else { "error" },
-                        action_result.details.as_deref(),
-                    );
-                    total_processed += 1;
+                    pending.push(action_activity(&action_result, &folder.id, &now_str));
+                    if action_result.fired() {
+                        let _ = db.update_rule_triggered(&action_result.rule_id, &folder.id, &now_str);
+                    }
+                    if !action_result.success {
+                        report.errors.push(ScanError {
+                            file_path: action_result.file_path.clone(),
+                            message: action_result.details.clone().unwrap_or_default(),
+                        });
+                    }
+                    report.record(folder_index, &action_result.action);
                 }
                 Ok(crate::rules::EvalOutcome::Scheduled { file_path, file_name, rule_name, newly_inserted, action_type, details }) => {
                     // Only log activity for newly scheduled files (avoid spam on re-scans)
                     if newly_inserted {
-                        let base = if action_type.contains("move") {
-                            "File scheduled for move"
-                        } else {
-                            "File scheduled for deletion"
-                        };
-                        let detail = match details {
-                            Some(ref d) => format!("{} {}", base, d),
-                            None => base.to_string(),
-                        };
-                        let _ = db.insert_activity(
-                            &Uuid::new_v4().to_string(),
-                            &file_path,
-                            &file_name,
-                            "scheduled",
-                            Some(&rule_name),
-                            Some(&folder.id),
-                            &now_str,
-                            "success",
-                            Some(&detail),
-                        );
+                        pending.push(scheduled_activity(
+                            file_path, file_name, rule_name, &action_type, details, &folder.id, &now_str,
+                        ));
                     }
-                    total_processed += 1;
+                    report.record(folder_index, "scheduled");
                 }
                 Ok(crate::rules::EvalOutcome::NoMatch) => {
                     // No rule matched — nothing to do
                 }
                 Err(e) => {
                     log::error!("Panic while processing file {}: {:?}", path.display(), e);
+                    report.errors.push(ScanError {
+                        file_path: path.to_string_lossy().to_string(),
+                        message: "Panic while processing file".to_string(),
+                    });
                 }
             }
+            for notice in pass.take_notifications() {
+                pending.push(action_activity(&notice, &folder.id, &now_str));
+                let _ = db.update_rule_triggered(&notice.rule_id, &folder.id, &now_str);
+                report.record(folder_index, &notice.action);
+            }
+        }
+        flush_activity(db, &mut pending);
+        if needs_recursive && config.settings.remove_empty_dirs {
+            remove_empty_dirs(folder, config, db, &now_str, &batch_id);
         }
     }
 
@@ -501,8 +1076,92 @@ pub fn scan_existing_files(
         }
     }
 
-    log::info!("Folder scan completed ({} files processed)", total_processed);
-    total_processed
+    log::info!("Folder scan completed ({} files processed)", report.total_processed);
+    report
+}
+
+/// Remove the folder's empty subdirectories (see [`empty_subdirs`]) and log each one.
+/// Returns how many were removed.
+fn remove_empty_dirs(folder: &WatchedFolder, config: &AppConfig, db: &Database, now_str: &str, batch_id: &str) -> u32 {
+    let expires = (Utc::now() + chrono::Duration::days(7)).format("%Y-%m-%d %H:%M:%S").to_string();
+    let mut removed = 0;
+    for dir in empty_subdirs(folder, config) {
+        // `remove_dir` only removes an empty directory, so a file that arrived since the
+        // walk is never taken along; the folder is just left for the next scan
+        if fs::read_dir(&dir).map(|mut entries| entries.next().is_some()).unwrap_or(true) {
+            continue;
+        }
+        let dir_name = dir.file_name().unwrap_or_default().to_string_lossy().to_string();
+        let (result, detail) = match fs::remove_dir(&dir) {
+            Ok(()) => {
+                removed += 1;
+                // Nothing to restore but the folder itself; undo recreates it
+                let _ = db.insert_undo(
+                    &Uuid::new_v4().to_string(),
+                    &dir.to_string_lossy(),
+                    None,
+                    "remove_empty_dir",
+                    now_str,
+                    &expires,
+                    Some(batch_id),
+                );
+                ("success", "Empty folder removed".to_string())
+            }
+            Err(e) => ("error", format!("Failed to remove empty folder: {}", friendly_io_error(&e))),
+        };
+        let _ = db.insert_activity(
+            &Uuid::new_v4().to_string(),
+            &dir.to_string_lossy(),
+            &dir_name,
+            "remove_empty_dir",
+            None,
+            Some(&folder.id),
+            now_str,
+            result,
+            Some(&detail),
+        );
+    }
+    if removed > 0 {
+        log::info!("Removed {} empty folders in {}", removed, folder.path.display());
+    }
+    removed
+}
+
+/// Subdirectories of `folder` holding nothing but other such directories, deepest first
+/// so each is empty by the time it's removed. The watched folder itself, other watched
+/// folders, Move destinations, whitelisted names and symlinks are never included (or
+/// descended into).
+fn empty_subdirs(folder: &WatchedFolder, config: &AppConfig) -> Vec<std::path::PathBuf> {
+    let mut dirs = Vec::new();
+    collect_empty_subdirs(&folder.path, folder, config, &mut dirs);
+    dirs
+}
+
+/// Whether `dir` will be empty once the subdirectories added to `dirs` are removed.
+fn collect_empty_subdirs(dir: &Path, folder: &WatchedFolder, config: &AppConfig, dirs: &mut Vec<std::path::PathBuf>) -> bool {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return false;
+    };
+    let mut empty = true;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let is_dir = entry.file_type().is_ok_and(|t| t.is_dir());
+        if is_dir && may_remove_dir(&path, folder, config) && collect_empty_subdirs(&path, folder, config, dirs) {
+            dirs.push(path);
+        } else {
+            empty = false;
+        }
+    }
+    empty
+}
+
+fn may_remove_dir(dir: &Path, folder: &WatchedFolder, config: &AppConfig) -> bool {
+    let name = dir.file_name().unwrap_or_default().to_string_lossy();
+    let relative = dir.strip_prefix(&folder.path).unwrap_or(dir).to_string_lossy().replace('\\', "/");
+    let normalized = normalize_folder_path(dir);
+    !is_managed_destination(dir, &folder.path, config)
+        && !config.folders.iter().any(|f| normalize_folder_path(&f.path) == normalized)
+        && !is_whitelisted_with_relative_path(&name, Some(&relative), &folder.whitelist)
 }
 
 /// Scan a single folder for existing files and evaluate rules.
@@ -513,6 +1172,7 @@ pub fn scan_single_folder(
     folder_id: &str,
 ) -> u32 {
     let now_str = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    let batch_id = Uuid::new_v4().to_string();
     let mut total_processed = 0u32;
 
     let folder = match config.folders.iter().find(|f| f.id == folder_id) {
@@ -520,7 +1180,8 @@ pub fn scan_single_folder(
         None => return 0,
     };
 
-    if !folder.enabled || !folder.path.exists() {
+    let now = Utc::now();
+    if !folder.enabled || !folder.path.exists() || folder.is_paused_at(now) || config.settings.is_paused_at(now) {
         return 0;
     }
 
@@ -528,49 +1189,37 @@ pub fn scan_single_folder(
         || folder.rules.iter().any(|r| r.match_subdirectories);
 
     let files = collect_files(&folder.path, needs_recursive);
+    let mut pass = crate::rules::ScanPass::new(folder, &files)
+        .with_ignored_extensions(&config.settings.global_ignore_extensions)
+        .with_run_command_allowed(config.settings.allow_run_command)
+        .with_deletion_mode(config.settings.deletion_mode)
+        .with_batch_id(&batch_id);
+    let mut pending = Vec::new();
 
     for path in files {
+        if is_managed_destination(&path, &folder.path, config) {
+            continue;
+        }
+        if pending.len() >= ACTIVITY_FLUSH_CHUNK {
+            flush_activity(db, &mut pending);
+        }
         let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-            crate::rules::evaluate_file_full(&path, folder, db)
+            crate::rules::evaluate_file_in_pass(&path, folder, db, &mut pass)
         }));
 
         match result {
             Ok(crate::rules::EvalOutcome::Action(action_result)) => {
-                let _ = db.insert_activity(
-                    &Uuid::new_v4().to_string(),
-                    &action_result.file_path,
-                    &action_result.file_name,
-                    &action_result.action,
-                    Some(&action_result.rule_name),
-                    Some(&folder.id),
-                    &now_str,
-                    if action_result.success { "success" } else { "error" },
-                    action_result.details.as_deref(),
-                );
+                pending.push(action_activity(&action_result, &folder.id, &now_str));
+                if action_result.fired() {
+                    let _ = db.update_rule_triggered(&action_result.rule_id, &folder.id, &now_str);
+                }
                 total_processed += 1;
             }
             Ok(crate::rules::EvalOutcome::Scheduled { file_path, file_name, rule_name, newly_inserted, action_type, details }) => {
                 if newly_inserted {
-                    let base = if action_type.contains("move") {
-                        "File scheduled for move"
-                    } else {
-                        "File scheduled for deletion"
-                    };
-                    let detail = match details {
-                        Some(ref d) => format!("{} {}", base, d),
-                        None => base.to_string(),
-                    };
-                    let _ = db.insert_activity(
-                        &Uuid::new_v4().to_string(),
-                        &file_path,
-                        &file_name,
-                        "scheduled",
-                        Some(&rule_name),
-                        Some(&folder.id),
-                        &now_str,
-                        "success",
-                        Some(&detail),
-                    );
+                    pending.push(scheduled_activity(
+                        file_path, file_name, rule_name, &action_type, details, &folder.id, &now_str,
+                    ));
                 }
                 total_processed += 1;
             }
@@ -579,6 +1228,14 @@ pub fn scan_single_folder(
                 log::error!("Panic while processing file {}: {:?}", path.display(), e);
             }
         }
+        for notice in pass.take_notifications() {
+            pending.push(action_activity(&notice, &folder.id, &now_str));
+            let _ = db.update_rule_triggered(&notice.rule_id, &folder.id, &now_str);
+        }
+    }
+    flush_activity(db, &mut pending);
+    if needs_recursive && config.settings.remove_empty_dirs {
+        remove_empty_dirs(folder, config, db, &now_str, &batch_id);
     }
 
     // Clean up scheduled entries for files that no longer exist in this folder
@@ -591,9 +1248,174 @@ pub fn scan_single_folder(
     total_processed
 }
 
+/// Evaluate every file in a folder against its rules and report what would happen,
+/// without moving, deleting, or scheduling anything.
+/// Works on disabled or paused folders too, so a rule can be previewed before turning it on.
+pub fn scan_dry_run(config: &AppConfig, folder_id: &str) -> Vec<PlannedAction> {
+    let folder = match config.folders.iter().find(|f| f.id == folder_id) {
+        Some(f) if f.path.exists() => f,
+        _ => return Vec::new(),
+    };
+
+    let needs_recursive = folder.watch_subdirectories
+        || folder.rules.iter().any(|r| r.match_subdirectories);
+    let files = collect_files(&folder.path, needs_recursive);
+    let mut pass = crate::rules::ScanPass::new(folder, &files)
+        .with_ignored_extensions(&config.settings.global_ignore_extensions);
+
+    let mut planned = Vec::new();
+    for path in &files {
+        if is_managed_destination(path, &folder.path, config) {
+            continue;
+        }
+        if let Some(plan) = crate::rules::plan_file(path, folder, &mut pass) {
+            planned.extend(plan.planned_actions(path));
+        }
+    }
+    planned
+}
+
+/// Most matches [`preview_rule_on_folder`] returns.
+const PREVIEW_MATCH_LIMIT: usize = 1000;
+
+/// Files a rule would catch, from [`preview_rule_on_folder`].
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct RulePreview {
+    pub matches: Vec<String>,
+    /// More files matched than `PREVIEW_MATCH_LIMIT`; only the first ones are listed
+    pub truncated: bool,
+}
+
+/// The folder's current files that `rule` (which needn't be saved yet) would match, by
+/// condition and whitelists. Nothing is executed or scheduled. Subdirectories are
+/// included when the folder is watched recursively or the rule has `match_subdirectories`.
+pub fn preview_rule_on_folder(config: &AppConfig, folder_id: &str, rule: &Rule) -> RulePreview {
+    let mut preview = RulePreview::default();
+    let folder = match config.folders.iter().find(|f| f.id == folder_id) {
+        Some(f) if f.path.exists() => f,
+        _ => return preview,
+    };
+    // Size ranks etc. are only computed for the folder's rules, so preview on a copy holding just this one
+    let folder = WatchedFolder { rules: vec![rule.clone()], default_action: None, ..folder.clone() };
+
+    let files = collect_files(&folder.path, folder.watch_subdirectories || rule.match_subdirectories);
+    let mut pass = crate::rules::ScanPass::new(&folder, &files)
+        .with_ignored_extensions(&config.settings.global_ignore_extensions);
+    for path in &files {
+        if is_managed_destination(path, &folder.path, config) || !crate::rules::rule_matches_file(path, &folder, rule, &mut pass) {
+            continue;
+        }
+        if preview.matches.len() == PREVIEW_MATCH_LIMIT {
+            preview.truncated = true;
+            break;
+        }
+        preview.matches.push(path.to_string_lossy().to_string());
+    }
+    preview
+}
+
+/// Time each rule's condition over every file in a folder, to spot slow rules
+/// (e.g. a pathological regex). Runs the same evaluation as a scan but, like
+/// `scan_dry_run`, executes and schedules nothing.
+pub fn profile_folder_scan(config: &AppConfig, folder_id: &str) -> Vec<RuleProfile> {
+    let folder = match config.folders.iter().find(|f| f.id == folder_id) {
+        Some(f) if f.path.exists() => f,
+        _ => return Vec::new(),
+    };
+
+    let needs_recursive = folder.watch_subdirectories
+        || folder.rules.iter().any(|r| r.match_subdirectories);
+    let files = collect_files(&folder.path, needs_recursive);
+    let mut pass = crate::rules::ScanPass::new(folder, &files)
+        .with_ignored_extensions(&config.settings.global_ignore_extensions)
+        .with_profiling();
+
+    for path in &files {
+        let _ = crate::rules::plan_file(path, folder, &mut pass);
+    }
+    pass.rule_profiles(folder)
+}
+
+/// How tidy a folder is: the share of its files some rule handles.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct CleanlinessScore {
+    /// 0–100; an empty folder scores 100
+    pub score: u32,
+    pub matched_files: u32,
+    pub unmatched_files: u32,
+    /// e.g. "23 .zip files unmatched — add a rule?", most common extensions first
+    pub suggestions: Vec<String>,
+}
+
+/// Maximum number of per-extension suggestions in a `CleanlinessScore`.
+const MAX_CLEANLINESS_SUGGESTIONS: usize = 3;
+
+/// Score a folder by planning every file in it (like `scan_dry_run`) and counting
+/// which ones some rule would handle. Whitelisted files don't count either way.
+/// None if the folder doesn't exist in config or on disk.
+pub fn folder_cleanliness(config: &AppConfig, folder_id: &str) -> Option<CleanlinessScore> {
+    let folder = config
+        .folders
+        .iter()
+        .find(|f| f.id == folder_id)
+        .filter(|f| f.path.exists())?;
+
+    let needs_recursive = folder.watch_subdirectories
+        || folder.rules.iter().any(|r| r.match_subdirectories);
+    let files = collect_files(&folder.path, needs_recursive);
+    let mut pass = crate::rules::ScanPass::new(folder, &files)
+        .with_ignored_extensions(&config.settings.global_ignore_extensions);
+
+    let mut matched_files = 0u32;
+    let mut unmatched_by_ext: HashMap<String, u32> = HashMap::new();
+    for path in &files {
+        match crate::rules::plan_file(path, folder, &mut pass) {
+            Some(plan) if plan.is_handled() => matched_files += 1,
+            Some(_) => {
+                let ext = path
+                    .extension()
+                    .map(|e| e.to_string_lossy().to_lowercase())
+                    .unwrap_or_default();
+                *unmatched_by_ext.entry(ext).or_default() += 1;
+            }
+            None => {}
+        }
+    }
+
+    let unmatched_files: u32 = unmatched_by_ext.values().sum();
+    let total = matched_files + unmatched_files;
+    let score = if total == 0 {
+        100
+    } else {
+        (matched_files as f64 * 100.0 / total as f64).round() as u32
+    };
+
+    let mut by_count: Vec<(String, u32)> = unmatched_by_ext.into_iter().collect();
+    by_count.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    let suggestions = by_count
+        .into_iter()
+        .take(MAX_CLEANLINESS_SUGGESTIONS)
+        .map(|(ext, count)| {
+            let noun = if count == 1 { "file" } else { "files" };
+            if ext.is_empty() {
+                format!("{} {} without an extension unmatched — add a rule?", count, noun)
+            } else {
+                format!("{} .{} {} unmatched — add a rule?", count, ext, noun)
+            }
+        })
+        .collect();
+
+    Some(CleanlinessScore {
+        score,
+        matched_files,
+        unmatched_files,
+        suggestions,
+    })
+}
+
 /// Collect all files from a directory, optionally recursing into subdirectories.
 /// Handles errors gracefully — skips unreadable directories.
-fn collect_files(dir: &Path, recursive: bool) -> Vec<std::path::PathBuf> {
+pub fn collect_files(dir: &Path, recursive: bool) -> Vec<std::path::PathBuf> {
     let mut files = Vec::new();
     collect_files_inner(dir, recursive, &mut files);
     files
@@ -626,26 +1448,908 @@ fn collect_files_inner(dir: &Path, recursive: bool, files: &mut Vec<std::path::P
     }
 }
 
-/// Safe delete: send file to the OS recycle bin.
+/// Safe delete: send file to the OS recycle bin, or to the staging folder with
+/// `DeletionMode::Staging` (also the fallback when recycling fails).
 /// Returns Ok on success, Err with a human-readable message on failure.
-fn safe_delete(file_path: &Path, db: &Database, now_str: &str, undo_action: &str) -> Result<(), String> {
-    match trash::delete(file_path) {
-        Ok(_) => {
-            // Undo expires in 7 days (user can restore from Recycle Bin)
-            let expires = Utc::now() + chrono::Duration::days(7);
+pub(crate) fn safe_delete(
+    file_path: &Path,
+    db: &Database,
+    now_str: &str,
+    undo_action: &str,
+    batch_id: Option<&str>,
+    mode: DeletionMode,
+) -> Result<(), String> {
+    // Undo expires in 7 days (user can restore from Recycle Bin or the staging folder)
+    let expires = (Utc::now() + chrono::Duration::days(7)).format("%Y-%m-%d %H:%M:%S").to_string();
+    let mut recycle_error = None;
+    if mode == DeletionMode::Trash {
+        match trash::delete(file_path) {
+            Ok(_) => {
+                let _ = db.insert_undo(
+                    &Uuid::new_v4().to_string(),
+                    &file_path.to_string_lossy(),
+                    None, // no staged path — it's in the OS recycle bin
+                    undo_action,
+                    now_str,
+                    &expires,
+                    batch_id,
+                );
+                return Ok(());
+            }
+            Err(e) => {
+                log::warn!("Failed to recycle {} ({}), staging it instead", file_path.display(), e);
+                recycle_error = Some(friendly_trash_error(&e));
+            }
+        }
+    }
+
+    match staging::stage(file_path, &db.staging_dir) {
+        Ok(staged) => {
             let _ = db.insert_undo(
                 &Uuid::new_v4().to_string(),
                 &file_path.to_string_lossy(),
-                None, // no staged path — it's in the OS recycle bin
+                Some(&staged.to_string_lossy()),
                 undo_action,
                 now_str,
-                &expires.format("%Y-%m-%d %H:%M:%S").to_string(),
+                &expires,
+                batch_id,
             );
             Ok(())
         }
         Err(e) => {
-            log::error!("Failed to recycle {}: {}", file_path.display(), e);
-            Err(format!("Recycle failed: {}", friendly_trash_error(&e)))
+            log::error!("Failed to stage {} for deletion: {}", file_path.display(), e);
+            Err(match recycle_error {
+                Some(recycle) => format!("Recycle failed: {} (staging also failed: {})", recycle, friendly_io_error(&e)),
+                None => format!("Staging failed: {}", friendly_io_error(&e)),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Datelike, TimeZone};
+    use crate::config::AppSettings;
+    use crate::test_support::{folder, move_to, rule, watched_dir};
+
+    fn config_with(folders: Vec<crate::config::WatchedFolder>) -> AppConfig {
+        AppConfig {
+            folders,
+            settings: AppSettings::default(),
+            presets: Vec::new(),
+            ..Default::default()
         }
     }
+
+    #[test]
+    fn orphaned_folder_data_is_swept_only_once() {
+        let mut config = config_with(vec![folder(Path::new("/watched"), Vec::new())]);
+        config.settings.archive_logs_before_pruning = false;
+        let db = Database::open_in_memory().unwrap();
+        let orphan_rules = |db: &Database| db.get_rule_metadata("gone").unwrap().len();
+
+        db.insert_rule_metadata("rule-1", "gone", "2026-01-01T00:00:00Z").unwrap();
+        run_scheduled_cleanup(&config, &db);
+        assert_eq!(orphan_rules(&db), 0);
+
+        db.insert_rule_metadata("rule-2", "gone", "2026-01-01T00:00:00Z").unwrap();
+        run_scheduled_cleanup(&config, &db);
+        assert_eq!(orphan_rules(&db), 1, "later cleanups leave folder data to purging");
+    }
+
+    #[test]
+    fn colliding_move_logs_renamed_collision_result() {
+        let (tmp, watched) = watched_dir();
+        let pdfs = tmp.path().join("pdfs");
+        fs::create_dir_all(&pdfs).unwrap();
+        fs::write(pdfs.join("a.pdf"), "existing").unwrap();
+        fs::write(watched.join("a.pdf"), "new").unwrap();
+        fs::write(watched.join("b.pdf"), "new").unwrap();
+
+        let config = config_with(vec![folder(&watched, vec![rule("PDFs", "*.pdf", move_to(&pdfs))])]);
+        let db = Database::open_in_memory().unwrap();
+        assert_eq!(scan_single_folder(&config, &db, "folder-1"), 2);
+
+        let log = db.get_activity_log(10, 0, None).unwrap();
+        let result_for = |name: &str| {
+            log.iter().find(|e| e.file_name == name).map(|e| e.result.clone()).unwrap()
+        };
+        assert_eq!(result_for("a.pdf"), "renamed_collision");
+        assert_eq!(result_for("b.pdf"), "success");
+
+        let stats = db.get_rule_execution_stats("folder-1", "1970-01-01 00:00:00").unwrap();
+        assert_eq!(stats[0].executions_this_week, 2);
+        assert_eq!(stats[0].renamed_collisions_this_week, 1);
+    }
+
+    #[test]
+    fn due_actions_pause_between_batches() {
+        let (tmp, watched) = watched_dir();
+        let dest = tmp.path().join("dest");
+        let db = Database::open_in_memory().unwrap();
+        for i in 0..7 {
+            let path = watched.join(format!("f{}.txt", i));
+            fs::write(&path, "x").unwrap();
+            db.upsert_scheduled_deletion(
+                &format!("id-{}", i),
+                &path.to_string_lossy(),
+                "folder-1",
+                "Move",
+                &format!("f{}.txt", i),
+                Some("txt"),
+                Some(1),
+                "2000-01-01 00:00:00",
+                "2000-01-01 00:00:00",
+                "move",
+                Some(&dest.to_string_lossy()),
+                false,
+                0,
+            )
+            .unwrap();
+        }
+
+        let mut pauses = Vec::new();
+        let throttle = BatchThrottle::new(3, Duration::from_millis(50), |d| pauses.push(d));
+        let processed = process_due_with_throttle(&db, None, throttle, true).processed;
+
+        assert_eq!(processed, 7);
+        // 7 items in batches of 3: pause after items 3 and 6, not after the last
+        assert_eq!(pauses, vec![Duration::from_millis(50); 2]);
+        assert_eq!(fs::read_dir(&dest).unwrap().count(), 7);
+    }
+
+    #[test]
+    fn failing_due_action_backs_off_then_gives_up() {
+        let (tmp, watched) = watched_dir();
+        let path = watched.join("locked.txt");
+        fs::write(&path, "x").unwrap();
+        // A file where the destination folder should be makes every attempt fail
+        let dest = tmp.path().join("dest");
+        fs::write(&dest, "not a folder").unwrap();
+        let db = Database::open_in_memory().unwrap();
+        db.upsert_scheduled_deletion(
+            "id-1",
+            &path.to_string_lossy(),
+            "folder-1",
+            "Move",
+            "locked.txt",
+            Some("txt"),
+            Some(1),
+            "2000-01-01 00:00:00",
+            "2000-01-01 00:00:00",
+            "move",
+            Some(&dest.to_string_lossy()),
+            false,
+            0,
+        )
+        .unwrap();
+        let run = || process_due_with_throttle(&db, None, BatchThrottle::new(0, Duration::ZERO, |_| {}), true).processed;
+        let attempts = || db.get_scheduled_deletions().unwrap().first().map(|e| e.attempt_count);
+
+        assert_eq!(run(), 0);
+        assert_eq!(attempts(), Some(1));
+        run();
+        assert_eq!(attempts(), Some(1), "still inside the backoff window");
+
+        // Pretend the earlier retries happened long ago
+        for _ in 0..3 {
+            db.record_deletion_attempt("id-1", "2000-01-01 00:00:00").unwrap();
+        }
+        run();
+        assert_eq!(attempts(), None, "dropped after the last attempt");
+
+        let log = db.get_activity_log(10, 0, None).unwrap();
+        assert_eq!(log.len(), 2, "first failure and the give-up, not every retry");
+        assert!(log.iter().all(|e| e.result == "error"));
+        assert!(log.iter().any(|e| e.details.as_deref().unwrap_or("").starts_with("Giving up after 5 attempts")));
+        assert_eq!(retry_backoff(1), chrono::Duration::minutes(5));
+        assert_eq!(retry_backoff(30), RETRY_BACKOFF_MAX);
+    }
+
+    #[test]
+    fn deletions_wait_for_approval_when_required() {
+        let (_tmp, watched) = watched_dir();
+        let file = watched.join("old.log");
+        fs::write(&file, "x").unwrap();
+        let mut config = config_with(vec![folder(&watched, vec![rule("Old logs", "*.log", crate::config::Action::Delete { after_days: 0, delay_minutes: 0 })])]);
+        config.settings.require_deletion_approval = true;
+        let db = Database::open_in_memory().unwrap();
+        db.upsert_scheduled_deletion(
+            "id-1",
+            &file.to_string_lossy(),
+            "folder-1",
+            "Old logs",
+            "old.log",
+            Some("log"),
+            Some(1),
+            "2000-01-01 00:00:00",
+            "2000-01-01 00:00:00",
+            "delete",
+            None,
+            false,
+            0,
+        )
+        .unwrap();
+
+        let run = process_due_deletions_with_config(&db, Some(&config), true);
+        assert_eq!(run, DueRun { processed: 0, awaiting_approval: 1 });
+        assert!(file.exists());
+        assert_eq!(db.get_scheduled_deletions().unwrap()[0].status, "awaiting_approval");
+        // Already flagged: later ticks don't report it again
+        assert_eq!(process_due_deletions_with_config(&db, Some(&config), true).awaiting_approval, 0);
+
+        assert_eq!(db.get_awaiting_approval_ids().unwrap(), vec!["id-1".to_string()]);
+        assert!(file.exists());
+    }
+
+    #[test]
+    fn due_actions_wait_for_the_rule_window() {
+        use chrono::Timelike;
+        let (_tmp, watched) = watched_dir();
+        let file = watched.join("old.log");
+        fs::write(&file, "x").unwrap();
+        let mut office = rule("Old logs", "*.log", crate::config::Action::Delete { after_days: 0, delay_minutes: 0 });
+        // A one-hour window that starts an hour from now
+        let start_hour = (chrono::Local::now().hour() + 1) % 24;
+        office.active_window = Some(crate::config::TimeWindow { start_hour, end_hour: (start_hour + 1) % 24, days: Vec::new() });
+        let config = config_with(vec![folder(&watched, vec![office])]);
+        let db = Database::open_in_memory().unwrap();
+        db.upsert_scheduled_deletion(
+            "id-1",
+            &file.to_string_lossy(),
+            "folder-1",
+            "Old logs",
+            "old.log",
+            Some("log"),
+            Some(1),
+            "2000-01-01 00:00:00",
+            "2000-01-01 00:00:00",
+            "delete",
+            None,
+            false,
+            0,
+        )
+        .unwrap();
+
+        assert_eq!(process_due_deletions_with_config(&db, Some(&config), true).processed, 0);
+        assert!(file.exists());
+        assert_eq!(db.get_scheduled_deletions().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn delayed_quota_moves_pick_their_destination_when_due() {
+        let (tmp, watched) = watched_dir();
+        let (drive_a, drive_b) = (tmp.path().join("a"), tmp.path().join("b"));
+        fs::create_dir_all(&drive_a).unwrap();
+        fs::write(drive_a.join("old.mkv"), [0u8; 100]).unwrap();
+        let file = watched.join("ep1.mkv");
+        fs::write(&file, [0u8; 100]).unwrap();
+        let mut action = move_to(&drive_a);
+        if let crate::config::Action::Move { ref mut destinations, .. } = action {
+            *destinations = vec![
+                crate::config::QuotaDestination { path: drive_a.clone(), max_bytes: Some(150) },
+                crate::config::QuotaDestination { path: drive_b.clone(), max_bytes: None },
+            ];
+        }
+        let config = config_with(vec![folder(&watched, vec![rule("Media", "*.mkv", action)])]);
+        let db = Database::open_in_memory().unwrap();
+        db.upsert_scheduled_deletion(
+            "id-1",
+            &file.to_string_lossy(),
+            "folder-1",
+            "Media",
+            "ep1.mkv",
+            Some("mkv"),
+            Some(100),
+            "2000-01-01 00:00:00",
+            "2000-01-01 00:00:00",
+            "move",
+            Some(&drive_a.to_string_lossy()),
+            false,
+            0,
+        )
+        .unwrap();
+
+        // Planned for A, but A's quota is used up by the time the move runs
+        assert_eq!(process_due_deletions_with_config(&db, Some(&config), true).processed, 1);
+        assert!(drive_b.join("ep1.mkv").exists());
+        let log = db.get_activity_log(10, 0, None).unwrap();
+        assert!(log[0].details.as_deref().unwrap_or("").contains(&*drive_b.to_string_lossy()));
+    }
+
+    #[test]
+    fn scan_groups_undo_entries_into_one_batch() {
+        let (tmp, watched) = watched_dir();
+        let pdfs = tmp.path().join("pdfs");
+        fs::write(watched.join("a.pdf"), "x").unwrap();
+        fs::write(watched.join("b.pdf"), "x").unwrap();
+
+        let config = config_with(vec![folder(&watched, vec![rule("PDFs", "*.pdf", move_to(&pdfs))])]);
+        let db = Database::open_in_memory().unwrap();
+        scan_existing_files(&config, &db);
+
+        let batches = db.get_undo_batches().unwrap();
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].count, 2);
+        assert_eq!(batches[0].actions.get("auto_move"), Some(&2));
+    }
+
+    #[test]
+    fn empty_subdirs_are_found_deepest_first() {
+        let (_tmp, watched) = watched_dir();
+        for dir in ["a/b", "c", "d", "e/f", "pdfs"] {
+            fs::create_dir_all(watched.join(dir)).unwrap();
+        }
+        fs::write(watched.join("c").join("note.txt"), "x").unwrap();
+        let mut f = folder(&watched, vec![rule("PDFs", "*.pdf", move_to(&watched.join("pdfs")))]);
+        f.whitelist = vec!["d".to_string()];
+        let config = config_with(vec![f.clone()]);
+
+        let dirs = empty_subdirs(&f, &config);
+        let position = |rel: &str| dirs.iter().position(|d| d == &watched.join(rel));
+        assert_eq!(dirs.len(), 4, "{:?}", dirs);
+        assert!(position("a/b") < position("a") && position("a").is_some());
+        assert!(position("e/f") < position("e") && position("e").is_some());
+        assert!(!dirs.contains(&watched), "never the watched folder");
+    }
+
+    #[test]
+    fn watched_subfolders_are_kept_however_their_path_is_written() {
+        let (_tmp, watched) = watched_dir();
+        fs::create_dir_all(watched.join("inbox")).unwrap();
+        fs::create_dir_all(watched.join("docs")).unwrap();
+        fs::write(watched.join("docs").join("a.txt"), "x").unwrap();
+        let f = folder(&watched, Vec::new());
+        let mut inbox = folder(&watched.join("docs").join("..").join("inbox"), Vec::new());
+        inbox.id = "inbox".to_string();
+        let config = config_with(vec![f.clone(), inbox]);
+
+        assert!(empty_subdirs(&f, &config).is_empty());
+    }
+
+    #[test]
+    fn empty_dirs_are_removed_in_place_with_an_undo_entry() {
+        let (_tmp, watched) = watched_dir();
+        fs::create_dir_all(watched.join("a").join("b")).unwrap();
+        let f = folder(&watched, Vec::new());
+        let config = config_with(vec![f.clone()]);
+        let db = Database::open_in_memory().unwrap();
+
+        assert_eq!(remove_empty_dirs(&f, &config, &db, "2026-01-01 00:00:00", "batch"), 2);
+        assert!(!watched.join("a").exists());
+        let undo = db.get_undo_entries().unwrap();
+        assert_eq!(undo.len(), 2);
+        assert!(undo.iter().all(|u| u.action == "remove_empty_dir" && u.current_path.is_none()));
+    }
+
+    #[test]
+    fn rule_preview_lists_matches_without_acting() {
+        let (tmp, watched) = watched_dir();
+        fs::create_dir(watched.join("sub")).unwrap();
+        fs::write(watched.join("a.pdf"), "x").unwrap();
+        fs::write(watched.join("keep.pdf"), "x").unwrap();
+        fs::write(watched.join("b.txt"), "x").unwrap();
+        fs::write(watched.join("sub").join("c.pdf"), "x").unwrap();
+        let config = config_with(vec![folder(&watched, Vec::new())]);
+        let mut pdfs = rule("PDFs", "*.pdf", move_to(&tmp.path().join("pdfs")));
+        pdfs.whitelist = vec!["keep.pdf".to_string()];
+
+        let preview = preview_rule_on_folder(&config, "folder-1", &pdfs);
+        assert_eq!(preview.matches, vec![watched.join("a.pdf").to_string_lossy().to_string()]);
+        assert!(!preview.truncated);
+        assert!(watched.join("a.pdf").exists(), "nothing is moved");
+
+        pdfs.match_subdirectories = true;
+        let mut matches = preview_rule_on_folder(&config, "folder-1", &pdfs).matches;
+        matches.sort();
+        assert_eq!(matches.len(), 2, "{:?}", matches);
+    }
+
+    #[test]
+    fn scan_logs_every_file_across_activity_flushes() {
+        let (tmp, watched) = watched_dir();
+        let files = ACTIVITY_FLUSH_CHUNK + 3;
+        for i in 0..files {
+            fs::write(watched.join(format!("{i}.pdf")), "x").unwrap();
+        }
+        let config = config_with(vec![folder(&watched, vec![rule("PDFs", "*.pdf", move_to(&tmp.path().join("pdfs")))])]);
+        let db = Database::open_in_memory().unwrap();
+
+        assert_eq!(scan_existing_files(&config, &db) as usize, files);
+        let logged = db.get_activity_log(files as u32 + 10, 0, Some("folder-1")).unwrap();
+        assert_eq!(logged.len(), files);
+    }
+
+    #[test]
+    fn scan_records_when_a_rule_last_triggered() {
+        let (tmp, watched) = watched_dir();
+        fs::write(watched.join("a.pdf"), "x").unwrap();
+        let config = config_with(vec![folder(&watched, vec![
+            rule("PDFs", "*.pdf", move_to(&tmp.path().join("pdfs"))),
+            rule("Images", "*.png", move_to(&tmp.path().join("images"))),
+        ])]);
+        let db = Database::open_in_memory().unwrap();
+        db.insert_rule_metadata("PDFs-id", "folder-1", "2024-01-01 00:00:00").unwrap();
+        db.insert_rule_metadata("Images-id", "folder-1", "2024-01-01 00:00:00").unwrap();
+
+        scan_single_folder(&config, &db, "folder-1");
+
+        let meta = db.get_rule_metadata("folder-1").unwrap();
+        let triggered = |id: &str| meta.iter().find(|m| m.rule_id == id).unwrap().last_triggered_at.clone();
+        assert!(triggered("PDFs-id").is_some());
+        assert_eq!(triggered("Images-id"), None, "a rule that matched nothing keeps no timestamp");
+    }
+
+    #[test]
+    fn scan_leaves_files_in_another_folders_destination() {
+        let (tmp, downloads) = watched_dir();
+        let inbox = tmp.path().join("inbox");
+        fs::create_dir_all(&inbox).unwrap();
+        fs::write(downloads.join("a.pdf"), "x").unwrap();
+
+        // Downloads → inbox, and the inbox is itself watched with a catch-all rule
+        let mut inbox_folder = folder(&inbox, vec![rule("Archive", "*", move_to(&tmp.path().join("archive")))]);
+        inbox_folder.id = "folder-2".to_string();
+        let config = config_with(vec![
+            folder(&downloads, vec![rule("PDFs", "*.pdf", move_to(&inbox))]),
+            inbox_folder,
+        ]);
+        let db = Database::open_in_memory().unwrap();
+
+        scan_single_folder(&config, &db, "folder-1");
+        assert!(inbox.join("a.pdf").exists());
+        assert_eq!(scan_single_folder(&config, &db, "folder-2"), 0);
+        assert!(inbox.join("a.pdf").exists());
+    }
+
+    #[test]
+    fn scan_leaves_files_in_a_destination_inside_the_folder() {
+        let (_tmp, downloads) = watched_dir();
+        let sorted = downloads.join("sorted");
+        fs::write(downloads.join("a.pdf"), "x").unwrap();
+
+        // A recursive folder sorting into its own subfolder, plus a catch-all that would bounce them out
+        let mut downloads_folder = folder(
+            &downloads,
+            vec![rule("PDFs", "*.pdf", move_to(&sorted)), rule("Rest", "*", move_to(&downloads.join("rest")))],
+        );
+        downloads_folder.watch_subdirectories = true;
+        let config = config_with(vec![downloads_folder]);
+        let db = Database::open_in_memory().unwrap();
+
+        assert_eq!(scan_single_folder(&config, &db, "folder-1"), 1);
+        assert!(sorted.join("a.pdf").exists());
+        assert_eq!(scan_single_folder(&config, &db, "folder-1"), 0);
+        assert!(sorted.join("a.pdf").exists());
+    }
+
+    #[test]
+    fn archiving_moves_queued_files_and_unschedules_them() {
+        let (tmp, watched) = watched_dir();
+        let archive = tmp.path().join("archive");
+        fs::create_dir_all(&archive).unwrap();
+        fs::write(archive.join("a.log"), "older").unwrap();
+        let delete_later = crate::config::Action::Delete { after_days: 0, delay_minutes: 60 };
+        let config = config_with(vec![folder(&watched, vec![
+            rule("Logs", "*.log", delete_later.clone()),
+            rule("Backups", "*.bak", delete_later),
+        ])]);
+        for name in ["a.log", "b.log", "c.bak"] {
+            fs::write(watched.join(name), "x").unwrap();
+        }
+        let db = Database::open_in_memory().unwrap();
+        scan_existing_files(&config, &db);
+        assert_eq!(db.get_scheduled_deletions().unwrap().len(), 3);
+
+        assert_eq!(archive_scheduled_deletions(&db, "folder-1", "Logs", &archive), 2);
+
+        assert!(archive.join("a (1).log").exists(), "collision is renamed");
+        assert!(archive.join("b.log").exists());
+        assert!(!watched.join("a.log").exists());
+        assert!(watched.join("c.bak").exists());
+        let remaining: Vec<String> = db.get_scheduled_deletions().unwrap().into_iter().map(|d| d.rule_name).collect();
+        assert_eq!(remaining, vec!["Backups"]);
+        assert_eq!(db.get_undo_batches().unwrap()[0].count, 2);
+    }
+
+    #[test]
+    fn scan_moves_only_the_largest_files() {
+        let (tmp, watched) = watched_dir();
+        let big = tmp.path().join("big");
+        for (name, size) in [("a.bin", 10), ("b.bin", 40), ("c.bin", 20), ("d.bin", 30)] {
+            fs::write(watched.join(name), vec![0u8; size]).unwrap();
+        }
+
+        let config = config_with(vec![folder(&watched, vec![rule("Biggest", "largest:2", move_to(&big))])]);
+        let db = Database::open_in_memory().unwrap();
+        assert_eq!(scan_single_folder(&config, &db, "folder-1"), 2);
+
+        assert!(big.join("b.bin").exists());
+        assert!(big.join("d.bin").exists());
+        assert!(watched.join("a.bin").exists());
+        assert!(watched.join("c.bin").exists());
+    }
+
+    #[test]
+    fn paused_folder_is_skipped_until_pause_expires() {
+        let (tmp, watched) = watched_dir();
+        let pdfs = tmp.path().join("pdfs");
+        fs::write(watched.join("a.pdf"), "x").unwrap();
+
+        let mut f = folder(&watched, vec![rule("PDFs", "*.pdf", move_to(&pdfs))]);
+        f.paused_until = Some((Utc::now() + chrono::Duration::hours(2)).to_rfc3339());
+        let mut config = config_with(vec![f]);
+        let db = Database::open_in_memory().unwrap();
+
+        assert_eq!(scan_single_folder(&config, &db, "folder-1"), 0);
+        assert_eq!(scan_existing_files(&config, &db), 0);
+        assert!(watched.join("a.pdf").exists());
+
+        // Pause in the past → folder is processed again
+        config.folders[0].paused_until = Some((Utc::now() - chrono::Duration::minutes(1)).to_rfc3339());
+        assert_eq!(scan_single_folder(&config, &db, "folder-1"), 1);
+        assert!(pdfs.join("a.pdf").exists());
+    }
+
+    #[test]
+    fn global_pause_skips_scans_and_due_actions() {
+        let (tmp, watched) = watched_dir();
+        let pdfs = tmp.path().join("pdfs");
+        let file = watched.join("a.pdf");
+        fs::write(&file, "x").unwrap();
+
+        let mut config = config_with(vec![folder(&watched, vec![rule("PDFs", "*.pdf", move_to(&pdfs))])]);
+        config.settings.paused_until = Some((Utc::now() + chrono::Duration::hours(2)).to_rfc3339());
+        let db = Database::open_in_memory().unwrap();
+        db.upsert_scheduled_deletion(
+            "id-1",
+            &file.to_string_lossy(),
+            "folder-1",
+            "Old files",
+            "a.pdf",
+            Some("pdf"),
+            Some(1),
+            "2000-01-01 00:00:00",
+            "2000-01-01 00:00:00",
+            "delete",
+            None,
+            false,
+            0,
+        )
+        .unwrap();
+
+        assert_eq!(scan_existing_files(&config, &db), 0);
+        assert_eq!(scan_single_folder(&config, &db, "folder-1"), 0);
+        assert_eq!(process_due_deletions_with_config(&db, Some(&config), true), DueRun::default());
+        assert!(file.exists());
+
+        config.settings.paused_until = None;
+        assert_eq!(scan_existing_files(&config, &db), 1);
+        assert!(pdfs.join("a.pdf").exists());
+    }
+
+    #[test]
+    fn max_per_run_caps_moves_in_one_scan() {
+        let (tmp, watched) = watched_dir();
+        let pdfs = tmp.path().join("pdfs");
+        for i in 0..10 {
+            fs::write(watched.join(format!("{}.pdf", i)), "x").unwrap();
+        }
+
+        let mut capped = rule("PDFs", "*.pdf", move_to(&pdfs));
+        capped.max_per_run = Some(5);
+        let config = config_with(vec![folder(&watched, vec![capped])]);
+        let db = Database::open_in_memory().unwrap();
+
+        assert_eq!(scan_single_folder(&config, &db, "folder-1"), 5);
+        assert_eq!(fs::read_dir(&pdfs).unwrap().count(), 5);
+        assert_eq!(fs::read_dir(&watched).unwrap().count(), 5);
+
+        // The next scan picks up the rest
+        assert_eq!(scan_single_folder(&config, &db, "folder-1"), 5);
+        assert_eq!(fs::read_dir(&watched).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn cleanliness_counts_unmatched_files_by_extension() {
+        let (_tmp, watched) = watched_dir();
+        for name in ["a.pdf", "b.pdf", "c.zip", "d.zip", "e.ZIP", "f.txt", "skip.tmp"] {
+            fs::write(watched.join(name), "x").unwrap();
+        }
+        let mut f = folder(&watched, vec![rule("PDFs", "*.pdf", move_to(&watched.join("pdfs")))]);
+        f.whitelist = vec!["*.tmp".to_string()];
+        let config = config_with(vec![f]);
+
+        let score = folder_cleanliness(&config, "folder-1").unwrap();
+        assert_eq!(score.matched_files, 2);
+        assert_eq!(score.unmatched_files, 4);
+        assert_eq!(score.score, 33);
+        assert_eq!(
+            score.suggestions,
+            vec![
+                "3 .zip files unmatched — add a rule?".to_string(),
+                "1 .txt file unmatched — add a rule?".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn cleanliness_of_empty_folder_is_perfect() {
+        let (_tmp, watched) = watched_dir();
+        let config = config_with(vec![folder(&watched, Vec::new())]);
+        let score = folder_cleanliness(&config, "folder-1").unwrap();
+        assert_eq!(score.score, 100);
+        assert!(score.suggestions.is_empty());
+        assert!(folder_cleanliness(&config, "missing").is_none());
+    }
+
+    #[test]
+    fn dry_run_reports_plan_without_touching_files() {
+        let (tmp, watched) = watched_dir();
+        let pdfs = tmp.path().join("pdfs");
+        fs::write(watched.join("a.pdf"), "x").unwrap();
+        fs::write(watched.join("b.log"), "x").unwrap();
+        fs::write(watched.join("c.txt"), "x").unwrap();
+
+        let delete_logs = rule("Logs", "*.log", crate::config::Action::Delete { after_days: 0, delay_minutes: 60 });
+        let config = config_with(vec![folder(
+            &watched,
+            vec![rule("PDFs", "*.pdf", move_to(&pdfs.join("{ext}"))), delete_logs],
+        )]);
+
+        let mut planned = scan_dry_run(&config, "folder-1");
+        planned.sort_by(|a, b| a.file_name.cmp(&b.file_name));
+        assert_eq!(planned.len(), 2);
+
+        assert_eq!(planned[0].file_name, "a.pdf");
+        assert_eq!(planned[0].rule_name, "PDFs");
+        assert_eq!(planned[0].action, "move");
+        assert_eq!(planned[0].destination.as_deref(), Some(pdfs.join("pdf").to_string_lossy().as_ref()));
+        assert!(planned[0].scheduled_for.is_none());
+
+        assert_eq!(planned[1].file_name, "b.log");
+        assert_eq!(planned[1].action, "delete");
+        assert!(planned[1].scheduled_for.is_some());
+
+        // Nothing was executed
+        assert!(watched.join("a.pdf").exists());
+        assert!(!pdfs.exists());
+    }
+
+    #[test]
+    fn profiling_collects_timings_for_each_rule() {
+        let (_tmp, watched) = watched_dir();
+        for name in ["a.pdf", "b.txt", "c.pdf"] {
+            fs::write(watched.join(name), "x").unwrap();
+        }
+        let delete_later = || crate::config::Action::Delete { after_days: 0, delay_minutes: 60 };
+        let config = config_with(vec![folder(
+            &watched,
+            vec![rule("PDFs", "*.pdf", delete_later()), rule("B files", r"/^b/", delete_later())],
+        )]);
+
+        let profiles = profile_folder_scan(&config, "folder-1");
+        assert_eq!(profiles.len(), 2);
+        assert_eq!(profiles[0].rule_name, "PDFs");
+        assert_eq!(profiles[0].files_tested, 3);
+        assert_eq!(profiles[0].matches, 2);
+        assert_eq!(profiles[1].rule_name, "B files");
+        assert_eq!(profiles[1].files_tested, 3);
+        assert_eq!(profiles[1].matches, 1);
+    }
+
+    #[test]
+    fn scan_interval_override_takes_effect() {
+        let now = Instant::now();
+        let slot = Mutex::new(None);
+        assert_eq!(effective_scan_interval(5, &slot, now), (5, false));
+
+        *slot.lock().unwrap() = Some(ScanIntervalOverride::new(1, 10, now));
+        assert_eq!(effective_scan_interval(5, &slot, now), (1, true));
+        assert_eq!(effective_scan_interval(5, &slot, now + Duration::from_secs(9 * 60)), (1, true));
+    }
+
+    #[test]
+    fn scan_interval_override_reverts_after_expiry() {
+        let now = Instant::now();
+        let slot = Mutex::new(Some(ScanIntervalOverride::new(1, 10, now)));
+        assert_eq!(effective_scan_interval(5, &slot, now + Duration::from_secs(10 * 60)), (5, false));
+        assert!(slot.lock().unwrap().is_none());
+    }
+
+    #[test]
+    fn scheduler_clock_decouples_the_cycle_from_the_tick() {
+        let start = Instant::now();
+        let minute = |n: u64| start + Duration::from_secs(n * 60);
+        let mut clock = SchedulerClock::default();
+
+        assert_eq!(clock.tick(minute(0), 10, 60, false, false), TickPlan { run_cycle: true, run_daily_scan: true });
+        clock.full_scan_done(10);
+        assert_eq!(clock.tick(minute(1), 10, 60, false, false), TickPlan { run_cycle: false, run_daily_scan: false });
+        assert!(!clock.tick(minute(59), 10, 60, false, false).run_cycle);
+        assert!(clock.tick(minute(60), 10, 60, false, false).run_cycle);
+
+        // A new day is picked up on the next tick, not at the next cycle
+        let plan = clock.tick(minute(61), 11, 60, false, false);
+        assert!(plan.run_daily_scan && !plan.run_cycle);
+        // Until the scan actually runs (e.g. while paused) it stays due
+        assert!(clock.tick(minute(62), 11, 60, false, false).run_daily_scan);
+        clock.full_scan_done(11);
+        assert!(!clock.tick(minute(63), 11, 60, false, false).run_daily_scan);
+
+        assert_eq!(clock.tick(minute(64), 11, 60, false, true), TickPlan { run_cycle: true, run_daily_scan: true });
+    }
+
+    #[test]
+    fn next_deletion_run_lands_on_a_scheduler_tick() {
+        let at = |secs: i64| chrono::DateTime::from_timestamp(1_700_000_000 + secs, 0).unwrap();
+        let mut clock = SchedulerClock::default();
+        // Before the first tick: one tick from now at the earliest
+        assert_eq!(clock.next_deletion_run(at(-3600), None, at(0)), Some(at(60)));
+
+        clock.mark_tick(at(0));
+        assert_eq!(clock.next_deletion_run(at(-10), None, at(5)), Some(at(60)), "overdue runs on the next tick");
+        assert_eq!(clock.next_deletion_run(at(90), None, at(5)), Some(at(120)));
+        assert_eq!(clock.next_deletion_run(at(120), None, at(5)), Some(at(120)));
+    }
+
+    /// Schedule a due move of `watched/name` to `dest` by the rule named `rule_name`.
+    fn schedule_due_move(db: &Database, watched: &Path, name: &str, dest: &Path, rule_name: &str) {
+        let path = watched.join(name);
+        fs::write(&path, "x").unwrap();
+        db.upsert_scheduled_deletion(
+            &format!("{}-id", name),
+            &path.to_string_lossy(),
+            "folder-1",
+            rule_name,
+            name,
+            None,
+            Some(1),
+            "2000-01-01 00:00:00",
+            "2000-01-01 00:00:00",
+            "move",
+            Some(&dest.to_string_lossy()),
+            false,
+            0,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn delayed_moves_respect_the_rules_free_space_minimum() {
+        let (tmp, watched) = watched_dir();
+        let dest = tmp.path().join("dest");
+        let mut full = rule("Full", "*", move_to(&dest));
+        full.min_dest_free_bytes = Some(u64::MAX);
+        let config = config_with(vec![folder(&watched, vec![full])]);
+        let db = Database::open_in_memory().unwrap();
+        schedule_due_move(&db, &watched, "a.pdf", &dest, "Full");
+
+        assert_eq!(process_due_deletions_with_config(&db, Some(&config), true).processed, 0);
+        assert!(watched.join("a.pdf").exists());
+        assert_eq!(db.get_scheduled_deletions().unwrap()[0].attempt_count, 1, "retried later");
+    }
+
+    #[test]
+    fn delayed_moves_follow_the_rules_conflict_policy() {
+        let (tmp, watched) = watched_dir();
+        let dest = tmp.path().join("dest");
+        fs::create_dir_all(&dest).unwrap();
+        fs::write(dest.join("a.pdf"), "existing").unwrap();
+        fs::write(dest.join("b.pdf"), "existing").unwrap();
+        let with_policy = |name: &str, policy: ConflictPolicy| {
+            let mut r = rule(name, "*", move_to(&dest));
+            if let crate::config::Action::Move { on_conflict, .. } = &mut r.action {
+                *on_conflict = policy;
+            }
+            r
+        };
+        let config = config_with(vec![folder(
+            &watched,
+            vec![with_policy("Skip", ConflictPolicy::Skip), with_policy("Overwrite", ConflictPolicy::Overwrite)],
+        )]);
+        let db = Database::open_in_memory().unwrap();
+        schedule_due_move(&db, &watched, "a.pdf", &dest, "Skip");
+        schedule_due_move(&db, &watched, "b.pdf", &dest, "Overwrite");
+
+        assert_eq!(process_due_deletions_with_config(&db, Some(&config), true).processed, 1);
+        assert!(watched.join("a.pdf").exists(), "skipped, not renamed");
+        assert_eq!(fs::read_to_string(dest.join("a.pdf")).unwrap(), "existing");
+        assert!(!dest.join("a (1).pdf").exists());
+        assert_eq!(fs::read_to_string(dest.join("b.pdf")).unwrap(), "x");
+        assert!(db.get_scheduled_deletions().unwrap().is_empty());
+        let log = db.get_activity_log(10, 0, None).unwrap();
+        assert!(log.iter().any(|e| e.file_name == "a.pdf" && e.result == "skipped_conflict"));
+    }
+
+    #[test]
+    fn delayed_moves_record_when_the_rule_last_triggered() {
+        let (tmp, watched) = watched_dir();
+        let dest = tmp.path().join("dest");
+        let config = config_with(vec![folder(&watched, vec![rule("Later", "*", move_to(&dest))])]);
+        let db = Database::open_in_memory().unwrap();
+        db.insert_rule_metadata("Later-id", "folder-1", "2026-01-01T00:00:00Z").unwrap();
+        schedule_due_move(&db, &watched, "a.pdf", &dest, "Later");
+
+        assert_eq!(process_due_deletions_with_config(&db, Some(&config), true).processed, 1);
+        let meta = db.get_rule_metadata("folder-1").unwrap();
+        assert!(meta[0].last_triggered_at.is_some());
+    }
+
+    #[test]
+    fn deletions_held_by_the_cron_leave_due_moves_running() {
+        let (tmp, watched) = watched_dir();
+        let dest = tmp.path().join("dest");
+        fs::create_dir_all(&dest).unwrap();
+        let db = Database::open_in_memory().unwrap();
+        for (id, name, action_type) in [("id-1", "old.log", "delete"), ("id-2", "report.pdf", "move")] {
+            let path = watched.join(name);
+            fs::write(&path, "x").unwrap();
+            let destination = (action_type == "move").then(|| dest.to_string_lossy().to_string());
+            db.upsert_scheduled_deletion(
+                id,
+                &path.to_string_lossy(),
+                "folder-1",
+                "Rule",
+                name,
+                None,
+                Some(1),
+                "2000-01-01 00:00:00",
+                "2000-01-01 00:00:00",
+                action_type,
+                destination.as_deref(),
+                false,
+                0,
+            )
+            .unwrap();
+        }
+
+        let run = process_due_with_throttle(&db, None, BatchThrottle::new(0, Duration::ZERO, |_| {}), false);
+        assert_eq!(run.processed, 1);
+        assert!(dest.join("report.pdf").exists());
+        assert!(watched.join("old.log").exists());
+        let left: Vec<String> = db.get_scheduled_deletions().unwrap().into_iter().map(|e| e.id).collect();
+        assert_eq!(left, ["id-1"]);
+    }
+
+    #[test]
+    fn standard_cron_weekdays_start_at_sunday_zero() {
+        let sunday = Utc.with_ymd_and_hms(2026, 10, 18, 0, 0, 0).unwrap();
+        let first_fire = |expr: &str| parse_deletion_cron(expr).unwrap().after(&sunday).next().unwrap();
+
+        assert_eq!(first_fire("0 9 * * 1-5"), Utc.with_ymd_and_hms(2026, 10, 19, 9, 0, 0).unwrap());
+        assert_eq!(first_fire("30 * * * 0"), Utc.with_ymd_and_hms(2026, 10, 18, 0, 30, 0).unwrap());
+        assert_eq!(first_fire("0 0 * * 6-7").weekday(), chrono::Weekday::Sat);
+        assert_eq!(crate_weekdays("5-7"), "6-7,1");
+        assert_eq!(crate_weekdays("Mon-Fri"), "Mon-Fri");
+        assert_eq!(crate_weekdays("*/2"), "*/2");
+        // The crate's own 6-field form keeps its numbering
+        assert_eq!(first_fire("0 0 9 * * 2").weekday(), chrono::Weekday::Mon);
+    }
+
+    #[test]
+    fn deletion_cron_gates_due_actions() {
+        assert!(parse_deletion_cron("0 9,17 * * Mon-Fri").is_ok());
+        assert!(parse_deletion_cron("every day at nine").is_err());
+
+        let local = |h: u32, m: u32| {
+            chrono::Local
+                .with_ymd_and_hms(2024, 3, 4, h, m, 0) // a Monday
+                .unwrap()
+                .to_utc()
+        };
+        let cron = Some("0 9,17 * * Mon-Fri");
+        let mut clock = SchedulerClock::default();
+        assert!(clock.deletions_due(None, local(8, 0)), "no cron: every tick");
+        assert!(!clock.deletions_due(cron, local(8, 0)));
+        assert!(!clock.deletions_due(cron, local(8, 59)));
+        assert!(clock.deletions_due(cron, local(9, 0)));
+        assert!(!clock.deletions_due(cron, local(9, 1)), "fires once per fire time");
+        // A fire missed while the thread wasn't ticking runs on the next tick
+        assert!(clock.deletions_due(cron, local(18, 30)));
+
+        clock.mark_tick(local(10, 0));
+        assert_eq!(clock.next_deletion_run(local(10, 0), cron, local(10, 0)), Some(local(17, 0)));
+    }
 }
@@ -0,0 +1,96 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Where deleted files go when the OS recycle bin isn't used (`DeletionMode::Staging`,
+/// or as a fallback when recycling fails): `trash_staging` next to the database in
+/// `data_dir`. Each file gets its own subfolder, so same-named files never collide and
+/// the folder's age is the time of deletion.
+pub fn staging_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join("trash_staging")
+}
+
+/// Move `file_path` (a file or folder) into a fresh subfolder of `root`.
+/// Returns where it ended up.
+pub fn stage(file_path: &Path, root: &Path) -> io::Result<PathBuf> {
+    let slot = root.join(uuid::Uuid::new_v4().to_string());
+    fs::create_dir_all(&slot)?;
+    let staged = slot.join(file_path.file_name().unwrap_or_default());
+    let moved = fs::rename(file_path, &staged).or_else(|e| {
+        // Different drive: copy then remove (files only — folders need the rename)
+        if !file_path.is_file() {
+            return Err(e);
+        }
+        crate::rules::copy_file_preserving_times(file_path, &staged)?;
+        fs::remove_file(file_path)
+    });
+    if moved.is_err() {
+        let _ = fs::remove_dir_all(&slot);
+    }
+    moved.map(|_| staged)
+}
+
+/// Permanently delete staged files older than `retention`, judged by when they were
+/// staged. Returns how many were removed.
+pub fn purge_older_than(root: &Path, retention: Duration, now: SystemTime) -> u32 {
+    let Ok(entries) = fs::read_dir(root) else {
+        return 0;
+    };
+    let mut purged = 0;
+    for slot in entries.flatten().map(|e| e.path()).filter(|p| p.is_dir()) {
+        let staged_at = fs::metadata(&slot).and_then(|m| m.modified());
+        let expired = staged_at.is_ok_and(|at| now.duration_since(at).is_ok_and(|age| age >= retention));
+        if !expired {
+            continue;
+        }
+        match fs::remove_dir_all(&slot) {
+            Ok(()) => purged += 1,
+            Err(e) => log::warn!("Failed to purge staged file {}: {}", slot.display(), e),
+        }
+    }
+    purged
+}
+
+/// Total size of everything under `root`, in bytes.
+pub fn staged_size(root: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(root) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.metadata() {
+            Ok(meta) if meta.is_dir() => staged_size(&entry.path()),
+            Ok(meta) => meta.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn staged_files_keep_their_name_and_expire() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().join("trash_staging");
+        let file = tmp.path().join("old.log");
+        fs::write(&file, "12345").unwrap();
+        let twin = tmp.path().join("sub").join("old.log");
+        fs::create_dir_all(twin.parent().unwrap()).unwrap();
+        fs::write(&twin, "x").unwrap();
+
+        let staged = stage(&file, &root).unwrap();
+        let staged_twin = stage(&twin, &root).unwrap();
+        assert!(!file.exists() && staged.exists());
+        assert_ne!(staged, staged_twin);
+        assert_eq!(staged.file_name().unwrap(), "old.log");
+        assert_eq!(staged_size(&root), 6);
+
+        let retention = Duration::from_secs(7 * 86_400);
+        assert_eq!(purge_older_than(&root, retention, SystemTime::now()), 0);
+        assert_eq!(purge_older_than(&root, retention, SystemTime::now() + retention), 2);
+        assert_eq!(staged_size(&root), 0);
+    }
+}
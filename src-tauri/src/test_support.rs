@@ -0,0 +1,70 @@
+//! Shared fixtures for unit tests.
+
+use std::fs;
+use std::path::Path;
+
+use crate::condition;
+use crate::config::{Action, ConflictPolicy, DeletionMode, Rule, WatchedFolder};
+use crate::db::Database;
+use crate::rules::{evaluate_file_in_pass, EvalOutcome, ScanPass};
+
+pub fn rule(name: &str, condition_text: &str, action: Action) -> Rule {
+    Rule {
+        id: format!("{}-id", name),
+        name: name.to_string(),
+        description: String::new(),
+        enabled: true,
+        condition: condition::parse(condition_text).unwrap(),
+        condition_text: condition_text.to_string(),
+        action,
+        whitelist: Vec::new(),
+        match_subdirectories: false,
+        tags: Vec::new(),
+        case_sensitive: false,
+        max_per_run: None,
+        min_dest_free_bytes: None,
+        priority: 0,
+        active_window: None,
+    }
+}
+
+pub fn folder(path: &Path, rules: Vec<Rule>) -> WatchedFolder {
+    WatchedFolder {
+        id: "folder-1".to_string(),
+        path: path.to_path_buf(),
+        enabled: true,
+        rules,
+        whitelist: Vec::new(),
+        watch_subdirectories: false,
+        default_action: None,
+        default_after_days: 0,
+        paused_until: None,
+        auto_watch_new_subdirs: None,
+    }
+}
+
+pub fn move_to(destination: &Path) -> Action {
+    Action::Move {
+        destination: destination.to_path_buf(),
+        delay_minutes: 0,
+        keep_source: false,
+        on_conflict: ConflictPolicy::Rename,
+        destinations: Vec::new(),
+        preserve_structure: false,
+    }
+}
+
+/// Temp dir with a `watched` subfolder; returns (guard, watched path).
+pub fn watched_dir() -> (tempfile::TempDir, std::path::PathBuf) {
+    let tmp = tempfile::tempdir().unwrap();
+    let watched = tmp.path().join("watched");
+    fs::create_dir_all(&watched).unwrap();
+    (tmp, watched)
+}
+
+/// Evaluate one file in its own default pass (RunCommand refused).
+pub fn evaluate_file_full(file_path: &Path, folder: &WatchedFolder, db: &Database) -> EvalOutcome {
+    // Staged (into the test staging dir) rather than sent to the real recycle bin
+    let mut pass = ScanPass::for_file(folder, file_path).with_deletion_mode(DeletionMode::Staging);
+    evaluate_file_in_pass(file_path, folder, db, &mut pass)
+}
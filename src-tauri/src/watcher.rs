@@ -1,24 +1,256 @@
-use std::sync::{Arc, Mutex};
-use std::time::Duration;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock, Weak};
+use std::time::{Duration, Instant};
 
 use notify::{RecommendedWatcher, RecursiveMode};
 use notify_debouncer_mini::{new_debouncer, DebouncedEventKind};
 
-use crate::config::AppConfig;
+use crate::config::{self, has_ignored_extension, is_managed_destination, AppConfig};
 use crate::db::Database;
 use crate::rules;
 
+/// How long events under a path the app just wrote are ignored. Covers the debounce
+/// plus a stability wait; anything missed is still seen by the next scan.
+const OWN_WRITE_TTL: Duration = Duration::from_secs(30);
+
+/// How long a new subfolder of an auto-watching folder must keep its name before it's
+/// watched, so Explorer's "New folder" is added under the name the user gives it.
+const NEW_SUBDIR_SETTLE: Duration = Duration::from_secs(30);
+
+/// How often work deferred by the watcher callback is re-checked. Also the gap between
+/// the size readings of a file waiting to settle.
+const DEFERRED_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A file whose size hadn't been seen to settle yet.
+#[derive(Debug)]
+struct PendingFile {
+    size: u64,
+    first_seen: Instant,
+}
+
+/// Work the watcher callback put off to a later tick instead of blocking on it.
+#[derive(Debug, Default)]
+struct Deferred {
+    /// Files whose size is still being watched, with their last reading
+    files: HashMap<PathBuf, PendingFile>,
+    /// New subfolders waiting to settle before being auto-watched, with when each was first seen
+    subdirs: HashMap<PathBuf, Instant>,
+}
+
+impl Deferred {
+    /// Queue `path` (last seen at `size`) to be handled once its size stops changing.
+    /// A file already queued keeps its first-seen time and takes the new reading.
+    fn note_file(&mut self, path: &Path, size: u64, now: Instant) {
+        self.files
+            .entry(path.to_path_buf())
+            .and_modify(|pending| pending.size = size)
+            .or_insert(PendingFile { size, first_seen: now });
+    }
+
+    /// Take a fresh size reading of every queued file. Returns the files whose size matched
+    /// the previous reading (ready to handle) and those still changing once `timeout` has
+    /// passed since they were first seen (given up on, left to the next scan). Files that
+    /// vanished are dropped; the rest stay queued with the new reading.
+    fn check_files(
+        &mut self,
+        now: Instant,
+        timeout: Duration,
+        mut size_of: impl FnMut(&Path) -> Option<u64>,
+    ) -> (Vec<PathBuf>, Vec<PathBuf>) {
+        let mut stable = Vec::new();
+        let mut timed_out = Vec::new();
+        self.files.retain(|path, pending| match size_of(path) {
+            Some(size) if size == pending.size => {
+                stable.push(path.clone());
+                false
+            }
+            Some(size) => {
+                pending.size = size;
+                if now.duration_since(pending.first_seen) >= timeout {
+                    timed_out.push(path.clone());
+                    false
+                } else {
+                    true
+                }
+            }
+            None => false,
+        });
+        (stable, timed_out)
+    }
+
+    fn note_subdir(&mut self, dir: &Path, now: Instant) {
+        self.subdirs.entry(dir.to_path_buf()).or_insert(now);
+    }
+
+    /// Subfolders that have kept their name for [`NEW_SUBDIR_SETTLE`]. Ones that no longer
+    /// exist (renamed or removed while settling) are dropped; the new name has its own entry.
+    fn take_settled_subdirs(&mut self, now: Instant) -> Vec<PathBuf> {
+        self.subdirs.retain(|dir, _| dir.is_dir());
+        let settled: Vec<PathBuf> = self
+            .subdirs
+            .iter()
+            .filter(|(_, seen)| now.duration_since(**seen) >= NEW_SUBDIR_SETTLE)
+            .map(|(dir, _)| dir.clone())
+            .collect();
+        for dir in &settled {
+            self.subdirs.remove(dir);
+        }
+        settled
+    }
+}
+
+/// Paths recently written by our own moves/copies, so landing a file in a
+/// (recursively) watched destination doesn't trigger rules on it again.
+#[derive(Debug, Default)]
+struct OwnWrites {
+    written_at: HashMap<PathBuf, Instant>,
+}
+
+impl OwnWrites {
+    fn note(&mut self, path: &Path, now: Instant) {
+        self.written_at.retain(|_, at| now.duration_since(*at) < OWN_WRITE_TTL);
+        self.written_at.insert(path.to_path_buf(), now);
+    }
+
+    /// True if `path` or one of its ancestors (a moved directory) was written recently.
+    fn covers(&self, path: &Path, now: Instant) -> bool {
+        path.ancestors().any(|p| {
+            self.written_at
+                .get(p)
+                .is_some_and(|at| now.duration_since(*at) < OWN_WRITE_TTL)
+        })
+    }
+}
+
+fn own_writes() -> &'static Mutex<OwnWrites> {
+    static OWN_WRITES: OnceLock<Mutex<OwnWrites>> = OnceLock::new();
+    OWN_WRITES.get_or_init(Default::default)
+}
+
+/// Record that the app itself is putting a file (or directory) at `path`.
+/// Watcher events for it over the next [`OWN_WRITE_TTL`] are ignored.
+pub fn note_own_write(path: &Path) {
+    if let Ok(mut writes) = own_writes().lock() {
+        writes.note(path, Instant::now());
+    }
+}
+
+/// Shows a desktop notification (title, body). Installed once at startup by the app,
+/// which owns the notification plugin; without one, Notify rules only log.
+type Notifier = Box<dyn Fn(&str, &str) + Send + Sync>;
+
+fn notifier() -> &'static OnceLock<Notifier> {
+    static NOTIFIER: OnceLock<Notifier> = OnceLock::new();
+    &NOTIFIER
+}
+
+/// Install the function used to show Notify rules' desktop notifications.
+pub fn set_notifier(show: impl Fn(&str, &str) + Send + Sync + 'static) {
+    let _ = notifier().set(Box::new(show));
+}
+
+/// One filesystem event as the debouncer delivered it, before any filtering.
+/// Sent to the frontend as `raw-fs-event` when `debug_watcher_events` is on.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct RawFsEvent {
+    pub path: String,
+    /// "Any", "AnyContinuous", or "Error: …" when the watcher itself failed
+    pub kind: String,
+}
+
+type RawEventSink = Box<dyn Fn(&RawFsEvent) + Send + Sync>;
+
+fn raw_event_sink() -> &'static OnceLock<RawEventSink> {
+    static RAW_EVENT_SINK: OnceLock<RawEventSink> = OnceLock::new();
+    &RAW_EVENT_SINK
+}
+
+/// Install the function that forwards raw events to the frontend.
+pub fn set_raw_event_sink(emit: impl Fn(&RawFsEvent) + Send + Sync + 'static) {
+    let _ = raw_event_sink().set(Box::new(emit));
+}
+
+type FoldersChangedSink = Box<dyn Fn() + Send + Sync>;
+
+fn folders_changed_sink() -> &'static OnceLock<FoldersChangedSink> {
+    static FOLDERS_CHANGED_SINK: OnceLock<FoldersChangedSink> = OnceLock::new();
+    &FOLDERS_CHANGED_SINK
+}
+
+/// Install the function that tells the frontend the watcher changed the folder list.
+pub fn set_folders_changed_sink(emit: impl Fn() + Send + Sync + 'static) {
+    let _ = folders_changed_sink().set(Box::new(emit));
+}
+
+fn folders_changed() {
+    if let Some(emit) = folders_changed_sink().get() {
+        emit();
+    }
+}
+
+/// Report a debouncer batch (or its error) to `emit`, if debugging is on.
+fn emit_raw_events(events: &notify_debouncer_mini::DebounceEventResult, enabled: bool, emit: impl Fn(&RawFsEvent)) {
+    if !enabled {
+        return;
+    }
+    match events {
+        Ok(events) => {
+            for event in events {
+                emit(&RawFsEvent {
+                    path: event.path.to_string_lossy().to_string(),
+                    kind: format!("{:?}", event.kind),
+                });
+            }
+        }
+        Err(e) => {
+            let path = e.paths.first().map(|p| p.to_string_lossy().to_string()).unwrap_or_default();
+            emit(&RawFsEvent { path, kind: format!("Error: {}", e) });
+        }
+    }
+}
+
+fn is_own_write(path: &Path) -> bool {
+    own_writes()
+        .lock()
+        .map(|writes| writes.covers(path, Instant::now()))
+        .unwrap_or(false)
+}
+
+/// Whether one configured folder is registered with the OS watcher.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct FolderWatchStatus {
+    pub folder_id: String,
+    pub path: String,
+    pub watching: bool,
+    /// Why the folder isn't watched; None when it is, or when it's disabled.
+    pub error: Option<String>,
+}
+
+/// The watcher's overall state plus every configured folder's registration.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct WatcherStatus {
+    pub running: bool,
+    pub folders: Vec<FolderWatchStatus>,
+}
+
 pub struct FileWatcher {
     debouncer: Option<notify_debouncer_mini::Debouncer<RecommendedWatcher>>,
+    /// Per-folder outcome of the last `start`.
+    folders: Vec<FolderWatchStatus>,
 }
 
 impl FileWatcher {
     pub fn new() -> Self {
-        Self { debouncer: None }
+        Self { debouncer: None, folders: Vec::new() }
     }
 
     /// Start watching all enabled folders from config.
     /// Calls `on_file` callback for each new/modified file (debounced).
+    /// Registration is best-effort: a folder that can't be watched (missing, no
+    /// permission, unmounted drive) is logged and recorded in `status()`, and the
+    /// remaining folders are still watched. Errs only when there were enabled folders
+    /// and none of them could be watched.
     pub fn start(
         &mut self,
         config: &AppConfig,
@@ -30,17 +262,79 @@ impl FileWatcher {
 
         let db_clone = db.clone();
         let config_for_callback = config_arc.clone();
+        let deferred = Arc::new(Mutex::new(Deferred::default()));
+        let deferred_for_callback = deferred.clone();
 
         let mut debouncer = new_debouncer(
             Duration::from_secs(3), // 3s debounce — wait for downloads to finish
             move |events: Result<Vec<notify_debouncer_mini::DebouncedEvent>, notify::Error>| {
-                if let Ok(events) = events {
+                let (stability_check, ignored_extensions, debug_events) = {
                     let cfg = config_for_callback.lock().unwrap();
+                    (
+                        cfg.settings.stability_timeout_secs > 0,
+                        cfg.settings.global_ignore_extensions.clone(),
+                        cfg.settings.debug_watcher_events,
+                    )
+                };
+                if let Some(sink) = raw_event_sink().get() {
+                    emit_raw_events(&events, debug_events, sink);
+                }
+                if let Ok(events) = events {
+                    // Watched folders whose directory vanished in this batch: the old
+                    // name of a rename whose new name is among the other events
+                    let vanished: Vec<PathBuf> = {
+                        let cfg = config_for_callback.lock().unwrap();
+                        events
+                            .iter()
+                            .map(|e| &e.path)
+                            .filter(|p| !p.exists() && cfg.folders.iter().any(|f| &f.path == *p))
+                            .cloned()
+                            .collect()
+                    };
                     for event in events {
                         if event.kind == DebouncedEventKind::Any {
                             let path = &event.path;
+                            // Partial downloads are ignored outright — don't wait on them
+                            if is_ignored_download(path, &ignored_extensions) {
+                                continue;
+                            }
+                            // Our own moves into a watched subtree shouldn't fire rules again
+                            if is_own_write(path) {
+                                log::debug!("Ignoring {} (written by a rule)", path.display());
+                                continue;
+                            }
+                            // Large downloads can outlast the debounce; rather than block this
+                            // thread until the size settles, re-check it on the deferred tick
+                            if stability_check && path.is_file() {
+                                if let Some(size) = file_size(path) {
+                                    deferred_for_callback.lock().unwrap().note_file(path, size, Instant::now());
+                                }
+                                continue;
+                            }
+                            // A new project folder under an auto-watching folder becomes a
+                            // watched folder itself instead of being handled by the parent's
+                            // rules: renamed ones follow their new name, new ones are added
+                            // once they've settled
+                            if path.is_dir() {
+                                let mut cfg = config_for_callback.lock().unwrap();
+                                if config::is_auto_watch_candidate(&cfg, path) {
+                                    let renamed = vanished
+                                        .iter()
+                                        .find_map(|old| config::rename_auto_watched_folder(&mut cfg, old, path));
+                                    match renamed {
+                                        Some(folder) => save_renamed_folder(&cfg, &folder),
+                                        None => deferred_for_callback
+                                            .lock()
+                                            .unwrap()
+                                            .note_subdir(path, Instant::now()),
+                                    }
+                                    continue;
+                                }
+                            }
                             // Process both files and directories (folder-name matching)
+                            // on a clone, so a slow rule (RunCommand) doesn't block config commands
                             if path.is_file() || path.is_dir() {
+                                let cfg = config_for_callback.lock().unwrap().clone();
                                 handle_file_event(path, &cfg, &db_clone);
                             }
                         }
@@ -50,77 +344,187 @@ impl FileWatcher {
         )
         .map_err(|e| format!("Failed to create file watcher: {}", e))?;
 
+        spawn_deferred_checks(Arc::downgrade(&deferred), config_arc.clone(), db.clone());
+        // The debouncer's callback holds the only other strong reference, so the checks
+        // end once the watcher is stopped
+        drop(deferred);
+
+        let mut folders = Vec::new();
         for folder in &config.folders {
-            if folder.enabled && folder.path.exists() {
-                let needs_recursive = folder.watch_subdirectories
-                    || folder.rules.iter().any(|r| r.match_subdirectories);
-                let mode = if needs_recursive {
-                    RecursiveMode::Recursive
-                } else {
-                    RecursiveMode::NonRecursive
-                };
-                debouncer
-                    .watcher()
-                    .watch(&folder.path, mode)
-                    .map_err(|e| {
-                        format!("Failed to watch {}: {}", folder.path.display(), e)
-                    })?;
-                log::info!("Watching{}: {}", if needs_recursive { " (recursive)" } else { "" }, folder.path.display());
+            let status = |watching: bool, error: Option<String>| FolderWatchStatus {
+                folder_id: folder.id.clone(),
+                path: folder.path.to_string_lossy().to_string(),
+                watching,
+                error,
+            };
+            if !folder.enabled {
+                folders.push(status(false, None));
+                continue;
+            }
+            if !folder.path.exists() {
+                log::warn!("Not watching {}: folder not found", folder.path.display());
+                folders.push(status(false, Some("Folder not found".to_string())));
+                continue;
+            }
+            // Auto-watching parents stay recursive so new subfolders' files are seen
+            // before the next restart watches them directly
+            let needs_recursive = folder.watch_subdirectories
+                || folder.auto_watch_new_subdirs.is_some()
+                || folder.rules.iter().any(|r| r.match_subdirectories);
+            let mode = if needs_recursive {
+                RecursiveMode::Recursive
+            } else {
+                RecursiveMode::NonRecursive
+            };
+            match debouncer.watcher().watch(&folder.path, mode) {
+                Ok(()) => {
+                    log::info!("Watching{}: {}", if needs_recursive { " (recursive)" } else { "" }, folder.path.display());
+                    folders.push(status(true, None));
+                }
+                Err(e) => {
+                    log::warn!("Failed to watch {}: {}", folder.path.display(), e);
+                    folders.push(status(false, Some(e.to_string())));
+                }
             }
         }
 
+        let failures: Vec<String> = folders
+            .iter()
+            .filter_map(|f| f.error.as_ref().map(|e| format!("{} ({})", f.path, e)))
+            .collect();
+        let any_watched = folders.iter().any(|f| f.watching);
+        self.folders = folders;
+        if !any_watched && !failures.is_empty() {
+            return Err(format!("No folder could be watched: {}", failures.join("; ")));
+        }
+        if !failures.is_empty() {
+            log::warn!("Watching with {} folder(s) skipped: {}", failures.len(), failures.join("; "));
+        }
         self.debouncer = Some(debouncer);
         Ok(())
     }
 
     pub fn stop(&mut self) {
         self.debouncer = None;
+        self.folders.clear();
         log::info!("File watcher stopped");
     }
 
+    /// Whether the watcher runs and which configured folders it actually watches.
+    pub fn status(&self) -> WatcherStatus {
+        WatcherStatus { running: self.is_running(), folders: self.folders.clone() }
+    }
+
     pub fn is_running(&self) -> bool {
         self.debouncer.is_some()
     }
 }
 
+fn file_size(path: &Path) -> Option<u64> {
+    std::fs::metadata(path).map(|m| m.len()).ok()
+}
+
+/// Re-check the watcher's deferred work every [`DEFERRED_CHECK_INTERVAL`] on a thread
+/// of its own, until `deferred` is dropped with the watcher.
+fn spawn_deferred_checks(deferred: Weak<Mutex<Deferred>>, config: Arc<Mutex<AppConfig>>, db: Arc<Database>) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(DEFERRED_CHECK_INTERVAL);
+        let Some(deferred) = deferred.upgrade() else {
+            break;
+        };
+        let timeout = Duration::from_secs(config.lock().unwrap().settings.stability_timeout_secs as u64);
+        let (stable, timed_out) = deferred.lock().unwrap().check_files(Instant::now(), timeout, file_size);
+        for path in timed_out {
+            // Still-growing files are picked up again by the next scan
+            log::info!("Skipping {} — still being written after {}s", path.display(), timeout.as_secs());
+        }
+        if !stable.is_empty() {
+            // Clone the config so a slow rule (RunCommand) doesn't hold the mutex
+            let cfg = config.lock().unwrap().clone();
+            for path in stable {
+                handle_file_event(&path, &cfg, &db);
+            }
+        }
+        let settled = deferred.lock().unwrap().take_settled_subdirs(Instant::now());
+        for dir in settled {
+            let mut cfg = config.lock().unwrap();
+            if let Some(folder) = config::auto_watch_new_subdir(&mut cfg, &dir) {
+                add_auto_watched_folder(&cfg, &folder, &db);
+            }
+        }
+    });
+}
+
+/// Persist a folder `auto_watch_new_subdir` just added to the config.
+fn add_auto_watched_folder(config: &AppConfig, folder: &config::WatchedFolder, db: &Database) {
+    if let Err(e) = config::save_config(config) {
+        log::warn!("Failed to save config after auto-watching {}: {}", folder.path.display(), e);
+    }
+    let now = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+    for rule in &folder.rules {
+        let _ = db.insert_rule_metadata(&rule.id, &folder.id, &now);
+    }
+    log::info!("Auto-watching new folder {} ({} rules)", folder.path.display(), folder.rules.len());
+    folders_changed();
+}
+
+/// Persist an auto-watched folder `rename_auto_watched_folder` just moved to its new name.
+fn save_renamed_folder(config: &AppConfig, folder: &config::WatchedFolder) {
+    if let Err(e) = config::save_config(config) {
+        log::warn!("Failed to save config after following rename to {}: {}", folder.path.display(), e);
+    }
+    log::info!("Auto-watched folder renamed to {}", folder.path.display());
+    folders_changed();
+}
+
+fn is_ignored_download(path: &Path, extensions: &[String]) -> bool {
+    path.file_name()
+        .map(|n| has_ignored_extension(&n.to_string_lossy(), extensions))
+        .unwrap_or(false)
+}
+
 fn handle_file_event(
     file_path: &std::path::Path,
     config: &AppConfig,
     db: &Database,
 ) {
+    if config.settings.is_paused_at(chrono::Utc::now()) {
+        log::debug!("Automation is paused, ignoring {}", file_path.display());
+        return;
+    }
+
+    // Global ignore list runs before the whitelist and every rule
+    if is_ignored_download(file_path, &config.settings.global_ignore_extensions) {
+        log::debug!("Ignoring {} (globally ignored extension)", file_path.display());
+        return;
+    }
+
     // Find which watched folder this file belongs to
-    let folder = config.folders.iter().find(|f| {
-        f.enabled
-            && if f.watch_subdirectories {
-                file_path.starts_with(&f.path)
-            } else {
-                // Direct child file or direct child directory
-                file_path
-                    .parent()
-                    .map(|p| p == f.path)
-                    .unwrap_or(false)
-            }
-    });
+    let folder = config::owning_folder(config, file_path);
 
     if let Some(folder) = folder {
+        if is_managed_destination(file_path, &folder.path, config) {
+            log::debug!("Ignoring {} (inside a Move destination)", file_path.display());
+            return;
+        }
+        if folder.is_paused_at(chrono::Utc::now()) {
+            log::debug!("Folder {} is paused, ignoring {}", folder.path.display(), file_path.display());
+            return;
+        }
+
         let now = chrono::Utc::now()
             .format("%Y-%m-%d %H:%M:%S")
             .to_string();
 
-        match rules::evaluate_file_full(file_path, folder, db) {
+        let mut pass = rules::ScanPass::for_file(folder, file_path)
+            .with_run_command_allowed(config.settings.allow_run_command)
+            .with_deletion_mode(config.settings.deletion_mode);
+        match rules::evaluate_file_in_pass(file_path, folder, db, &mut pass) {
             rules::EvalOutcome::Action(result) => {
-                let id = uuid::Uuid::new_v4().to_string();
-                let _ = db.insert_activity(
-                    &id,
-                    &result.file_path,
-                    &result.file_name,
-                    &result.action,
-                    Some(&result.rule_name),
-                    Some(&folder.id),
-                    &now,
-                    if result.success { "success" } else { "error" },
-                    result.details.as_deref(),
-                );
+                let _ = db.insert_activity_sized(&crate::scheduler::action_activity(&result, &folder.id, &now));
+                if result.fired() {
+                    let _ = db.update_rule_triggered(&result.rule_id, &folder.id, &now);
+                }
 
                 log::info!(
                     "[{}] {} → {} ({})",
@@ -164,5 +568,178 @@ fn handle_file_event(
             }
             rules::EvalOutcome::NoMatch => {}
         }
+
+        for notice in pass.take_notifications() {
+            let _ = db.insert_activity(
+                &uuid::Uuid::new_v4().to_string(),
+                &notice.file_path,
+                &notice.file_name,
+                &notice.action,
+                Some(&notice.rule_name),
+                Some(&folder.id),
+                &now,
+                notice.result_label(),
+                notice.details.as_deref(),
+            );
+            let _ = db.update_rule_triggered(&notice.rule_id, &folder.id, &now);
+            if config.settings.show_notifications {
+                if let Some(show) = notifier().get() {
+                    show(&notice.rule_name, notice.details.as_deref().unwrap_or(&notice.file_name));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::folder;
+
+    #[test]
+    fn start_watches_remaining_folders_when_one_is_missing() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut missing = folder(&dir.path().join("unplugged"), Vec::new());
+        missing.id = "missing".to_string();
+        let mut disabled = folder(dir.path(), Vec::new());
+        disabled.id = "disabled".to_string();
+        disabled.enabled = false;
+        let present = folder(dir.path(), Vec::new());
+        let config = AppConfig { folders: vec![missing, disabled, present], ..Default::default() };
+        let db = Arc::new(Database::open_in_memory().unwrap());
+
+        let mut watcher = FileWatcher::new();
+        watcher.start(&config, db, Arc::new(Mutex::new(config.clone()))).unwrap();
+
+        let status = watcher.status();
+        assert!(status.running);
+        let by_id = |id: &str| status.folders.iter().find(|f| f.folder_id == id).unwrap();
+        assert!(!by_id("missing").watching);
+        assert_eq!(by_id("missing").error.as_deref(), Some("Folder not found"));
+        assert!(!by_id("disabled").watching && by_id("disabled").error.is_none());
+        assert!(by_id("folder-1").watching);
+
+        watcher.stop();
+        assert!(watcher.status().folders.is_empty());
+    }
+
+    #[test]
+    fn start_fails_only_when_no_folder_can_be_watched() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = AppConfig { folders: vec![folder(&dir.path().join("gone"), Vec::new())], ..Default::default() };
+        let db = Arc::new(Database::open_in_memory().unwrap());
+        let mut watcher = FileWatcher::new();
+
+        let err = watcher.start(&config, db.clone(), Arc::new(Mutex::new(config.clone()))).unwrap_err();
+        assert!(err.contains("gone"));
+        let status = watcher.status();
+        assert!(!status.running);
+        assert_eq!(status.folders[0].error.as_deref(), Some("Folder not found"));
+
+        // No enabled folders is not a failure — there is simply nothing to watch yet
+        let empty = AppConfig::default();
+        watcher.start(&empty, db, Arc::new(Mutex::new(empty.clone()))).unwrap();
+        assert!(watcher.is_running());
+    }
+
+    #[test]
+    fn raw_events_are_emitted_only_when_debugging() {
+        use notify_debouncer_mini::DebouncedEvent;
+        let batch = Ok(vec![DebouncedEvent::new(PathBuf::from("/downloads/a.pdf"), DebouncedEventKind::Any)]);
+        let emitted = Mutex::new(Vec::new());
+        let record = |e: &RawFsEvent| emitted.lock().unwrap().push(e.clone());
+
+        emit_raw_events(&batch, false, record);
+        assert!(emitted.lock().unwrap().is_empty());
+
+        emit_raw_events(&batch, true, record);
+        assert_eq!(
+            *emitted.lock().unwrap(),
+            vec![RawFsEvent { path: PathBuf::from("/downloads/a.pdf").to_string_lossy().to_string(), kind: "Any".to_string() }]
+        );
+    }
+
+    #[test]
+    fn own_writes_cover_descendants_until_they_expire() {
+        let start = Instant::now();
+        let mut writes = OwnWrites::default();
+        writes.note(Path::new("/sorted/Photos"), start);
+
+        assert!(writes.covers(Path::new("/sorted/Photos"), start));
+        assert!(writes.covers(Path::new("/sorted/Photos/a.jpg"), start));
+        assert!(!writes.covers(Path::new("/sorted/Docs/a.pdf"), start));
+        assert!(!writes.covers(Path::new("/sorted/Photos/a.jpg"), start + OWN_WRITE_TTL));
+    }
+
+    #[test]
+    fn settled_file_is_ready_after_one_matching_reading() {
+        let start = Instant::now();
+        let mut deferred = Deferred::default();
+        deferred.note_file(Path::new("a.zip"), 10, start);
+
+        let (stable, timed_out) = deferred.check_files(start + DEFERRED_CHECK_INTERVAL, Duration::from_secs(5), |_| Some(10));
+        assert_eq!(stable, vec![PathBuf::from("a.zip")]);
+        assert!(timed_out.is_empty());
+        assert!(deferred.files.is_empty());
+    }
+
+    #[test]
+    fn growing_file_stays_queued_until_it_settles() {
+        let start = Instant::now();
+        let mut deferred = Deferred::default();
+        deferred.note_file(Path::new("a.zip"), 1, start);
+
+        let (stable, _) = deferred.check_files(start + Duration::from_secs(1), Duration::from_secs(5), |_| Some(2));
+        assert!(stable.is_empty());
+        // A new event for the queued file keeps its first-seen time
+        deferred.note_file(Path::new("a.zip"), 3, start + Duration::from_secs(2));
+        assert_eq!(deferred.files[Path::new("a.zip")].first_seen, start);
+        let (stable, _) = deferred.check_files(start + Duration::from_secs(3), Duration::from_secs(5), |_| Some(3));
+        assert_eq!(stable, vec![PathBuf::from("a.zip")]);
+    }
+
+    #[test]
+    fn still_growing_at_timeout_is_skipped() {
+        let start = Instant::now();
+        let mut deferred = Deferred::default();
+        deferred.note_file(Path::new("a.zip"), 1, start);
+
+        let (_, timed_out) = deferred.check_files(start + Duration::from_secs(4), Duration::from_secs(5), |_| Some(2));
+        assert!(timed_out.is_empty());
+        let (stable, timed_out) = deferred.check_files(start + Duration::from_secs(5), Duration::from_secs(5), |_| Some(3));
+        assert!(stable.is_empty());
+        assert_eq!(timed_out, vec![PathBuf::from("a.zip")]);
+        assert!(deferred.files.is_empty());
+    }
+
+    #[test]
+    fn vanished_file_is_dropped() {
+        let start = Instant::now();
+        let mut deferred = Deferred::default();
+        deferred.note_file(Path::new("a"), 1, start);
+
+        let (stable, timed_out) = deferred.check_files(start, Duration::from_secs(5), |_| None);
+        assert!(stable.is_empty() && timed_out.is_empty());
+        assert!(deferred.files.is_empty());
+    }
+
+    #[test]
+    fn new_subdirs_are_auto_watched_only_once_settled_under_their_final_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let new_folder = dir.path().join("New folder");
+        std::fs::create_dir(&new_folder).unwrap();
+        let start = Instant::now();
+        let mut deferred = Deferred::default();
+        deferred.note_subdir(&new_folder, start);
+        assert!(deferred.take_settled_subdirs(start + Duration::from_secs(5)).is_empty());
+
+        // Renamed while settling: the old name is dropped, the new one settles on its own clock
+        let acme = dir.path().join("acme");
+        std::fs::rename(&new_folder, &acme).unwrap();
+        deferred.note_subdir(&acme, start + Duration::from_secs(10));
+        assert!(deferred.take_settled_subdirs(start + NEW_SUBDIR_SETTLE).is_empty());
+        let settled = deferred.take_settled_subdirs(start + Duration::from_secs(10) + NEW_SUBDIR_SETTLE);
+        assert_eq!(settled, vec![acme]);
+        assert!(deferred.subdirs.is_empty());
     }
 }
@@ -2,15 +2,44 @@ import { invoke } from "@tauri-apps/api/core";
 import type {
   AppConfig,
   WatchedFolder,
+  FolderPauseStatus,
+  FolderOverview,
+  PlannedAction,
+  RuleProfile,
   Rule,
+  RulePreset,
   Condition,
+  Action,
   ActivityLogEntry,
+  ActivityLogPage,
+  LogArchiveInfo,
   FileIndexEntry,
   UndoEntry,
   ScheduledDeletion,
+  ScheduledDeletionPage,
   RuleMetadata,
   DbStats,
   TableQueryResult,
+  TokenSpan,
+  RuleBackoff,
+  GoldenResult,
+  DestinationProbe,
+  RuleTestResult,
+  FolderRule,
+  UndoBatchSummary,
+  UndoBatch,
+  CleanlinessScore,
+  ReconcileReport,
+  ReviewItem,
+  ReviewDecision,
+  BucketSize,
+  TimeBucket,
+  ActivitySummary,
+  WatcherStatus,
+  ShadowWarning,
+  FolderAddReport,
+  ConfigMergeSummary,
+  RulePreview,
 } from "./types";
 
 // ── Config ──────────────────────────────────────────────────
@@ -26,6 +55,19 @@ export const exportConfig = (path: string) =>
 export const importConfig = (path: string) =>
   invoke<void>("import_config", { path });
 
+/** Merge a config file into the current one; existing folders/rules/settings win unless overwriteExisting.
+ *  Pass baseDir for a portable export */
+export const mergeConfig = (path: string, overwriteExisting: boolean, baseDir?: string) =>
+  invoke<ConfigMergeSummary>("merge_config", { path, overwriteExisting, baseDir });
+
+/** Export with paths under baseDir written as {BASE}/… for another machine. Returns how many paths were templated */
+export const exportConfigPortable = (path: string, baseDir: string) =>
+  invoke<number>("export_config_portable", { path, baseDir });
+
+/** Import a portable export, resolving its {BASE}/… paths against this machine's baseDir */
+export const importConfigPortable = (path: string, baseDir: string) =>
+  invoke<void>("import_config_portable", { path, baseDir });
+
 export const getConfigPath = () =>
   invoke<string>("get_config_path");
 
@@ -34,41 +76,133 @@ export const getConfigPath = () =>
 export const getWatchedFolders = () =>
   invoke<WatchedFolder[]>("get_watched_folders");
 
+/** All folders with live status, scheduled counts, and last activity in one call. */
+export const getFoldersOverview = () =>
+  invoke<FolderOverview[]>("get_folders_overview");
+
 export const addWatchedFolder = (path: string) =>
   invoke<WatchedFolder>("add_watched_folder", { path });
 
+/** Add several folders at once; each path gets its own outcome, in order */
+export const addWatchedFolders = (paths: string[]) =>
+  invoke<FolderAddReport>("add_watched_folders", { paths });
+
+/** Describe the watched folder this path contains or lies inside, or null if none */
+export const checkFolderOverlap = (path: string) =>
+  invoke<string | null>("check_folder_overlap", { path });
+
+/** Stop watching a folder; it's archived with its rules and can be restored */
 export const removeWatchedFolder = (folderId: string) =>
   invoke<void>("remove_watched_folder", { folderId });
 
+/** Folders removed from the watch list that can still be restored */
+export const getArchivedFolders = () =>
+  invoke<WatchedFolder[]>("get_archived_folders");
+
+/** Put an archived folder back on the watch list with its rules */
+export const restoreWatchedFolder = (folderId: string) =>
+  invoke<WatchedFolder>("restore_watched_folder", { folderId });
+
+/** Permanently delete an archived folder and its rules */
+export const purgeArchivedFolder = (folderId: string) =>
+  invoke<void>("purge_archived_folder", { folderId });
+
 export const toggleWatchedFolder = (folderId: string, enabled: boolean) =>
   invoke<void>("toggle_watched_folder", { folderId, enabled });
 
 export const toggleWatchSubdirectories = (folderId: string, enabled: boolean) =>
   invoke<void>("toggle_watch_subdirectories", { folderId, enabled });
 
+/** Auto-watch new subfolders of a folder with a preset's rules (null to stop). Restart the watcher after. */
+export const setAutoWatchNewSubdirs = (folderId: string, preset: string | null) =>
+  invoke<void>("set_auto_watch_new_subdirs", { folderId, preset });
+
+/** Merge a folder's rules, whitelist, and history into another, removing the source. */
+export const mergeFolders = (sourceFolderId: string, targetFolderId: string) =>
+  invoke<void>("merge_folders", { sourceFolderId, targetFolderId });
+
+/** Disable (or with removeMissing, archive) folders whose path no longer exists on disk */
+export const reconcileFolders = (removeMissing: boolean) =>
+  invoke<ReconcileReport>("reconcile_folders", { removeMissing });
+
 export const getFolderWhitelist = (folderId: string) =>
   invoke<string[]>("get_folder_whitelist", { folderId });
 
 export const setFolderWhitelist = (folderId: string, whitelist: string[]) =>
   invoke<void>("set_folder_whitelist", { folderId, whitelist });
 
+/** Set (or clear with null) the catch-all action for files no rule matches. */
+export const setFolderDefaultAction = (
+  folderId: string,
+  defaultAction: Action | null,
+  defaultAfterDays: number
+) =>
+  invoke<void>("set_folder_default_action", { folderId, defaultAction, defaultAfterDays });
+
+/** Pause a folder for `minutes`; returns the RFC3339 time the pause ends. */
+export const pauseFolder = (folderId: string, minutes: number) =>
+  invoke<string>("pause_folder", { folderId, minutes });
+
+export const resumeFolder = (folderId: string) =>
+  invoke<void>("resume_folder", { folderId });
+
+export const getFolderPauseStatus = (folderId: string) =>
+  invoke<FolderPauseStatus>("get_folder_pause_status", { folderId });
+
+/** Pause the watcher, scans and scheduled actions for `minutes`; returns the RFC3339 end time. */
+export const pauseAutomation = (minutes: number) =>
+  invoke<string>("pause_automation", { minutes });
+
+export const resumeAutomation = () =>
+  invoke<void>("resume_automation");
+
 // ── Rules ───────────────────────────────────────────────────
 
 export const getRules = (folderId: string) =>
   invoke<Rule[]>("get_rules", { folderId });
 
+/** Saves the rule; resolves to non-fatal warnings from validateMoveRule (empty if none) */
 export const addRule = (folderId: string, rule: Rule) =>
-  invoke<void>("add_rule", { folderId, rule });
+  invoke<string[]>("add_rule", { folderId, rule });
 
+/** Saves the rule; resolves to non-fatal warnings from validateMoveRule (empty if none) */
 export const updateRule = (folderId: string, rule: Rule) =>
-  invoke<void>("update_rule", { folderId, rule });
+  invoke<string[]>("update_rule", { folderId, rule });
+
+/** Non-fatal rule warnings: organize loops, invalid regex, or a destination drive that isn't connected */
+export const validateMoveRule = (folderId: string, rule: Rule) =>
+  invoke<string[]>("validate_move_rule", { folderId, rule });
+
+/** Rules that never act because an earlier rule matches every file */
+export const findShadowedRules = (folderId: string) =>
+  invoke<ShadowWarning[]>("find_shadowed_rules", { folderId });
 
 export const deleteRule = (folderId: string, ruleId: string) =>
   invoke<void>("delete_rule", { folderId, ruleId });
 
+/** Rules tagged `tag` (case-insensitive) across all folders */
+export const getRulesByTag = (tag: string) =>
+  invoke<FolderRule[]>("get_rules_by_tag", { tag });
+
+/** Replace a rule's tags; resolves to the saved (trimmed, deduped) list */
+export const setRuleTags = (folderId: string, ruleId: string, tags: string[]) =>
+  invoke<string[]>("set_rule_tags", { folderId, ruleId, tags });
+
 export const reorderRules = (folderId: string, ruleIds: string[]) =>
   invoke<void>("reorder_rules", { folderId, ruleIds });
 
+/** Move one rule to `newIndex` (clamped to the end); resolves to the index it landed at. */
+export const moveRule = (folderId: string, ruleId: string, newIndex: number) =>
+  invoke<number>("move_rule", { folderId, ruleId, newIndex });
+
+/** Write one folder's rules (only the rule list) to a JSON file. Returns count written. */
+export const exportFolderRules = (folderId: string, path: string) =>
+  invoke<number>("export_folder_rules", { folderId, path });
+
+/** Append rules from an exported file to a folder, with fresh ids. Fails without changes if any condition is invalid. */
+export const importFolderRules = (folderId: string, path: string) =>
+  invoke<number>("import_folder_rules", { folderId, path });
+
 /** Copy rules from other folders into a target folder. Returns count of rules copied. */
 export const copyRulesToFolder = (
   targetFolderId: string,
@@ -76,6 +210,25 @@ export const copyRulesToFolder = (
 ) =>
   invoke<number>("copy_rules_to_folder", { targetFolderId, sources });
 
+/** Groups of rule IDs in a folder that do the same thing (first of each group is kept by dedupe). */
+export const findDuplicateRules = (folderId: string) =>
+  invoke<string[][]>("find_duplicate_rules", { folderId });
+
+/** Remove duplicate rules in a folder, keeping the first of each group. Returns count removed. */
+export const dedupeRules = (folderId: string) =>
+  invoke<number>("dedupe_rules", { folderId });
+
+/** All saved rule presets. */
+export const getRulePresets = () => invoke<RulePreset[]>("get_rule_presets");
+
+/** Save a folder's rules and whitelist as a preset (replaces one with the same name). */
+export const saveFolderAsPreset = (folderId: string, name: string) =>
+  invoke<void>("save_folder_as_preset", { folderId, name });
+
+/** Delete a preset; folders auto-watching with it stop doing so. */
+export const deleteRulePreset = (name: string) =>
+  invoke<void>("delete_rule_preset", { name });
+
 /** Move a rule from one folder to another at a specific position. */
 export const moveRuleToFolder = (
   sourceFolderId: string,
@@ -99,6 +252,29 @@ export const getActivityLog = (
 ) =>
   invoke<ActivityLogEntry[]>("get_activity_log", { limit, offset, folderId });
 
+/** A page of the activity log plus the total for the same folder filter */
+export const getActivityLogPage = (
+  limit?: number,
+  offset?: number,
+  folderId?: string
+) =>
+  invoke<ActivityLogPage>("get_activity_log_page", { limit, offset, folderId });
+
+/** Full-text search of the activity log (name, path, rule, details), best matches first */
+export const searchActivityLog = (query: string, limit?: number, offset?: number) =>
+  invoke<ActivityLogEntry[]>("search_activity_log", { query, limit, offset });
+
+/** Write the activity log to a CSV file, optionally for one folder and since an ISO time. Returns rows written */
+export const exportActivityLogCsv = (path: string, folderId?: string, since?: string) =>
+  invoke<number>("export_activity_log_csv", { path, folderId, since });
+
+/** Monthly archives of pruned log entries (when archiving is enabled), newest first */
+export const listLogArchives = () => invoke<LogArchiveInfo[]>("list_log_archives");
+
+/** Entries stored in one archive, by file name from listLogArchives */
+export const readLogArchive = (name: string) =>
+  invoke<ActivityLogEntry[]>("read_log_archive", { name });
+
 export const getPendingActions = () =>
   invoke<FileIndexEntry[]>("get_pending_actions");
 
@@ -107,12 +283,36 @@ export const getUndoEntries = () => invoke<UndoEntry[]>("get_undo_entries");
 export const undoAction = (undoId: string) =>
   invoke<void>("undo_action", { undoId });
 
+/** Undo entries grouped by the scan/scheduler run that created them */
+export const getUndoBatches = () => invoke<UndoBatch[]>("get_undo_batches");
+
+/** Undo many entries at once; keeps going past failures */
+export const undoBatch = (undoIds: string[]) =>
+  invoke<UndoBatchSummary>("undo_batch", { undoIds });
+
+/** Undo every not-yet-restored action after a UTC "YYYY-MM-DD HH:MM:SS" timestamp */
+export const undoAllSince = (timestamp: string) =>
+  invoke<UndoBatchSummary>("undo_all_since", { timestamp });
+
+// ── Review Queue ────────────────────────────────────────────
+
+/** Files flagged by Review rules still awaiting a decision, oldest first */
+export const getReviewQueue = () => invoke<ReviewItem[]>("get_review_queue");
+
+/** Approve (runs the rule's on_approve action now) or dismiss a flagged file */
+export const resolveReviewItem = (id: string, decision: ReviewDecision) =>
+  invoke<void>("resolve_review_item", { id, decision });
+
 // ── Scheduled Deletions ─────────────────────────────────────
 
 /** Get all files currently scheduled for deletion. */
 export const getScheduledDeletions = () =>
   invoke<ScheduledDeletion[]>("get_scheduled_deletions");
 
+/** One page of scheduled deletions plus the total, optionally for one folder */
+export const getScheduledDeletionsPaged = (limit: number, offset: number, folderId?: string) =>
+  invoke<ScheduledDeletionPage>("get_scheduled_deletions_paged", { limit, offset, folderId });
+
 /** Cancel a single scheduled deletion by ID. */
 export const cancelScheduledDeletion = (deletionId: string) =>
   invoke<void>("cancel_scheduled_deletion", { deletionId });
@@ -121,19 +321,64 @@ export const cancelScheduledDeletion = (deletionId: string) =>
 export const deleteScheduledNow = (deletionIds: string[]) =>
   invoke<number>("delete_scheduled_now", { deletionIds });
 
+/** Approve a deletion held back for approval and run it now. Returns 1 if deleted. */
+export const approveDeletion = (deletionId: string) =>
+  invoke<number>("approve_deletion", { deletionId });
+
+/** Approve and run every deletion awaiting approval. Returns count deleted. */
+export const approveAllDue = () => invoke<number>("approve_all_due");
+
+/** RFC3339 time the scheduler will next delete a file, or null if none is pending. */
+export const getNextDeletionRun = () => invoke<string | null>("get_next_deletion_run");
+
+/** Move all of a rule's queued files in a folder to `destination` instead. Returns count archived. */
+export const archiveScheduledDeletions = (folderId: string, ruleName: string, destination: string) =>
+  invoke<number>("archive_scheduled_deletions", { folderId, ruleName, destination });
+
 // ── Watcher Control ─────────────────────────────────────────
 
-export const scanNow = () => invoke<void>("scan_now");
+/** Scan every folder and wait for it; resolves to the number of files processed. */
+export const scanNow = () => invoke<number>("scan_now");
 
 export const scanFolder = (folderId: string) =>
   invoke<void>("scan_folder", { folderId });
 
+/** Scan one folder and wait for it; resolves to the number of files processed. */
+export const scanFolderNow = (folderId: string) =>
+  invoke<number>("scan_folder_now", { folderId });
+
+/** Preview what scanning a folder would do, without touching any files. */
+export const scanDryRun = (folderId: string) =>
+  invoke<PlannedAction[]>("scan_dry_run", { folderId });
+
+/** Files in the folder a (possibly unsaved) rule would match, up to 1000; nothing is executed */
+export const previewRuleOnFolder = (folderId: string, rule: Rule) =>
+  invoke<RulePreview>("preview_rule_on_folder", { folderId, rule });
+
+/** Time each of a folder's rules over its files (no actions are taken). */
+export const profileFolderScan = (folderId: string) =>
+  invoke<RuleProfile[]>("profile_folder_scan", { folderId });
+
+/** Share of a folder's current files its rules handle (0-100) plus suggestions */
+export const getFolderCleanliness = (folderId: string) =>
+  invoke<CleanlinessScore>("get_folder_cleanliness", { folderId });
+
 export const restartWatcher = () => invoke<void>("restart_watcher");
 
 export const stopWatcher = () => invoke<void>("stop_watcher");
 
 export const getWatcherStatus = () => invoke<boolean>("get_watcher_status");
 
+/** Which folders the watcher really watches, with the error for any that failed to register. */
+export const getWatcherDetails = () => invoke<WatcherStatus>("get_watcher_details");
+
+/** Rules suppressed (or close to it) after repeated action failures. */
+export const getRuleBackoffs = () => invoke<RuleBackoff[]>("get_rule_backoffs");
+
+/** Temporarily scan every `minutes` for the next `forMinutes` (0 clears the override). */
+export const setTemporaryScanInterval = (minutes: number, forMinutes: number) =>
+  invoke<void>("set_temporary_scan_interval", { minutes, forMinutes });
+
 // ── Condition Helpers ───────────────────────────────────────
 
 /** Parse condition text syntax (e.g. `*.pdf AND *invoice*`) into a Condition tree. */
@@ -144,22 +389,46 @@ export const parseConditionText = (text: string) =>
 export const conditionToText = (cond: Condition) =>
   invoke<string>("condition_to_text", { cond });
 
+/** Condition text fully parenthesized, showing how AND/OR/XOR precedence grouped it */
+export const conditionToAstString = (text: string) =>
+  invoke<string>("condition_to_ast_string", { text });
+
+/** Tokenize condition text into typed spans for syntax highlighting. */
+export const tokenizeCondition = (text: string) =>
+  invoke<TokenSpan[]>("tokenize_condition", { text });
+
 /** Validate condition text; resolves if valid, rejects with error message. */
 export const validateConditionText = (text: string) =>
   invoke<void>("validate_condition_text", { text });
 
+/** Run a golden file of condition/filename expectations; one result per case. */
+export const runConditionGoldenTests = (path: string) =>
+  invoke<GoldenResult[]>("run_condition_golden_tests", { path });
+
 /** Test a condition against a filename (for live preview in the UI). */
 export const testCondition = (cond: Condition, fileName: string) =>
   invoke<boolean>("test_condition", { cond, fileName });
 
+/** Run a whole rule (condition + whitelist + subfolder matching) against sample names. */
+export const testRule = (rule: Rule, fileNames: string[]) =>
+  invoke<RuleTestResult[]>("test_rule", { rule, fileNames });
+
 /** Ensure a directory exists (creates it and parents if needed). */
 export const ensureDir = (path: string) =>
   invoke<void>("ensure_dir", { path });
 
+/** Time a small write+delete in a destination folder (gives up after 5 s). */
+export const probeDestination = (path: string) =>
+  invoke<DestinationProbe>("probe_destination", { path });
+
 /** Open a folder in the OS file explorer. */
 export const openInExplorer = (path: string) =>
   invoke<void>("open_in_explorer", { path });
 
+/** Open the OS file explorer with a file selected (Linux: opens its folder). */
+export const revealInExplorer = (path: string) =>
+  invoke<void>("reveal_in_explorer", { path });
+
 // ── Rule Metadata ───────────────────────────────────────────
 
 /** Get metadata (created_at, last_triggered_at) for all rules in a folder. */
@@ -169,6 +438,14 @@ export const getRuleMetadata = (folderId: string) =>
 export const getRuleExecutionStats = (folderId: string) =>
   invoke<import("./types").RuleExecutionStats[]>("get_rule_execution_stats", { folderId });
 
+/** Dashboard totals for the last `days` days: per-day counts and bytes moved, actions by type, top rules */
+export const getActivitySummary = (days: number) =>
+  invoke<ActivitySummary>("get_activity_summary", { days });
+
+/** Moved/deleted/error counts per day or hour over the last `days` days; quiet periods are zero buckets */
+export const getActivityTimeseries = (days: number, bucket: BucketSize, folderId?: string) =>
+  invoke<TimeBucket[]>("get_activity_timeseries", { days, bucket, folderId });
+
 // ── Database Viewer ─────────────────────────────────────────
 
 /** Get DB stats: file size, trash size, per-table row counts. */
@@ -200,3 +477,6 @@ export const enforceStorageLimit = () =>
 
 /** Get the database file path. */
 export const getDbPath = () => invoke<string>("get_db_path");
+
+/** Run SQLite's integrity check; false means the database is damaged (restored from backup on next launch) */
+export const checkDbIntegrity = () => invoke<boolean>("check_db_integrity");
@@ -170,6 +170,8 @@ export default function ActivityPage() {
                           className={`text-xs px-2 py-1 rounded-full ${
                             entry.result === "success"
                               ? "bg-green-900/50 text-green-400"
+                              : entry.result === "renamed_collision" || entry.result.startsWith("skipped_")
+                              ? "bg-amber-900/50 text-amber-400"
                               : "bg-red-900/50 text-red-400"
                           }`}
                         >
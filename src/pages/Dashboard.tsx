@@ -240,6 +240,15 @@ export default function Dashboard() {
     }
   };
 
+  /** Show the file selected in the explorer; if it's gone, open its folder instead. */
+  const handleRevealFile = async (filePath: string) => {
+    try {
+      await api.revealInExplorer(filePath);
+    } catch {
+      await handleOpenFolder(filePath);
+    }
+  };
+
   if (loading) {
     return (
       <div className="flex items-center justify-center h-full text-zinc-500">
@@ -533,7 +542,7 @@ export default function Dashboard() {
                             </td>
                             <td className="px-5 py-3 overflow-hidden">
                               <button
-                                onClick={() => handleOpenFolder(entry.file_path)}
+                                onClick={() => handleRevealFile(entry.file_path)}
                                 className="text-left underline decoration-zinc-600 underline-offset-2 hover:decoration-amber-400 transition-colors cursor-pointer break-words max-w-full"
                                 title={entry.file_path}
                               >
@@ -618,7 +627,7 @@ export default function Dashboard() {
                     <div className="px-5 py-3 flex items-center justify-between">
                       <div className="min-w-0 flex-1 mr-3">
                         <button
-                          onClick={() => handleOpenFolder(entry.file_path)}
+                          onClick={() => handleRevealFile(entry.file_path)}
                           className="text-left underline decoration-zinc-600 underline-offset-2 hover:decoration-zinc-400 transition-colors cursor-pointer text-sm break-words max-w-full"
                           title={entry.file_path}
                         >
@@ -627,7 +636,7 @@ export default function Dashboard() {
                         </button>
                         {destDisplay && (
                           <button
-                            onClick={() => handleOpenFolder(destination!)}
+                            onClick={() => handleRevealFile(destination!)}
                             className="text-left text-xs text-blue-400/70 hover:text-blue-300 underline decoration-zinc-700 underline-offset-2 hover:decoration-blue-400/50 transition-colors cursor-pointer break-words max-w-full mt-0.5 block"
                             title={destination!}
                           >
@@ -638,7 +647,7 @@ export default function Dashboard() {
                         <p className="text-xs text-zinc-500 mt-0.5">
                           {entry.action} — {entry.rule_name ?? t("dashboard.manual")}
                         </p>
-                        {entry.result !== "success" && entry.details && (
+                        {entry.result === "error" && entry.details && (
                           <p className="text-xs text-red-400/80 mt-0.5" title={entry.details}>
                             {entry.details}
                           </p>
@@ -661,6 +670,8 @@ export default function Dashboard() {
                           className={`text-xs px-2 py-1 rounded-full ${
                             entry.result === "success"
                               ? "bg-green-900/50 text-green-400"
+                              : entry.result === "renamed_collision" || entry.result.startsWith("skipped_")
+                              ? "bg-amber-900/50 text-amber-400"
                               : "bg-red-900/50 text-red-400"
                           }`}
                         >
@@ -144,8 +144,13 @@ export default function Folders() {
       }
     });
 
+    const unlistenFolders = listen("folders-changed", () => {
+      loadFolders();
+    });
+
     return () => {
       unlistenScan.then((fn) => fn());
+      unlistenFolders.then((fn) => fn());
     };
   }, []);
 
@@ -278,10 +283,11 @@ export default function Folders() {
     if (!editingFolderId) return;
     const folderId = editingFolderId;
     const wasNewRule = isNewRule;
-    if (wasNewRule) {
-      await api.addRule(folderId, rule);
-    } else {
-      await api.updateRule(folderId, rule);
+    const warnings = wasNewRule
+      ? await api.addRule(folderId, rule)
+      : await api.updateRule(folderId, rule);
+    if (warnings.length > 0) {
+      message(warnings.join("\n"), { title: t("rules.loopWarningTitle"), kind: "warning" }).catch(() => {});
     }
     setEditingRule(null);
     setIsNewRule(false);
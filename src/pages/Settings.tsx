@@ -179,6 +179,29 @@ export default function SettingsPage() {
           </label>
         </div>
 
+        {/* Desktop notifications */}
+        <div className="px-5 py-4 flex items-center justify-between">
+          <div>
+            <p className="text-sm font-medium">{t("settings.showNotifications")}</p>
+            <p className="text-xs text-zinc-500">
+              {t("settings.showNotificationsDesc")}
+            </p>
+          </div>
+          <label className="relative inline-flex items-center cursor-pointer">
+            <input
+              type="checkbox"
+              checked={settings.show_notifications ?? true}
+              onChange={(e) =>
+                setSettings({
+                  ...settings,
+                  show_notifications: e.target.checked,
+                })
+              }
+              className="sr-only peer"
+            />
+            <div className="w-11 h-6 bg-zinc-700 rounded-full peer peer-checked:bg-blue-600 after:content-[''] after:absolute after:top-0.5 after:start-[2px] after:bg-white after:rounded-full after:h-5 after:w-5 after:transition-all peer-checked:after:translate-x-full" />
+          </label>
+        </div>
 
         {/* Explorer context menu */}
         <div className="px-5 py-4 flex items-center justify-between">
@@ -274,6 +297,103 @@ export default function SettingsPage() {
           />
         </div>
 
+        {/* Archive logs before pruning */}
+        <div className="px-5 py-4 flex items-center justify-between">
+          <div>
+            <p className="text-sm font-medium">{t("settings.archiveLogs")}</p>
+            <p className="text-xs text-zinc-500">
+              {t("settings.archiveLogsDesc")}
+            </p>
+          </div>
+          <label className="relative inline-flex items-center cursor-pointer">
+            <input
+              type="checkbox"
+              checked={settings.archive_logs_before_pruning ?? false}
+              onChange={(e) =>
+                setSettings({
+                  ...settings,
+                  archive_logs_before_pruning: e.target.checked,
+                })
+              }
+              className="sr-only peer"
+            />
+            <div className="w-11 h-6 bg-zinc-700 rounded-full peer peer-checked:bg-blue-600 after:content-[''] after:absolute after:top-0.5 after:start-[2px] after:bg-white after:rounded-full after:h-5 after:w-5 after:transition-all peer-checked:after:translate-x-full" />
+          </label>
+        </div>
+
+        {/* Allow run-command rules */}
+        <div className="px-5 py-4 flex items-center justify-between">
+          <div>
+            <p className="text-sm font-medium">{t("settings.allowRunCommand")}</p>
+            <p className="text-xs text-zinc-500">
+              {t("settings.allowRunCommandDesc")}
+            </p>
+          </div>
+          <label className="relative inline-flex items-center cursor-pointer">
+            <input
+              type="checkbox"
+              checked={settings.allow_run_command ?? false}
+              onChange={(e) =>
+                setSettings({
+                  ...settings,
+                  allow_run_command: e.target.checked,
+                })
+              }
+              className="sr-only peer"
+            />
+            <div className="w-11 h-6 bg-zinc-700 rounded-full peer peer-checked:bg-blue-600 after:content-[''] after:absolute after:top-0.5 after:start-[2px] after:bg-white after:rounded-full after:h-5 after:w-5 after:transition-all peer-checked:after:translate-x-full" />
+          </label>
+        </div>
+
+        {/* Debug: raw watcher events */}
+        <div className="px-5 py-4 flex items-center justify-between">
+          <div>
+            <p className="text-sm font-medium">{t("settings.debugWatcherEvents")}</p>
+            <p className="text-xs text-zinc-500">
+              {t("settings.debugWatcherEventsDesc")}
+            </p>
+          </div>
+          <label className="relative inline-flex items-center cursor-pointer">
+            <input
+              type="checkbox"
+              checked={settings.debug_watcher_events ?? false}
+              onChange={(e) =>
+                setSettings({
+                  ...settings,
+                  debug_watcher_events: e.target.checked,
+                })
+              }
+              className="sr-only peer"
+            />
+            <div className="w-11 h-6 bg-zinc-700 rounded-full peer peer-checked:bg-blue-600 after:content-[''] after:absolute after:top-0.5 after:start-[2px] after:bg-white after:rounded-full after:h-5 after:w-5 after:transition-all peer-checked:after:translate-x-full" />
+          </label>
+        </div>
+
+        {/* Global ignore extensions */}
+        <div className="px-5 py-4">
+          <div className="mb-2">
+            <p className="text-sm font-medium">{t("settings.ignoreExtensions")}</p>
+            <p className="text-xs text-zinc-500">
+              {t("settings.ignoreExtensionsDesc")}
+            </p>
+          </div>
+          <input
+            type="text"
+            value={(settings.global_ignore_extensions ?? []).join(", ")}
+            onChange={(e) =>
+              setSettings({
+                ...settings,
+                global_ignore_extensions: e.target.value
+                  .split(",")
+                  .map((ext) => ext.trim())
+                  .filter((ext, i, all) => ext !== "" || i === all.length - 1),
+              })
+            }
+            placeholder="crdownload, part, tmp"
+            className="w-full px-3 py-2 bg-zinc-800 border border-zinc-700 rounded-lg text-sm focus:outline-none focus:border-blue-500"
+          />
+        </div>
+
         {/* Max storage */}
         <div className="px-5 py-4 flex items-center justify-between">
           <div>
@@ -83,6 +83,17 @@ export function RuleEditor({ rule, isNew, defaultSortRoot, onSave, onCancel }: R
     setActionType(newType);
     if (newType === "Move") {
       setDraft({ ...draft, action: { type: "Move", destination: defaultSortRoot.replace(/[\\/]$/, "") + "\\", delay_minutes: 0 } });
+    } else if (newType === "MoveByCategory") {
+      setDraft({ ...draft, action: { type: "MoveByCategory", root: defaultSortRoot.replace(/[\\/]$/, "") } });
+    } else if (newType === "Archive") {
+      setDraft({
+        ...draft,
+        action: {
+          type: "Archive",
+          destination: defaultSortRoot.replace(/[\\/]$/, "") + "\\Archive",
+          archive_name: "archive-{year}-{month}.zip",
+        },
+      });
     } else {
       setDraft({ ...draft, action: defaultAction(newType) });
     }
@@ -177,7 +188,8 @@ export function RuleEditor({ rule, isNew, defaultSortRoot, onSave, onCancel }: R
     draft.name.trim() !== "" &&
     conditionValid &&
     (draft.action.type !== "Move" ||
-      (draft.action.type === "Move" && draft.action.destination.trim() !== ""));
+      (draft.action.type === "Move" && draft.action.destination.trim() !== "")) &&
+    (draft.action.type !== "MoveByCategory" || draft.action.root.trim() !== "");
 
   return (
     <div className="bg-zinc-900 rounded-xl border border-blue-600 p-5 space-y-5">
@@ -354,7 +366,7 @@ export function RuleEditor({ rule, isNew, defaultSortRoot, onSave, onCancel }: R
       <div>
         <label className="text-xs text-zinc-400 block mb-1">{t("rules.action")}</label>
         <div className="flex gap-2 mb-3">
-          {(["Move", "Delete"] as ActionType[]).map((aType) => (
+          {(["Move", "MoveByCategory", "Delete", "Archive", "Link", "Notify", "RunCommand", "Review"] as ActionType[]).map((aType) => (
             <button
               key={aType}
               onClick={() => handleActionTypeChange(aType)}
@@ -492,6 +504,202 @@ export function RuleEditor({ rule, isNew, defaultSortRoot, onSave, onCancel }: R
             </p>
           </div>
         )}
+
+        {actionType === "MoveByCategory" && draft.action.type === "MoveByCategory" && (
+          <div>
+            <label className="text-xs text-zinc-400 block mb-1">
+              {t("rules.categoryRoot")} *
+            </label>
+            <input
+              type="text"
+              value={draft.action.root}
+              onChange={(e) =>
+                setDraft({
+                  ...draft,
+                  action: { type: "MoveByCategory", root: e.target.value },
+                })
+              }
+              placeholder={defaultSortRoot}
+              className="w-full px-3 py-2 bg-zinc-800 border border-zinc-700 rounded-lg text-sm focus:outline-none focus:border-blue-500"
+            />
+            <p className="text-xs text-zinc-500 mt-1">
+              {t("rules.categoryHint")}
+            </p>
+          </div>
+        )}
+
+        {actionType === "Archive" && draft.action.type === "Archive" && (
+          <div className="space-y-3">
+            <div>
+              <label className="text-xs text-zinc-400 block mb-1">
+                {t("rules.destination")} *
+              </label>
+              <input
+                type="text"
+                value={draft.action.destination}
+                onChange={(e) =>
+                  setDraft({
+                    ...draft,
+                    action: { ...draft.action, type: "Archive", destination: e.target.value },
+                  })
+                }
+                placeholder={defaultSortRoot + "\\Archive"}
+                className="w-full px-3 py-2 bg-zinc-800 border border-zinc-700 rounded-lg text-sm focus:outline-none focus:border-blue-500"
+              />
+            </div>
+            <div>
+              <label className="text-xs text-zinc-400 block mb-1">
+                {t("rules.archiveName")}
+              </label>
+              <input
+                type="text"
+                value={draft.action.archive_name}
+                onChange={(e) =>
+                  setDraft({
+                    ...draft,
+                    action: { ...draft.action, type: "Archive", archive_name: e.target.value },
+                  })
+                }
+                placeholder="logs-{year}-{month}.zip"
+                className="w-full px-3 py-2 bg-zinc-800 border border-zinc-700 rounded-lg text-sm focus:outline-none focus:border-blue-500"
+              />
+              <p className="text-xs text-zinc-500 mt-1">
+                {t("rules.archiveHint")}
+              </p>
+            </div>
+          </div>
+        )}
+
+        {actionType === "Link" && draft.action.type === "Link" && (
+          <div className="space-y-3">
+            <div>
+              <label className="text-xs text-zinc-400 block mb-1">
+                {t("rules.destination")} *
+              </label>
+              <input
+                type="text"
+                value={draft.action.destination}
+                onChange={(e) =>
+                  setDraft({
+                    ...draft,
+                    action: { ...draft.action, type: "Link", destination: e.target.value },
+                  })
+                }
+                placeholder={defaultSortRoot + "\\Shortcuts"}
+                className="w-full px-3 py-2 bg-zinc-800 border border-zinc-700 rounded-lg text-sm focus:outline-none focus:border-blue-500"
+              />
+            </div>
+            <div>
+              <div className="flex items-center gap-2">
+                <label className="relative inline-flex items-center cursor-pointer">
+                  <input
+                    type="checkbox"
+                    checked={!!draft.action.hard}
+                    onChange={(e) =>
+                      setDraft({
+                        ...draft,
+                        action: { ...draft.action, type: "Link", hard: e.target.checked },
+                      })
+                    }
+                    className="sr-only peer"
+                  />
+                  <div className="w-8 h-4 bg-zinc-700 peer-focus:outline-none rounded-full peer peer-checked:bg-blue-600 transition-colors after:content-[''] after:absolute after:top-[2px] after:left-[2px] after:bg-white after:rounded-full after:h-3 after:w-3 after:transition-all peer-checked:after:translate-x-4" />
+                </label>
+                <span className="text-xs text-zinc-400">{t("rules.hardLink")}</span>
+              </div>
+              <p className="text-xs text-zinc-500 mt-1 ml-10">
+                {t(draft.action.hard ? "rules.hardLinkOnDesc" : "rules.hardLinkOffDesc")}
+              </p>
+            </div>
+          </div>
+        )}
+
+        {actionType === "Notify" && draft.action.type === "Notify" && (
+          <div>
+            <label className="text-xs text-zinc-400 block mb-1">
+              {t("rules.notifyMessage")}
+            </label>
+            <input
+              type="text"
+              value={draft.action.message}
+              onChange={(e) =>
+                setDraft({
+                  ...draft,
+                  action: { type: "Notify", message: e.target.value },
+                })
+              }
+              placeholder="{name} landed in Downloads"
+              className="w-full px-3 py-2 bg-zinc-800 border border-zinc-700 rounded-lg text-sm focus:outline-none focus:border-blue-500"
+            />
+            <p className="text-xs text-zinc-500 mt-1">
+              {t("rules.notifyHint")}
+            </p>
+          </div>
+        )}
+
+        {actionType === "RunCommand" && draft.action.type === "RunCommand" && (
+          <div className="space-y-3">
+            <div>
+              <label className="text-xs text-zinc-400 block mb-1">
+                {t("rules.program")} *
+              </label>
+              <input
+                type="text"
+                value={draft.action.program}
+                onChange={(e) =>
+                  setDraft({
+                    ...draft,
+                    action: { ...draft.action, type: "RunCommand", program: e.target.value },
+                  })
+                }
+                placeholder="C:\\Tools\\ffmpeg.exe"
+                className="w-full px-3 py-2 bg-zinc-800 border border-zinc-700 rounded-lg text-sm font-mono focus:outline-none focus:border-blue-500"
+              />
+            </div>
+            <div>
+              <label className="text-xs text-zinc-400 block mb-1">
+                {t("rules.programArgs")}
+              </label>
+              <textarea
+                value={draft.action.args.join("\n")}
+                onChange={(e) =>
+                  setDraft({
+                    ...draft,
+                    action: { ...draft.action, type: "RunCommand", args: e.target.value.split("\n") },
+                  })
+                }
+                rows={3}
+                placeholder={"-i\n{path}"}
+                className="w-full px-3 py-2 bg-zinc-800 border border-zinc-700 rounded-lg text-sm font-mono focus:outline-none focus:border-blue-500"
+              />
+              <p className="text-xs text-zinc-500 mt-1">
+                {t("rules.runCommandHint")}
+              </p>
+            </div>
+          </div>
+        )}
+
+        {actionType === "Review" && draft.action.type === "Review" && (
+          <div>
+            <label className="text-xs text-zinc-400 block mb-1">
+              {t("rules.reviewReason")}
+            </label>
+            <input
+              type="text"
+              value={draft.action.reason ?? ""}
+              onChange={(e) =>
+                setDraft({
+                  ...draft,
+                  action: { ...draft.action, type: "Review", reason: e.target.value },
+                })
+              }
+              className="w-full px-3 py-2 bg-zinc-800 border border-zinc-700 rounded-lg text-sm focus:outline-none focus:border-blue-500"
+            />
+            <p className="text-xs text-zinc-500 mt-1">
+              {t("rules.reviewHint")}
+            </p>
+          </div>
+        )}
       </div>
 
       {/* Rule Whitelist */}
@@ -4,7 +4,7 @@ import type { TFunction } from "i18next";
 
 // ── Types ───────────────────────────────────────────────────
 
-export type ActionType = "Move" | "Delete";
+export type ActionType = "Move" | "MoveByCategory" | "Delete" | "Archive" | "Link" | "Notify" | "RunCommand" | "Review";
 
 // ── Helper Functions ────────────────────────────────────────
 
@@ -12,8 +12,20 @@ export function defaultAction(type: ActionType): Action {
   switch (type) {
     case "Move":
       return { type: "Move", destination: "", delay_minutes: 0, keep_source: false };
+    case "MoveByCategory":
+      return { type: "MoveByCategory", root: "" };
     case "Delete":
       return { type: "Delete", delay_minutes: 1440 }; // 1 day default
+    case "Archive":
+      return { type: "Archive", destination: "", archive_name: "archive-{year}-{month}.zip" };
+    case "Link":
+      return { type: "Link", destination: "", hard: false };
+    case "Notify":
+      return { type: "Notify", message: "{name}" };
+    case "RunCommand":
+      return { type: "RunCommand", program: "", args: ["{path}"] };
+    case "Review":
+      return { type: "Review", reason: "" };
   }
 }
 
@@ -55,8 +67,20 @@ export function actionLabel(action: Action, t: TFunction): string {
       }
       return `${t(isCopy ? "rules.copyTo" : "rules.moveTo")} ${dest}`;
     }
+    case "MoveByCategory":
+      return `${t("rules.sortByCategoryInto")} ${action.root || "…"}`;
     case "Delete":
       return t("rules.deleteAfter", { time: formatDelayTime(action.delay_minutes, t) });
+    case "Archive":
+      return `${t("rules.archiveInto")} ${action.destination || "…"}${action.destination ? "\\" : ""}${action.archive_name}`;
+    case "Link":
+      return `${t(action.hard ? "rules.hardLinkIn" : "rules.linkIn")} ${action.destination || "…"}`;
+    case "Notify":
+      return `${t("rules.notify")}: ${action.message}`;
+    case "RunCommand":
+      return `${t("rules.runCommand")} ${[action.program || "…", ...action.args].join(" ")}`;
+    case "Review":
+      return action.reason ? `${t("rules.flagForReview")}: ${action.reason}` : t("rules.flagForReview");
   }
 }
 
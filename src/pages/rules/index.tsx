@@ -1,6 +1,7 @@
 import { useEffect, useState } from "react";
 import { useTranslation } from "react-i18next";
 import { Plus, ChevronDown, Copy } from "lucide-react";
+import { message } from "@tauri-apps/plugin-dialog";
 import * as api from "../../api";
 import type { WatchedFolder, Rule } from "../../types";
 import { createEmptyRule } from "./helpers";
@@ -44,10 +45,11 @@ export default function Rules() {
 
   const handleSaveRule = async (rule: Rule) => {
     if (!selectedFolderId) return;
-    if (isNewRule) {
-      await api.addRule(selectedFolderId, rule);
-    } else {
-      await api.updateRule(selectedFolderId, rule);
+    const warnings = isNewRule
+      ? await api.addRule(selectedFolderId, rule)
+      : await api.updateRule(selectedFolderId, rule);
+    if (warnings.length > 0) {
+      message(warnings.join("\n"), { title: t("rules.loopWarningTitle"), kind: "warning" }).catch(() => {});
     }
     setEditingRule(null);
     setIsNewRule(false);
@@ -1,16 +1,63 @@
 // Mirrors Rust types from config.rs and db.rs
 
 export interface AppConfig {
+  /** On-disk config shape; older files are upgraded on load */
+  schema_version?: number;
   folders: WatchedFolder[];
   settings: AppSettings;
+  /** Named rule sets applied to new folders */
+  presets?: RulePreset[];
+  /** Removed folders kept with their rules until purged; not watched or scanned */
+  archived_folders?: WatchedFolder[];
+}
+
+/** A saved set of rules (and folder whitelist) used as a template for new folders. */
+export interface RulePreset {
+  name: string;
+  rules: Rule[];
+  whitelist: string[];
 }
 
 export interface AppSettings {
   scan_interval_minutes: number;
   start_with_os: boolean;
   minimize_to_tray: boolean;
+  /** Show desktop notifications (used by Notify rules) */
+  show_notifications?: boolean;
   // ...existing code...
   log_retention_days: number;
+  /** Append pruned log entries to monthly gzip archives instead of discarding them */
+  archive_logs_before_pruning?: boolean;
+  /** Scheduled actions run per batch before pausing (0 = no batching) */
+  deletion_batch_size?: number;
+  /** Pause between batches of scheduled actions, in ms */
+  deletion_batch_pause_ms?: number;
+  /** Where deleted files go: the OS recycle bin (falls back to staging) or the app's staging folder */
+  deletion_mode?: DeletionMode;
+  /** Days staged deletions are kept before being removed for good */
+  staging_retention_days?: number;
+  /** Failed attempts before a scheduled action is given up on */
+  max_deletion_attempts?: number;
+  /** Keep given-up scheduled actions instead of dropping them */
+  keep_failed_deletions?: boolean;
+  /** Hold due deletions for approval instead of running them */
+  require_deletion_approval?: boolean;
+  /** Cron expression (local time) for when due actions run; unset = every minute */
+  deletion_cron?: string | null;
+  /** Seconds the watcher waits for a file's size to settle (0 = don't wait) */
+  stability_timeout_secs?: number;
+  /** Extensions no rule ever touches (partial downloads, temp files) */
+  global_ignore_extensions?: string[];
+  /** Emit every raw watcher event as `raw-fs-event` (debugging) */
+  debug_watcher_events?: boolean;
+  /** Let RunCommand rules start external programs (off by default) */
+  allow_run_command?: boolean;
+  /** Adding a folder that contains or sits inside a watched one: refuse, or add with a warning */
+  nested_folder_policy?: NestedFolderPolicy;
+  /** After scanning a recursive folder, recycle subfolders left empty (never the watched folder) */
+  remove_empty_dirs?: boolean;
+  /** RFC3339 time until which all automation is paused (see pauseAutomation) */
+  paused_until?: string | null;
   /** Maximum database size in MB (0 = unlimited) */
   max_storage_mb: number;
   /** Default root directory for sorted files (Move destinations resolve relative to this) */
@@ -25,6 +72,10 @@ export interface AppSettings {
   context_menu_prompted: boolean;
 }
 
+export type DeletionMode = "Trash" | "Staging";
+
+export type NestedFolderPolicy = "Reject" | "Warn";
+
 export interface WatchedFolder {
   id: string;
   path: string;
@@ -34,6 +85,124 @@ export interface WatchedFolder {
   whitelist: string[];
   /** Whether to watch subdirectories recursively */
   watch_subdirectories: boolean;
+  /** Catch-all action for files no rule matches (lowest priority) */
+  default_action?: Action | null;
+  /** Minimum file age in days before the default action applies (0 = any age) */
+  default_after_days?: number;
+  /** RFC3339 time until which this folder is paused (ignored once passed) */
+  paused_until?: string | null;
+  /** Preset applied to new subfolders, which then become watched folders themselves */
+  auto_watch_new_subdirs?: string | null;
+}
+
+/** One action a dry-run scan says would happen to a file. */
+/** Files a rule would match in a folder (preview_rule_on_folder) */
+export interface RulePreview {
+  matches: string[];
+  /** More than 1000 files matched; only the first ones are listed */
+  truncated: boolean;
+}
+
+export interface PlannedAction {
+  file_path: string;
+  file_name: string;
+  rule_name: string;
+  action: "move" | "copy" | "delete";
+  /** Resolved destination folder for moves/copies */
+  destination: string | null;
+  /** null = immediately; otherwise "YYYY-MM-DD HH:MM:SS" (UTC) */
+  scheduled_for: string | null;
+}
+
+/** Condition timing for one rule from profile_folder_scan. */
+export interface RuleProfile {
+  rule_name: string;
+  files_tested: number;
+  total_micros: number;
+  matches: number;
+}
+
+/** Result of get_folder_cleanliness. */
+export interface CleanlinessScore {
+  /** 0-100: share of files some rule handles (empty folder = 100) */
+  score: number;
+  matched_files: number;
+  unmatched_files: number;
+  /** e.g. "23 .zip files unmatched — add a rule?" */
+  suggestions: string[];
+}
+
+/** What merge_config changed */
+export interface ConfigMergeSummary {
+  folders_added: number;
+  rules_added: number;
+  /** Existing rules replaced by the imported version (overwrite only) */
+  rules_replaced: number;
+  presets_added: number;
+  /** Settings fields taken from the import (overwrite only) */
+  settings_changed: string[];
+}
+
+/** One path's result from add_watched_folders: the added folder, or why it was skipped */
+export type FolderAddOutcome = { Ok: WatchedFolder } | { Err: string };
+
+/** Result of add_watched_folders */
+export interface FolderAddReport {
+  /** Each path's outcome, in the order given */
+  outcomes: FolderAddOutcome[];
+  /** Why the running watcher couldn't be restarted; the folders are still saved */
+  watcher_error: string | null;
+}
+
+/** One folder's dashboard summary from get_folders_overview. */
+export interface FolderOverview {
+  folder: WatchedFolder;
+  enabled: boolean;
+  /** Folder path currently exists on disk */
+  available: boolean;
+  rule_count: number;
+  scheduled_count: number;
+  last_activity_ts: string | null;
+  /** Watcher running and folder enabled, available, and not paused */
+  watch_active: boolean;
+}
+
+/** Result of reconcile_folders. `missing` holds folders as they were before the change. */
+export interface ReconcileReport {
+  missing: WatchedFolder[];
+  /** Ids of folders whose path still exists */
+  ok: string[];
+}
+
+/** Result of probe_destination: whether a Move destination is usable and how fast. */
+export interface DestinationProbe {
+  reachable: boolean;
+  writable: boolean;
+  /** Milliseconds to write and delete a small probe file */
+  write_latency_ms: number | null;
+  error: string | null;
+}
+
+/** A rule with its folder, from cross-folder queries like get_rules_by_tag. */
+export interface FolderRule {
+  folder_id: string;
+  folder_path: string;
+  rule: Rule;
+}
+
+/** One sample name's outcome from test_rule. */
+export interface RuleTestResult {
+  file_name: string;
+  matched: boolean;
+  whitelisted: boolean;
+  /** e.g. "Move to D:\sorted\pdfs"; null unless the rule would fire */
+  action_preview: string | null;
+}
+
+/** Result of get_folder_pause_status. */
+export interface FolderPauseStatus {
+  paused: boolean;
+  paused_until: string | null;
 }
 
 // ── Composable Rule System ──────────────────────────────────
@@ -51,6 +220,27 @@ export interface Rule {
   whitelist: string[];
   /** When true, conditions match against relative path from watched folder instead of filename only */
   match_subdirectories: boolean;
+  /** Free-form labels for grouping rules ("tax", "media") */
+  tags?: string[];
+  /** Glob patterns distinguish case (`README` vs `readme`); default false */
+  case_sensitive?: boolean;
+  /** Max immediate actions per scan; remaining matches wait for the next scan (null = unlimited) */
+  max_per_run?: number | null;
+  /** Skip moves/copies when the destination drive has fewer free bytes than this (null = no check) */
+  min_dest_free_bytes?: number | null;
+  /** Evaluation order: higher runs first; ties keep list order (default 0) */
+  priority?: number;
+  /** Local hours/weekdays the rule may run in (null = always) */
+  active_window?: TimeWindow | null;
+}
+
+/** Daily span of local hours; end < start wraps past midnight, end == start = all day. */
+export interface TimeWindow {
+  start_hour: number;
+  /** Exclusive */
+  end_hour: number;
+  /** Weekdays the window opens on; empty = every day */
+  days: ("Mon" | "Tue" | "Wed" | "Thu" | "Fri" | "Sat" | "Sun")[];
 }
 
 /**
@@ -61,25 +251,122 @@ export interface Rule {
  *   *.pdf                  — glob
  *   *.jpg OR *.png         — or
  *   *.pdf AND *invoice*    — and
+ *   *.pdf XOR *draft*      — exactly one matches
  *   NOT *.tmp              — not
  *   /^IMG_\d+/             — regex
+ *   /^img_\d+/i            — regex with flags (i, m, s, x)
  *   (*.pdf OR *.docx) AND *report*  — grouped
+ *   modified>30d / created<7d        — file age in days
+ *   largest:1 / smallest:3           — N largest/smallest files in the directory
+ *   tag:Red                          — OS file tag (macOS Finder tags only)
+ *   content:image/png                — real file type from magic bytes (wildcards ok)
+ *   dated>30d/(\d{4}-\d{2}-\d{2})/    — date captured from the name is over 30 days old
+ *   duplicate:D:\Archive             — identical content exists under that folder (SHA-256)
+ *   meta:author~"ACME"               — document metadata (PDF Info, EXIF); = ~ > <
  */
 export type Condition =
   | { type: "Glob"; pattern: string }
-  | { type: "Regex"; pattern: string }
+  | { type: "Regex"; pattern: string; flags?: string }
   | { type: "And"; conditions: Condition[] }
   | { type: "Or"; conditions: Condition[] }
+  | { type: "Xor"; conditions: Condition[] }
   | { type: "Not"; condition: Condition }
+  | { type: "Age"; op: AgeOp; days: number; field: TimeField }
+  | { type: "ExtremeSize"; which: SizeExtreme; n: number }
+  | { type: "HasTag"; tag: string }
+  | { type: "ParentDir"; pattern: string }
+  | { type: "ContentType"; mime: string }
+  | { type: "EmbeddedDateOlderThan"; regex_with_date_group: string; days: number }
+  | { type: "Duplicate"; reference_dir: string }
+  | { type: "MetaField"; field: string; op: MetaOp; value: string }
   | { type: "Always" };
 
+/** Comparison for an Age condition: older (`>`) or newer (`<`) than N days. */
+export type AgeOp = "GreaterThan" | "LessThan";
+
+/** Comparison for a MetaField condition: equals, contains, greater or less than. */
+export type MetaOp = "Equals" | "Contains" | "GreaterThan" | "LessThan";
+
+/** Which end of the per-directory size ranking an ExtremeSize condition matches. */
+export type SizeExtreme = "Largest" | "Smallest";
+
+/** File timestamp read by an Age condition. Created falls back to Modified where unsupported. */
+export type TimeField = "Modified" | "Created";
+
+/** Token category from the condition tokenizer (for syntax highlighting). */
+export type TokenKind = "And" | "Or" | "Xor" | "Not" | "LParen" | "RParen" | "Glob" | "Regex";
+
+/** A condition token with character offsets into the source text (end-exclusive). */
+/** Whether one configured folder is registered with the OS watcher. */
+export interface FolderWatchStatus {
+  folder_id: string;
+  path: string;
+  watching: boolean;
+  /** Why the folder isn't watched; null when it is, or when it's disabled */
+  error: string | null;
+}
+
+/** Watcher state plus each configured folder's registration. */
+export interface WatcherStatus {
+  running: boolean;
+  folders: FolderWatchStatus[];
+}
+
+/** A rule whose actions keep failing; suppressed_for_secs > 0 while it is backed off. */
+export interface RuleBackoff {
+  rule_id: string;
+  rule_name: string;
+  consecutive_failures: number;
+  last_error: string | null;
+  suppressed_for_secs: number;
+}
+
+/** Outcome of one golden-file case; actual_match is null when the condition didn't parse. */
+export interface GoldenResult {
+  condition_text: string;
+  filename: string;
+  expected_match: boolean;
+  actual_match: boolean | null;
+  passed: boolean;
+  error: string | null;
+}
+
+export interface TokenSpan {
+  kind: TokenKind;
+  start: number;
+  end: number;
+  text: string;
+}
+
 /**
  * What to do when the condition matches.
  * Tagged union using `type` discriminant.
  */
 export type Action =
-  | { type: "Move"; destination: string; delay_minutes: number; keep_source?: boolean }
-  | { type: "Delete"; delay_minutes: number };
+  | { type: "Move"; destination: string; delay_minutes: number; keep_source?: boolean; on_conflict?: ConflictPolicy; destinations?: QuotaDestination[]; preserve_structure?: boolean }
+  | { type: "Delete"; delay_minutes: number }
+  /** Move into `root/<Category>` by extension (Images, Videos, Audio, Documents, Archives, Code, Other) */
+  | { type: "MoveByCategory"; root: string }
+  /** Zip into `archive_name` ({year}/{month}/{day}/{ext} allowed) under `destination`, then recycle */
+  | { type: "Archive"; destination: string; archive_name: string }
+  /** Symlink (or hard-link) the file into `destination`; the original stays put */
+  | { type: "Link"; destination: string; hard?: boolean }
+  /** Log the file (and show a desktop notification) without touching it; {name} is substituted */
+  | { type: "Notify"; message: string }
+  /** Run a program on the file; {path}/{name}/{dir} are substituted in program and args */
+  | { type: "RunCommand"; program: string; args: string[] }
+  /** Flag for the review queue; approving runs `on_approve` immediately */
+  | { type: "Review"; reason?: string; on_approve?: Action | null };
+
+/** One destination of a multi-destination Move; immediate moves use the first under quota with room. */
+export interface QuotaDestination {
+  path: string;
+  /** Most bytes the destination may hold (measured on disk each run); null = only free space counts */
+  max_bytes?: number | null;
+}
+
+/** How a Move handles a same-named file at the destination (default 'Rename'). */
+export type ConflictPolicy = "Rename" | "Skip" | "Overwrite" | "KeepNewer";
 
 export interface ActivityLogEntry {
   id: string;
@@ -89,10 +376,26 @@ export interface ActivityLogEntry {
   rule_name: string | null;
   folder_id: string | null;
   timestamp: string;
+  /** 'success', 'renamed_collision' (moved, but renamed with a ` (n)` suffix),
+   *  'skipped_low_space' (destination drive below the rule's free-space minimum),
+   *  'skipped_conflict' (conflict policy kept the existing destination file), or 'error' */
   result: string;
   details: string | null;
 }
 
+/** A page of the activity log plus the total across all pages */
+export interface ActivityLogPage {
+  entries: ActivityLogEntry[];
+  total: number;
+}
+
+/** A monthly archive of pruned activity log entries. */
+export interface LogArchiveInfo {
+  /** e.g. "2026-03.ndjson.gz" */
+  name: string;
+  size_bytes: number;
+}
+
 export interface FileIndexEntry {
   id: string;
   file_path: string;
@@ -114,6 +417,28 @@ export interface UndoEntry {
   timestamp: string;
   expires_at: string;
   restored: boolean;
+  /** Shared by entries created in the same scan/scheduler run */
+  batch_id?: string | null;
+}
+
+/** Undo entries created together in one run, from get_undo_batches. */
+export interface UndoBatch {
+  /** Run id, or the entry id for an entry recorded outside a batch */
+  batch_id: string;
+  /** Pass to undoBatch to revert the whole run */
+  undo_ids: string[];
+  count: number;
+  /** Earliest entry timestamp in the batch */
+  timestamp: string;
+  /** Entry count per undo action, e.g. { auto_move: 12, auto_delete: 3 } */
+  actions: Record<string, number>;
+}
+
+/** Result of undo_batch / undo_all_since. */
+export interface UndoBatchSummary {
+  restored: number;
+  /** [undo_id, reason] for each entry that couldn't be restored */
+  failed: [string, string][];
 }
 
 export interface RuleMetadata {
@@ -123,10 +448,37 @@ export interface RuleMetadata {
   last_triggered_at: string | null;
 }
 
+/** Dashboard totals over the last N days. Scheduling and undo entries don't count as processed. */
+export interface ActivitySummary {
+  files_processed: number;
+  /** Total size of moved files, where the size was recorded */
+  bytes_moved: number;
+  /** Days with activity, oldest first */
+  per_day: { day: string; files_processed: number; bytes_moved: number }[];
+  /** Most frequent first */
+  by_action: { action: string; count: number }[];
+  /** Up to ten rules, busiest first */
+  top_rules: { rule_name: string; count: number; bytes_moved: number }[];
+}
+
+/** Width of one activity time-series bucket. */
+export type BucketSize = "Daily" | "Hourly";
+
+/** Activity counts for one time-series bucket (all zero for a quiet period). */
+export interface TimeBucket {
+  /** `YYYY-MM-DD HH:00:00`, UTC */
+  bucket_start: string;
+  moved: number;
+  deleted: number;
+  errors: number;
+}
+
 export interface RuleExecutionStats {
   rule_name: string;
   last_executed: string | null;
   executions_this_week: number;
+  /** Executions this week whose move was renamed on a destination collision */
+  renamed_collisions_this_week: number;
 }
 
 export interface ScheduledDeletion {
@@ -147,6 +499,18 @@ export interface ScheduledDeletion {
   move_destination: string | null;
   /** Whether to keep the source file after copying */
   keep_source: boolean;
+  /** Failed execution attempts so far */
+  attempt_count?: number;
+  /** When the last failed attempt happened */
+  last_attempt_at?: string | null;
+  /** "pending", or "awaiting_approval" when held back for approval */
+  status?: "pending" | "awaiting_approval";
+}
+
+/** One page of scheduled deletions plus the total across all pages */
+export interface ScheduledDeletionPage {
+  total: number;
+  rows: ScheduledDeletion[];
 }
 
 export interface TableStats {
@@ -165,3 +529,34 @@ export interface DbStats {
   trash_size_bytes: number;
   tables: TableStats[];
 }
+
+/** A file flagged by a Review rule (see get_review_queue). */
+export interface ReviewItem {
+  id: string;
+  file_path: string;
+  file_name: string;
+  folder_id: string;
+  rule_id: string;
+  rule_name: string;
+  reason: string;
+  flagged_at: string;
+  status: "pending" | "approved" | "dismissed";
+  resolved_at: string | null;
+}
+
+export type ReviewDecision = "Approve" | "Dismiss";
+
+/** Payload of the `raw-fs-event` event, sent while `debug_watcher_events` is on */
+export interface RawFsEvent {
+  path: string;
+  /** "Any", "AnyContinuous", or "Error: …" */
+  kind: string;
+}
+
+/** A rule that can never act because an earlier rule matches every file */
+export interface ShadowWarning {
+  shadowed_rule_id: string;
+  by_rule_id: string;
+  /** Human-readable explanation */
+  reason: string;
+}